@@ -0,0 +1,65 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin blob_proof`
+//!
+//! Outputs the proof that a data submission to Avail is attested by the VectorX contract at
+//! CONTRACT_ADDRESS on CHAIN_ID. Requires the events service to have indexed the contract's data
+//! commitment ranges in Redis.
+//!
+
+use std::{env, fs};
+
+use alloy_primitives::Address;
+use clap::Parser;
+use log::info;
+use vectorx::inclusion::blob::build_data_submission_proof;
+use vectorx::input::{RedisClient, RpcDataFetcher};
+
+#[derive(Parser, Debug, Clone)]
+#[command(about = "Get the proof that a data submission to Avail is attested by VectorX.")]
+pub struct BlobProofArgs {
+    #[arg(long, required = true)]
+    pub block: u32,
+    #[arg(long, required = true)]
+    pub tx_index: u32,
+    /// Write the proof to this file instead of stdout.
+    #[arg(long)]
+    pub output: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    env::set_var("RUST_LOG", "info");
+    dotenv::dotenv().ok();
+    env_logger::init();
+    let args = BlobProofArgs::parse();
+
+    let contract_address = env::var("CONTRACT_ADDRESS").expect("CONTRACT_ADDRESS must be set");
+    let address = contract_address
+        .parse::<Address>()
+        .expect("invalid address");
+    let chain_id = env::var("CHAIN_ID")
+        .expect("CHAIN_ID must be set")
+        .parse::<u64>()
+        .expect("invalid chain id");
+
+    let mut redis_client = RedisClient::new().await;
+    let range = redis_client
+        .get_data_commitment_range(chain_id, address.0.to_vec(), args.block)
+        .await
+        .expect("Block is not in a data commitment range stored in VectorX");
+
+    let mut fetcher = RpcDataFetcher::new().await;
+    let proof = build_data_submission_proof(&mut fetcher, &range, args.block, args.tx_index)
+        .await
+        .expect("Failed to build data submission proof");
+
+    let proof_json = serde_json::to_string_pretty(&proof).unwrap();
+    match args.output {
+        Some(path) => {
+            fs::write(&path, proof_json).expect("Failed to write proof");
+            info!("Wrote data submission proof to {}", path);
+        }
+        None => println!("{}", proof_json),
+    }
+}
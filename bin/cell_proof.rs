@@ -0,0 +1,109 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin cell_proof`
+//!
+//! Samples cells of an Avail block's extended data matrix, verifies their KZG proofs against the
+//! commitments in the block's header, and outputs them with the proof that the block's data root is
+//! attested by the VectorX contract at CONTRACT_ADDRESS on CHAIN_ID. The header is verified to be an
+//! ancestor of the header stored in the contract for the end block of its data commitment range.
+//! Requires the events service to have indexed the contract's data commitment ranges in Redis.
+//!
+
+use std::{env, fs};
+
+use alloy_primitives::Address;
+use clap::Parser;
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::H256;
+use log::info;
+use vectorx::inclusion::cell::build_cell_sampling_proof;
+use vectorx::input::{RedisClient, RpcDataFetcher};
+
+// Note: Update ABI when updating contract.
+abigen!(VectorX, "./abi/VectorX.abi.json",);
+
+#[derive(Parser, Debug, Clone)]
+#[command(about = "Verify cells of an Avail block against a header attested by VectorX.")]
+pub struct CellProofArgs {
+    #[arg(long, required = true)]
+    pub block: u32,
+    /// A cell to sample, as <row>:<col>. Can be repeated.
+    #[arg(long = "cell", required = true, value_parser = parse_cell)]
+    pub cells: Vec<(u32, u16)>,
+    /// Write the proof to this file instead of stdout.
+    #[arg(long)]
+    pub output: Option<String>,
+}
+
+fn parse_cell(cell: &str) -> Result<(u32, u16), String> {
+    let (row, col) = cell
+        .split_once(':')
+        .ok_or_else(|| format!("invalid cell {}, expected <row>:<col>", cell))?;
+    Ok((
+        row.parse()
+            .map_err(|e| format!("invalid row {}: {}", row, e))?,
+        col.parse()
+            .map_err(|e| format!("invalid col {}: {}", col, e))?,
+    ))
+}
+
+#[tokio::main]
+async fn main() {
+    env::set_var("RUST_LOG", "info");
+    dotenv::dotenv().ok();
+    env_logger::init();
+    let args = CellProofArgs::parse();
+
+    let contract_address = env::var("CONTRACT_ADDRESS").expect("CONTRACT_ADDRESS must be set");
+    let address = contract_address
+        .parse::<Address>()
+        .expect("invalid address");
+    let chain_id = env::var("CHAIN_ID")
+        .expect("CHAIN_ID must be set")
+        .parse::<u64>()
+        .expect("invalid chain id");
+
+    let ethereum_rpc_url = env::var("ETHEREUM_RPC_URL").expect("ETHEREUM_RPC_URL must be set");
+    let provider =
+        Provider::<Http>::try_from(ethereum_rpc_url).expect("could not connect to client");
+    let contract = VectorX::new(address.0 .0, provider.into());
+
+    let mut redis_client = RedisClient::new().await;
+    let range = redis_client
+        .get_data_commitment_range(chain_id, address.0.to_vec(), args.block)
+        .await
+        .expect("Block is not in a data commitment range stored in VectorX");
+    let trusted_header_hash = H256::from(
+        contract
+            .block_height_to_header_hash(range.end)
+            .await
+            .unwrap(),
+    );
+
+    let mut fetcher = RpcDataFetcher::new().await;
+    let proof = build_cell_sampling_proof(
+        &mut fetcher,
+        &range,
+        trusted_header_hash,
+        args.block,
+        &args.cells,
+    )
+    .await
+    .expect("Failed to build cell sampling proof");
+    info!(
+        "Verified {} cells of block {} against its header, an ancestor of block {}",
+        proof.cells.len(),
+        args.block,
+        range.end
+    );
+
+    let proof_json = serde_json::to_string_pretty(&proof).unwrap();
+    match args.output {
+        Some(path) => {
+            fs::write(&path, proof_json).expect("Failed to write proof");
+            info!("Wrote cell sampling proof to {}", path);
+        }
+        None => println!("{}", proof_json),
+    }
+}
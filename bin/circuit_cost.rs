@@ -0,0 +1,226 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin circuit_cost`
+//!
+//! Builds the header range, header ancestry and rotate circuits and each of their gadgets for the
+//! given parameter sets, and reports the gate count, rows, degree and estimated proving time of
+//! each. The header range report includes its map circuit and each level of its reduce circuits,
+//! which the header ancestry circuit shares. Each gadget is
+//! built with as many instances as the subcircuit it runs in, and its rows per instance include its
+//! share of the Curta STARK verifier. Calibrate --ns-per-row with a proof on the prover's machine,
+//! e.g. the proving time of a header range proof divided by the rows of HeaderRangeCircuit.
+//!
+
+use std::{env, fs};
+
+use clap::{Parser, ValueEnum};
+use log::info;
+use serde::Serialize;
+use vectorx::authority_set::AuthoritySetCommitmentMode;
+use vectorx::consts::{
+    DELAY_LENGTH, HEADERS_PER_MAP, MAX_AUTHORITY_SET_SIZE, MAX_AUTHORITY_SET_SIZE_TIER,
+    MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE, SMALL_MAX_HEADER_SIZE, VALIDATOR_LENGTH,
+};
+use vectorx::cost::{
+    gadget_costs, header_ancestry_cost, header_range_cost, header_range_map_cost,
+    header_range_reduce_costs, rotate_cost, CircuitCost,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum ParameterSet {
+    /// 64 authorities, SMALL_MAX_HEADER_SIZE headers (MAX_HEADER_SIZE for rotate), 256 headers.
+    Small,
+    /// MAX_AUTHORITY_SET_SIZE authorities, MAX_HEADER_SIZE headers, 256 headers.
+    Default,
+    /// MAX_AUTHORITY_SET_SIZE_TIER authorities, MAX_HEADER_SIZE headers, 512 headers.
+    Large,
+}
+
+// The rotate circuits read the encoded authority set from the epoch end header, so they are always
+// built with MAX_HEADER_SIZE, as in the rotate binaries. The header size of a parameter set only
+// applies to the header range circuits and gadgets.
+const ROTATE_MAX_HEADER_SIZE: usize = MAX_HEADER_SIZE;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum CircuitKind {
+    Gadgets,
+    HeaderRange,
+    HeaderAncestry,
+    Rotate,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[command(about = "Report the cost of the VectorX circuits and their gadgets.")]
+struct CircuitCostArgs {
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "small,default"
+    )]
+    params: Vec<ParameterSet>,
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "gadgets")]
+    circuits: Vec<CircuitKind>,
+    /// Proving time per row in nanoseconds, for the estimated proving time.
+    #[arg(long, default_value_t = 10_000)]
+    ns_per_row: u64,
+    /// Write the report as JSON to this path.
+    #[arg(long)]
+    output: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CostReport {
+    params: String,
+    max_authority_set_size: usize,
+    max_header_size: usize,
+    max_num_headers: usize,
+    headers_per_map: usize,
+    costs: Vec<CircuitCost>,
+}
+
+fn report<
+    const MAX_AUTHORITY_SET_SIZE: usize,
+    const MAX_HEADER_SIZE: usize,
+    const MAX_NUM_HEADERS: usize,
+    const MAX_SUBARRAY_SIZE: usize,
+>(
+    params: ParameterSet,
+    circuits: &[CircuitKind],
+) -> CostReport {
+    let mut costs = Vec::new();
+    for circuit in circuits {
+        info!("Building {:?} with {:?} parameters", circuit, params);
+        match circuit {
+            CircuitKind::Gadgets => {
+                costs.extend(gadget_costs::<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE>(
+                    AuthoritySetCommitmentMode::ChainedSha256,
+                ))
+            }
+            CircuitKind::HeaderRange => {
+                costs.push(header_range_cost::<
+                    MAX_AUTHORITY_SET_SIZE,
+                    MAX_HEADER_SIZE,
+                    MAX_NUM_HEADERS,
+                >());
+                costs.push(header_range_map_cost::<MAX_HEADER_SIZE>());
+                costs.extend(header_range_reduce_costs::<MAX_HEADER_SIZE, MAX_NUM_HEADERS>());
+            }
+            CircuitKind::HeaderAncestry => {
+                costs.push(header_ancestry_cost::<MAX_HEADER_SIZE, MAX_NUM_HEADERS>())
+            }
+            CircuitKind::Rotate => costs.push(rotate_cost::<
+                MAX_AUTHORITY_SET_SIZE,
+                ROTATE_MAX_HEADER_SIZE,
+                MAX_SUBARRAY_SIZE,
+            >()),
+        }
+    }
+    CostReport {
+        params: format!("{:?}", params).to_lowercase(),
+        max_authority_set_size: MAX_AUTHORITY_SET_SIZE,
+        max_header_size: MAX_HEADER_SIZE,
+        max_num_headers: MAX_NUM_HEADERS,
+        headers_per_map: HEADERS_PER_MAP,
+        costs,
+    }
+}
+
+fn print_reports(reports: &[CostReport], ns_per_row: u64) {
+    for report in reports {
+        println!(
+            "\n{} (authorities: {}, header size: {}, headers: {}, headers per map: {})",
+            report.params,
+            report.max_authority_set_size,
+            report.max_header_size,
+            report.max_num_headers,
+            report.headers_per_map
+        );
+        println!(
+            "{:<40} {:>10} {:>12} {:>12} {:>14} {:>8} {:>12} {:>16}",
+            "circuit",
+            "instances",
+            "gates",
+            "rows",
+            "rows/instance",
+            "degree",
+            "gate types",
+            "est. prove (s)"
+        );
+        for cost in report.costs.iter() {
+            println!(
+                "{:<40} {:>10} {:>12} {:>12} {:>14.0} {:>8} {:>12} {:>16.1}",
+                cost.name,
+                cost.num_instances,
+                cost.num_gates,
+                cost.num_rows,
+                cost.rows_per_instance(),
+                cost.degree_bits,
+                cost.num_gate_types,
+                cost.estimated_proving_time_secs(ns_per_row)
+            );
+        }
+    }
+
+    // Compare the rows of each circuit to the first parameter set.
+    let Some((baseline, others)) = reports.split_first() else {
+        return;
+    };
+    for report in others {
+        println!("\n{} vs {} (rows)", report.params, baseline.params);
+        for (cost, baseline_cost) in report.costs.iter().zip(baseline.costs.iter()) {
+            println!(
+                "{:<40} {:>12.2}x",
+                cost.name,
+                cost.num_rows as f64 / baseline_cost.num_rows as f64
+            );
+        }
+    }
+}
+
+fn main() {
+    env::set_var("RUST_LOG", "info");
+    dotenv::dotenv().ok();
+    env_logger::init();
+    let args = CircuitCostArgs::parse();
+
+    const SMALL_MAX_AUTHORITY_SET_SIZE: usize = 64;
+    const SMALL_MAX_SUBARRAY_SIZE: usize =
+        SMALL_MAX_AUTHORITY_SET_SIZE * VALIDATOR_LENGTH + DELAY_LENGTH;
+    const LARGE_MAX_SUBARRAY_SIZE: usize =
+        MAX_AUTHORITY_SET_SIZE_TIER * VALIDATOR_LENGTH + DELAY_LENGTH;
+
+    let reports =
+        args.params
+            .iter()
+            .map(|&params| match params {
+                ParameterSet::Small => report::<
+                    SMALL_MAX_AUTHORITY_SET_SIZE,
+                    SMALL_MAX_HEADER_SIZE,
+                    256,
+                    SMALL_MAX_SUBARRAY_SIZE,
+                >(params, &args.circuits),
+                ParameterSet::Default => {
+                    report::<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, 256, MAX_SUBARRAY_SIZE>(
+                        params,
+                        &args.circuits,
+                    )
+                }
+                ParameterSet::Large => report::<
+                    MAX_AUTHORITY_SET_SIZE_TIER,
+                    MAX_HEADER_SIZE,
+                    512,
+                    LARGE_MAX_SUBARRAY_SIZE,
+                >(params, &args.circuits),
+            })
+            .collect::<Vec<_>>();
+
+    print_reports(&reports, args.ns_per_row);
+
+    if let Some(output) = args.output {
+        fs::write(&output, serde_json::to_string_pretty(&reports).unwrap())
+            .expect("Failed to write the report");
+        info!("Wrote the report to {}", output);
+    }
+}
@@ -56,7 +56,7 @@ fn get_ethereum_rpc(chain_id: u64) -> Option<String> {
     }
 }
 
-type HeaderRangeCommitmentStoredTuple = sol! { tuple(uint32, uint32, bytes32, bytes32) };
+type HeaderRangeCommitmentStoredTuple = sol! { tuple(uint32, uint32, bytes32, bytes32, uint32) };
 
 sol! { struct RangeHashInput {
     uint32 trusted_block;
@@ -93,7 +93,7 @@ async fn store_events(
             .address(contract_address)
             .from_block(curr_start_block)
             .to_block(batch_end_block)
-            .event("HeaderRangeCommitmentStored(uint32,uint32,bytes32,bytes32)");
+            .event("HeaderRangeCommitmentStored(uint32,uint32,bytes32,bytes32,uint32)");
 
         let logs = client.get_logs(&header_range_filter).await.unwrap();
         for log in logs {
@@ -105,10 +105,15 @@ async fn store_events(
             let expected_data_commitment: Vec<u8> = decoded.2.to_vec();
             let expected_data_commitment: [u8; 32] = expected_data_commitment.try_into().unwrap();
 
+            // Aggregated ranges are committed over a larger tree than the contract's
+            // headerRangeCommitmentTreeSize, so the tree size is stored with each range.
+            let commitment_tree_size = decoded.4;
+
             let data_commitment_range = DataCommitmentRange {
                 start: trusted_block,
                 end: end_block,
                 data_commitment: expected_data_commitment.to_vec(),
+                commitment_tree_size,
             };
 
             redis_client
@@ -2,27 +2,112 @@
 //!
 //!     `cargo build --release --bin genesis`
 //!
+//! Gets the genesis parameters of the VectorX contract from a block, or from the head if no block
+//! is given. With `--out <path>`, writes them to a `.env` file that can be sourced before running
+//! `contracts/script/Deploy.s.sol`, or with `--format json`, to a JSON file with the field names of
+//! `VectorX.InitParameters`.
 //!
+//! With `--chain-spec <path>`, derives the genesis parameters from the GRANDPA authorities of a raw
+//! chain spec instead of trusting the RPC, by verifying the justification and ScheduledChange log of
+//! every epoch end block up to the block. `--transcript <path>` writes the verified rotates to a JSON
+//! file. The block must have a justification, or be an epoch end block.
 //!
-//!
+//! With `--verify <address>`, also checks the genesis parameters against the
+//! `blockHeightToHeaderHash` and `authoritySetIdToHash` of the VectorX contract at the address,
+//! whose RPC is read from ETHEREUM_RPC_URL.
 //!
 
-use std::env;
+use std::{env, fs};
 
+use anyhow::{ensure, Result};
 use avail_subxt::config::Header;
-use clap::Parser;
-use log::info;
+use clap::{Parser, ValueEnum};
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, H256};
+use log::{error, info, warn};
+use vectorx::genesis::{derive_genesis, GenesisParameters, TrustedAuthoritySet};
 use vectorx::input::RpcDataFetcher;
 
+// Note: Update ABI when updating contract.
+abigen!(VectorX, "./abi/VectorX.abi.json",);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Env,
+    Json,
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(about = "Get the genesis parameters from a block.")]
 pub struct GenesisArgs {
     #[arg(long)]
     pub block: Option<u32>,
+    /// File to write the genesis parameters to.
+    #[arg(long)]
+    pub out: Option<String>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Env)]
+    pub format: OutputFormat,
+    /// Address of a deployed VectorX contract to check the genesis parameters against.
+    #[arg(long)]
+    pub verify: Option<Address>,
+    /// Raw chain spec to derive the genesis parameters from, instead of trusting the RPC.
+    #[arg(long)]
+    pub chain_spec: Option<String>,
+    /// File to write the verification transcript of a chain spec derivation to.
+    #[arg(long, requires = "chain_spec")]
+    pub transcript: Option<String>,
+}
+
+// Check the genesis parameters against the contract's state. A value the contract has not set is a
+// mismatch, as the contract was not initialised at the genesis block.
+async fn verify(address: Address, genesis: &GenesisParameters) -> Result<()> {
+    let ethereum_rpc_url = env::var("ETHEREUM_RPC_URL").expect("ETHEREUM_RPC_URL must be set");
+    let provider = Provider::<Http>::try_from(ethereum_rpc_url)?;
+    let contract = VectorX::new(address, provider.into());
+
+    let checks = [
+        (
+            "Header hash",
+            H256::from(contract.block_height_to_header_hash(genesis.height).await?),
+            genesis.header,
+        ),
+        (
+            "Authority set hash",
+            H256::from(
+                contract
+                    .authority_set_id_to_hash(genesis.authority_set_id)
+                    .await?,
+            ),
+            genesis.authority_set_hash,
+        ),
+    ];
+
+    let mut consistent = true;
+    for (name, on_chain, expected) in checks {
+        if on_chain == H256::zero() {
+            error!("{}: not set in the contract", name);
+            consistent = false;
+        } else if on_chain == expected {
+            info!("{}: matches the chain", name);
+        } else {
+            error!(
+                "{}: the chain has {:#x}, the contract has {:#x}",
+                name, expected, on_chain
+            );
+            consistent = false;
+        }
+    }
+    ensure!(
+        consistent,
+        "The contract at {:#x} is inconsistent with the chain",
+        address
+    );
+    Ok(())
 }
 
 #[tokio::main]
-pub async fn main() {
+pub async fn main() -> Result<()> {
     env::set_var("RUST_LOG", "info");
     dotenv::dotenv().ok();
     env_logger::init();
@@ -36,15 +121,64 @@ pub async fn main() {
     } else {
         header = fetcher.get_head().await;
     }
-    let header_hash = header.hash();
-    let authority_set_id = fetcher.get_authority_set_id(header.number).await;
-    let authority_set_hash = fetcher.compute_authority_set_hash(header.number).await;
-
-    info!(
-        "\nGENESIS_HEIGHT={:?}\nGENESIS_HEADER={}\nGENESIS_AUTHORITY_SET_ID={}\nGENESIS_AUTHORITY_SET_HASH={}\n",
-        header.number,
-        format!("{:#x}", header_hash),
-        authority_set_id,
-        format!("{:#x}", authority_set_hash)
-    );
+    // The set id in storage changes at the epoch end block, which is still signed by the previous
+    // authority set. The genesis authority set is the one that signs the blocks after the genesis
+    // block, so the previous set is never rotated to on the contract.
+    let (genesis, is_epoch_end_block) = if let Some(chain_spec) = &args.chain_spec {
+        let chain_spec: serde_json::Value = serde_json::from_str(&fs::read_to_string(chain_spec)?)?;
+        let transcript = derive_genesis(
+            &mut fetcher,
+            TrustedAuthoritySet::from_chain_spec(&chain_spec)?,
+            header.number,
+        )
+        .await?;
+        info!(
+            "Derived the genesis parameters from the chain spec with {} verified rotates",
+            transcript.rotates.len()
+        );
+        if let Some(path) = &args.transcript {
+            fs::write(path, serde_json::to_string_pretty(&transcript)?)?;
+            info!("Wrote the verification transcript to {}", path);
+        }
+        // The block is verified by its justification, or is the epoch end block of the last
+        // verified rotate.
+        let is_epoch_end_block = transcript.target_justification.is_none();
+        (transcript.genesis, is_epoch_end_block)
+    } else {
+        let genesis = GenesisParameters {
+            height: header.number,
+            header: H256::from(header.hash().0),
+            authority_set_id: fetcher.get_authority_set_id(header.number).await,
+            authority_set_hash: fetcher.compute_authority_set_hash(header.number).await,
+        };
+        let is_epoch_end_block = header.number > 0
+            && fetcher.get_authority_set_id(header.number - 1).await != genesis.authority_set_id;
+        (genesis, is_epoch_end_block)
+    };
+
+    if is_epoch_end_block {
+        warn!(
+            "Block {} is the epoch end block of authority set {}, and is justified by that set \
+             rather than by the genesis authority set {}. Prefer a block inside an epoch.",
+            header.number,
+            genesis.authority_set_id - 1,
+            genesis.authority_set_id
+        );
+    }
+
+    info!("\n{}", genesis.to_env());
+
+    if let Some(out) = &args.out {
+        let contents = match args.format {
+            OutputFormat::Env => genesis.to_env(),
+            OutputFormat::Json => serde_json::to_string_pretty(&genesis)?,
+        };
+        fs::write(out, contents)?;
+        info!("Wrote the genesis parameters to {}", out);
+    }
+
+    if let Some(address) = args.verify {
+        verify(address, &genesis).await?;
+    }
+    Ok(())
 }
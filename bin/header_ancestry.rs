@@ -0,0 +1,18 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin header_ancestry`
+//!
+//!
+//!
+//!
+//!
+
+use plonky2x::backend::function::Plonky2xFunction;
+use vectorx::consts::MAX_HEADER_SIZE;
+use vectorx::header_ancestry::HeaderAncestryCircuit;
+
+fn main() {
+    // The target block can be at most MAX_NUM_HEADERS - 1 blocks before the trusted block.
+    const MAX_NUM_HEADERS: usize = 256;
+    HeaderAncestryCircuit::<MAX_HEADER_SIZE, MAX_NUM_HEADERS>::entrypoint();
+}
@@ -0,0 +1,18 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin header_range_256_512`
+//!
+//!
+//!
+//!
+//!
+
+use plonky2x::backend::function::Plonky2xFunction;
+use vectorx::consts::MAX_HEADER_SIZE;
+use vectorx::header_range::HeaderRangeCircuit;
+
+fn main() {
+    const MAX_AUTHORITY_SET_SIZE: usize = 512;
+    const HEADER_RANGE_COMMITMENT_TREE_SIZE: usize = 256;
+    HeaderRangeCircuit::<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, HEADER_RANGE_COMMITMENT_TREE_SIZE>::entrypoint();
+}
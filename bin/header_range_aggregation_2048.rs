@@ -0,0 +1,19 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin header_range_aggregation_2048`
+//!
+//!
+//!
+//!
+//!
+
+use plonky2x::backend::function::Plonky2xFunction;
+use vectorx::consts::{MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE};
+use vectorx::header_range_aggregation::HeaderRangeAggregationCircuit;
+
+fn main() {
+    // Aggregates 8 header ranges of 256 headers, so the header range commitment tree size is 2048.
+    const RANGE_SIZE: usize = 256;
+    const NUM_RANGES: usize = 8;
+    HeaderRangeAggregationCircuit::<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, RANGE_SIZE, NUM_RANGES>::entrypoint();
+}
@@ -0,0 +1,21 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin header_range_kzg_256`
+//!
+//! Header range circuit which also commits to the KZG commitments of the target header.
+//!
+//!
+//!
+
+use plonky2x::backend::function::Plonky2xFunction;
+use vectorx::consts::{MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE};
+use vectorx::header_range_kzg::HeaderRangeKzgCircuit;
+
+fn main() {
+    const HEADER_RANGE_COMMITMENT_TREE_SIZE: usize = 256;
+    HeaderRangeKzgCircuit::<
+        MAX_AUTHORITY_SET_SIZE,
+        MAX_HEADER_SIZE,
+        HEADER_RANGE_COMMITMENT_TREE_SIZE,
+    >::entrypoint();
+}
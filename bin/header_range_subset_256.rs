@@ -0,0 +1,24 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin header_range_subset_256`
+//!
+//! Header range circuit which verifies the justification from a 2/3 signer subset of the authority
+//! set, instead of every authority.
+//!
+//!
+
+use plonky2x::backend::function::Plonky2xFunction;
+use vectorx::builder::justification::get_num_justification_signers;
+use vectorx::consts::{MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE};
+use vectorx::header_range_subset::HeaderRangeSubsetCircuit;
+
+fn main() {
+    const HEADER_RANGE_COMMITMENT_TREE_SIZE: usize = 256;
+    const NUM_SIGNERS: usize = get_num_justification_signers(MAX_AUTHORITY_SET_SIZE);
+    HeaderRangeSubsetCircuit::<
+        MAX_AUTHORITY_SET_SIZE,
+        MAX_HEADER_SIZE,
+        HEADER_RANGE_COMMITMENT_TREE_SIZE,
+        NUM_SIGNERS,
+    >::entrypoint();
+}
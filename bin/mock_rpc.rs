@@ -0,0 +1,344 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin mock_rpc`
+//!
+//! Serves the Avail JSON-RPC methods used by VectorX over WebSocket from a synthetic chain, or from
+//! a recorded chain in a witness bundle, so the binaries can be run end to end without an Avail
+//! node. Point AVAIL_URL at the mock node, e.g. `ws://127.0.0.1:9944`.
+//!
+//! The client fetches the runtime metadata on connect, so pass the Avail runtime metadata with
+//! `--metadata`, e.g. the output of `subxt metadata --url <AVAIL_URL> -f bytes`.
+//!
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use std::{env, fs};
+
+use avail_subxt::primitives::Header;
+use clap::Parser;
+use codec::{Decode, Encode};
+use ethers::types::{Bytes, H256};
+use jsonrpsee::server::{PendingSubscriptionSink, RpcModule, Server, SubscriptionMessage};
+use jsonrpsee::types::{ErrorObjectOwned, Params};
+use log::info;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use vectorx::mock_chain::{current_set_id_storage_key, MockChain};
+use vectorx::synthetic::{SyntheticChain, SyntheticChainConfig};
+use vectorx::witness::WitnessBundle;
+
+#[derive(Parser, Debug, Clone)]
+#[command(about = "Serve a synthetic or recorded Avail chain over JSON-RPC.")]
+pub struct MockRpcArgs {
+    #[arg(long, default_value = "127.0.0.1:9944")]
+    pub address: SocketAddr,
+    /// Serve the recorded chain in this witness bundle instead of a synthetic chain.
+    #[arg(long)]
+    pub witness: Option<String>,
+    /// SCALE encoded runtime metadata served to clients.
+    #[arg(long)]
+    pub metadata: Option<String>,
+    #[arg(long, default_value_t = 0)]
+    pub spec_version: u32,
+    #[arg(long, default_value_t = 0)]
+    pub start_block: u32,
+    #[arg(long, default_value_t = 512)]
+    pub num_blocks: u32,
+    #[arg(long, default_value_t = 100)]
+    pub epoch_length: u32,
+    /// Sizes of the synthetic authority sets, by authority set id.
+    #[arg(long, value_delimiter = ',', default_value = "10")]
+    pub authority_set_sizes: Vec<usize>,
+    #[arg(long)]
+    pub header_size: Option<usize>,
+    /// Initial finalized head. Defaults to the last block of the chain.
+    #[arg(long)]
+    pub head: Option<u32>,
+    /// Seconds between finalized blocks while the head is behind the last block of the chain.
+    #[arg(long, default_value_t = 20)]
+    pub block_time: u64,
+}
+
+struct MockNode {
+    chain: MockChain,
+    finalized_head: AtomicU32,
+    metadata: Option<Vec<u8>>,
+    spec_version: u32,
+    // Newly finalized block numbers, for the justification subscriptions.
+    finalized_blocks: broadcast::Sender<u32>,
+}
+
+fn rpc_error(message: impl Into<String>) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(-32000, message.into(), None::<()>)
+}
+
+impl MockNode {
+    fn finalized_head(&self) -> u32 {
+        self.finalized_head.load(Ordering::SeqCst)
+    }
+
+    // The block number of an optional block hash param, defaulting to the finalized head.
+    fn block_number_at(&self, block_hash: Option<H256>) -> Result<u32, ErrorObjectOwned> {
+        match block_hash {
+            Some(block_hash) => self
+                .chain
+                .block_number(block_hash)
+                .ok_or_else(|| rpc_error(format!("Unknown block {:#x}", block_hash))),
+            None => Ok(self.finalized_head()),
+        }
+    }
+
+    fn header_json(&self, block_number: u32) -> Result<Value, ErrorObjectOwned> {
+        let mut encoded_header = self.chain.header(block_number).unwrap();
+        let header = Header::decode(&mut encoded_header)
+            .map_err(|e| rpc_error(format!("Failed to decode header: {}", e)))?;
+        serde_json::to_value(header).map_err(|e| rpc_error(e.to_string()))
+    }
+
+    fn metadata(&self) -> Result<&[u8], ErrorObjectOwned> {
+        self.metadata
+            .as_deref()
+            .ok_or_else(|| rpc_error("Start the mock node with --metadata to serve metadata"))
+    }
+
+    fn state_call(
+        &self,
+        method: &str,
+        data: &[u8],
+        block_number: u32,
+    ) -> Result<Vec<u8>, ErrorObjectOwned> {
+        match method {
+            "GrandpaApi_grandpa_authorities" => self
+                .chain
+                .encode_grandpa_authorities(block_number)
+                .ok_or_else(|| rpc_error(format!("Unknown authorities at block {}", block_number))),
+            // Metadata is stored with a 4 byte magic number followed by the version.
+            "Metadata_metadata_versions" => Ok(vec![self.metadata()?[4] as u32].encode()),
+            "Metadata_metadata_at_version" => {
+                let version = u32::decode(&mut &data[..]).map_err(|e| rpc_error(e.to_string()))?;
+                let metadata = self.metadata()?;
+                if metadata[4] as u32 == version {
+                    Ok(Some(metadata.to_vec()).encode())
+                } else {
+                    Ok(None::<Vec<u8>>.encode())
+                }
+            }
+            _ => Err(rpc_error(format!("Unsupported runtime call {}", method))),
+        }
+    }
+}
+
+fn register_methods(module: &mut RpcModule<Arc<MockNode>>) {
+    module
+        .register_method(
+            "system_health",
+            |_, _| json!({ "peers": 0, "isSyncing": false, "shouldHavePeers": false }),
+        )
+        .unwrap();
+
+    module
+        .register_method(
+            "chain_getBlockHash",
+            |params: Params, node: &Arc<MockNode>| {
+                let block_number = match params.sequence().optional_next::<Value>()? {
+                    Some(Value::Number(n)) => n.as_u64().map(|n| n as u32),
+                    Some(Value::String(s)) => {
+                        u32::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+                    }
+                    _ => Some(node.finalized_head()),
+                }
+                .ok_or_else(|| rpc_error("Invalid block number"))?;
+
+                // Clients request the genesis hash on connect, which is the first block's hash if the
+                // chain doesn't start at block 0.
+                let block_number = if block_number == 0 {
+                    node.chain.start_block()
+                } else {
+                    block_number
+                };
+                Ok::<_, ErrorObjectOwned>(node.chain.block_hash(block_number))
+            },
+        )
+        .unwrap();
+
+    module
+        .register_method("chain_getHeader", |params: Params, node: &Arc<MockNode>| {
+            let block_hash: Option<H256> = params.sequence().optional_next()?;
+            match block_hash {
+                Some(block_hash) => match node.chain.block_number(block_hash) {
+                    Some(block_number) => node.header_json(block_number),
+                    None => Ok(Value::Null),
+                },
+                None => node.header_json(node.finalized_head()),
+            }
+        })
+        .unwrap();
+
+    module
+        .register_method("chain_getFinalizedHead", |_, node: &Arc<MockNode>| {
+            node.chain.block_hash(node.finalized_head()).unwrap()
+        })
+        .unwrap();
+
+    module
+        .register_method("state_getRuntimeVersion", |_, node: &Arc<MockNode>| {
+            json!({
+                "specName": "avail",
+                "implName": "avail",
+                "authoringVersion": 1,
+                "specVersion": node.spec_version,
+                "implVersion": 0,
+                "apis": [],
+                "transactionVersion": 1,
+                "stateVersion": 1,
+            })
+        })
+        .unwrap();
+
+    module
+        .register_method("state_getMetadata", |_, node: &Arc<MockNode>| {
+            Ok::<_, ErrorObjectOwned>(Bytes::from(node.metadata()?.to_vec()))
+        })
+        .unwrap();
+
+    module
+        .register_method(
+            "state_getStorage",
+            |params: Params, node: &Arc<MockNode>| {
+                let mut params = params.sequence();
+                let key: Bytes = params.next()?;
+                let block_number = node.block_number_at(params.optional_next()?)?;
+
+                // Grandpa::CurrentSetId is the only storage item served.
+                if key.as_ref() == current_set_id_storage_key().as_slice() {
+                    Ok::<_, ErrorObjectOwned>(Some(Bytes::from(
+                        node.chain.encode_current_set_id(block_number),
+                    )))
+                } else {
+                    Ok(None)
+                }
+            },
+        )
+        .unwrap();
+
+    module
+        .register_method("state_call", |params: Params, node: &Arc<MockNode>| {
+            let mut params = params.sequence();
+            let method: String = params.next()?;
+            let data: Bytes = params.next()?;
+            let block_number = node.block_number_at(params.optional_next()?)?;
+            node.state_call(&method, &data, block_number)
+                .map(Bytes::from)
+        })
+        .unwrap();
+
+    module
+        .register_method(
+            "grandpa_proveFinality",
+            |params: Params, node: &Arc<MockNode>| {
+                let block_number: u32 = params.one()?;
+                node.chain
+                    .encode_finality_proof(block_number)
+                    .map(Bytes::from)
+                    .ok_or_else(|| {
+                        rpc_error(format!("No justification for block {}", block_number))
+                    })
+            },
+        )
+        .unwrap();
+
+    module
+        .register_subscription(
+            "grandpa_subscribeJustifications",
+            "grandpa_justifications",
+            "grandpa_unsubscribeJustifications",
+            |_, pending: PendingSubscriptionSink, node: Arc<Arc<MockNode>>| async move {
+                let mut finalized_blocks = node.finalized_blocks.subscribe();
+                let sink = pending.accept().await?;
+                while let Ok(block_number) = finalized_blocks.recv().await {
+                    if let Some(justification) = node.chain.encode_justification(block_number) {
+                        let message = SubscriptionMessage::from_json(&Bytes::from(justification))?;
+                        sink.send(message).await?;
+                    }
+                }
+                Ok(())
+            },
+        )
+        .unwrap();
+}
+
+// Finalize the blocks after the head one at a time, notifying the justification subscriptions.
+async fn advance_finalized_head(node: Arc<MockNode>, block_time: Duration) {
+    while node.finalized_head() < node.chain.end_block() {
+        tokio::time::sleep(block_time).await;
+        let block_number = node.finalized_head.fetch_add(1, Ordering::SeqCst) + 1;
+        info!("Finalized block {}", block_number);
+        // Sending fails if there are no subscriptions.
+        let _ = node.finalized_blocks.send(block_number);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    env::set_var("RUST_LOG", "info");
+    dotenv::dotenv().ok();
+    env_logger::init();
+    let args = MockRpcArgs::parse();
+
+    let chain = match &args.witness {
+        Some(path) => {
+            let bundle = WitnessBundle::read(path).expect("Failed to read the witness bundle");
+            MockChain::from_witness_bundle(&bundle).expect("Invalid witness bundle")
+        }
+        None => MockChain::from_synthetic(&SyntheticChain::new(SyntheticChainConfig {
+            start_block: args.start_block,
+            num_blocks: args.num_blocks,
+            epoch_length: args.epoch_length,
+            authority_set_sizes: args.authority_set_sizes.clone(),
+            header_size: args.header_size,
+            ..Default::default()
+        })),
+    };
+    let metadata = args
+        .metadata
+        .as_ref()
+        .map(|path| fs::read(path).expect("Failed to read the metadata"));
+
+    let head = args.head.unwrap_or(chain.end_block());
+    assert!(
+        head >= chain.start_block() && head <= chain.end_block(),
+        "Head is not in the chain"
+    );
+    info!(
+        "Serving blocks [{}, {}] with finalized head {}",
+        chain.start_block(),
+        chain.end_block(),
+        head
+    );
+
+    let (finalized_blocks, _) = broadcast::channel(64);
+    let node = Arc::new(MockNode {
+        chain,
+        finalized_head: AtomicU32::new(head),
+        metadata,
+        spec_version: args.spec_version,
+        finalized_blocks,
+    });
+
+    let server = Server::builder()
+        .build(args.address)
+        .await
+        .expect("Failed to start the server");
+    let mut module = RpcModule::new(node.clone());
+    register_methods(&mut module);
+    let handle = server.start(module);
+    info!("Listening on ws://{}", args.address);
+
+    tokio::spawn(advance_finalized_head(
+        node,
+        Duration::from_secs(args.block_time),
+    ));
+    handle.stopped().await;
+}
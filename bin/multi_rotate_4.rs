@@ -0,0 +1,17 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin multi_rotate_4`
+//!
+//!
+//!
+//!
+//!
+
+use plonky2x::backend::function::Plonky2xFunction;
+use vectorx::consts::{MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE};
+use vectorx::multi_rotate::MultiRotateCircuit;
+
+fn main() {
+    const NUM_EPOCHS: usize = 4;
+    MultiRotateCircuit::<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE, NUM_EPOCHS>::entrypoint();
+}
@@ -0,0 +1,158 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin proof`
+//!
+//! Outputs a proof bundle showing an Avail block's data root (and optionally a bridge message or
+//! data submission in the block) is attested by a VectorX deployment. The bundle includes the
+//! calldata of the `eth_call` returning the on-chain data commitment it is verified against.
+//!
+//! The deployment is either an index into deployments.json (whose RPC is read from RPC_{CHAIN_ID})
+//! or CONTRACT_ADDRESS and CHAIN_ID (whose RPC is read from ETHEREUM_RPC_URL). Requires the events
+//! service to have indexed the deployment's data commitment ranges in Redis.
+//!
+
+use std::env;
+use std::fs::{self, File};
+use std::str::FromStr;
+
+use clap::Parser;
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, H256};
+use log::info;
+use vectorx::inclusion::bundle::build_proof_bundle;
+use vectorx::inclusion::get_range_hash;
+use vectorx::input::{RedisClient, RpcDataFetcher};
+
+// Note: Update ABI when updating contract.
+abigen!(VectorX, "./abi/VectorX.abi.json",);
+
+#[derive(Parser, Debug, Clone)]
+#[command(about = "Get the proof bundle that an Avail block is attested by a VectorX deployment.")]
+pub struct ProofArgs {
+    #[arg(
+        long,
+        required_unless_present = "block_hash",
+        conflicts_with = "block_hash"
+    )]
+    pub block: Option<u32>,
+    #[arg(long)]
+    pub block_hash: Option<String>,
+    /// Include the proof of the bridge message or data submission at this transaction index.
+    #[arg(long)]
+    pub tx_index: Option<u32>,
+    /// Index of the deployment in deployments.json.
+    #[arg(long)]
+    pub deployment: Option<usize>,
+    /// Write the proof bundle to this file instead of stdout.
+    #[arg(long)]
+    pub output: Option<String>,
+}
+
+// Get the chain id, address and RPC URL of the deployment.
+fn get_deployment(index: Option<usize>) -> (u64, Address, String) {
+    match index {
+        Some(index) => {
+            let deployments_file = File::open("deployments.json").unwrap();
+            let deployments_json: serde_json::Value =
+                serde_json::from_reader(deployments_file).unwrap();
+            let deployment = &deployments_json["deployments"][index];
+            let chain_id = deployment["contractChainId"]
+                .as_u64()
+                .expect("Deployment not found in deployments.json");
+            let address = deployment["contractAddress"]
+                .as_str()
+                .unwrap()
+                .parse::<Address>()
+                .expect("invalid address");
+            let rpc_url = env::var(format!("RPC_{}", chain_id))
+                .unwrap_or_else(|_| panic!("RPC_{} must be set", chain_id));
+            (chain_id, address, rpc_url)
+        }
+        None => {
+            let chain_id = env::var("CHAIN_ID")
+                .expect("CHAIN_ID must be set")
+                .parse::<u64>()
+                .expect("invalid chain id");
+            let address = env::var("CONTRACT_ADDRESS")
+                .expect("CONTRACT_ADDRESS must be set")
+                .parse::<Address>()
+                .expect("invalid address");
+            let rpc_url = env::var("ETHEREUM_RPC_URL").expect("ETHEREUM_RPC_URL must be set");
+            (chain_id, address, rpc_url)
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    env::set_var("RUST_LOG", "info");
+    dotenv::dotenv().ok();
+    env_logger::init();
+    let args = ProofArgs::parse();
+
+    let (chain_id, address, rpc_url) = get_deployment(args.deployment);
+    let provider = Provider::<Http>::try_from(rpc_url).expect("could not connect to client");
+    let contract = VectorX::new(address, provider.into());
+
+    let mut fetcher = RpcDataFetcher::new().await;
+    let block_hash = args
+        .block_hash
+        .map(|block_hash| H256::from_str(&block_hash).expect("invalid block hash"));
+    let block_number = match (args.block, block_hash) {
+        (Some(block_number), _) => block_number,
+        (None, Some(block_hash)) => {
+            fetcher
+                .get_header_by_hash(block_hash)
+                .await
+                .expect("Failed to get the block hash's header")
+                .expect("Block hash not found on Avail")
+                .number
+        }
+        (None, None) => unreachable!(),
+    };
+
+    let mut redis_client = RedisClient::new().await;
+    let range = redis_client
+        .get_data_commitment_range(chain_id, address.as_bytes().to_vec(), block_number)
+        .await
+        .expect("Block is not in a data commitment range stored in VectorX");
+    info!(
+        "Block {} is in the data commitment range ({}, {}] with commitment tree size {}",
+        block_number, range.start, range.end, range.commitment_tree_size
+    );
+
+    let range_hash = get_range_hash(range.start, range.end);
+    let on_chain_data_commitment =
+        H256::from(contract.data_root_commitments(range_hash.0).await.unwrap());
+
+    let bundle = build_proof_bundle(
+        &mut fetcher,
+        chain_id,
+        address,
+        &range,
+        on_chain_data_commitment,
+        block_number,
+        args.tx_index,
+    )
+    .await
+    .expect("Failed to build proof bundle");
+    // The block hash may be of a non-canonical block at block_number.
+    if let Some(block_hash) = block_hash {
+        assert_eq!(
+            bundle.block_hash, block_hash,
+            "Block hash {:?} is not the canonical block at block {}",
+            block_hash, block_number
+        );
+    }
+    info!("Verified proof bundle for block {}", block_number);
+
+    let bundle_json = serde_json::to_string_pretty(&bundle).unwrap();
+    match args.output {
+        Some(path) => {
+            fs::write(&path, bundle_json).expect("Failed to write proof bundle");
+            info!("Wrote proof bundle to {}", path);
+        }
+        None => println!("{}", bundle_json),
+    }
+}
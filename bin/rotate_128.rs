@@ -0,0 +1,18 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin rotate_128`
+//!
+//!
+//!
+//!
+//!
+
+use plonky2x::backend::function::Plonky2xFunction;
+use vectorx::consts::{DELAY_LENGTH, MAX_HEADER_SIZE, VALIDATOR_LENGTH};
+use vectorx::rotate::RotateCircuit;
+
+fn main() {
+    const MAX_AUTHORITY_SET_SIZE: usize = 128;
+    const MAX_SUBARRAY_SIZE: usize = MAX_AUTHORITY_SET_SIZE * VALIDATOR_LENGTH + DELAY_LENGTH;
+    RotateCircuit::<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE>::entrypoint();
+}
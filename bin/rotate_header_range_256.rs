@@ -0,0 +1,22 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin rotate_header_range_256`
+//!
+//!
+//!
+//!
+//!
+
+use plonky2x::backend::function::Plonky2xFunction;
+use vectorx::consts::{MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE};
+use vectorx::rotate_header_range::RotateHeaderRangeCircuit;
+
+fn main() {
+    const HEADER_RANGE_COMMITMENT_TREE_SIZE: usize = 256;
+    RotateHeaderRangeCircuit::<
+        MAX_AUTHORITY_SET_SIZE,
+        MAX_HEADER_SIZE,
+        MAX_SUBARRAY_SIZE,
+        HEADER_RANGE_COMMITMENT_TREE_SIZE,
+    >::entrypoint();
+}
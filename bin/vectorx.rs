@@ -2,15 +2,23 @@ use std::cmp::min;
 use std::env;
 
 use alloy_primitives::{Address, Bytes, FixedBytes, B256};
-use alloy_sol_types::{sol, SolType};
 use anyhow::Result;
+use codec::Encode;
 use ethers::abi::AbiEncode;
 use ethers::contract::abigen;
 use ethers::providers::{Http, Provider};
+use ethers::types::H256;
 use log::{error, info};
 use succinct_client::request::SuccinctClient;
-use vectorx::consts::MAX_AUTHORITY_SET_SIZE;
+use vectorx::consts::{
+    AUTHORITY_SET_SIZE_TIERS, MAX_AUTHORITY_SET_SIZE, MAX_AUTHORITY_SET_SIZE_TIER,
+    SMALL_MAX_HEADER_SIZE,
+};
+use vectorx::evm::{
+    HeaderAncestryRequest, HeaderRangeRequest, RotateHeaderRangeRequest, RotateRequest,
+};
 use vectorx::input::RpcDataFetcher;
+use vectorx::tiers::get_authority_set_size_tier;
 
 // Note: Update ABI when updating contract.
 abigen!(VectorX, "./abi/VectorX.abi.json",);
@@ -21,10 +29,6 @@ pub struct VectorXConfig {
     chain_id: u32,
 }
 
-type RotateInputTuple = sol! { tuple(uint64, bytes32) };
-
-type HeaderRangeInputTuple = sol! { tuple(uint32, bytes32, uint64, bytes32, uint32) };
-
 struct VectorXOperator {
     config: VectorXConfig,
     contract: VectorX<Provider<Http>>,
@@ -40,6 +44,27 @@ struct HeaderRangeContractData {
     header_range_commitment_tree_size: u32,
     next_authority_set_hash_exists: bool,
     header_range_function_id: B256,
+    rotate_header_range_function_id: B256,
+    header_range_aggregation_function_id: B256,
+    header_range_aggregation_range_size: u32,
+    header_range_aggregation_num_ranges: u32,
+    header_range_kzg_function_id: B256,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RequestType {
+    HeaderRange,
+    Rotate,
+}
+
+/// The function id to request a proof from. If max_authority_set_size is None, the function id is
+/// the contract's default function id, which is built for MAX_AUTHORITY_SET_SIZE. If small_header
+/// is true, the function id is the tier's header range function built for SMALL_MAX_HEADER_SIZE.
+#[derive(Debug, Clone, Copy)]
+struct TierFunctionId {
+    function_id: B256,
+    max_authority_set_size: Option<u32>,
+    small_header: bool,
 }
 
 #[derive(Debug)]
@@ -47,6 +72,9 @@ struct RotateContractData {
     current_block: u32,
     next_authority_set_hash_exists: bool,
     rotate_function_id: B256,
+    rotate_header_range_function_id: B256,
+    multi_rotate_function_id: B256,
+    multi_rotate_num_epochs: u32,
 }
 
 impl VectorXOperator {
@@ -94,7 +122,7 @@ impl VectorXOperator {
         trusted_block: u32,
         trusted_authority_set_id: u64,
         target_block: u32,
-        header_range_function_id: B256,
+        header_range_function_id: TierFunctionId,
     ) -> Result<String> {
         let client = self.get_succinct_client();
         let config = self.get_config();
@@ -103,28 +131,45 @@ impl VectorXOperator {
             .get_header_range_input_data(trusted_block, trusted_authority_set_id)
             .await;
 
-        let input = HeaderRangeInputTuple::abi_encode_packed(&(
+        let input = HeaderRangeRequest {
             trusted_block,
             trusted_header_hash,
-            trusted_authority_set_id,
-            trusted_authority_set_hash,
+            authority_set_id: trusted_authority_set_id,
+            authority_set_hash: trusted_authority_set_hash,
             target_block,
-        ));
+        }
+        .encode();
 
         // Encode the call into calldata.
         // Note: Use vector_x because the calls are the same.
-        let commit_header_range_call = vector_x::CommitHeaderRangeCall {
-            authority_set_id: trusted_authority_set_id,
-            target_block,
+        let function_data = match header_range_function_id.max_authority_set_size {
+            Some(max_authority_set_size) if header_range_function_id.small_header => {
+                vector_x::CommitHeaderRangeSmallTierCall {
+                    max_authority_set_size,
+                    authority_set_id: trusted_authority_set_id,
+                    target_block,
+                }
+                .encode()
+            }
+            Some(max_authority_set_size) => vector_x::CommitHeaderRangeTierCall {
+                max_authority_set_size,
+                authority_set_id: trusted_authority_set_id,
+                target_block,
+            }
+            .encode(),
+            None => vector_x::CommitHeaderRangeCall {
+                authority_set_id: trusted_authority_set_id,
+                target_block,
+            }
+            .encode(),
         };
-        let function_data = commit_header_range_call.encode();
 
         let request_id = client
             .submit_platform_request(
                 config.chain_id,
                 config.address,
                 function_data.into(),
-                header_range_function_id,
+                header_range_function_id.function_id,
                 Bytes::copy_from_slice(&input),
             )
             .await?;
@@ -135,7 +180,7 @@ impl VectorXOperator {
     async fn request_rotate(
         &mut self,
         current_authority_set_id: u64,
-        rotate_function_id: B256,
+        rotate_function_id: TierFunctionId,
     ) -> Result<String> {
         let client = self.get_succinct_client();
         let config = self.get_config();
@@ -147,22 +192,106 @@ impl VectorXOperator {
             hex::encode(current_authority_set_hash)
         );
 
-        let input = RotateInputTuple::abi_encode_packed(&(
+        let input = RotateRequest {
+            authority_set_id: current_authority_set_id,
+            authority_set_hash: current_authority_set_hash,
+        }
+        .encode();
+
+        let function_data = match rotate_function_id.max_authority_set_size {
+            Some(max_authority_set_size) => vector_x::RotateTierCall {
+                max_authority_set_size,
+                current_authority_set_id,
+            }
+            .encode(),
+            None => vector_x::RotateCall {
+                current_authority_set_id,
+            }
+            .encode(),
+        };
+
+        let request_id = client
+            .submit_platform_request(
+                config.chain_id,
+                config.address,
+                function_data.into(),
+                rotate_function_id.function_id,
+                Bytes::copy_from_slice(&input),
+            )
+            .await?;
+
+        Ok(request_id)
+    }
+
+    async fn request_multi_rotate(
+        &mut self,
+        current_authority_set_id: u64,
+        multi_rotate_function_id: B256,
+    ) -> Result<String> {
+        let client = self.get_succinct_client();
+        let config = self.get_config();
+
+        let current_authority_set_hash = self.get_rotate_input_data(current_authority_set_id).await;
+
+        let input = RotateRequest {
+            authority_set_id: current_authority_set_id,
+            authority_set_hash: current_authority_set_hash,
+        }
+        .encode();
+
+        let function_data = vector_x::MultiRotateCall {
             current_authority_set_id,
-            current_authority_set_hash,
-        ));
+        }
+        .encode();
+
+        let request_id = client
+            .submit_platform_request(
+                config.chain_id,
+                config.address,
+                function_data.into(),
+                multi_rotate_function_id,
+                Bytes::copy_from_slice(&input),
+            )
+            .await?;
+
+        Ok(request_id)
+    }
+
+    async fn request_rotate_header_range(
+        &mut self,
+        current_authority_set_id: u64,
+        trusted_block: u32,
+        target_block: u32,
+        rotate_header_range_function_id: B256,
+    ) -> Result<String> {
+        let client = self.get_succinct_client();
+        let config = self.get_config();
+
+        let (trusted_header_hash, current_authority_set_hash) = self
+            .get_header_range_input_data(trusted_block, current_authority_set_id)
+            .await;
 
-        let rotate_call = vector_x::RotateCall {
+        let input = RotateHeaderRangeRequest {
+            authority_set_id: current_authority_set_id,
+            authority_set_hash: current_authority_set_hash,
+            trusted_block,
+            trusted_header_hash,
+            target_block,
+        }
+        .encode();
+
+        let function_data = vector_x::RotateHeaderRangeCall {
             current_authority_set_id,
-        };
-        let function_data = rotate_call.encode();
+            target_block,
+        }
+        .encode();
 
         let request_id = client
             .submit_platform_request(
                 config.chain_id,
                 config.address,
                 function_data.into(),
-                rotate_function_id,
+                rotate_header_range_function_id,
                 Bytes::copy_from_slice(&input),
             )
             .await?;
@@ -170,7 +299,88 @@ impl VectorXOperator {
         Ok(request_id)
     }
 
-    async fn find_and_request_rotate(&mut self) {
+    async fn request_header_range_aggregation(
+        &mut self,
+        request: &HeaderRangeRequest,
+        header_range_aggregation_function_id: B256,
+    ) -> Result<String> {
+        let client = self.get_succinct_client();
+        let config = self.get_config();
+
+        let function_data = vector_x::CommitHeaderRangeAggregationCall {
+            authority_set_id: request.authority_set_id,
+            target_block: request.target_block,
+        }
+        .encode();
+
+        let request_id = client
+            .submit_platform_request(
+                config.chain_id,
+                config.address,
+                function_data.into(),
+                header_range_aggregation_function_id,
+                Bytes::copy_from_slice(&request.encode()),
+            )
+            .await?;
+
+        Ok(request_id)
+    }
+
+    async fn request_header_range_kzg(
+        &mut self,
+        request: &HeaderRangeRequest,
+        header_range_kzg_function_id: B256,
+    ) -> Result<String> {
+        let client = self.get_succinct_client();
+        let config = self.get_config();
+
+        let function_data = vector_x::CommitHeaderRangeKzgCall {
+            authority_set_id: request.authority_set_id,
+            target_block: request.target_block,
+        }
+        .encode();
+
+        let request_id = client
+            .submit_platform_request(
+                config.chain_id,
+                config.address,
+                function_data.into(),
+                header_range_kzg_function_id,
+                Bytes::copy_from_slice(&request.encode()),
+            )
+            .await?;
+
+        Ok(request_id)
+    }
+
+    async fn request_header_ancestry(
+        &mut self,
+        request: &HeaderAncestryRequest,
+        header_ancestry_function_id: B256,
+    ) -> Result<String> {
+        let client = self.get_succinct_client();
+        let config = self.get_config();
+
+        let function_data = vector_x::AttestHeaderAncestryCall {
+            trusted_block: request.trusted_block,
+            target_block: request.target_block,
+        }
+        .encode();
+
+        let request_id = client
+            .submit_platform_request(
+                config.chain_id,
+                config.address,
+                function_data.into(),
+                header_ancestry_function_id,
+                Bytes::copy_from_slice(&request.encode()),
+            )
+            .await?;
+
+        Ok(request_id)
+    }
+
+    async fn find_and_request_rotate(&mut self, ideal_block_interval: u32) {
         let rotate_contract_data = self.get_contract_data_for_rotate().await;
 
         let head = self.data_fetcher.get_head().await;
@@ -186,18 +396,94 @@ impl VectorXOperator {
         if current_authority_set_id < head_authority_set_id
             && !rotate_contract_data.next_authority_set_hash_exists
         {
+            // If the contract is at least multi_rotate_num_epochs authority sets behind Avail,
+            // rotate through them in a single proof.
+            if rotate_contract_data.multi_rotate_function_id != B256::ZERO
+                && head_authority_set_id - current_authority_set_id
+                    >= rotate_contract_data.multi_rotate_num_epochs as u64
+            {
+                info!(
+                    "Requesting multi rotate to authority set id {:?}.",
+                    current_authority_set_id + rotate_contract_data.multi_rotate_num_epochs as u64
+                );
+                match self
+                    .request_multi_rotate(
+                        current_authority_set_id,
+                        rotate_contract_data.multi_rotate_function_id,
+                    )
+                    .await
+                {
+                    Ok(request_id) => {
+                        info!("Multi rotate request submitted: {}", request_id)
+                    }
+                    Err(e) => {
+                        error!("Multi rotate request failed: {}", e);
+                    }
+                };
+                return;
+            }
+
             info!(
                 "Requesting rotate to next authority set id, which is {:?}.",
                 current_authority_set_id + 1
             );
 
-            // Request a rotate for the next authority set id.
-            match self
-                .request_rotate(
-                    current_authority_set_id,
+            // The rotate circuit must fit both the current authority set, which signs the epoch end
+            // block, and the next authority set, which is specified in the epoch end block.
+            let epoch_end_block = self
+                .data_fetcher
+                .last_justified_block(current_authority_set_id)
+                .await;
+
+            // If the contract is at the epoch end block, the next header range needs the next
+            // authority set. Request both in a single rotate header range proof instead, and fall
+            // back to a rotate if no rotate header range request is submitted.
+            if rotate_contract_data.rotate_header_range_function_id != B256::ZERO
+                && rotate_contract_data.current_block == epoch_end_block
+            {
+                let header_range_contract_data = self.get_contract_data_for_header_range().await;
+                if self
+                    .find_and_request_rotate_header_range(
+                        ideal_block_interval,
+                        &header_range_contract_data,
+                        current_authority_set_id,
+                    )
+                    .await
+                {
+                    return;
+                }
+                info!(
+                    "No rotate header range request submitted, requesting rotate to next authority set id {:?}.",
+                    current_authority_set_id + 1
+                );
+            }
+
+            let num_current_authorities = self
+                .data_fetcher
+                .get_authorities(epoch_end_block - 1)
+                .await
+                .len();
+            let num_next_authorities = self
+                .data_fetcher
+                .get_authorities(epoch_end_block)
+                .await
+                .len();
+            let rotate_function_id = match self
+                .get_tier_function_id(
+                    RequestType::Rotate,
+                    num_current_authorities.max(num_next_authorities),
                     rotate_contract_data.rotate_function_id,
                 )
                 .await
+            {
+                Some(rotate_function_id) => rotate_function_id,
+                None => return,
+            };
+
+            // Request a rotate for the next authority set id.
+            match self
+                .request_rotate(current_authority_set_id, rotate_function_id)
+                .await
             {
                 Ok(request_id) => {
                     info!("Rotate request submitted: {}", request_id)
@@ -230,13 +516,25 @@ impl VectorXOperator {
         if header_range_contract_data.vectorx_latest_block == last_justified_block {
             let next_authority_set_id = current_authority_set_id + 1;
 
-            // Check if the next authority set id exists in the contract. If not, a rotate is needed.
+            // Check if the next authority set id exists in the contract. If not, a rotate is needed,
+            // which find_and_request_rotate requests, with the header range if the contract has a
+            // rotate header range function.
             if !header_range_contract_data.next_authority_set_hash_exists {
                 return;
             }
             request_authority_set_id = next_authority_set_id;
         }
 
+        // If the contract is far enough behind Avail, aggregate several header ranges into one
+        // proof instead.
+        if header_range_contract_data.header_range_aggregation_function_id != B256::ZERO
+            && self
+                .find_and_request_header_range_aggregation(&header_range_contract_data)
+                .await
+        {
+            return;
+        }
+
         // Find the block to step to. If no block is returned, either 1) there is no block satisfying
         // the conditions that is available to step to or 2) something has gone wrong with the indexer.
         let block_to_step_to = self
@@ -252,6 +550,46 @@ impl VectorXOperator {
             return;
         }
 
+        // If the contract has a header range KZG function, request every header range with it, so
+        // the KZG commitments of each target block are stored. It is built for
+        // MAX_AUTHORITY_SET_SIZE and MAX_HEADER_SIZE, so it fits every header range.
+        if header_range_contract_data.header_range_kzg_function_id != B256::ZERO {
+            self.find_and_request_header_range_kzg(
+                &header_range_contract_data,
+                request_authority_set_id,
+                block_to_step_to.unwrap(),
+            )
+            .await;
+            return;
+        }
+
+        // The authority set which validates the block after the latest block in the contract
+        // signs the justification of the target block.
+        let num_authorities = self
+            .data_fetcher
+            .get_authorities(header_range_contract_data.vectorx_latest_block)
+            .await
+            .len();
+        let header_range_function_id = match self
+            .get_tier_function_id(
+                RequestType::HeaderRange,
+                num_authorities,
+                header_range_contract_data.header_range_function_id,
+            )
+            .await
+        {
+            Some(header_range_function_id) => header_range_function_id,
+            None => return,
+        };
+
+        let header_range_function_id = self
+            .get_small_header_function_id(
+                header_range_function_id,
+                header_range_contract_data.vectorx_latest_block,
+                block_to_step_to.unwrap(),
+            )
+            .await;
+
         info!(
             "Requesting header range with end block: {:?}.",
             block_to_step_to.unwrap()
@@ -263,7 +601,7 @@ impl VectorXOperator {
                 header_range_contract_data.vectorx_latest_block,
                 request_authority_set_id,
                 block_to_step_to.unwrap(),
-                header_range_contract_data.header_range_function_id,
+                header_range_function_id,
             )
             .await
         {
@@ -281,11 +619,286 @@ impl VectorXOperator {
         };
     }
 
+    // Request a header range KZG proof from the contract's latest block to target_block.
+    async fn find_and_request_header_range_kzg(
+        &mut self,
+        header_range_contract_data: &HeaderRangeContractData,
+        authority_set_id: u64,
+        target_block: u32,
+    ) {
+        let trusted_block = header_range_contract_data.vectorx_latest_block;
+        let (trusted_header_hash, authority_set_hash) = self
+            .get_header_range_input_data(trusted_block, authority_set_id)
+            .await;
+        let request = HeaderRangeRequest {
+            trusted_block,
+            trusted_header_hash,
+            authority_set_id,
+            authority_set_hash,
+            target_block,
+        };
+
+        info!(
+            "Requesting header range KZG with end block: {:?}.",
+            target_block
+        );
+
+        match self
+            .request_header_range_kzg(
+                &request,
+                header_range_contract_data.header_range_kzg_function_id,
+            )
+            .await
+        {
+            Ok(request_id) => {
+                info!(
+                    "Header range KZG request submitted from block {} to block {} with request ID: {}",
+                    trusted_block, target_block, request_id
+                )
+            }
+            Err(e) => {
+                error!("Header range KZG request failed: {}", e);
+            }
+        };
+    }
+
+    // Request a rotate from current_authority_set_id and a header range from the contract's latest
+    // block, which is the epoch end block of current_authority_set_id, justified by the next
+    // authority set. Returns whether a request was submitted.
+    async fn find_and_request_rotate_header_range(
+        &mut self,
+        ideal_block_interval: u32,
+        header_range_contract_data: &HeaderRangeContractData,
+        current_authority_set_id: u64,
+    ) -> bool {
+        let block_to_step_to = match self
+            .find_block_to_step_to(
+                ideal_block_interval,
+                header_range_contract_data.header_range_commitment_tree_size,
+                header_range_contract_data.vectorx_latest_block,
+                header_range_contract_data.avail_current_block,
+                current_authority_set_id + 1,
+            )
+            .await
+        {
+            Some(block_to_step_to) => block_to_step_to,
+            None => return false,
+        };
+
+        info!(
+            "Requesting rotate header range to next authority set id {:?} with end block: {:?}.",
+            current_authority_set_id + 1,
+            block_to_step_to
+        );
+
+        match self
+            .request_rotate_header_range(
+                current_authority_set_id,
+                header_range_contract_data.vectorx_latest_block,
+                block_to_step_to,
+                header_range_contract_data.rotate_header_range_function_id,
+            )
+            .await
+        {
+            Ok(request_id) => {
+                info!(
+                    "Rotate header range request submitted from block {} to block {} with request ID: {}",
+                    header_range_contract_data.vectorx_latest_block, block_to_step_to, request_id
+                );
+                true
+            }
+            Err(e) => {
+                error!("Rotate header range request failed: {}", e);
+                false
+            }
+        }
+    }
+
+    // Request a header ancestry proof of target_block from the closest header at or after it in the
+    // contract, if target_block is not attested yet. The header must be at most max_num_headers - 1
+    // blocks after target_block.
+    async fn find_and_request_header_ancestry(&mut self, target_block: u32) {
+        let header_ancestry_function_id: B256 =
+            FixedBytes(self.contract.header_ancestry_function_id().await.unwrap());
+        if header_ancestry_function_id == B256::ZERO {
+            info!(
+                "No header ancestry function id is set, so block {} can't be attested.",
+                target_block
+            );
+            return;
+        }
+
+        let data_root = self
+            .contract
+            .block_height_to_data_root(target_block)
+            .await
+            .unwrap();
+        if B256::from_slice(&data_root) != B256::ZERO {
+            return;
+        }
+
+        let max_num_headers = self
+            .contract
+            .header_ancestry_max_num_headers()
+            .await
+            .unwrap();
+        let latest_block = self.contract.latest_block().await.unwrap();
+        if target_block > latest_block {
+            info!(
+                "Block {} is after the contract's latest block {}.",
+                target_block, latest_block
+            );
+            return;
+        }
+
+        let max_trusted_block = min(target_block + max_num_headers - 1, latest_block);
+        let mut trusted = None;
+        for trusted_block in target_block..=max_trusted_block {
+            let trusted_header_hash = H256::from(
+                self.contract
+                    .block_height_to_header_hash(trusted_block)
+                    .await
+                    .unwrap(),
+            );
+            if trusted_header_hash != H256::zero() {
+                trusted = Some((trusted_block, trusted_header_hash));
+                break;
+            }
+        }
+        let (trusted_block, trusted_header_hash) = match trusted {
+            Some(trusted) => trusted,
+            None => {
+                info!(
+                    "No header from block {} to block {} is in the contract, so block {} can't be attested.",
+                    target_block, max_trusted_block, target_block
+                );
+                return;
+            }
+        };
+
+        let request = HeaderAncestryRequest {
+            trusted_block,
+            trusted_header_hash,
+            target_block,
+        };
+
+        info!(
+            "Requesting header ancestry of block {} from block {}.",
+            target_block, trusted_block
+        );
+
+        match self
+            .request_header_ancestry(&request, header_ancestry_function_id)
+            .await
+        {
+            Ok(request_id) => {
+                info!(
+                    "Header ancestry request submitted for block {} with request ID: {}",
+                    target_block, request_id
+                )
+            }
+            Err(e) => {
+                error!("Header ancestry request failed: {}", e);
+            }
+        };
+    }
+
+    // If the contract is more than (num_ranges - 1) * range_size blocks behind Avail, request a header
+    // range aggregation from the contract's latest block to the latest block with a justification in
+    // the last range. Only the target block must have a justification, and the authority set which
+    // justifies it must be in the contract. Returns whether a request was submitted.
+    async fn find_and_request_header_range_aggregation(
+        &mut self,
+        header_range_contract_data: &HeaderRangeContractData,
+    ) -> bool {
+        let trusted_block = header_range_contract_data.vectorx_latest_block;
+        let range_size = header_range_contract_data.header_range_aggregation_range_size;
+        let num_ranges = header_range_contract_data.header_range_aggregation_num_ranges;
+        let min_target_block = trusted_block + (num_ranges - 1) * range_size + 1;
+        let max_target_block = min(
+            trusted_block + num_ranges * range_size,
+            header_range_contract_data.avail_current_block,
+        );
+        if max_target_block < min_target_block {
+            return false;
+        }
+
+        // Step to the latest block in the last range with a justification.
+        let mut target_block = max_target_block;
+        while !self.has_justification(target_block).await {
+            if target_block == min_target_block {
+                info!(
+                    "No block from block {} to block {} has a justification, so the header ranges can't be aggregated.",
+                    min_target_block, max_target_block
+                );
+                return false;
+            }
+            target_block -= 1;
+        }
+
+        // The authority set in the state of the block before the target block justifies it.
+        let authority_set_id = self
+            .data_fetcher
+            .get_authority_set_id(target_block - 1)
+            .await;
+        let (trusted_header_hash, authority_set_hash) = self
+            .get_header_range_input_data(trusted_block, authority_set_id)
+            .await;
+        if authority_set_hash == H256::zero() {
+            info!(
+                "Authority set id {} which justifies block {} is not in the contract.",
+                authority_set_id, target_block
+            );
+            return false;
+        }
+        let request = HeaderRangeRequest {
+            trusted_block,
+            trusted_header_hash,
+            authority_set_id,
+            authority_set_hash,
+            target_block,
+        };
+
+        info!(
+            "Requesting header range aggregation of {} ranges with end block: {:?}.",
+            num_ranges, target_block
+        );
+
+        match self
+            .request_header_range_aggregation(
+                &request,
+                header_range_contract_data.header_range_aggregation_function_id,
+            )
+            .await
+        {
+            Ok(request_id) => {
+                info!(
+                    "Header range aggregation request submitted from block {} to block {} with request ID: {}",
+                    trusted_block, target_block, request_id
+                );
+                true
+            }
+            Err(e) => {
+                error!("Header range aggregation request failed: {}", e);
+                false
+            }
+        }
+    }
+
+    async fn has_justification(&mut self, block_number: u32) -> bool {
+        self.is_dummy_operator
+            || self
+                .data_fetcher
+                .get_justification_from_block::<MAX_AUTHORITY_SET_SIZE_TIER>(block_number)
+                .await
+                .is_ok()
+    }
+
     async fn get_header_range_input_data(
         &mut self,
         trusted_block: u32,
         trusted_authority_set_id: u64,
-    ) -> (B256, B256) {
+    ) -> (H256, H256) {
         let trusted_header_hash = self
             .contract
             .block_height_to_header_hash(trusted_block)
@@ -298,14 +911,14 @@ impl VectorXOperator {
             .unwrap();
 
         (
-            B256::from_slice(&trusted_header_hash),
-            B256::from_slice(&trusted_authority_set_hash),
+            H256::from(trusted_header_hash),
+            H256::from(trusted_authority_set_hash),
         )
     }
 
     // Current authority set hash.
-    async fn get_rotate_input_data(&mut self, current_authority_set_id: u64) -> B256 {
-        alloy_primitives::FixedBytes(
+    async fn get_rotate_input_data(&mut self, current_authority_set_id: u64) -> H256 {
+        H256::from(
             self.contract
                 .authority_set_id_to_hash(current_authority_set_id)
                 .await
@@ -317,6 +930,30 @@ impl VectorXOperator {
     async fn get_contract_data_for_header_range(&mut self) -> HeaderRangeContractData {
         let header_range_function_id: B256 =
             FixedBytes(self.contract.header_range_function_id().await.unwrap());
+        let rotate_header_range_function_id: B256 = FixedBytes(
+            self.contract
+                .rotate_header_range_function_id()
+                .await
+                .unwrap(),
+        );
+        let header_range_aggregation_function_id: B256 = FixedBytes(
+            self.contract
+                .header_range_aggregation_function_id()
+                .await
+                .unwrap(),
+        );
+        let header_range_aggregation_range_size = self
+            .contract
+            .header_range_aggregation_range_size()
+            .await
+            .unwrap();
+        let header_range_aggregation_num_ranges = self
+            .contract
+            .header_range_aggregation_num_ranges()
+            .await
+            .unwrap();
+        let header_range_kzg_function_id: B256 =
+            FixedBytes(self.contract.header_range_kzg_function_id().await.unwrap());
         let vectorx_latest_block = self.contract.latest_block().await.unwrap();
         let header_range_commitment_tree_size = self
             .contract
@@ -345,6 +982,11 @@ impl VectorXOperator {
             next_authority_set_hash_exists: B256::from_slice(&next_authority_set_hash)
                 != B256::ZERO,
             header_range_function_id,
+            rotate_header_range_function_id,
+            header_range_aggregation_function_id,
+            header_range_aggregation_range_size,
+            header_range_aggregation_num_ranges,
+            header_range_kzg_function_id,
         }
     }
 
@@ -352,6 +994,15 @@ impl VectorXOperator {
     async fn get_contract_data_for_rotate(&mut self) -> RotateContractData {
         let rotate_function_id: B256 =
             FixedBytes(self.contract.rotate_function_id().await.unwrap());
+        let rotate_header_range_function_id: B256 = FixedBytes(
+            self.contract
+                .rotate_header_range_function_id()
+                .await
+                .unwrap(),
+        );
+        let multi_rotate_function_id: B256 =
+            FixedBytes(self.contract.multi_rotate_function_id().await.unwrap());
+        let multi_rotate_num_epochs = self.contract.multi_rotate_num_epochs().await.unwrap();
         let current_block = self.contract.latest_block().await.unwrap();
 
         let current_authority_set_id = self
@@ -371,6 +1022,114 @@ impl VectorXOperator {
             next_authority_set_hash_exists: B256::from_slice(&next_authority_set_hash)
                 != B256::ZERO,
             rotate_function_id,
+            rotate_header_range_function_id,
+            multi_rotate_function_id,
+            multi_rotate_num_epochs,
+        }
+    }
+
+    // Get the function id of the smallest authority set size tier that fits num_authorities and has a
+    // function id in the contract. The contract's default function id is built for the
+    // MAX_AUTHORITY_SET_SIZE tier.
+    async fn get_tier_function_id(
+        &mut self,
+        request_type: RequestType,
+        num_authorities: usize,
+        default_function_id: B256,
+    ) -> Option<TierFunctionId> {
+        let smallest_tier = match get_authority_set_size_tier(num_authorities) {
+            Some(tier) => tier,
+            None => {
+                error!(
+                    "Authority set of {} authorities is larger than the largest authority set size tier of {}.",
+                    num_authorities, MAX_AUTHORITY_SET_SIZE_TIER
+                );
+                return None;
+            }
+        };
+
+        for tier in AUTHORITY_SET_SIZE_TIERS
+            .iter()
+            .copied()
+            .filter(|&tier| tier >= smallest_tier)
+        {
+            let tier_function_id = match request_type {
+                RequestType::HeaderRange => self
+                    .contract
+                    .header_range_tier_function_ids(tier as u32)
+                    .await
+                    .unwrap(),
+                RequestType::Rotate => self
+                    .contract
+                    .rotate_tier_function_ids(tier as u32)
+                    .await
+                    .unwrap(),
+            };
+            if tier_function_id != [0u8; 32] {
+                return Some(TierFunctionId {
+                    function_id: FixedBytes(tier_function_id),
+                    max_authority_set_size: Some(tier as u32),
+                    small_header: false,
+                });
+            }
+            if tier == MAX_AUTHORITY_SET_SIZE && default_function_id != B256::ZERO {
+                return Some(TierFunctionId {
+                    function_id: default_function_id,
+                    max_authority_set_size: None,
+                    small_header: false,
+                });
+            }
+        }
+
+        error!(
+            "No {:?} function id in the contract fits an authority set of {} authorities.",
+            request_type, num_authorities
+        );
+        None
+    }
+
+    // If every header in (trusted_block, target_block] is at most SMALL_MAX_HEADER_SIZE bytes and the
+    // tier has a small max header size header range function in the contract, use it instead, as
+    // hashing headers padded to MAX_HEADER_SIZE dominates header range proving time. Otherwise, fall
+    // back to header_range_function_id.
+    async fn get_small_header_function_id(
+        &mut self,
+        header_range_function_id: TierFunctionId,
+        trusted_block: u32,
+        target_block: u32,
+    ) -> TierFunctionId {
+        let max_authority_set_size = header_range_function_id
+            .max_authority_set_size
+            .unwrap_or(MAX_AUTHORITY_SET_SIZE as u32);
+        let small_header_function_id = self
+            .contract
+            .small_header_range_tier_function_ids(max_authority_set_size)
+            .await
+            .unwrap();
+        if small_header_function_id == [0u8; 32] {
+            return header_range_function_id;
+        }
+
+        let max_header_size = self
+            .data_fetcher
+            .get_block_headers_range(trusted_block + 1, target_block)
+            .await
+            .iter()
+            .map(|header| header.encode().len())
+            .max()
+            .unwrap_or(0);
+        if max_header_size > SMALL_MAX_HEADER_SIZE {
+            info!(
+                "Header of {} bytes is larger than the small max header size of {} bytes.",
+                max_header_size, SMALL_MAX_HEADER_SIZE
+            );
+            return header_range_function_id;
+        }
+
+        TierFunctionId {
+            function_id: FixedBytes(small_header_function_id),
+            max_authority_set_size: Some(max_authority_set_size),
+            small_header: true,
         }
     }
 
@@ -446,7 +1205,7 @@ impl VectorXOperator {
 
             if self
                 .data_fetcher
-                .get_justification_from_block::<MAX_AUTHORITY_SET_SIZE>(block_to_step_to)
+                .get_justification_from_block::<MAX_AUTHORITY_SET_SIZE_TIER>(block_to_step_to)
                 .await
                 .is_ok()
             {
@@ -466,11 +1225,16 @@ impl VectorXOperator {
             let block_interval = get_update_delay_blocks();
 
             // Check if there is a rotate available for the next authority set.
-            self.find_and_request_rotate().await;
+            self.find_and_request_rotate(block_interval).await;
 
             // Check if there is a header range request available.
             self.find_and_request_header_range(block_interval).await;
 
+            // Attest the requested blocks which are not covered by a header range commitment.
+            for target_block in get_attest_blocks() {
+                self.find_and_request_header_ancestry(target_block).await;
+            }
+
             // Sleep for N minutes.
             info!("Sleeping for {} minutes.", loop_delay_mins);
             tokio::time::sleep(tokio::time::Duration::from_secs(60 * loop_delay_mins)).await;
@@ -502,6 +1266,18 @@ fn get_update_delay_blocks() -> u32 {
     update_delay_blocks
 }
 
+// The blocks to attest with header ancestry proofs, from the comma separated ATTEST_BLOCKS.
+fn get_attest_blocks() -> Vec<u32> {
+    match env::var("ATTEST_BLOCKS") {
+        Ok(attest_blocks) => attest_blocks
+            .split(',')
+            .filter(|block| !block.trim().is_empty())
+            .map(|block| block.trim().parse::<u32>().expect("invalid ATTEST_BLOCKS"))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
 #[tokio::main]
 async fn main() {
     env::set_var("RUST_LOG", "info");
@@ -0,0 +1,346 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin verify_proof`
+//!
+//! Verifies a header range or rotate proof locally against the circuit build, without the Succinct
+//! platform. Decodes the EVM-encoded inputs and outputs of the proof into named fields, and if
+//! CONTRACT_ADDRESS and ETHEREUM_RPC_URL are set, checks them against the VectorX contract's state.
+//!
+//! The proof is the output of the circuit's `prove` command and the input is its proof request,
+//! e.g. `output.json` and `input.json`. The circuit build is the output of the circuit's `build`
+//! command, and must be of the given circuit and tier.
+//!
+
+use std::{env, fs};
+
+use anyhow::{anyhow, ensure, Result};
+use clap::{Parser, ValueEnum};
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, H256};
+use log::info;
+use plonky2x::backend::circuit::{Circuit, CircuitBuild, PublicInput, PublicOutput};
+use plonky2x::prelude::plonky2::plonk::proof::ProofWithPublicInputs;
+use plonky2x::prelude::{DefaultParameters, GateRegistry, HintRegistry, PlonkParameters};
+use vectorx::consts::{
+    DELAY_LENGTH, MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, SMALL_MAX_HEADER_SIZE, VALIDATOR_LENGTH,
+};
+use vectorx::evm::{HeaderRangeRequest, HeaderRangeResponse, RotateRequest, RotateResponse};
+use vectorx::header_range::HeaderRangeCircuit;
+use vectorx::inclusion::get_range_hash;
+use vectorx::rotate::RotateCircuit;
+
+// Note: Update ABI when updating contract.
+abigen!(VectorX, "./abi/VectorX.abi.json",);
+
+type L = DefaultParameters;
+const D: usize = 2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum CircuitKind {
+    HeaderRange,
+    Rotate,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[command(
+    about = "Verify a header range or rotate proof locally and inspect its inputs and outputs."
+)]
+struct VerifyProofArgs {
+    #[arg(long, value_enum)]
+    circuit: CircuitKind,
+    /// Max authority set size of the circuit's authority set size tier.
+    #[arg(long, default_value_t = MAX_AUTHORITY_SET_SIZE)]
+    max_authority_set_size: usize,
+    /// Header range commitment tree size of a header range circuit.
+    #[arg(long, default_value_t = 256)]
+    num_headers: usize,
+    /// Whether the header range circuit is built for SMALL_MAX_HEADER_SIZE.
+    #[arg(long)]
+    small_header: bool,
+    #[arg(long, default_value = "build/main.circuit")]
+    build: String,
+    #[arg(long, default_value = "input.json")]
+    input: String,
+    #[arg(long, default_value = "output.json")]
+    proof: String,
+}
+
+// Read the hex field at pointer in a proof request or result JSON file.
+fn read_hex_field(path: &str, pointer: &str) -> Result<Vec<u8>> {
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let hex_str = json
+        .pointer(pointer)
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| anyhow!("{} has no {}", path, pointer))?;
+    Ok(hex::decode(hex_str.trim_start_matches("0x"))?)
+}
+
+/// Load the circuit build of C and verify the proof with the given EVM input and output.
+fn verify<C: Circuit>(build: &str, proof: Vec<u8>, input: &[u8], output: &[u8]) -> Result<()> {
+    let mut hint_registry = HintRegistry::<L, D>::new();
+    let mut gate_registry = GateRegistry::<L, D>::new();
+    C::register_generators(&mut hint_registry);
+    C::register_gates(&mut gate_registry);
+
+    info!("Loading the circuit build at {}", build);
+    let circuit = CircuitBuild::<L, D>::load(build, &gate_registry, &hint_registry)
+        .map_err(|e| anyhow!("Failed to load the circuit build: {:?}", e))?;
+    let proof = ProofWithPublicInputs::<
+        <L as PlonkParameters<D>>::Field,
+        <L as PlonkParameters<D>>::Config,
+        D,
+    >::from_bytes(proof, &circuit.data.common)?;
+
+    // Verifies the proof, and that its public inputs are the input and output.
+    circuit.verify(
+        &proof,
+        &PublicInput::Bytes(input.to_vec()),
+        &PublicOutput::Bytes(output.to_vec()),
+    );
+    Ok(())
+}
+
+fn verify_tier(args: &VerifyProofArgs, proof: Vec<u8>, input: &[u8], output: &[u8]) -> Result<()> {
+    const SUBARRAY_SIZE_64: usize = 64 * VALIDATOR_LENGTH + DELAY_LENGTH;
+    const SUBARRAY_SIZE_128: usize = 128 * VALIDATOR_LENGTH + DELAY_LENGTH;
+    const SUBARRAY_SIZE_300: usize = MAX_AUTHORITY_SET_SIZE * VALIDATOR_LENGTH + DELAY_LENGTH;
+    const SUBARRAY_SIZE_512: usize = 512 * VALIDATOR_LENGTH + DELAY_LENGTH;
+
+    let build = args.build.as_str();
+    match (
+        args.circuit,
+        args.max_authority_set_size,
+        args.num_headers,
+        args.small_header,
+    ) {
+        (CircuitKind::HeaderRange, MAX_AUTHORITY_SET_SIZE, 256, true) => {
+            verify::<HeaderRangeCircuit<MAX_AUTHORITY_SET_SIZE, SMALL_MAX_HEADER_SIZE, 256>>(
+                build, proof, input, output,
+            )
+        }
+        (CircuitKind::HeaderRange, MAX_AUTHORITY_SET_SIZE, 512, true) => {
+            verify::<HeaderRangeCircuit<MAX_AUTHORITY_SET_SIZE, SMALL_MAX_HEADER_SIZE, 512>>(
+                build, proof, input, output,
+            )
+        }
+        (CircuitKind::HeaderRange, 64, 256, false) => {
+            verify::<HeaderRangeCircuit<64, MAX_HEADER_SIZE, 256>>(build, proof, input, output)
+        }
+        (CircuitKind::HeaderRange, 64, 512, false) => {
+            verify::<HeaderRangeCircuit<64, MAX_HEADER_SIZE, 512>>(build, proof, input, output)
+        }
+        (CircuitKind::HeaderRange, 128, 256, false) => {
+            verify::<HeaderRangeCircuit<128, MAX_HEADER_SIZE, 256>>(build, proof, input, output)
+        }
+        (CircuitKind::HeaderRange, 128, 512, false) => {
+            verify::<HeaderRangeCircuit<128, MAX_HEADER_SIZE, 512>>(build, proof, input, output)
+        }
+        (CircuitKind::HeaderRange, MAX_AUTHORITY_SET_SIZE, 256, false) => {
+            verify::<HeaderRangeCircuit<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, 256>>(
+                build, proof, input, output,
+            )
+        }
+        (CircuitKind::HeaderRange, MAX_AUTHORITY_SET_SIZE, 512, false) => {
+            verify::<HeaderRangeCircuit<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, 512>>(
+                build, proof, input, output,
+            )
+        }
+        (CircuitKind::HeaderRange, 512, 256, false) => {
+            verify::<HeaderRangeCircuit<512, MAX_HEADER_SIZE, 256>>(build, proof, input, output)
+        }
+        (CircuitKind::HeaderRange, 512, 512, false) => {
+            verify::<HeaderRangeCircuit<512, MAX_HEADER_SIZE, 512>>(build, proof, input, output)
+        }
+        (CircuitKind::Rotate, 64, _, _) => verify::<
+            RotateCircuit<64, MAX_HEADER_SIZE, SUBARRAY_SIZE_64>,
+        >(build, proof, input, output),
+        (CircuitKind::Rotate, 128, _, _) => verify::<
+            RotateCircuit<128, MAX_HEADER_SIZE, SUBARRAY_SIZE_128>,
+        >(build, proof, input, output),
+        (CircuitKind::Rotate, MAX_AUTHORITY_SET_SIZE, _, _) => {
+            verify::<RotateCircuit<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, SUBARRAY_SIZE_300>>(
+                build, proof, input, output,
+            )
+        }
+        (CircuitKind::Rotate, 512, _, _) => verify::<
+            RotateCircuit<512, MAX_HEADER_SIZE, SUBARRAY_SIZE_512>,
+        >(build, proof, input, output),
+        _ => Err(anyhow!("No circuit is built for {:?}", args)),
+    }
+}
+
+// Compare a value in the contract's state to the value from the proof. Returns false on a mismatch.
+// A required value, i.e. one the contract reads when verifying the proof, is a mismatch if unset. The
+// others are unset until the proof is submitted.
+fn check(name: &str, on_chain: H256, proven: H256, required: bool) -> bool {
+    if on_chain == H256::zero() {
+        if required {
+            log::error!("{}: not set in the contract", name);
+            false
+        } else {
+            info!("{}: not set in the contract", name);
+            true
+        }
+    } else if on_chain == proven {
+        info!("{}: matches the contract", name);
+        true
+    } else {
+        log::error!(
+            "{}: the proof has {:#x}, the contract has {:#x}",
+            name,
+            proven,
+            on_chain
+        );
+        false
+    }
+}
+
+async fn check_header_range(
+    contract: &VectorX<Provider<Http>>,
+    request: &HeaderRangeRequest,
+    response: &HeaderRangeResponse,
+) -> bool {
+    let range_hash = get_range_hash(request.trusted_block, request.target_block);
+    let checks = [
+        check(
+            "Trusted header hash",
+            H256::from(
+                contract
+                    .block_height_to_header_hash(request.trusted_block)
+                    .await
+                    .unwrap(),
+            ),
+            request.trusted_header_hash,
+            true,
+        ),
+        check(
+            "Authority set hash",
+            H256::from(
+                contract
+                    .authority_set_id_to_hash(request.authority_set_id)
+                    .await
+                    .unwrap(),
+            ),
+            request.authority_set_hash,
+            true,
+        ),
+        check(
+            "Target header hash",
+            H256::from(
+                contract
+                    .block_height_to_header_hash(request.target_block)
+                    .await
+                    .unwrap(),
+            ),
+            response.target_header_hash,
+            false,
+        ),
+        check(
+            "State root commitment",
+            H256::from(contract.state_root_commitments(range_hash.0).await.unwrap()),
+            response.state_root_commitment,
+            false,
+        ),
+        check(
+            "Data root commitment",
+            H256::from(contract.data_root_commitments(range_hash.0).await.unwrap()),
+            response.data_root_commitment,
+            false,
+        ),
+    ];
+    checks.iter().all(|ok| *ok)
+}
+
+async fn check_rotate(
+    contract: &VectorX<Provider<Http>>,
+    request: &RotateRequest,
+    response: &RotateResponse,
+) -> bool {
+    let checks = [
+        check(
+            "Authority set hash",
+            H256::from(
+                contract
+                    .authority_set_id_to_hash(request.authority_set_id)
+                    .await
+                    .unwrap(),
+            ),
+            request.authority_set_hash,
+            true,
+        ),
+        check(
+            "New authority set hash",
+            H256::from(
+                contract
+                    .authority_set_id_to_hash(request.authority_set_id + 1)
+                    .await
+                    .unwrap(),
+            ),
+            response.new_authority_set_hash,
+            false,
+        ),
+    ];
+    checks.iter().all(|ok| *ok)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env::set_var("RUST_LOG", "info");
+    dotenv::dotenv().ok();
+    env_logger::init();
+    let args = VerifyProofArgs::parse();
+
+    let input = read_hex_field(&args.input, "/data/input")?;
+    let output = read_hex_field(&args.proof, "/data/output")?;
+    let proof = read_hex_field(&args.proof, "/data/proof")?;
+
+    // Decode the inputs and outputs first, so malformed artifacts fail before loading the build.
+    let (header_range_data, rotate_data) = match args.circuit {
+        CircuitKind::HeaderRange => (
+            Some((
+                HeaderRangeRequest::decode(&input)?,
+                HeaderRangeResponse::decode(&output)?,
+            )),
+            None,
+        ),
+        CircuitKind::Rotate => (
+            None,
+            Some((
+                RotateRequest::decode(&input)?,
+                RotateResponse::decode(&output)?,
+            )),
+        ),
+    };
+
+    verify_tier(&args, proof, &input, &output)?;
+    info!("Proof verified");
+    if let Some((request, response)) = &header_range_data {
+        info!("{:#?}\n{:#?}", request, response);
+    }
+    if let Some((request, response)) = &rotate_data {
+        info!("{:#?}\n{:#?}", request, response);
+    }
+
+    let (Ok(contract_address), Ok(ethereum_rpc_url)) =
+        (env::var("CONTRACT_ADDRESS"), env::var("ETHEREUM_RPC_URL"))
+    else {
+        info!("CONTRACT_ADDRESS or ETHEREUM_RPC_URL not set, skipping the contract checks");
+        return Ok(());
+    };
+    let address = contract_address.parse::<Address>()?;
+    let provider = Provider::<Http>::try_from(ethereum_rpc_url)?;
+    let contract = VectorX::new(address, provider.into());
+
+    let consistent = match (&header_range_data, &rotate_data) {
+        (Some((request, response)), _) => check_header_range(&contract, request, response).await,
+        (_, Some((request, response))) => check_rotate(&contract, request, response).await,
+        _ => unreachable!(),
+    };
+    ensure!(
+        consistent,
+        "The proof is inconsistent with the contract at {:#x}",
+        address
+    );
+    Ok(())
+}
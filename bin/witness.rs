@@ -0,0 +1,104 @@
+//! To build the binary:
+//!
+//!     `cargo build --release --bin witness`
+//!
+//! Generates a witness bundle with the data the hints of header range, rotate and header ancestry
+//! proofs read, so the proofs can be generated on a machine without access to the Avail RPC or
+//! Redis. Set WITNESS_BUNDLE to the bundle's path when proving.
+//!
+//! The header range witness also covers the header range KZG, aggregation and subset circuits, whose
+//! hints read the same headers and target justification. A rotate header range proof needs the
+//! header range witness and the rotate witness of the current authority set, and a multi rotate proof
+//! from authority set N over K epochs needs the rotate witnesses of N, ..., N + K - 1.
+//!
+
+use std::env;
+
+use clap::Parser;
+use log::info;
+use vectorx::input::RpcDataFetcher;
+use vectorx::witness::WitnessBundle;
+
+#[derive(Parser, Debug, Clone)]
+#[command(about = "Generate a witness bundle for offline proving.")]
+pub struct WitnessArgs {
+    /// Trusted block of the header range proof.
+    #[arg(long, requires = "target_block")]
+    pub trusted_block: Option<u32>,
+    /// Target block of the header range proof.
+    #[arg(long, requires = "trusted_block")]
+    pub target_block: Option<u32>,
+    /// Authority set ids of the rotate proofs.
+    #[arg(long, value_delimiter = ',')]
+    pub authority_set_id: Vec<u64>,
+    /// Trusted block of the header ancestry proof.
+    #[arg(long, requires = "ancestry_target_block")]
+    pub ancestry_trusted_block: Option<u32>,
+    /// Target block of the header ancestry proof.
+    #[arg(long, requires = "ancestry_trusted_block")]
+    pub ancestry_target_block: Option<u32>,
+    #[arg(long, default_value = "witness.json")]
+    pub output: String,
+}
+
+#[tokio::main]
+async fn main() {
+    env::set_var("RUST_LOG", "info");
+    dotenv::dotenv().ok();
+    env_logger::init();
+    let args = WitnessArgs::parse();
+
+    if args.target_block.is_none()
+        && args.authority_set_id.is_empty()
+        && args.ancestry_target_block.is_none()
+    {
+        panic!("Specify --trusted-block and --target-block, --authority-set-id, and/or --ancestry-trusted-block and --ancestry-target-block");
+    }
+
+    let mut fetcher = RpcDataFetcher::new().await;
+    let mut bundle = WitnessBundle::new();
+
+    if let (Some(trusted_block), Some(target_block)) = (args.trusted_block, args.target_block) {
+        info!(
+            "Fetching header range witness for trusted block {} and target block {}",
+            trusted_block, target_block
+        );
+        bundle
+            .add_headers(&mut fetcher, trusted_block + 1, target_block)
+            .await;
+        bundle
+            .add_justification(&mut fetcher, target_block)
+            .await
+            .expect("Failed to get justification");
+    }
+
+    for authority_set_id in args.authority_set_id {
+        info!(
+            "Fetching rotate witness for authority set {}",
+            authority_set_id
+        );
+        bundle
+            .add_rotate(&mut fetcher, authority_set_id)
+            .await
+            .expect("Failed to get rotate");
+    }
+
+    if let (Some(trusted_block), Some(target_block)) =
+        (args.ancestry_trusted_block, args.ancestry_target_block)
+    {
+        info!(
+            "Fetching header ancestry witness for trusted block {} and target block {}",
+            trusted_block, target_block
+        );
+        // The header ancestry circuit hashes the headers from the target header to the trusted
+        // header, including both.
+        bundle
+            .add_headers(&mut fetcher, target_block, trusted_block)
+            .await;
+    }
+
+    bundle
+        .write(&args.output)
+        .expect("Failed to write witness bundle");
+    info!("Wrote witness bundle to {}", args.output);
+}
@@ -0,0 +1,277 @@
+use anyhow::{ensure, Error};
+use ethers::types::H256;
+use plonky2x::frontend::curta::ec::point::CompressedEdwardsY;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp_core::keccak_256;
+
+use crate::consts::AUTHORITY_SET_MERKLE_TREE_DEPTH;
+
+/// How an authority set is committed to in `authoritySetIdToHash`. This is a circuit-level option: the
+/// rotate and header range circuits select it with their AUTHORITY_SET_COMMITMENT_MODE parameter (the
+/// id of the mode), while the VectorX contract stores the commitments without interpreting them.
+/// Every built binary and the genesis binary use ChainedSha256.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthoritySetCommitmentMode {
+    /// The chained hash SHA256(SHA256(SHA256(pubkey[0]) || pubkey[1]) || pubkey[2])... This is the
+    /// original commitment of VectorX.
+    ChainedSha256,
+    /// The root of a SHA256 Merkle tree over the pubkeys. See get_authority_set_merkle_root.
+    MerkleSha256,
+    /// The root of a Keccak256 Merkle tree over the pubkeys. See get_authority_set_merkle_root.
+    MerkleKeccak,
+}
+
+impl AuthoritySetCommitmentMode {
+    pub const CHAINED_SHA256: u8 = 0;
+    pub const MERKLE_SHA256: u8 = 1;
+    pub const MERKLE_KECCAK: u8 = 2;
+
+    pub const fn id(self) -> u8 {
+        match self {
+            AuthoritySetCommitmentMode::ChainedSha256 => Self::CHAINED_SHA256,
+            AuthoritySetCommitmentMode::MerkleSha256 => Self::MERKLE_SHA256,
+            AuthoritySetCommitmentMode::MerkleKeccak => Self::MERKLE_KECCAK,
+        }
+    }
+
+    /// Get the commitment mode from its id. None if the id is unknown.
+    pub const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            Self::CHAINED_SHA256 => Some(AuthoritySetCommitmentMode::ChainedSha256),
+            Self::MERKLE_SHA256 => Some(AuthoritySetCommitmentMode::MerkleSha256),
+            Self::MERKLE_KECCAK => Some(AuthoritySetCommitmentMode::MerkleKeccak),
+            _ => None,
+        }
+    }
+
+    /// Hash two nodes of the Merkle tree of a Merkle commitment mode.
+    pub fn hash_nodes(self, left: &[u8], right: &[u8]) -> [u8; 32] {
+        match self {
+            AuthoritySetCommitmentMode::ChainedSha256 => {
+                panic!("ChainedSha256 is not a Merkle commitment mode")
+            }
+            AuthoritySetCommitmentMode::MerkleSha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update(left);
+                hasher.update(right);
+                hasher.finalize().into()
+            }
+            AuthoritySetCommitmentMode::MerkleKeccak => keccak_256(&[left, right].concat()),
+        }
+    }
+
+    /// Hash the root of the Merkle tree of a Merkle commitment mode with the number of authorities,
+    /// as H(tree_root || uint256(num_authorities)).
+    pub fn hash_num_authorities(self, tree_root: &[u8], num_authorities: u32) -> [u8; 32] {
+        let mut num_authorities_bytes = [0u8; 32];
+        num_authorities_bytes[28..].copy_from_slice(&num_authorities.to_be_bytes());
+        self.hash_nodes(tree_root, &num_authorities_bytes)
+    }
+
+    /// The roots of the empty subtrees of each height, from the empty leaf up to the empty tree of
+    /// height AUTHORITY_SET_MERKLE_TREE_DEPTH - 1.
+    pub fn empty_subtree_roots(self) -> Vec<[u8; 32]> {
+        let mut roots = vec![[0u8; 32]];
+        for i in 1..AUTHORITY_SET_MERKLE_TREE_DEPTH {
+            roots.push(self.hash_nodes(&roots[i - 1], &roots[i - 1]));
+        }
+        roots
+    }
+}
+
+// Compute the nodes of each level of the authority set Merkle tree, from the leaves up to the root.
+fn get_authority_set_merkle_tree(
+    authorities: &[CompressedEdwardsY],
+    mode: AuthoritySetCommitmentMode,
+) -> Vec<Vec<[u8; 32]>> {
+    assert!(
+        authorities.len() <= 1 << AUTHORITY_SET_MERKLE_TREE_DEPTH,
+        "Authority set is too large for the authority set Merkle tree"
+    );
+
+    let mut leaves = authorities.iter().map(|a| a.0).collect::<Vec<_>>();
+    leaves.resize(1 << AUTHORITY_SET_MERKLE_TREE_DEPTH, [0u8; 32]);
+
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let nodes = levels.last().unwrap();
+        let parents = nodes
+            .chunks_exact(2)
+            .map(|pair| mode.hash_nodes(&pair[0], &pair[1]))
+            .collect();
+        levels.push(parents);
+    }
+    levels
+}
+
+/// Compute the Merkle commitment of an authority set. The leaves of the Merkle tree are the pubkeys
+/// in the order of the authority set, padded with empty (zero) leaves to a tree of depth
+/// AUTHORITY_SET_MERKLE_TREE_DEPTH, so the root does not depend on the max authority set size of
+/// the circuit. The leaves are not hashed.
+///
+/// The commitment is the tree root hashed with the number of authorities. Otherwise, an authority
+/// set followed by zero pubkeys, which are small order points, would have the same commitment.
+pub fn get_authority_set_merkle_root(
+    authorities: &[CompressedEdwardsY],
+    mode: AuthoritySetCommitmentMode,
+) -> H256 {
+    let levels = get_authority_set_merkle_tree(authorities, mode);
+    H256::from(mode.hash_num_authorities(&levels.last().unwrap()[0], authorities.len() as u32))
+}
+
+/// Proof that pubkey is the authority at index in an authority set committed to with a Merkle
+/// commitment mode.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorityMembershipProof {
+    pub pubkey: H256,
+    pub index: u32,
+    /// Sibling nodes from the pubkey up to the root of the Merkle tree.
+    pub branch: Vec<H256>,
+    /// The number of authorities in the authority set.
+    pub num_authorities: u32,
+}
+
+impl AuthorityMembershipProof {
+    /// Verify the pubkey is the authority at index in the authority set with the Merkle root
+    /// authority_set_hash.
+    pub fn verify(&self, authority_set_hash: H256, mode: AuthoritySetCommitmentMode) -> bool {
+        if self.branch.len() != AUTHORITY_SET_MERKLE_TREE_DEPTH
+            || self.index >= self.num_authorities
+        {
+            return false;
+        }
+        let mut node = self.pubkey.0;
+        let mut index = self.index;
+        for sibling in &self.branch {
+            node = if index % 2 == 0 {
+                mode.hash_nodes(&node, &sibling.0)
+            } else {
+                mode.hash_nodes(&sibling.0, &node)
+            };
+            index /= 2;
+        }
+        index == 0 && mode.hash_num_authorities(&node, self.num_authorities) == authority_set_hash.0
+    }
+}
+
+/// Get the proof that the authority at index is in the authority set, against the Merkle root of
+/// the authority set.
+pub fn get_authority_membership_proof(
+    authorities: &[CompressedEdwardsY],
+    index: usize,
+    mode: AuthoritySetCommitmentMode,
+) -> Result<AuthorityMembershipProof, Error> {
+    ensure!(
+        index < authorities.len(),
+        "Index {} is out of range of the authority set of {} authorities",
+        index,
+        authorities.len()
+    );
+
+    let levels = get_authority_set_merkle_tree(authorities, mode);
+    let mut branch = Vec::new();
+    let mut node_index = index;
+    for nodes in &levels[..levels.len() - 1] {
+        branch.push(H256::from(nodes[node_index ^ 1]));
+        node_index /= 2;
+    }
+
+    Ok(AuthorityMembershipProof {
+        pubkey: H256::from(authorities[index].0),
+        index: index as u32,
+        branch,
+        num_authorities: authorities.len() as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_authorities(num_authorities: usize) -> Vec<CompressedEdwardsY> {
+        (0..num_authorities)
+            .map(|i| CompressedEdwardsY([i as u8 + 1; 32]))
+            .collect()
+    }
+
+    #[test]
+    fn test_commitment_mode_id() {
+        for mode in [
+            AuthoritySetCommitmentMode::ChainedSha256,
+            AuthoritySetCommitmentMode::MerkleSha256,
+            AuthoritySetCommitmentMode::MerkleKeccak,
+        ] {
+            assert_eq!(AuthoritySetCommitmentMode::from_id(mode.id()), Some(mode));
+        }
+        assert_eq!(AuthoritySetCommitmentMode::from_id(3), None);
+    }
+
+    #[test]
+    fn test_merkle_commitment_num_authorities() {
+        for mode in [
+            AuthoritySetCommitmentMode::MerkleSha256,
+            AuthoritySetCommitmentMode::MerkleKeccak,
+        ] {
+            // The tree of an authority set followed by zero pubkeys is the same as the tree of the
+            // authority set, but the commitments differ by the number of authorities.
+            let authorities = get_authorities(5);
+            let mut padded_authorities = authorities.clone();
+            padded_authorities.resize(8, CompressedEdwardsY([0u8; 32]));
+            assert_eq!(
+                get_authority_set_merkle_tree(&authorities, mode),
+                get_authority_set_merkle_tree(&padded_authorities, mode)
+            );
+            assert_ne!(
+                get_authority_set_merkle_root(&authorities, mode),
+                get_authority_set_merkle_root(&padded_authorities, mode)
+            );
+        }
+    }
+
+    #[test]
+    fn test_merkle_commitment() {
+        for mode in [
+            AuthoritySetCommitmentMode::MerkleSha256,
+            AuthoritySetCommitmentMode::MerkleKeccak,
+        ] {
+            // A single authority is the leftmost leaf of a tree of empty subtrees.
+            let authorities = get_authorities(1);
+            let mut expected = authorities[0].0;
+            for empty_root in mode.empty_subtree_roots() {
+                expected = mode.hash_nodes(&expected, &empty_root);
+            }
+            assert_eq!(
+                get_authority_set_merkle_root(&authorities, mode),
+                H256::from(mode.hash_num_authorities(&expected, 1))
+            );
+
+            let authorities = get_authorities(5);
+            let root = get_authority_set_merkle_root(&authorities, mode);
+            for index in 0..authorities.len() {
+                let proof = get_authority_membership_proof(&authorities, index, mode).unwrap();
+                assert!(proof.verify(root, mode));
+
+                let mut invalid_proof = proof.clone();
+                invalid_proof.index += 1;
+                assert!(!invalid_proof.verify(root, mode));
+
+                let mut invalid_proof = proof.clone();
+                invalid_proof.num_authorities += 1;
+                assert!(!invalid_proof.verify(root, mode));
+            }
+            assert!(get_authority_membership_proof(&authorities, 5, mode).is_err());
+
+            // A zero pubkey past the end of the authority set is not a member.
+            let mut padded_authorities = authorities.clone();
+            padded_authorities.push(CompressedEdwardsY([0u8; 32]));
+            let proof = get_authority_membership_proof(&padded_authorities, 5, mode).unwrap();
+            assert!(!AuthorityMembershipProof {
+                num_authorities: 5,
+                ..proof
+            }
+            .verify(root, mode));
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use itertools::Itertools;
+use plonky2x::backend::circuit::Circuit;
+use plonky2x::frontend::vars::{U32Variable, VariableStream};
+use plonky2x::prelude::{ArrayVariable, Bytes32Variable, CircuitBuilder, PlonkParameters};
+
+use crate::builder::decoder::DecodingMethods;
+use crate::builder::header::HeaderMethods;
+use crate::builder::subchain_verification::{
+    map_subchain, reduce_subchain, HeaderRangeFetcherHint, MapReduceSubchainVariable,
+    SubchainVerificationCtx,
+};
+use crate::consts::HEADERS_PER_MAP;
+use crate::vars::{EncodedHeaderVariable, HeaderAncestryVariable};
+
+pub trait HeaderAncestryVerifier<L: PlonkParameters<D>, const D: usize> {
+    /// Verify the header at target_block is an ancestor of the trusted header at trusted_block by
+    /// verifying the chain of headers [target_block, trusted_block] ends at the trusted header.
+    /// Returns the verified target header hash, state root and data root.
+    ///
+    /// MAX_NUM_HEADERS includes both the target and trusted headers, so the maximum distance between
+    /// target_block and trusted_block is MAX_NUM_HEADERS - 1. The chain is verified with the same
+    /// map and reduce circuits as `verify_subchain`, so no single circuit hashes more than
+    /// HEADERS_PER_MAP headers.
+    fn verify_header_ancestry<
+        C: Circuit,
+        const MAX_HEADER_SIZE: usize,
+        const MAX_NUM_HEADERS: usize,
+    >(
+        &mut self,
+        trusted_block: U32Variable,
+        trusted_header_hash: Bytes32Variable,
+        target_block: U32Variable,
+    ) -> HeaderAncestryVariable
+    where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<<L as PlonkParameters<D>>::Field>;
+}
+
+impl<L: PlonkParameters<D>, const D: usize> HeaderAncestryVerifier<L, D> for CircuitBuilder<L, D> {
+    fn verify_header_ancestry<
+        C: Circuit,
+        const MAX_HEADER_SIZE: usize,
+        const MAX_NUM_HEADERS: usize,
+    >(
+        &mut self,
+        trusted_block: U32Variable,
+        trusted_header_hash: Bytes32Variable,
+        target_block: U32Variable,
+    ) -> HeaderAncestryVariable
+    where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        // The target block must not be after the trusted block, and must be at most
+        // MAX_NUM_HEADERS - 1 blocks before it.
+        let false_const = self._false();
+        let is_after_trusted_block = self.lt(trusted_block, target_block);
+        self.assert_is_equal(is_after_trusted_block, false_const);
+        let max_distance = self.constant::<U32Variable>(MAX_NUM_HEADERS as u32 - 1);
+        let distance = self.sub(trusted_block, target_block);
+        let is_too_far = self.lt(max_distance, distance);
+        self.assert_is_equal(is_too_far, false_const);
+
+        // Verify the chain of headers [target_block, trusted_block]. Unlike `verify_subchain`, the
+        // relative block numbers start at 0, so the first map job starts at the target header.
+        let ctx = SubchainVerificationCtx {
+            global_start_block: target_block,
+            global_end_block: trusted_block,
+        };
+
+        // The number of map jobs is the smallest power of 2 that is >= to MAX_NUM_HEADERS / HEADERS_PER_MAP.
+        let num_map_jobs = (MAX_NUM_HEADERS / HEADERS_PER_MAP).next_power_of_two();
+
+        let relative_block_nums =
+            (0u32..num_map_jobs as u32 * HEADERS_PER_MAP as u32).collect_vec();
+
+        let output =
+            self.mapreduce::<SubchainVerificationCtx, U32Variable, MapReduceSubchainVariable, C, HEADERS_PER_MAP, _, _>(
+                ctx,
+                relative_block_nums,
+                |map_ctx, map_relative_block_nums, builder| {
+                    map_subchain::<L, D, MAX_HEADER_SIZE>(builder, map_ctx, map_relative_block_nums)
+                },
+                |_, left, right, builder| reduce_subchain(builder, left, right),
+            );
+
+        // The chain of headers must start at the target block and end at the trusted header.
+        self.assert_is_equal(output.start_block, target_block);
+        self.assert_is_equal(output.end_block, trusted_block);
+        self.assert_is_equal(output.end_header_hash, trusted_header_hash);
+
+        // The map reduce only outputs the merkle roots of the chain, so fetch the target header
+        // again to read its state and data roots.
+        // Note: This header is untrusted as it is fetched via a hint, and so is constrained to the
+        // verified target header hash.
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&target_block);
+        input_stream.write(&target_block);
+        input_stream.write(&target_block);
+        let header_fetcher = HeaderRangeFetcherHint::<MAX_HEADER_SIZE, 1> {};
+        let headers = self
+            .async_hint(input_stream, header_fetcher)
+            .read::<ArrayVariable<EncodedHeaderVariable<MAX_HEADER_SIZE>, 1>>(self);
+
+        let target_header_hash = self.hash_encoded_header::<MAX_HEADER_SIZE>(&headers[0]);
+        self.assert_is_equal(target_header_hash, output.start_header_hash);
+        let target_header = self.decode_header::<MAX_HEADER_SIZE>(&headers[0], &target_header_hash);
+
+        HeaderAncestryVariable {
+            target_header_hash,
+            state_root: target_header.state_root,
+            data_root: target_header.data_root,
+        }
+    }
+}
@@ -5,8 +5,10 @@ use plonky2x::prelude::{
 };
 
 use crate::consts::{
-    DATA_ROOT_OFFSET_FROM_END, ENCODED_PRECOMMIT_LENGTH, HASH_SIZE, MAX_COMPACT_UINT_BYTES,
+    ENCODED_PRECOMMIT_LENGTH, HASH_SIZE, MAX_COMPACT_UINT_BYTES, MAX_DIGEST_LOG_PREFIX_LENGTH,
+    MAX_NUM_DIGEST_LOGS,
 };
+use crate::layout::{HeaderLayout, CURRENT_HEADER_LAYOUT};
 use crate::vars::*;
 
 pub trait DecodingMethods {
@@ -28,6 +30,40 @@ pub trait DecodingMethods {
         header_hash: &Bytes32Variable,
     ) -> HeaderVariable;
 
+    /// Get the start of the header extension (its version tag) of a header, by decoding the
+    /// compact encoded block number and the logs of the digest. Headers with more than
+    /// MAX_NUM_DIGEST_LOGS logs are not supported. header_hash is used for the RLC challenge in
+    /// get_fixed_subarray.
+    fn get_extension_start<const S: usize>(
+        &mut self,
+        header: &EncodedHeaderVariable<S>,
+        header_hash: &Bytes32Variable,
+    ) -> Variable;
+
+    /// Decode a header with the given layout into its components. Only layouts with the data root
+    /// at a fixed offset from the end of the header are supported.
+    ///
+    /// Note: The version tag of the header extension is not constrained, as locating it takes
+    /// MAX_NUM_DIGEST_LOGS + 2 get_fixed_subarray calls over the whole header, several times the
+    /// cost of the rest of the decoding. The rotate circuits constrain it on each epoch end header
+    /// with assert_header_layout, and decode_header_extension on the target header of the KZG
+    /// circuit. The header fetcher hints check the layout of every fetched header natively.
+    fn decode_header_with_layout<const S: usize>(
+        &mut self,
+        header: &EncodedHeaderVariable<S>,
+        header_hash: &Bytes32Variable,
+        layout: HeaderLayout,
+    ) -> HeaderVariable;
+
+    /// Assert the header extension of a header starts with the version tag of the given layout.
+    /// header_hash is used for the RLC challenge in get_fixed_subarray.
+    fn assert_header_layout<const S: usize>(
+        &mut self,
+        header: &EncodedHeaderVariable<S>,
+        header_hash: &Bytes32Variable,
+        layout: HeaderLayout,
+    );
+
     /// Decode a precommit message into its components: {block_hash, block_nb, justification_round, authority_set_id}.
     fn decode_precommit(
         &mut self,
@@ -106,6 +142,126 @@ impl<L: PlonkParameters<D>, const D: usize> DecodingMethods for CircuitBuilder<L
         header: &EncodedHeaderVariable<S>,
         header_hash: &Bytes32Variable,
     ) -> HeaderVariable {
+        self.decode_header_with_layout(header, header_hash, CURRENT_HEADER_LAYOUT)
+    }
+
+    fn get_extension_start<const S: usize>(
+        &mut self,
+        header: &EncodedHeaderVariable<S>,
+        header_hash: &Bytes32Variable,
+    ) -> Variable {
+        let true_v = self._true();
+        let false_v = self._false();
+        let zero = self.zero::<Variable>();
+        let one = self.one::<Variable>();
+
+        // The digest starts after the parent hash, the compact encoded block number, the state root
+        // and the extrinsics root.
+        let block_number_bytes = ArrayVariable::<ByteVariable, MAX_COMPACT_UINT_BYTES>::from(
+            header.header_bytes[HASH_SIZE..HASH_SIZE + MAX_COMPACT_UINT_BYTES].to_vec(),
+        );
+        let (_, compress_mode) = self.decode_compact_int(block_number_bytes);
+        let block_number_length = self.get_compact_int_byte_length(compress_mode);
+        let hashes_length =
+            self.constant::<Variable>(L::Field::from_canonical_usize(3 * HASH_SIZE));
+        let digest_start = self.add(block_number_length, hashes_length);
+
+        // The digest is the compact encoded number of logs, followed by the logs.
+        let num_logs_bytes = self.get_fixed_subarray::<S, MAX_COMPACT_UINT_BYTES>(
+            &header.header_bytes,
+            digest_start,
+            &header_hash.as_bytes(),
+        );
+        let (num_logs, compress_mode) = self.decode_compact_int(num_logs_bytes);
+        let num_logs_length = self.get_compact_int_byte_length(compress_mode);
+        let max_num_logs = self.constant::<U32Variable>(MAX_NUM_DIGEST_LOGS as u32);
+        let too_many_logs = self.gt(num_logs, max_num_logs);
+        self.assert_is_equal(too_many_logs, false_v);
+        let mut cursor = self.add(digest_start, num_logs_length);
+
+        // Spec for digest logs: https://github.com/availproject/polkadot-sdk/blob/polkadot-1.7.1-patch-2/substrate/primitives/runtime/src/generic/digest.rs
+        // Other (0) logs are a Vec<u8>, Consensus (4), Seal (5) and PreRuntime (6) logs are a 4
+        // byte consensus engine id and a Vec<u8>, and RuntimeEnvironmentUpdated (8) logs are empty.
+        let other = self.constant::<Variable>(L::Field::from_canonical_u8(0));
+        let consensus = self.constant::<Variable>(L::Field::from_canonical_u8(4));
+        let seal = self.constant::<Variable>(L::Field::from_canonical_u8(5));
+        let pre_runtime = self.constant::<Variable>(L::Field::from_canonical_u8(6));
+        let runtime_environment_updated = self.constant::<Variable>(L::Field::from_canonical_u8(8));
+        let engine_id_length = self.constant::<Variable>(L::Field::from_canonical_u8(4));
+        let zero_bytes =
+            self.constant::<ArrayVariable<ByteVariable, MAX_COMPACT_UINT_BYTES>>(vec![
+                0u8;
+                MAX_COMPACT_UINT_BYTES
+            ]);
+        for i in 0..MAX_NUM_DIGEST_LOGS {
+            let idx = self.constant::<U32Variable>(i as u32);
+            let is_enabled = self.lt(idx, num_logs);
+            let is_disabled = self.not(is_enabled);
+
+            let log_prefix = self.get_fixed_subarray::<S, MAX_DIGEST_LOG_PREFIX_LENGTH>(
+                &header.header_bytes,
+                cursor,
+                &header_hash.as_bytes(),
+            );
+            let variant = log_prefix[0].to_variable(self);
+            let is_other = self.is_equal(variant, other);
+            let is_consensus = self.is_equal(variant, consensus);
+            let is_seal = self.is_equal(variant, seal);
+            let is_pre_runtime = self.is_equal(variant, pre_runtime);
+            let is_runtime_environment_updated =
+                self.is_equal(variant, runtime_environment_updated);
+            let has_engine_id = self.or(is_consensus, is_seal);
+            let has_engine_id = self.or(has_engine_id, is_pre_runtime);
+
+            // Enabled logs must be of a known variant.
+            let is_valid = self.or(has_engine_id, is_other);
+            let is_valid = self.or(is_valid, is_runtime_environment_updated);
+            let is_valid_or_disabled = self.or(is_valid, is_disabled);
+            self.assert_is_equal(is_valid_or_disabled, true_v);
+
+            // Select the compact encoded data length before decoding it, as the bytes at the other
+            // offset, and the bytes of disabled and empty logs, can be any data.
+            let data_length_bytes = self.select(
+                has_engine_id,
+                ArrayVariable::<ByteVariable, MAX_COMPACT_UINT_BYTES>::from(
+                    log_prefix[5..5 + MAX_COMPACT_UINT_BYTES].to_vec(),
+                ),
+                ArrayVariable::<ByteVariable, MAX_COMPACT_UINT_BYTES>::from(
+                    log_prefix[1..1 + MAX_COMPACT_UINT_BYTES].to_vec(),
+                ),
+            );
+            let has_data = self.or(has_engine_id, is_other);
+            let has_data = self.and(has_data, is_enabled);
+            let data_length_bytes = self.select(has_data, data_length_bytes, zero_bytes.clone());
+            let (data_length, compress_mode) = self.decode_compact_int(data_length_bytes);
+            let data_length_length = self.get_compact_int_byte_length(compress_mode);
+
+            let prefix_length = self.select(has_engine_id, engine_id_length, zero);
+            let mut log_length = self.add(one, prefix_length);
+            log_length = self.add(log_length, data_length_length);
+            log_length = self.add(log_length, data_length.variable);
+            log_length = self.select(is_runtime_environment_updated, one, log_length);
+
+            // Only advance the cursor past enabled logs.
+            let next_cursor = self.add(cursor, log_length);
+            cursor = self.select(is_enabled, next_cursor, cursor);
+        }
+        cursor
+    }
+
+    fn decode_header_with_layout<const S: usize>(
+        &mut self,
+        header: &EncodedHeaderVariable<S>,
+        header_hash: &Bytes32Variable,
+        layout: HeaderLayout,
+    ) -> HeaderVariable {
+        let data_root_offset_from_end = layout.data_root_offset_from_end().unwrap_or_else(|| {
+            panic!(
+                "header layout {:?} does not have the data root at a fixed offset from the end of the header",
+                layout
+            )
+        });
+
         // Spec for Avail header: https://github.com/availproject/avail-core/blob/main/core/src/header/mod.rs#L44-L66
 
         // The first 32 bytes are the parent hash.
@@ -127,9 +283,10 @@ impl<L: PlonkParameters<D>, const D: usize> DecodingMethods for CircuitBuilder<L
 
         let state_root = self.select_array_random_gate(&all_possible_state_roots, compress_mode);
 
-        // The next field is the data root. The data root is the last 32 bytes of the header.
+        // The next field is the data root, at a fixed offset from the end of the header (the last
+        // 32 bytes for V2 and V3 headers).
         // Spec: https://github.com/availproject/avail-core/blob/main/core/src/header/extension/v3.rs#L9-L15
-        let data_root_offset = self.constant::<U32Variable>(DATA_ROOT_OFFSET_FROM_END);
+        let data_root_offset = self.constant::<U32Variable>(data_root_offset_from_end as u32);
         let mut data_root_start = self.sub(header.header_size, data_root_offset);
 
         // If header_size == 0, then set data_root_start to 0.
@@ -156,6 +313,26 @@ impl<L: PlonkParameters<D>, const D: usize> DecodingMethods for CircuitBuilder<L
         }
     }
 
+    fn assert_header_layout<const S: usize>(
+        &mut self,
+        header: &EncodedHeaderVariable<S>,
+        header_hash: &Bytes32Variable,
+        layout: HeaderLayout,
+    ) {
+        // The header extension starts with the version tag of the layout.
+        // Spec: https://github.com/availproject/avail-core/blob/main/core/src/header/extension/mod.rs
+        let extension_start = self.get_extension_start(header, header_hash);
+        let version_tag_bytes = self.get_fixed_subarray::<S, 1>(
+            &header.header_bytes,
+            extension_start,
+            &header_hash.as_bytes(),
+        );
+        let version_tag =
+            self.constant::<Variable>(L::Field::from_canonical_u8(layout.version_tag()));
+        let header_version_tag = version_tag_bytes[0].to_variable(self);
+        self.assert_is_equal(header_version_tag, version_tag);
+    }
+
     fn decode_precommit(
         &mut self,
         precommit: BytesVariable<ENCODED_PRECOMMIT_LENGTH>,
@@ -203,18 +380,25 @@ impl<L: PlonkParameters<D>, const D: usize> DecodingMethods for CircuitBuilder<L
 #[cfg(test)]
 pub mod tests {
     use std::env;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
 
     use avail_subxt::config::Header;
     use codec::{Compact, Encode};
+    use ethers::types::H256;
     use plonky2x::frontend::vars::U32Variable;
     use plonky2x::prelude::{
         ArrayVariable, ByteVariable, Bytes32Variable, BytesVariable, DefaultBuilder, Field,
         GoldilocksField, U64Variable, Variable,
     };
+    use sp_core::blake2_256;
 
     use super::DecodingMethods;
-    use crate::consts::{ENCODED_PRECOMMIT_LENGTH, MAX_COMPACT_UINT_BYTES, MAX_HEADER_SIZE};
+    use crate::consts::{
+        ENCODED_PRECOMMIT_LENGTH, MAX_COMPACT_UINT_BYTES, MAX_HEADER_SIZE, SMALL_MAX_HEADER_SIZE,
+    };
     use crate::input::RpcDataFetcher;
+    use crate::layout::{get_extension_start, HeaderLayout, CURRENT_HEADER_LAYOUT};
+    use crate::synthetic::{SyntheticChain, SyntheticChainConfig};
     use crate::vars::{EncodedHeader, EncodedHeaderVariable};
 
     #[test]
@@ -369,6 +553,72 @@ pub mod tests {
         circuit.verify(&proof, &input, &output);
     }
 
+    #[test]
+    fn test_decode_header_layout() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        type F = GoldilocksField;
+        const MAX_HEADER_SIZE: usize = SMALL_MAX_HEADER_SIZE;
+
+        let mut builder = DefaultBuilder::new();
+        let header = builder.read::<EncodedHeaderVariable<MAX_HEADER_SIZE>>();
+        let header_hash = builder.read::<Bytes32Variable>();
+        let extension_start = builder.get_extension_start(&header, &header_hash);
+        builder.assert_header_layout(&header, &header_hash, CURRENT_HEADER_LAYOUT);
+        let decoded_header = builder.decode_header(&header, &header_hash);
+        builder.write::<Variable>(extension_start);
+        builder.write::<U32Variable>(decoded_header.block_number);
+        let circuit = builder.build();
+
+        let prove = |header: &[u8]| {
+            let mut header_bytes = header.to_vec();
+            header_bytes.resize(MAX_HEADER_SIZE, 0);
+            let mut input = circuit.input();
+            input.write::<EncodedHeaderVariable<MAX_HEADER_SIZE>>(EncodedHeader {
+                header_bytes,
+                header_size: header.len() as u32,
+            });
+            input.write::<Bytes32Variable>(H256::from(blake2_256(header)));
+            let (proof, output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            output
+        };
+
+        // Headers are padded with an Other log, whose length has a 2 byte compact encoding.
+        let start_block = (1 << 14) - 8;
+        let chain = SyntheticChain::new(SyntheticChainConfig {
+            start_block,
+            num_blocks: 20,
+            epoch_length: 8,
+            authority_set_sizes: vec![4, 7],
+            header_size: Some(1024),
+            ..Default::default()
+        });
+
+        // Headers inside an epoch, and epoch end headers with a GRANDPA ScheduledChange log.
+        for block_number in [
+            start_block,
+            start_block + 3,
+            start_block + 8,
+            start_block + 16,
+        ] {
+            let header = chain.header(block_number);
+            let mut output = prove(header);
+            assert_eq!(
+                output.read::<Variable>(),
+                F::from_canonical_usize(get_extension_start(header).unwrap())
+            );
+            assert_eq!(output.read::<U32Variable>(), block_number);
+        }
+
+        // A header whose extension has the version tag of another layout fails the layout check.
+        let mut header = chain.header(start_block + 3).to_vec();
+        let extension_start = get_extension_start(&header).unwrap();
+        header[extension_start] = HeaderLayout::V2.version_tag();
+        assert!(catch_unwind(AssertUnwindSafe(|| prove(&header))).is_err());
+    }
+
     #[test]
     fn test_decode_precommit() {
         env::set_var("RUST_LOG", "debug");
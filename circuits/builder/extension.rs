@@ -0,0 +1,385 @@
+use async_trait::async_trait;
+use codec::Encode;
+use log::debug;
+use plonky2x::frontend::hint::asynchronous::hint::AsyncHint;
+use plonky2x::frontend::vars::{U32Variable, VariableStream};
+use plonky2x::prelude::{
+    ArrayVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder, Field,
+    PlonkParameters, ValueStream, Variable,
+};
+use serde::{Deserialize, Serialize};
+
+use super::decoder::DecodingMethods;
+use crate::consts::{
+    HASH_SIZE, KZG_COMMITMENT_SIZE, MAX_APP_LOOKUP_LEN, MAX_COMPACT_UINT_BYTES,
+    MAX_EXTENSION_PREFIX_LENGTH, MAX_NUM_KZG_COMMITMENTS,
+};
+use crate::input::RpcDataFetcher;
+use crate::layout::{HeaderLayout, CURRENT_HEADER_LAYOUT};
+use crate::vars::*;
+use crate::witness::WitnessBundle;
+
+pub trait HeaderExtensionMethods {
+    /// Fetch the header at block_number with a hint.
+    /// Note: The header is untrusted, and should be constrained to a trusted header hash.
+    fn fetch_header_extension<const MAX_HEADER_SIZE: usize>(
+        &mut self,
+        block_number: U32Variable,
+    ) -> EncodedHeaderVariable<MAX_HEADER_SIZE>;
+
+    /// Decode the header extension of a header with CURRENT_HEADER_LAYOUT (V3) into its components:
+    /// {app_lookup, rows, cols, KZG commitments}. header_hash is used for the RLC challenge in
+    /// get_fixed_subarray.
+    ///
+    /// The start of the extension is derived from the digest with get_extension_start, and the KZG
+    /// commitments must end exactly at the data root, the last 32 bytes of the header.
+    fn decode_header_extension<const MAX_HEADER_SIZE: usize>(
+        &mut self,
+        header: &EncodedHeaderVariable<MAX_HEADER_SIZE>,
+        header_hash: &Bytes32Variable,
+    ) -> HeaderExtensionVariable;
+
+    /// Compute the simple Merkle root over the SHA256 hashes of the KZG commitments of a decoded
+    /// header extension. Leaves after num_commitments are empty.
+    fn compute_kzg_commitments_root(
+        &mut self,
+        extension: &HeaderExtensionVariable,
+    ) -> Bytes32Variable;
+}
+
+/// Decode the compact int at cursor in the extension prefix. Returns the decoded int and the index
+/// in the prefix after the compact int.
+fn decode_compact_int_at<L: PlonkParameters<D>, const D: usize>(
+    builder: &mut CircuitBuilder<L, D>,
+    prefix: &ArrayVariable<ByteVariable, MAX_EXTENSION_PREFIX_LENGTH>,
+    cursor: Variable,
+    header_hash: &Bytes32Variable,
+) -> (U32Variable, Variable) {
+    let compact_bytes = builder
+        .get_fixed_subarray::<MAX_EXTENSION_PREFIX_LENGTH, MAX_COMPACT_UINT_BYTES>(
+            prefix,
+            cursor,
+            &header_hash.as_bytes(),
+        );
+    let (value, compress_mode) = builder.decode_compact_int(compact_bytes);
+    let byte_length = builder.get_compact_int_byte_length(compress_mode);
+    (value, builder.add(cursor, byte_length))
+}
+
+impl<L: PlonkParameters<D>, const D: usize> HeaderExtensionMethods for CircuitBuilder<L, D> {
+    fn fetch_header_extension<const MAX_HEADER_SIZE: usize>(
+        &mut self,
+        block_number: U32Variable,
+    ) -> EncodedHeaderVariable<MAX_HEADER_SIZE> {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&block_number);
+        let mut output_stream = self.async_hint(
+            input_stream,
+            HeaderExtensionFetcherHint::<MAX_HEADER_SIZE> {},
+        );
+        output_stream.read::<EncodedHeaderVariable<MAX_HEADER_SIZE>>(self)
+    }
+
+    fn decode_header_extension<const MAX_HEADER_SIZE: usize>(
+        &mut self,
+        header: &EncodedHeaderVariable<MAX_HEADER_SIZE>,
+        header_hash: &Bytes32Variable,
+    ) -> HeaderExtensionVariable {
+        // Spec for the V3 header extension: https://github.com/availproject/avail-core/blob/main/core/src/header/extension/v3.rs
+        assert_eq!(
+            CURRENT_HEADER_LAYOUT,
+            HeaderLayout::V3,
+            "decode_header_extension only supports V3 header extensions."
+        );
+        assert!(
+            MAX_HEADER_SIZE >= MAX_NUM_KZG_COMMITMENTS * KZG_COMMITMENT_SIZE,
+            "MAX_HEADER_SIZE must be at least MAX_NUM_KZG_COMMITMENTS * KZG_COMMITMENT_SIZE."
+        );
+
+        let true_v = self._true();
+        let false_v = self._false();
+        let zero_u32 = self.zero::<U32Variable>();
+
+        // The extension starts after the last log of the digest.
+        let extension_start = self.get_extension_start(header, header_hash);
+
+        // Get the subarray of the header extension before the KZG commitments.
+        let prefix = self.get_fixed_subarray::<MAX_HEADER_SIZE, MAX_EXTENSION_PREFIX_LENGTH>(
+            &header.header_bytes,
+            extension_start,
+            &header_hash.as_bytes(),
+        );
+
+        // The first byte of the extension is the version tag.
+        let version_tag = self.constant::<ByteVariable>(CURRENT_HEADER_LAYOUT.version_tag());
+        self.assert_is_equal(prefix[0], version_tag);
+        let mut cursor = self.one::<Variable>();
+
+        // The app lookup is the compact encoded size, followed by the index of compact encoded
+        // (app_id, start) entries.
+        let (app_lookup_size, next_cursor) =
+            decode_compact_int_at(self, &prefix, cursor, header_hash);
+        let (app_lookup_len, next_cursor) =
+            decode_compact_int_at(self, &prefix, next_cursor, header_hash);
+        cursor = next_cursor;
+
+        let max_app_lookup_len = self.constant::<U32Variable>(MAX_APP_LOOKUP_LEN as u32);
+        let app_lookup_too_long = self.gt(app_lookup_len, max_app_lookup_len);
+        self.assert_is_equal(app_lookup_too_long, false_v);
+
+        let mut app_lookup = Vec::new();
+        for i in 0..MAX_APP_LOOKUP_LEN {
+            let idx = self.constant::<U32Variable>(i as u32);
+            let is_enabled = self.lt(idx, app_lookup_len);
+
+            let (app_id, next_cursor) = decode_compact_int_at(self, &prefix, cursor, header_hash);
+            let (start, next_cursor) =
+                decode_compact_int_at(self, &prefix, next_cursor, header_hash);
+
+            // Only advance the cursor past enabled entries.
+            cursor = self.select(is_enabled, next_cursor, cursor);
+            app_lookup.push(AppLookupItemVariable {
+                app_id: self.select(is_enabled, app_id, zero_u32),
+                start: self.select(is_enabled, start, zero_u32),
+            });
+        }
+
+        // The commitment is {rows, cols, commitment, data_root}, where rows and cols are compact
+        // encoded u16s and commitment is the SCALE encoded concatenation of the KZG commitments.
+        let (rows, next_cursor) = decode_compact_int_at(self, &prefix, cursor, header_hash);
+        let (cols, next_cursor) = decode_compact_int_at(self, &prefix, next_cursor, header_hash);
+        let (commitment_length, next_cursor) =
+            decode_compact_int_at(self, &prefix, next_cursor, header_hash);
+        cursor = next_cursor;
+
+        // The KZG commitments must end at the data root, which is the last 32 bytes of the header.
+        let commitments_start = self.add(extension_start, cursor);
+        let commitments_end = self.add(commitments_start, commitment_length.variable);
+        let data_root_length = self.constant::<Variable>(L::Field::from_canonical_usize(HASH_SIZE));
+        let extension_end = self.add(commitments_end, data_root_length);
+        self.assert_is_equal(extension_end, header.header_size.variable);
+
+        let commitment_bytes = self
+            .get_fixed_subarray::<MAX_HEADER_SIZE, { MAX_NUM_KZG_COMMITMENTS * KZG_COMMITMENT_SIZE }>(
+                &header.header_bytes,
+                commitments_start,
+                &header_hash.as_bytes(),
+            );
+
+        let zero_commitment =
+            self.constant::<BytesVariable<KZG_COMMITMENT_SIZE>>([0u8; KZG_COMMITMENT_SIZE]);
+        let mut num_commitments = zero_u32;
+        let mut commitments = Vec::new();
+        for i in 0..MAX_NUM_KZG_COMMITMENTS {
+            let offset = self.constant::<U32Variable>((i * KZG_COMMITMENT_SIZE) as u32);
+            let is_enabled = self.lt(offset, commitment_length);
+
+            // 1 if the commitment is enabled, 0 otherwise. BoolVariable is already range-checked (as
+            // a bool), so using unsafe to convert to U32Variable is safe.
+            let is_enabled_u32 = U32Variable::from_variables_unsafe(&[is_enabled.variable]);
+            num_commitments = self.add(num_commitments, is_enabled_u32);
+
+            let commitment = BytesVariable::<KZG_COMMITMENT_SIZE>(
+                commitment_bytes[i * KZG_COMMITMENT_SIZE..(i + 1) * KZG_COMMITMENT_SIZE]
+                    .try_into()
+                    .unwrap(),
+            );
+            commitments.push(self.select(is_enabled, commitment, zero_commitment));
+        }
+
+        // The commitment must be a whole number of KZG commitments.
+        let kzg_commitment_size = self.constant::<U32Variable>(KZG_COMMITMENT_SIZE as u32);
+        let num_commitment_bytes = self.mul(num_commitments, kzg_commitment_size);
+        let is_whole_commitments = self.is_equal(num_commitment_bytes, commitment_length);
+        self.assert_is_equal(is_whole_commitments, true_v);
+
+        HeaderExtensionVariable {
+            app_lookup_size,
+            app_lookup_len,
+            app_lookup: ArrayVariable::new(app_lookup),
+            rows,
+            cols,
+            num_commitments,
+            commitments: ArrayVariable::new(commitments),
+        }
+    }
+
+    fn compute_kzg_commitments_root(
+        &mut self,
+        extension: &HeaderExtensionVariable,
+    ) -> Bytes32Variable {
+        let leaves = extension
+            .commitments
+            .as_vec()
+            .iter()
+            .map(|commitment| self.sha256(&commitment.0))
+            .collect::<Vec<_>>();
+        self.get_root_from_hashed_leaves::<MAX_NUM_KZG_COMMITMENTS>(
+            ArrayVariable::new(leaves),
+            extension.num_commitments.variable,
+        )
+    }
+}
+
+// Fetch a header with a hint, and check it has CURRENT_HEADER_LAYOUT.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeaderExtensionFetcherHint<const HEADER_LENGTH: usize> {}
+
+#[async_trait]
+impl<const HEADER_LENGTH: usize, L: PlonkParameters<D>, const D: usize> AsyncHint<L, D>
+    for HeaderExtensionFetcherHint<HEADER_LENGTH>
+{
+    async fn hint(
+        &self,
+        input_stream: &mut ValueStream<L, D>,
+        output_stream: &mut ValueStream<L, D>,
+    ) {
+        let block_number = input_stream.read_value::<U32Variable>();
+
+        debug!(
+            "HeaderExtensionFetcherHint: downloading header of block_number={}",
+            block_number
+        );
+
+        let mut header_bytes = match WitnessBundle::from_env() {
+            Some(bundle) => bundle
+                .get_header(block_number)
+                .expect("Failed to get header from the witness bundle"),
+            None => {
+                let data_fetcher = RpcDataFetcher::new().await;
+                data_fetcher.get_header(block_number).await.encode()
+            }
+        };
+        let header_size = header_bytes.len();
+        if header_size > HEADER_LENGTH {
+            panic!(
+                "Block {}'s header size is {}, which is greater than the maximum header size of {} bytes.",
+                block_number, header_size, HEADER_LENGTH
+            );
+        }
+        if let Err(e) = CURRENT_HEADER_LAYOUT.check(&header_bytes) {
+            panic!("Block {}'s header is invalid: {}", block_number, e);
+        }
+        header_bytes.resize(HEADER_LENGTH, 0);
+
+        output_stream.write_value::<EncodedHeaderVariable<HEADER_LENGTH>>(EncodedHeader {
+            header_bytes,
+            header_size: header_size as u32,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use codec::Encode;
+    use ethers::types::H256;
+    use plonky2x::prelude::DefaultBuilder;
+
+    use super::*;
+    use crate::builder::header::HeaderMethods;
+    use crate::consts::MAX_HEADER_SIZE;
+    use crate::synthetic::{SyntheticChain, SyntheticChainConfig};
+
+    #[test]
+    fn test_decode_synthetic_header_extension() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        let mut builder = DefaultBuilder::new();
+        let header = builder.read::<EncodedHeaderVariable<MAX_HEADER_SIZE>>();
+        let header_hash = builder.hash_encoded_header::<MAX_HEADER_SIZE>(&header);
+        let extension = builder.decode_header_extension::<MAX_HEADER_SIZE>(&header, &header_hash);
+        let kzg_commitments_root = builder.compute_kzg_commitments_root(&extension);
+        builder.write::<U32Variable>(extension.rows);
+        builder.write::<U32Variable>(extension.cols);
+        builder.write::<U32Variable>(extension.num_commitments);
+        builder.write::<Bytes32Variable>(kzg_commitments_root);
+        let circuit = builder.build();
+
+        // The extension of an epoch end header starts after its GRANDPA ScheduledChange log.
+        let chain = SyntheticChain::new(SyntheticChainConfig {
+            authority_set_sizes: vec![4, 7],
+            ..Default::default()
+        });
+        for block_number in [5, 16] {
+            let header_bytes = chain.header(block_number).to_vec();
+            let expected = CURRENT_HEADER_LAYOUT
+                .decode_extension(&header_bytes)
+                .unwrap();
+
+            let mut input = circuit.input();
+            let header_size = header_bytes.len() as u32;
+            let mut header_bytes = header_bytes;
+            header_bytes.resize(MAX_HEADER_SIZE, 0);
+            input.write::<EncodedHeaderVariable<MAX_HEADER_SIZE>>(EncodedHeader {
+                header_bytes,
+                header_size,
+            });
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+
+            assert_eq!(output.read::<U32Variable>(), expected.rows as u32);
+            assert_eq!(output.read::<U32Variable>(), expected.cols as u32);
+            assert_eq!(
+                output.read::<U32Variable>(),
+                expected.kzg_commitments().unwrap().len() as u32
+            );
+            let root: H256 = output.read::<Bytes32Variable>();
+            assert_eq!(root, expected.kzg_commitments_root().unwrap());
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_decode_header_extension() {
+        const BLOCK_NUM: u32 = 272515;
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        let mut builder = DefaultBuilder::new();
+
+        let block_number = builder.read::<U32Variable>();
+        let header = builder.fetch_header_extension::<MAX_HEADER_SIZE>(block_number);
+        let header_hash = builder.hash_encoded_header::<MAX_HEADER_SIZE>(&header);
+        let extension = builder.decode_header_extension::<MAX_HEADER_SIZE>(&header, &header_hash);
+        let kzg_commitments_root = builder.compute_kzg_commitments_root(&extension);
+
+        builder.write::<U32Variable>(extension.rows);
+        builder.write::<U32Variable>(extension.cols);
+        builder.write::<U32Variable>(extension.num_commitments);
+        builder.write::<U32Variable>(extension.app_lookup_size);
+        builder.write::<U32Variable>(extension.app_lookup_len);
+        builder.write::<Bytes32Variable>(kzg_commitments_root);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<U32Variable>(BLOCK_NUM);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let header = rt.block_on(async {
+            let fetcher = RpcDataFetcher::new().await;
+            fetcher.get_header(BLOCK_NUM).await
+        });
+        let expected = CURRENT_HEADER_LAYOUT
+            .decode_extension(&header.encode())
+            .unwrap();
+
+        assert_eq!(output.read::<U32Variable>(), expected.rows as u32);
+        assert_eq!(output.read::<U32Variable>(), expected.cols as u32);
+        assert_eq!(
+            output.read::<U32Variable>(),
+            expected.kzg_commitments().unwrap().len() as u32
+        );
+        assert_eq!(output.read::<U32Variable>(), expected.app_lookup_size);
+        assert_eq!(
+            output.read::<U32Variable>(),
+            expected.app_lookup.len() as u32
+        );
+        let root: H256 = output.read::<Bytes32Variable>();
+        assert_eq!(root, expected.kzg_commitments_root().unwrap());
+    }
+}
@@ -1,22 +1,29 @@
 use async_trait::async_trait;
-use ethers::types::U256;
+use ethers::types::{H256, U256};
 use log::debug;
 use plonky2x::frontend::curta::ec::point::{CompressedEdwardsY, CompressedEdwardsYVariable};
-use plonky2x::frontend::ecc::curve25519::ed25519::eddsa::EDDSASignatureVariableValue;
+use plonky2x::frontend::ecc::curve25519::ed25519::eddsa::{
+    EDDSASignatureVariableValue, DUMMY_SIGNATURE,
+};
 use plonky2x::frontend::hint::asynchronous::hint::AsyncHint;
 use plonky2x::frontend::uint::uint64::U64Variable;
-use plonky2x::frontend::vars::{U32Variable, ValueStream, VariableStream};
+use plonky2x::frontend::vars::{EvmVariable, U32Variable, ValueStream, VariableStream};
 use plonky2x::prelude::{
-    ArrayVariable, BoolVariable, Bytes32Variable, CircuitBuilder, CircuitVariable, Field,
-    PlonkParameters, Variable,
+    ArrayVariable, BoolVariable, ByteVariable, Bytes32Variable, CircuitBuilder, CircuitVariable,
+    Field, PlonkParameters, RichField, Variable,
 };
 use serde::{Deserialize, Serialize};
 
 use super::decoder::DecodingMethods;
-use crate::consts::ENCODED_PRECOMMIT_LENGTH;
+use crate::authority_set::AuthoritySetCommitmentMode;
+use crate::consts::{AUTHORITY_SET_MERKLE_TREE_DEPTH, ENCODED_PRECOMMIT_LENGTH};
 use crate::input::types::CircuitJustification;
 use crate::input::{verify_signature, RpcDataFetcher};
-use crate::vars::{JustificationStruct, JustificationVariable};
+use crate::vars::{
+    JustificationStruct, JustificationSubsetStruct, JustificationSubsetVariable,
+    JustificationVariable,
+};
+use crate::witness::WitnessBundle;
 
 /// Fetch the simple justification for a block.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,17 +46,24 @@ impl<const NUM_AUTHORITIES: usize, L: PlonkParameters<D>, const D: usize> AsyncH
             block_number, authority_set_id
         );
 
-        let mut data_fetcher = RpcDataFetcher::new().await;
-        let justification_data: CircuitJustification = data_fetcher
-            .get_justification_from_block::<NUM_AUTHORITIES>(block_number)
-            .await
-            .expect("Failed to get justification");
+        let justification_data: CircuitJustification = match WitnessBundle::from_env() {
+            Some(bundle) => bundle
+                .get_justification::<NUM_AUTHORITIES>(block_number)
+                .expect("Failed to get justification from the witness bundle"),
+            None => {
+                let mut data_fetcher = RpcDataFetcher::new().await;
+                data_fetcher
+                    .get_justification_from_block::<NUM_AUTHORITIES>(block_number)
+                    .await
+                    .expect("Failed to get justification")
+            }
+        };
 
         if justification_data.authority_set_id != authority_set_id {
             panic!("Authority set id does not match");
         }
 
-        let encoded_precommit = justification_data.signed_message;
+        let encoded_precommit = &justification_data.signed_message;
         if encoded_precommit.len() != ENCODED_PRECOMMIT_LENGTH {
             panic!("Encoded precommit is not the correct length");
         }
@@ -61,7 +75,7 @@ impl<const NUM_AUTHORITIES: usize, L: PlonkParameters<D>, const D: usize> AsyncH
             }
             verify_signature(
                 justification_data.pubkeys[i].as_bytes(),
-                &encoded_precommit,
+                encoded_precommit,
                 &justification_data.signatures[i],
             );
         }
@@ -83,6 +97,145 @@ impl<const NUM_AUTHORITIES: usize, L: PlonkParameters<D>, const D: usize> AsyncH
     }
 }
 
+/// The number of signatures verified by `verify_simple_justification_subset` for an authority set
+/// of num_authorities authorities, ceil(2 * num_authorities / 3) + 1, which is always enough for more
+/// than 2/3 of the authorities.
+pub const fn get_num_justification_signers(num_authorities: usize) -> usize {
+    let num_signers = (2 * num_authorities + 2) / 3 + 1;
+    if num_signers < num_authorities {
+        num_signers
+    } else {
+        num_authorities
+    }
+}
+
+/// Select the indices of the first get_num_justification_signers(num_authorities) authorities that
+/// signed, in increasing order. If fewer authorities signed, all of them are selected.
+pub fn select_justification_signers(
+    validator_signed: &[bool],
+    num_authorities: usize,
+) -> Vec<usize> {
+    validator_signed[..num_authorities]
+        .iter()
+        .enumerate()
+        .filter(|(_, signed)| **signed)
+        .map(|(i, _)| i)
+        .take(get_num_justification_signers(num_authorities))
+        .collect()
+}
+
+/// Fetch the simple justification for a block, with only the signatures of the signer subset
+/// selected by `select_justification_signers`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HintSubsetJustification<const NUM_AUTHORITIES: usize, const NUM_SIGNERS: usize> {}
+
+#[async_trait]
+impl<
+        const NUM_AUTHORITIES: usize,
+        const NUM_SIGNERS: usize,
+        L: PlonkParameters<D>,
+        const D: usize,
+    > AsyncHint<L, D> for HintSubsetJustification<NUM_AUTHORITIES, NUM_SIGNERS>
+{
+    async fn hint(
+        &self,
+        input_stream: &mut ValueStream<L, D>,
+        output_stream: &mut ValueStream<L, D>,
+    ) {
+        let block_number = input_stream.read_value::<U32Variable>();
+        let authority_set_id = input_stream.read_value::<U64Variable>();
+
+        debug!(
+            "HintSubsetJustification: downloading justification for block_number={} authority_set_id={}",
+            block_number, authority_set_id
+        );
+
+        let justification_data: CircuitJustification = match WitnessBundle::from_env() {
+            Some(bundle) => bundle
+                .get_justification::<NUM_AUTHORITIES>(block_number)
+                .expect("Failed to get justification from the witness bundle"),
+            None => {
+                let mut data_fetcher = RpcDataFetcher::new().await;
+                data_fetcher
+                    .get_justification_from_block::<NUM_AUTHORITIES>(block_number)
+                    .await
+                    .expect("Failed to get justification")
+            }
+        };
+
+        if justification_data.authority_set_id != authority_set_id {
+            panic!("Authority set id does not match");
+        }
+
+        let encoded_precommit = &justification_data.signed_message;
+        if encoded_precommit.len() != ENCODED_PRECOMMIT_LENGTH {
+            panic!("Encoded precommit is not the correct length");
+        }
+
+        let signers = select_justification_signers(
+            &justification_data.validator_signed,
+            justification_data.num_authorities,
+        );
+        if signers.len() > NUM_SIGNERS {
+            panic!("Too many signers for the number of signer slots");
+        }
+
+        for &i in &signers {
+            verify_signature(
+                justification_data.pubkeys[i].as_bytes(),
+                encoded_precommit,
+                &justification_data.signatures[i],
+            );
+        }
+
+        output_stream.write_value::<JustificationSubsetVariable<NUM_AUTHORITIES, NUM_SIGNERS>>(
+            get_justification_subset(&justification_data, &signers),
+        );
+    }
+}
+
+/// The value of a JustificationSubsetVariable with the signatures of the authorities at the signer
+/// indices, in order. The signatures are not verified. Pads the disabled signer slots, which select
+/// the first authority.
+pub fn get_justification_subset<
+    const NUM_AUTHORITIES: usize,
+    const NUM_SIGNERS: usize,
+    F: RichField,
+>(
+    justification: &CircuitJustification,
+    signers: &[usize],
+) -> JustificationSubsetStruct<NUM_AUTHORITIES, NUM_SIGNERS, F> {
+    assert!(
+        signers.len() <= NUM_SIGNERS,
+        "Too many signers for the number of signer slots"
+    );
+
+    let mut signer_enabled = vec![true; signers.len()];
+    let mut signer_indices = signers.iter().map(|&i| i as u32).collect::<Vec<_>>();
+    let mut signatures = signers
+        .iter()
+        .map(|&i| justification.signatures[i])
+        .collect::<Vec<_>>();
+    signer_enabled.resize(NUM_SIGNERS, false);
+    signer_indices.resize(NUM_SIGNERS, 0);
+    signatures.resize(NUM_SIGNERS, DUMMY_SIGNATURE);
+
+    JustificationSubsetStruct {
+        encoded_precommit: justification.signed_message.clone().try_into().unwrap(),
+        pubkeys: justification.pubkeys.clone(),
+        num_authorities: justification.num_authorities as u32,
+        signer_enabled,
+        signer_indices,
+        signatures: signatures
+            .iter()
+            .map(|sig| EDDSASignatureVariableValue {
+                r: CompressedEdwardsY::from_slice(&sig[0..32]).unwrap(),
+                s: U256::from_little_endian(&sig[32..64]),
+            })
+            .collect(),
+    }
+}
+
 pub trait GrandpaJustificationVerifier {
     /// Compute the authority set commitment of an authority set. This is the chained SHA256 hash of
     /// the first num_active_authorities public keys. The ordering of the authorities is the same as
@@ -96,6 +249,25 @@ pub trait GrandpaJustificationVerifier {
         authority_set_signers: &ArrayVariable<CompressedEdwardsYVariable, MAX_NUM_AUTHORITIES>,
     ) -> Bytes32Variable;
 
+    /// Compute the Merkle commitment of the first num_active_authorities public keys, with the hash
+    /// function of a Merkle commitment mode. Matches `get_authority_set_merkle_root`: the leaves are
+    /// the public keys, padded with zero leaves to a tree of depth AUTHORITY_SET_MERKLE_TREE_DEPTH,
+    /// and the root of the tree is hashed with num_active_authorities.
+    fn compute_authority_set_merkle_root<const MAX_NUM_AUTHORITIES: usize>(
+        &mut self,
+        num_active_authorities: Variable,
+        authority_set_signers: &ArrayVariable<CompressedEdwardsYVariable, MAX_NUM_AUTHORITIES>,
+        mode: AuthoritySetCommitmentMode,
+    ) -> Bytes32Variable;
+
+    /// Compute the authority set commitment of an authority set with the given commitment mode.
+    fn compute_authority_set_commitment_with_mode<const MAX_NUM_AUTHORITIES: usize>(
+        &mut self,
+        num_active_authorities: Variable,
+        authority_set_signers: &ArrayVariable<CompressedEdwardsYVariable, MAX_NUM_AUTHORITIES>,
+        mode: AuthoritySetCommitmentMode,
+    ) -> Bytes32Variable;
+
     /// Verify the number of validators that signed is > the threshold.
     fn verify_voting_threshold<const MAX_NUM_AUTHORITIES: usize>(
         &mut self,
@@ -121,6 +293,66 @@ pub trait GrandpaJustificationVerifier {
         authority_set_id: U64Variable,
         authority_set_hash: Bytes32Variable,
     );
+
+    /// Verify a simple justification on a block from the specified authority set, where
+    /// authority_set_hash is the commitment to the authority set with the given commitment mode.
+    fn verify_simple_justification_with_mode<const MAX_NUM_AUTHORITIES: usize>(
+        &mut self,
+        block_number: U32Variable,
+        block_hash: Bytes32Variable,
+        authority_set_id: U64Variable,
+        authority_set_hash: Bytes32Variable,
+        mode: AuthoritySetCommitmentMode,
+    );
+
+    /// Verify a simple justification on a block from the specified authority set, from the
+    /// signatures of only NUM_SIGNERS authorities instead of every authority in the set.
+    /// NUM_SIGNERS must be get_num_justification_signers(MAX_NUM_AUTHORITIES).
+    ///
+    /// Specifically, this verifies that:
+    ///     1) Authority set commitment matches the authority set.
+    ///     2) Specified precommit message matches the block #, authority set id, and block hash.
+    ///     3) The signer indices are strictly increasing (so distinct), and in the authority set.
+    ///     4) Signatures on the precommit message are valid from the authority at each signer index.
+    ///     5) More than 2/3 of the validators have signed the precommit message.
+    fn verify_simple_justification_subset<
+        const MAX_NUM_AUTHORITIES: usize,
+        const NUM_SIGNERS: usize,
+    >(
+        &mut self,
+        block_number: U32Variable,
+        block_hash: Bytes32Variable,
+        authority_set_id: U64Variable,
+        authority_set_hash: Bytes32Variable,
+        mode: AuthoritySetCommitmentMode,
+    );
+
+    /// Verify a justification of a block from the signatures of a subset of the authorities, as in
+    /// `verify_simple_justification_subset`, with the justification given instead of fetched.
+    fn verify_justification_subset<const MAX_NUM_AUTHORITIES: usize, const NUM_SIGNERS: usize>(
+        &mut self,
+        block_number: U32Variable,
+        block_hash: Bytes32Variable,
+        authority_set_id: U64Variable,
+        authority_set_hash: Bytes32Variable,
+        justification: JustificationSubsetVariable<MAX_NUM_AUTHORITIES, NUM_SIGNERS>,
+        mode: AuthoritySetCommitmentMode,
+    );
+}
+
+// Hash the concatenation of two nodes of the Merkle tree of a Merkle commitment mode.
+fn hash_merkle_nodes<L: PlonkParameters<D>, const D: usize>(
+    builder: &mut CircuitBuilder<L, D>,
+    input_to_hash: &[ByteVariable],
+    mode: AuthoritySetCommitmentMode,
+) -> Bytes32Variable {
+    match mode {
+        AuthoritySetCommitmentMode::MerkleSha256 => builder.curta_sha256(input_to_hash),
+        AuthoritySetCommitmentMode::MerkleKeccak => builder.curta_keccak256(input_to_hash),
+        AuthoritySetCommitmentMode::ChainedSha256 => {
+            panic!("ChainedSha256 is not a Merkle commitment mode")
+        }
+    }
 }
 
 impl<L: PlonkParameters<D>, const D: usize> GrandpaJustificationVerifier for CircuitBuilder<L, D> {
@@ -161,6 +393,85 @@ impl<L: PlonkParameters<D>, const D: usize> GrandpaJustificationVerifier for Cir
         commitment_so_far
     }
 
+    fn compute_authority_set_merkle_root<const MAX_NUM_AUTHORITIES: usize>(
+        &mut self,
+        num_active_authorities: Variable,
+        authority_set_signers: &ArrayVariable<CompressedEdwardsYVariable, MAX_NUM_AUTHORITIES>,
+        mode: AuthoritySetCommitmentMode,
+    ) -> Bytes32Variable {
+        assert!(
+            MAX_NUM_AUTHORITIES <= 1 << AUTHORITY_SET_MERKLE_TREE_DEPTH,
+            "Max number of authorities is too large for the authority set Merkle tree"
+        );
+
+        let false_v = self._false();
+        let zero = self.zero();
+        let invalid_num_authorities = self.is_equal(num_active_authorities, zero);
+        // Assert there is at least 1 authority.
+        self.assert_is_equal(invalid_num_authorities, false_v);
+
+        // The leaves are the first num_active_authorities public keys, followed by empty leaves.
+        let empty_leaf = self.constant::<Bytes32Variable>(H256::zero());
+        let mut authority_enabled = self._true();
+        let mut nodes = Vec::new();
+        for i in 0..MAX_NUM_AUTHORITIES {
+            let curr_idx = self.constant::<Variable>(L::Field::from_canonical_usize(i));
+            let at_end = self.is_equal(curr_idx, num_active_authorities);
+            let not_at_end = self.not(at_end);
+            authority_enabled = self.and(authority_enabled, not_at_end);
+
+            nodes.push(self.select(authority_enabled, authority_set_signers[i].0, empty_leaf));
+        }
+
+        // Hash up the tree. Subtrees past MAX_NUM_AUTHORITIES are always empty, so their roots are
+        // constants.
+        let empty_subtree_roots = mode.empty_subtree_roots();
+        for empty_subtree_root in empty_subtree_roots {
+            if nodes.len() % 2 == 1 {
+                nodes.push(self.constant::<Bytes32Variable>(H256::from(empty_subtree_root)));
+            }
+            nodes = nodes
+                .chunks_exact(2)
+                .map(|pair| {
+                    let mut input_to_hash = Vec::new();
+                    input_to_hash.extend_from_slice(&pair[0].as_bytes());
+                    input_to_hash.extend_from_slice(&pair[1].as_bytes());
+                    hash_merkle_nodes(self, &input_to_hash, mode)
+                })
+                .collect();
+        }
+
+        // Hash the root with the number of authorities as a uint256, so the authority set can't be
+        // extended with zero pubkeys, which are the empty leaves.
+        let num_authorities = U32Variable::from_variables_unsafe(&[num_active_authorities]);
+        let zero_byte = self.constant::<ByteVariable>(0);
+        let mut input_to_hash = Vec::new();
+        input_to_hash.extend_from_slice(&nodes[0].as_bytes());
+        input_to_hash.extend(vec![zero_byte; 28]);
+        input_to_hash.extend(num_authorities.encode(self));
+        hash_merkle_nodes(self, &input_to_hash, mode)
+    }
+
+    fn compute_authority_set_commitment_with_mode<const MAX_NUM_AUTHORITIES: usize>(
+        &mut self,
+        num_active_authorities: Variable,
+        authority_set_signers: &ArrayVariable<CompressedEdwardsYVariable, MAX_NUM_AUTHORITIES>,
+        mode: AuthoritySetCommitmentMode,
+    ) -> Bytes32Variable {
+        match mode {
+            AuthoritySetCommitmentMode::ChainedSha256 => {
+                self.compute_authority_set_commitment(num_active_authorities, authority_set_signers)
+            }
+            AuthoritySetCommitmentMode::MerkleSha256 | AuthoritySetCommitmentMode::MerkleKeccak => {
+                self.compute_authority_set_merkle_root(
+                    num_active_authorities,
+                    authority_set_signers,
+                    mode,
+                )
+            }
+        }
+    }
+
     fn verify_voting_threshold<const MAX_NUM_AUTHORITIES: usize>(
         &mut self,
         num_active_authorities: U32Variable,
@@ -198,6 +509,23 @@ impl<L: PlonkParameters<D>, const D: usize> GrandpaJustificationVerifier for Cir
         block_hash: Bytes32Variable,
         authority_set_id: U64Variable,
         authority_set_hash: Bytes32Variable,
+    ) {
+        self.verify_simple_justification_with_mode::<MAX_NUM_AUTHORITIES>(
+            block_number,
+            block_hash,
+            authority_set_id,
+            authority_set_hash,
+            AuthoritySetCommitmentMode::ChainedSha256,
+        )
+    }
+
+    fn verify_simple_justification_with_mode<const MAX_NUM_AUTHORITIES: usize>(
+        &mut self,
+        block_number: U32Variable,
+        block_hash: Bytes32Variable,
+        authority_set_id: U64Variable,
+        authority_set_hash: Bytes32Variable,
+        mode: AuthoritySetCommitmentMode,
     ) {
         let mut input_stream = VariableStream::new();
         input_stream.write(&block_number);
@@ -211,9 +539,10 @@ impl<L: PlonkParameters<D>, const D: usize> GrandpaJustificationVerifier for Cir
         let justification = output_stream.read::<JustificationVariable<MAX_NUM_AUTHORITIES>>(self);
 
         // Verify the authority set commitment is valid.
-        let computed_authority_set_commitment = self.compute_authority_set_commitment(
+        let computed_authority_set_commitment = self.compute_authority_set_commitment_with_mode(
             justification.num_authorities.variable,
             &justification.pubkeys,
+            mode,
         );
         self.assert_is_equal(authority_set_hash, computed_authority_set_commitment);
 
@@ -255,15 +584,227 @@ impl<L: PlonkParameters<D>, const D: usize> GrandpaJustificationVerifier for Cir
             three_v,
         )
     }
+
+    fn verify_simple_justification_subset<
+        const MAX_NUM_AUTHORITIES: usize,
+        const NUM_SIGNERS: usize,
+    >(
+        &mut self,
+        block_number: U32Variable,
+        block_hash: Bytes32Variable,
+        authority_set_id: U64Variable,
+        authority_set_hash: Bytes32Variable,
+        mode: AuthoritySetCommitmentMode,
+    ) {
+        assert_eq!(
+            NUM_SIGNERS,
+            get_num_justification_signers(MAX_NUM_AUTHORITIES),
+            "NUM_SIGNERS must be get_num_justification_signers(MAX_NUM_AUTHORITIES)."
+        );
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&block_number);
+        input_stream.write(&authority_set_id);
+        let output_stream = self.async_hint(
+            input_stream,
+            HintSubsetJustification::<MAX_NUM_AUTHORITIES, NUM_SIGNERS> {},
+        );
+
+        // justification is untrusted, and must be linked to the trusted authority_set_hash.
+        let justification = output_stream
+            .read::<JustificationSubsetVariable<MAX_NUM_AUTHORITIES, NUM_SIGNERS>>(self);
+        self.verify_justification_subset(
+            block_number,
+            block_hash,
+            authority_set_id,
+            authority_set_hash,
+            justification,
+            mode,
+        );
+    }
+
+    fn verify_justification_subset<const MAX_NUM_AUTHORITIES: usize, const NUM_SIGNERS: usize>(
+        &mut self,
+        block_number: U32Variable,
+        block_hash: Bytes32Variable,
+        authority_set_id: U64Variable,
+        authority_set_hash: Bytes32Variable,
+        justification: JustificationSubsetVariable<MAX_NUM_AUTHORITIES, NUM_SIGNERS>,
+        mode: AuthoritySetCommitmentMode,
+    ) {
+        // Verify the authority set commitment is valid.
+        let computed_authority_set_commitment = self.compute_authority_set_commitment_with_mode(
+            justification.num_authorities.variable,
+            &justification.pubkeys,
+            mode,
+        );
+        self.assert_is_equal(authority_set_hash, computed_authority_set_commitment);
+
+        // Verify the correctness of the encoded_precommit message.
+        let decoded_precommit = self.decode_precommit(justification.encoded_precommit);
+        self.assert_is_equal(decoded_precommit.block_number, block_number);
+        self.assert_is_equal(decoded_precommit.authority_set_id, authority_set_id);
+        self.assert_is_equal(decoded_precommit.block_hash, block_hash);
+
+        // The committed authority set only covers the first num_authorities pubkeys.
+        let true_v = self._true();
+        let max_num_authorities = self.constant::<U32Variable>(MAX_NUM_AUTHORITIES as u32);
+        let is_valid_num_authorities = self.lte(justification.num_authorities, max_num_authorities);
+        self.assert_is_equal(is_valid_num_authorities, true_v);
+
+        // Verify the signer indices and look up the pubkey of each signer.
+        let zero = self.zero();
+        let mut signer_pubkeys = Vec::new();
+        for i in 0..NUM_SIGNERS {
+            let signer_enabled = justification.signer_enabled[i];
+            let signer_disabled = self.not(signer_enabled);
+            let signer_index = justification.signer_indices[i];
+
+            // The enabled signers are a prefix of the signer slots, so a signer is only enabled if
+            // the previous signer is enabled.
+            // Note: The indices of enabled signers are strictly increasing, so each authority can
+            // be counted at most once towards the threshold.
+            if i > 0 {
+                let prev_signer_enabled = justification.signer_enabled[i - 1];
+                let is_valid_prefix = self.or(prev_signer_enabled, signer_disabled);
+                self.assert_is_equal(is_valid_prefix, true_v);
+
+                let is_increasing = self.gt(signer_index, justification.signer_indices[i - 1]);
+                let is_valid_order = self.or(is_increasing, signer_disabled);
+                self.assert_is_equal(is_valid_order, true_v);
+            }
+
+            // The signer must be in the committed authority set.
+            let is_member = self.lt(signer_index, justification.num_authorities);
+            let is_valid_member = self.or(is_member, signer_disabled);
+            self.assert_is_equal(is_valid_member, true_v);
+
+            // Disabled signer slots select the first authority, whose signature is not verified.
+            let selector = self.select(signer_enabled, signer_index.variable, zero);
+            signer_pubkeys
+                .push(self.select_array_random_gate(&justification.pubkeys.data, selector));
+        }
+
+        // Verify the signatures of the signers on the encoded_precommit message.
+        let message_byte_lengths = self.constant::<ArrayVariable<U32Variable, NUM_SIGNERS>>(vec![
+                ENCODED_PRECOMMIT_LENGTH
+                    as u32;
+                NUM_SIGNERS
+            ]);
+        // Note: Sharing one precommit guarantees that all votes occurred during the same round of consensus.
+        let messages = vec![justification.encoded_precommit; NUM_SIGNERS];
+        self.curta_eddsa_verify_sigs_conditional(
+            justification.signer_enabled.clone(),
+            Some(message_byte_lengths),
+            messages.into(),
+            justification.signatures,
+            signer_pubkeys.into(),
+        );
+
+        // Verify more than 2/3 of the validators have signed the message.
+        let two_v = self.constant::<U32Variable>(2u32);
+        let three_v = self.constant::<U32Variable>(3u32);
+        self.verify_voting_threshold(
+            justification.num_authorities,
+            &justification.signer_enabled,
+            two_v,
+            three_v,
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
 
-    use plonky2x::prelude::DefaultBuilder;
+    use plonky2x::prelude::{DefaultBuilder, GoldilocksField};
 
     use super::*;
+    use crate::authority_set::get_authority_set_merkle_root;
+    use crate::input::compute_authority_set_hash;
+    use crate::synthetic::{SyntheticChain, SyntheticChainConfig};
+
+    #[test]
+    fn test_select_justification_signers() {
+        assert_eq!(get_num_justification_signers(1), 1);
+        assert_eq!(get_num_justification_signers(4), 4);
+        assert_eq!(get_num_justification_signers(10), 8);
+        assert_eq!(get_num_justification_signers(300), 201);
+
+        // Only the first get_num_justification_signers(num_authorities) signers are selected.
+        let validator_signed = [
+            true, false, true, true, true, true, true, true, true, true, true,
+        ];
+        assert_eq!(
+            select_justification_signers(&validator_signed, 10),
+            vec![0, 2, 3, 4, 5, 6, 7, 8]
+        );
+
+        // Slots past num_authorities are not selected.
+        let validator_signed = [false, true, true, true];
+        assert_eq!(
+            select_justification_signers(&validator_signed, 3),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_compute_authority_set_merkle_root() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        const MAX_NUM_AUTHORITIES: usize = 6;
+        let authorities = (0..4)
+            .map(|i| CompressedEdwardsY([i as u8 + 1; 32]))
+            .collect::<Vec<_>>();
+        let mut padded_authorities = authorities.clone();
+        padded_authorities.resize(MAX_NUM_AUTHORITIES, CompressedEdwardsY([9u8; 32]));
+
+        for mode in [
+            AuthoritySetCommitmentMode::MerkleSha256,
+            AuthoritySetCommitmentMode::MerkleKeccak,
+        ] {
+            let mut builder = DefaultBuilder::new();
+            let num_active_authorities = builder.read::<Variable>();
+            let pubkeys =
+                builder.read::<ArrayVariable<CompressedEdwardsYVariable, MAX_NUM_AUTHORITIES>>();
+            let root =
+                builder.compute_authority_set_merkle_root(num_active_authorities, &pubkeys, mode);
+            builder.write(root);
+            let circuit = builder.build();
+
+            let mut input = circuit.input();
+            input.write::<Variable>(GoldilocksField::from_canonical_usize(authorities.len()));
+            input.write::<ArrayVariable<CompressedEdwardsYVariable, MAX_NUM_AUTHORITIES>>(
+                padded_authorities.clone(),
+            );
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+
+            // The pubkeys past num_active_authorities are not committed to.
+            let root = output.read::<Bytes32Variable>();
+            assert_eq!(root, get_authority_set_merkle_root(&authorities, mode));
+
+            // Zero pubkeys, which are the empty leaves, can't extend the authority set.
+            let mut zero_padded_authorities = authorities.clone();
+            zero_padded_authorities.resize(MAX_NUM_AUTHORITIES, CompressedEdwardsY([0u8; 32]));
+            let mut input = circuit.input();
+            input.write::<Variable>(GoldilocksField::from_canonical_usize(MAX_NUM_AUTHORITIES));
+            input.write::<ArrayVariable<CompressedEdwardsYVariable, MAX_NUM_AUTHORITIES>>(
+                zero_padded_authorities.clone(),
+            );
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            let zero_padded_root = output.read::<Bytes32Variable>();
+            assert_eq!(
+                zero_padded_root,
+                get_authority_set_merkle_root(&zero_padded_authorities, mode)
+            );
+            assert_ne!(zero_padded_root, root);
+        }
+    }
 
     #[test]
     #[cfg_attr(feature = "ci", ignore)]
@@ -317,4 +858,67 @@ mod tests {
 
         circuit.verify(&proof, &input, &output);
     }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_justification_subset() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        const MAX_NUM_AUTHORITIES: usize = 8;
+        const NUM_SIGNERS: usize = get_num_justification_signers(MAX_NUM_AUTHORITIES);
+        let mut builder = DefaultBuilder::new();
+        let block_number = builder.read::<U32Variable>();
+        let block_hash = builder.read::<Bytes32Variable>();
+        let authority_set_id = builder.read::<U64Variable>();
+        let authority_set_hash = builder.read::<Bytes32Variable>();
+        let justification =
+            builder.read::<JustificationSubsetVariable<MAX_NUM_AUTHORITIES, NUM_SIGNERS>>();
+        builder.verify_justification_subset(
+            block_number,
+            block_hash,
+            authority_set_id,
+            authority_set_hash,
+            justification,
+            AuthoritySetCommitmentMode::ChainedSha256,
+        );
+        let circuit = builder.build();
+
+        // Every authority of a set of 5 signs the block. Claim a set of only the first 4
+        // authorities, so the 5th authority's signature is valid but not from a member.
+        let chain = SyntheticChain::new(SyntheticChainConfig {
+            authority_set_sizes: vec![5],
+            ..Default::default()
+        });
+        let target_block = 10;
+        let mut justification = chain
+            .witness_bundle(&[target_block])
+            .get_justification::<MAX_NUM_AUTHORITIES>(target_block)
+            .unwrap();
+        assert!(justification.validator_signed.iter().all(|signed| *signed));
+        justification.num_authorities = 4;
+        let authority_set_hash =
+            H256::from_slice(&compute_authority_set_hash(&justification.pubkeys[..4]));
+
+        let prove = |signers: &[usize]| {
+            let mut input = circuit.input();
+            input.write::<U32Variable>(target_block);
+            input.write::<Bytes32Variable>(chain.header_hash(target_block));
+            input.write::<U64Variable>(justification.authority_set_id);
+            input.write::<Bytes32Variable>(authority_set_hash);
+            input.write::<JustificationSubsetVariable<MAX_NUM_AUTHORITIES, NUM_SIGNERS>>(
+                get_justification_subset(&justification, signers),
+            );
+            let (proof, output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+        };
+
+        // 3 of the 4 authorities is more than 2/3 of the authority set.
+        prove(&[0, 1, 2]);
+
+        // 2 distinct members are not enough, whether the third signer repeats a member or is
+        // outside the authority set.
+        assert!(catch_unwind(AssertUnwindSafe(|| prove(&[0, 1, 1]))).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| prove(&[0, 1, 4]))).is_err());
+    }
 }
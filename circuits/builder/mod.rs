@@ -1,4 +1,6 @@
+pub mod ancestry;
 pub mod decoder;
+pub mod extension;
 pub mod header;
 pub mod justification;
 pub mod rotate;
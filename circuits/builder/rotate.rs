@@ -7,11 +7,13 @@ use plonky2x::prelude::{
 
 use super::decoder::DecodingMethods;
 use super::header::HeaderMethods;
+use crate::authority_set::AuthoritySetCommitmentMode;
 use crate::builder::justification::GrandpaJustificationVerifier;
 use crate::consts::{
     CONSENSUS_ENGINE_ID_PREFIX_LENGTH, DELAY_LENGTH, MAX_COMPACT_UINT_BYTES, MAX_PREFIX_LENGTH,
     PUBKEY_LENGTH, VALIDATOR_LENGTH, WEIGHT_LENGTH,
 };
+use crate::layout::CURRENT_HEADER_LAYOUT;
 use crate::vars::*;
 
 pub trait RotateMethods {
@@ -68,6 +70,19 @@ pub trait RotateMethods {
         current_authority_set_hash: Bytes32Variable,
         rotate: RotateVariable<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE>,
     ) -> Bytes32Variable;
+
+    // Rotate where the current and new authority set commitments use the given commitment mode.
+    fn rotate_with_mode<
+        const MAX_HEADER_SIZE: usize,
+        const MAX_AUTHORITY_SET_SIZE: usize,
+        const MAX_SUBARRAY_SIZE: usize,
+    >(
+        &mut self,
+        current_authority_set_id: U64Variable,
+        current_authority_set_hash: Bytes32Variable,
+        rotate: RotateVariable<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE>,
+        mode: AuthoritySetCommitmentMode,
+    ) -> Bytes32Variable;
 }
 
 impl<L: PlonkParameters<D>, const D: usize> RotateMethods for CircuitBuilder<L, D> {
@@ -284,23 +299,58 @@ impl<L: PlonkParameters<D>, const D: usize> RotateMethods for CircuitBuilder<L,
         current_authority_set_id: U64Variable,
         current_authority_set_hash: Bytes32Variable,
         rotate: RotateVariable<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE>,
+    ) -> Bytes32Variable {
+        self.rotate_with_mode::<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE, MAX_SUBARRAY_SIZE>(
+            current_authority_set_id,
+            current_authority_set_hash,
+            rotate,
+            AuthoritySetCommitmentMode::ChainedSha256,
+        )
+    }
+
+    fn rotate_with_mode<
+        const MAX_HEADER_SIZE: usize,
+        const MAX_AUTHORITY_SET_SIZE: usize,
+        const MAX_SUBARRAY_SIZE: usize,
+    >(
+        &mut self,
+        current_authority_set_id: U64Variable,
+        current_authority_set_hash: Bytes32Variable,
+        rotate: RotateVariable<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE>,
+        mode: AuthoritySetCommitmentMode,
     ) -> Bytes32Variable {
         assert_eq!(
             MAX_SUBARRAY_SIZE,
             MAX_AUTHORITY_SET_SIZE * VALIDATOR_LENGTH + DELAY_LENGTH,
             "MAX_SUBARRAY_SIZE must be equal to MAX_AUTHORITY_SET_SIZE * VALIDATOR_LENGTH + DELAY_LENGTH."
         );
+        // The encoded authority set is read from the header, so it must fit in the header.
+        assert!(
+            MAX_SUBARRAY_SIZE <= MAX_HEADER_SIZE,
+            "MAX_SUBARRAY_SIZE {} does not fit in MAX_HEADER_SIZE {}.",
+            MAX_SUBARRAY_SIZE,
+            MAX_HEADER_SIZE
+        );
 
         // Hash the header at epoch_end_block.
         let target_header_hash = self.hash_encoded_header::<MAX_HEADER_SIZE>(&rotate.target_header);
 
+        // decode_header does not constrain the header layout, so constrain it once per epoch on the
+        // epoch end header.
+        self.assert_header_layout::<MAX_HEADER_SIZE>(
+            &rotate.target_header,
+            &target_header_hash,
+            CURRENT_HEADER_LAYOUT,
+        );
+
         // Verify the justification from the current authority set on the epoch end header.
         // Note: current_authority_set_id and current_authority_set_hash are trusted at this point.
-        self.verify_simple_justification::<MAX_AUTHORITY_SET_SIZE>(
+        self.verify_simple_justification_with_mode::<MAX_AUTHORITY_SET_SIZE>(
             rotate.epoch_end_block_number,
             target_header_hash,
             current_authority_set_id,
             current_authority_set_hash,
+            mode,
         );
 
         // Verify the epoch end header and the new authority set are valid.
@@ -316,9 +366,10 @@ impl<L: PlonkParameters<D>, const D: usize> RotateMethods for CircuitBuilder<L,
         // Compute the authority set commitment of the new authority set. The order of the validators
         // in the authority set commitment matches the order of the encoded validator data in the epoch end header.
         // Note: target_header_num_authorities and next_authority_set_start_position are trusted at this point.
-        self.compute_authority_set_commitment(
+        self.compute_authority_set_commitment_with_mode(
             rotate.target_header_num_authorities,
             &rotate.new_pubkeys,
+            mode,
         )
     }
 }
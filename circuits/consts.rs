@@ -1,4 +1,4 @@
-// The data root start byte number from the end.
+// The data root start byte number from the end of V2 and V3 headers (see layout::HeaderLayout).
 // E.g. data root byte start idx is N - DATA_ROOT_OFFSET_FROM_END where N is the header size.
 pub const DATA_ROOT_OFFSET_FROM_END: u32 = 32;
 
@@ -15,6 +15,13 @@ pub const BLAKE2B_CHUNK_SIZE_BYTES: usize = 128;
 // (Data limit is 512KB).
 pub const MAX_HEADER_SIZE: usize = MAX_HEADER_CHUNK_SIZE * BLAKE2B_CHUNK_SIZE_BYTES;
 
+// Maximum header size (in blake2b chunks) that can be processed by the small header range circuits.
+// Header ranges with a larger header are proven with the MAX_HEADER_SIZE circuits instead.
+pub const SMALL_MAX_HEADER_CHUNK_SIZE: usize = 16;
+
+// Maximum header size (in bytes) that can be processed by the small header range circuits.
+pub const SMALL_MAX_HEADER_SIZE: usize = SMALL_MAX_HEADER_CHUNK_SIZE * BLAKE2B_CHUNK_SIZE_BYTES;
+
 // Digest byte size.
 pub const HASH_SIZE: usize = 32;
 
@@ -51,5 +58,44 @@ pub const MAX_SUBARRAY_SIZE: usize = MAX_AUTHORITY_SET_SIZE * VALIDATOR_LENGTH +
 // Max number of authorities this circuit currently supports.
 pub const MAX_AUTHORITY_SET_SIZE: usize = 300;
 
+// Max authority set sizes of the authority set size tiers that the rotate and header range circuits
+// are built for. The operator requests proofs from the smallest tier that fits the authority set.
+// Note: The encoded authority set of a tier's epoch end header must fit in MAX_HEADER_SIZE, which
+// limits the tiers to MAX_HEADER_SIZE / VALIDATOR_LENGTH = 896 authorities.
+pub const AUTHORITY_SET_SIZE_TIERS: [usize; 4] = [64, 128, MAX_AUTHORITY_SET_SIZE, 512];
+
+// Max number of authorities supported by any authority set size tier.
+pub const MAX_AUTHORITY_SET_SIZE_TIER: usize = 512;
+
+// Depth of the Merkle tree of the Merkle authority set commitment modes. The tree fits 2^10
+// authorities, more than the largest authority set size tier.
+pub const AUTHORITY_SET_MERKLE_TREE_DEPTH: usize = 10;
+
 // Can need up to 5 bytes to represent a compact u32.
 pub const MAX_COMPACT_UINT_BYTES: usize = 5;
+
+// Size in bytes of a KZG commitment (a compressed BLS12-381 G1 point) in the Avail header extension.
+pub const KZG_COMMITMENT_SIZE: usize = 48;
+
+// Max number of KZG commitments (one per row of the extended data matrix) in a header extension
+// that can be decoded by the circuit.
+pub const MAX_NUM_KZG_COMMITMENTS: usize = 256;
+
+// Max number of entries in the app lookup index of a header extension that can be decoded by the
+// circuit.
+pub const MAX_APP_LOOKUP_LEN: usize = 16;
+
+// The maximum length of the header extension before the KZG commitments. Includes the version tag,
+// the compact encoded app lookup size and index length, MAX_APP_LOOKUP_LEN compact encoded
+// (app_id, start) entries, and the compact encoded rows, cols and commitment length.
+pub const MAX_EXTENSION_PREFIX_LENGTH: usize =
+    1 + 2 * MAX_COMPACT_UINT_BYTES * (MAX_APP_LOOKUP_LEN + 1) + 3 * MAX_COMPACT_UINT_BYTES;
+
+// Max number of logs in the digest of a header that can be decoded by the circuit to find the start
+// of the header extension. Avail headers have a BABE pre-runtime log and a seal, and epoch end
+// headers also have BABE and GRANDPA consensus logs.
+pub const MAX_NUM_DIGEST_LOGS: usize = 8;
+
+// The maximum length of a digest log before its data. Includes the variant, the consensus engine id
+// of PreRuntime, Consensus and Seal logs, and the compact encoded length of the data.
+pub const MAX_DIGEST_LOG_PREFIX_LENGTH: usize = 1 + 4 + MAX_COMPACT_UINT_BYTES;
@@ -0,0 +1,349 @@
+use std::time::Instant;
+
+use plonky2x::backend::circuit::Circuit;
+use plonky2x::frontend::curta::ec::point::CompressedEdwardsYVariable;
+use plonky2x::frontend::merkle::simple::SimpleMerkleTree;
+use plonky2x::frontend::vars::U32Variable;
+use plonky2x::prelude::plonky2::plonk::circuit_data::{CommonCircuitData, VerifierOnlyCircuitData};
+use plonky2x::prelude::plonky2::plonk::config::PoseidonGoldilocksConfig;
+use plonky2x::prelude::{
+    ArrayVariable, Bytes32Variable, DefaultBuilder, GoldilocksField, Variable,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::authority_set::AuthoritySetCommitmentMode;
+use crate::builder::decoder::DecodingMethods;
+use crate::builder::header::HeaderMethods;
+use crate::builder::justification::GrandpaJustificationVerifier;
+use crate::builder::subchain_verification::{
+    map_subchain, reduce_subchain, MapReduceSubchainVariable, SubchainVerificationCtx,
+};
+use crate::consts::{ENCODED_PRECOMMIT_LENGTH, HEADERS_PER_MAP};
+use crate::header_ancestry::HeaderAncestryCircuit;
+use crate::header_range::HeaderRangeCircuit;
+use crate::layout::CURRENT_HEADER_LAYOUT;
+use crate::rotate::RotateCircuit;
+use crate::vars::{EncodedHeaderVariable, JustificationVariable};
+
+/// The size of a circuit built with the default plonky2x parameters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitCost {
+    pub name: String,
+    /// Number of instances of the gadget in the circuit, as in the subcircuit of the header range
+    /// or rotate circuit that the gadget runs in. 1 for a whole circuit.
+    pub num_instances: usize,
+    /// Number of gates, excluding the Curta STARK verifiers which are added when the circuit is built.
+    pub num_gates: usize,
+    /// Number of rows of the built circuit, including the Curta STARK verifiers. Padded to a power
+    /// of 2.
+    pub num_rows: usize,
+    pub degree_bits: usize,
+    pub num_gate_types: usize,
+    pub build_time_secs: f64,
+}
+
+impl CircuitCost {
+    /// Estimated proving time of the circuit, with a proving time per row measured on the prover's
+    /// machine. Proving time is roughly linear in the number of rows.
+    pub fn estimated_proving_time_secs(&self, ns_per_row: u64) -> f64 {
+        (self.num_rows as u64 * ns_per_row) as f64 / 1e9
+    }
+
+    /// Rows per instance of the gadget. The Curta STARK verifier of a gadget is shared by all of
+    /// its instances in a circuit, so its rows are amortized over the instances.
+    pub fn rows_per_instance(&self) -> f64 {
+        self.num_rows as f64 / self.num_instances as f64
+    }
+}
+
+/// The verifier data of a built circuit, to verify its proofs in another circuit.
+#[derive(Clone, Debug)]
+pub struct ChildCircuitData {
+    pub common: CommonCircuitData<GoldilocksField, 2>,
+    pub verifier_only: VerifierOnlyCircuitData<PoseidonGoldilocksConfig, 2>,
+}
+
+/// Build the circuit defined by define and measure its size.
+pub fn measure_circuit(name: &str, define: impl FnOnce(&mut DefaultBuilder)) -> CircuitCost {
+    measure_gadget(name, 1, define)
+}
+
+/// Build the circuit defined by define, which has num_instances instances of a gadget, and measure
+/// its size.
+pub fn measure_gadget(
+    name: &str,
+    num_instances: usize,
+    define: impl FnOnce(&mut DefaultBuilder),
+) -> CircuitCost {
+    build_and_measure(name, num_instances, define).0
+}
+
+fn build_and_measure(
+    name: &str,
+    num_instances: usize,
+    define: impl FnOnce(&mut DefaultBuilder),
+) -> (CircuitCost, ChildCircuitData) {
+    let start = Instant::now();
+    let mut builder = DefaultBuilder::new();
+    define(&mut builder);
+    let num_gates = builder.api.num_gates();
+    let circuit = builder.build();
+    let common = &circuit.data.common;
+
+    let cost = CircuitCost {
+        name: name.to_string(),
+        num_instances,
+        num_gates,
+        num_rows: common.degree(),
+        degree_bits: common.degree_bits(),
+        num_gate_types: common.gates.len(),
+        build_time_secs: start.elapsed().as_secs_f64(),
+    };
+    let child = ChildCircuitData {
+        common: circuit.data.common.clone(),
+        verifier_only: circuit.data.verifier_only.clone(),
+    };
+    (cost, child)
+}
+
+// Verify a proof of the child circuit, as a reduce circuit verifies the proofs of its children.
+fn verify_child_proof(builder: &mut DefaultBuilder, child: &ChildCircuitData) {
+    let verifier_data = builder.api.constant_verifier_data(&child.verifier_only);
+    let proof = builder.api.add_virtual_proof_with_pis(&child.common);
+    builder
+        .api
+        .verify_proof::<PoseidonGoldilocksConfig>(&proof, &verifier_data, &child.common);
+}
+
+/// The cost of hashing the HEADERS_PER_MAP headers of a map job with `hash_encoded_header`, which
+/// share one Curta STARK verifier.
+pub fn hash_encoded_header_cost<const MAX_HEADER_SIZE: usize>() -> CircuitCost {
+    measure_gadget("hash_encoded_header", HEADERS_PER_MAP, |builder| {
+        for _ in 0..HEADERS_PER_MAP {
+            let header = builder.read::<EncodedHeaderVariable<MAX_HEADER_SIZE>>();
+            let header_hash = builder.hash_encoded_header(&header);
+            builder.write(header_hash);
+        }
+    })
+}
+
+/// The cost of decoding the HEADERS_PER_MAP headers of a map job with `decode_header`.
+pub fn decode_header_cost<const MAX_HEADER_SIZE: usize>() -> CircuitCost {
+    measure_gadget("decode_header", HEADERS_PER_MAP, |builder| {
+        for _ in 0..HEADERS_PER_MAP {
+            let header = builder.read::<EncodedHeaderVariable<MAX_HEADER_SIZE>>();
+            let header_hash = builder.read::<Bytes32Variable>();
+            let header_variable = builder.decode_header(&header, &header_hash);
+            builder.write(header_variable.data_root);
+        }
+    })
+}
+
+/// The cost of checking the layout of the epoch end header of a rotate with `assert_header_layout`.
+pub fn assert_header_layout_cost<const MAX_HEADER_SIZE: usize>() -> CircuitCost {
+    measure_gadget("assert_header_layout", 1, |builder| {
+        let header = builder.read::<EncodedHeaderVariable<MAX_HEADER_SIZE>>();
+        let header_hash = builder.read::<Bytes32Variable>();
+        builder.assert_header_layout(&header, &header_hash, CURRENT_HEADER_LAYOUT);
+    })
+}
+
+/// The cost of computing the authority set commitment of MAX_NUM_AUTHORITIES authorities with the
+/// given commitment mode.
+pub fn authority_set_commitment_cost<const MAX_NUM_AUTHORITIES: usize>(
+    mode: AuthoritySetCommitmentMode,
+) -> CircuitCost {
+    measure_circuit("compute_authority_set_commitment", |builder| {
+        let num_active_authorities = builder.read::<Variable>();
+        let pubkeys =
+            builder.read::<ArrayVariable<CompressedEdwardsYVariable, MAX_NUM_AUTHORITIES>>();
+        let commitment = builder.compute_authority_set_commitment_with_mode(
+            num_active_authorities,
+            &pubkeys,
+            mode,
+        );
+        builder.write(commitment);
+    })
+}
+
+/// The cost of verifying the signatures of a justification of MAX_NUM_AUTHORITIES authorities with
+/// `curta_eddsa_verify_sigs_conditional`.
+pub fn eddsa_verify_cost<const MAX_NUM_AUTHORITIES: usize>() -> CircuitCost {
+    measure_circuit("curta_eddsa_verify_sigs_conditional", |builder| {
+        let justification = builder.read::<JustificationVariable<MAX_NUM_AUTHORITIES>>();
+        let message_byte_lengths = builder
+            .constant::<ArrayVariable<U32Variable, MAX_NUM_AUTHORITIES>>(vec![
+                ENCODED_PRECOMMIT_LENGTH
+                    as u32;
+                MAX_NUM_AUTHORITIES
+            ]);
+        let messages = vec![justification.encoded_precommit; MAX_NUM_AUTHORITIES];
+        builder.curta_eddsa_verify_sigs_conditional(
+            justification.validator_signed,
+            Some(message_byte_lengths),
+            messages.into(),
+            justification.signatures,
+            justification.pubkeys,
+        );
+    })
+}
+
+/// The cost of the state and data Merkle roots over the HEADERS_PER_MAP headers of a map job, whose
+/// hashes share one Curta STARK verifier.
+pub fn merkle_map_cost() -> CircuitCost {
+    measure_gadget("merkle_map", 2, |builder| {
+        let num_enabled_leaves = builder.read::<Variable>();
+        for _ in 0..2 {
+            let leaves = builder.read::<ArrayVariable<Bytes32Variable, HEADERS_PER_MAP>>();
+            let root =
+                builder.get_root_from_hashed_leaves::<HEADERS_PER_MAP>(leaves, num_enabled_leaves);
+            builder.write(root);
+        }
+    })
+}
+
+/// The cost of combining the state and data Merkle roots of two subchains in a reduce job, whose
+/// two SHA256 hashes share one Curta STARK verifier.
+pub fn merkle_reduce_cost() -> CircuitCost {
+    measure_gadget("merkle_reduce", 2, |builder| {
+        for _ in 0..2 {
+            let left = builder.read::<Bytes32Variable>();
+            let right = builder.read::<Bytes32Variable>();
+            let mut root_bytes = left.as_bytes().to_vec();
+            root_bytes.extend(&right.as_bytes());
+            let root = builder.sha256(&root_bytes);
+            builder.write(root);
+        }
+    })
+}
+
+// Define the map circuit of `verify_subchain`, without the commitment to its inputs that the
+// plonky2x map reduce adds.
+fn define_header_range_map<const MAX_HEADER_SIZE: usize>(builder: &mut DefaultBuilder) {
+    let ctx = builder.read::<SubchainVerificationCtx>();
+    let relative_block_nums = builder.read::<ArrayVariable<U32Variable, HEADERS_PER_MAP>>();
+    let output = map_subchain::<_, 2, MAX_HEADER_SIZE>(builder, ctx, relative_block_nums);
+    builder.write(output);
+}
+
+/// The cost of the map circuit of the header range circuit, which verifies HEADERS_PER_MAP headers.
+pub fn header_range_map_cost<const MAX_HEADER_SIZE: usize>() -> CircuitCost {
+    measure_circuit(
+        "header_range_map",
+        define_header_range_map::<MAX_HEADER_SIZE>,
+    )
+}
+
+/// The cost of each level of reduce circuits of the header range circuit, from the level that
+/// verifies the map proofs to the level whose proof the header range circuit verifies. Each reduce
+/// circuit verifies the proofs of its two children and combines their subchains.
+pub fn header_range_reduce_costs<const MAX_HEADER_SIZE: usize, const MAX_NUM_HEADERS: usize>(
+) -> Vec<CircuitCost> {
+    let num_map_jobs = (MAX_NUM_HEADERS / HEADERS_PER_MAP).next_power_of_two();
+    let (_, mut child) = build_and_measure(
+        "header_range_map",
+        1,
+        define_header_range_map::<MAX_HEADER_SIZE>,
+    );
+
+    let mut costs = Vec::new();
+    for level in 0..num_map_jobs.trailing_zeros() {
+        let (cost, reduce) =
+            build_and_measure(&format!("header_range_reduce_{}", level), 1, |builder| {
+                for _ in 0..2 {
+                    verify_child_proof(builder, &child);
+                }
+                let left = builder.read::<MapReduceSubchainVariable>();
+                let right = builder.read::<MapReduceSubchainVariable>();
+                let output = reduce_subchain(builder, left, right);
+                builder.write(output);
+            });
+        costs.push(cost);
+        child = reduce;
+    }
+    costs
+}
+
+/// The cost of each gadget of the header range and rotate circuits with the given parameters. Each
+/// gadget is built with as many instances as the subcircuit it runs in, so the cost of its Curta
+/// STARK verifier is shared as in that subcircuit.
+pub fn gadget_costs<const MAX_AUTHORITY_SET_SIZE: usize, const MAX_HEADER_SIZE: usize>(
+    mode: AuthoritySetCommitmentMode,
+) -> Vec<CircuitCost> {
+    vec![
+        hash_encoded_header_cost::<MAX_HEADER_SIZE>(),
+        decode_header_cost::<MAX_HEADER_SIZE>(),
+        assert_header_layout_cost::<MAX_HEADER_SIZE>(),
+        authority_set_commitment_cost::<MAX_AUTHORITY_SET_SIZE>(mode),
+        eddsa_verify_cost::<MAX_AUTHORITY_SET_SIZE>(),
+        merkle_map_cost(),
+        merkle_reduce_cost(),
+    ]
+}
+
+/// The cost of the header range circuit, which verifies the proof of the last reduce level. See
+/// `header_range_map_cost` and `header_range_reduce_costs` for its map and reduce circuits.
+pub fn header_range_cost<
+    const MAX_AUTHORITY_SET_SIZE: usize,
+    const MAX_HEADER_SIZE: usize,
+    const MAX_NUM_HEADERS: usize,
+>() -> CircuitCost {
+    measure_circuit("HeaderRangeCircuit", |builder| {
+        HeaderRangeCircuit::<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, MAX_NUM_HEADERS>::define(
+            builder,
+        )
+    })
+}
+
+/// The cost of the header ancestry circuit. It verifies its chain of headers with the map and
+/// reduce circuits of the header range circuit, so their costs are those of
+/// `header_range_map_cost` and `header_range_reduce_costs`.
+pub fn header_ancestry_cost<const MAX_HEADER_SIZE: usize, const MAX_NUM_HEADERS: usize>(
+) -> CircuitCost {
+    measure_circuit("HeaderAncestryCircuit", |builder| {
+        HeaderAncestryCircuit::<MAX_HEADER_SIZE, MAX_NUM_HEADERS>::define(builder)
+    })
+}
+
+pub fn rotate_cost<
+    const MAX_AUTHORITY_SET_SIZE: usize,
+    const MAX_HEADER_SIZE: usize,
+    const MAX_SUBARRAY_SIZE: usize,
+>() -> CircuitCost {
+    measure_circuit("RotateCircuit", |builder| {
+        RotateCircuit::<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE>::define(builder)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::{BLAKE2B_CHUNK_SIZE_BYTES, SMALL_MAX_HEADER_SIZE};
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_decode_header_cost() {
+        let small_cost = decode_header_cost::<BLAKE2B_CHUNK_SIZE_BYTES>();
+        let large_cost = decode_header_cost::<SMALL_MAX_HEADER_SIZE>();
+
+        assert!(small_cost.num_rows.is_power_of_two());
+        assert_eq!(small_cost.num_rows, 1 << small_cost.degree_bits);
+        assert!(small_cost.num_gates <= small_cost.num_rows);
+        assert!(large_cost.num_gates > small_cost.num_gates);
+        assert_eq!(
+            large_cost.estimated_proving_time_secs(1_000_000_000),
+            large_cost.num_rows as f64
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_header_range_reduce_costs() {
+        // 32 headers are verified by 4 map jobs, which are combined by 2 levels of reduce circuits.
+        let costs = header_range_reduce_costs::<BLAKE2B_CHUNK_SIZE_BYTES, 32>();
+        assert_eq!(costs.len(), 2);
+        assert_eq!(costs[0].name, "header_range_reduce_0");
+        assert!(costs.iter().all(|cost| cost.num_instances == 1));
+    }
+}
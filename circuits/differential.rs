@@ -0,0 +1,453 @@
+// Differential tests of the circuit gadgets against the native reference implementation in
+// `native`. Each gadget is proven on the same inputs as its native equivalent: when the native
+// model accepts, the circuit must prove the same outputs, and when the native model rejects, the
+// circuit must fail to prove. Inputs are synthetic chains with random mutations, from a seeded
+// RNG so failures can be reproduced. The subchain differential test reads its headers from a
+// witness bundle, so it runs in its own test binary, tests/differential_subchain.rs.
+
+use std::env;
+use std::fmt::Debug;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use anyhow::Error;
+use codec::{Compact, Encode};
+use ethers::types::H256;
+use log::info;
+use plonky2x::frontend::curta::ec::point::{CompressedEdwardsY, CompressedEdwardsYVariable};
+use plonky2x::frontend::ecc::curve25519::ed25519::eddsa::DUMMY_PUBLIC_KEY;
+use plonky2x::frontend::uint::uint64::U64Variable;
+use plonky2x::frontend::vars::U32Variable;
+use plonky2x::prelude::{
+    ArrayVariable, ByteVariable, Bytes32Variable, BytesVariable, DefaultBuilder, Field,
+    GoldilocksField, Variable,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sp_core::blake2_256;
+
+use crate::builder::decoder::DecodingMethods;
+use crate::builder::rotate::RotateMethods;
+use crate::consts::{
+    CONSENSUS_ENGINE_ID_PREFIX_LENGTH, DELAY_LENGTH, ENCODED_PRECOMMIT_LENGTH,
+    MAX_COMPACT_UINT_BYTES, PUBKEY_LENGTH, SMALL_MAX_HEADER_SIZE, VALIDATOR_LENGTH,
+};
+use crate::native;
+use crate::synthetic::{SyntheticChain, SyntheticChainConfig};
+use crate::vars::{EncodedHeader, EncodedHeaderVariable};
+
+type F = GoldilocksField;
+
+const SEED: u64 = 0x7665_6374_6f72_78;
+
+// The byte length of a compact int for each compress mode.
+const COMPACT_INT_BYTE_LENGTHS: [usize; 4] = [1, 2, 4, 5];
+
+/// A case where the circuit and the native model disagree.
+#[derive(Debug)]
+struct Divergence {
+    gadget: &'static str,
+    case: String,
+    native: String,
+    circuit: String,
+}
+
+#[derive(Default)]
+struct Harness {
+    num_cases: usize,
+    divergences: Vec<Divergence>,
+}
+
+impl Harness {
+    /// Compare the native result of a case with the outputs of proving the circuit on it, which is
+    /// None if the circuit failed to prove.
+    fn check<T: PartialEq + Debug>(
+        &mut self,
+        gadget: &'static str,
+        case: String,
+        native: Result<T, Error>,
+        circuit: Option<T>,
+    ) {
+        self.num_cases += 1;
+        let diverged = match (&native, &circuit) {
+            (Ok(native), Some(circuit)) => native != circuit,
+            (Err(_), None) => false,
+            _ => true,
+        };
+        if diverged {
+            self.divergences.push(Divergence {
+                gadget,
+                case,
+                native: format!("{:?}", native),
+                circuit: circuit.map_or("failed to prove".to_string(), |c| format!("{:?}", c)),
+            });
+        }
+    }
+
+    fn assert_no_divergences(&self) {
+        info!("Checked {} differential cases", self.num_cases);
+        assert!(
+            self.divergences.is_empty(),
+            "{} of {} cases diverged:\n{:#?}",
+            self.divergences.len(),
+            self.num_cases,
+            self.divergences
+        );
+    }
+}
+
+/// Run prove, and return None if it panics because a constraint is not satisfied.
+fn try_prove<T>(prove: impl FnOnce() -> T) -> Option<T> {
+    catch_unwind(AssertUnwindSafe(prove)).ok()
+}
+
+fn encoded_header<const S: usize>(header: &[u8]) -> EncodedHeader<S, F> {
+    let mut header_bytes = header.to_vec();
+    header_bytes.resize(S, 0);
+    EncodedHeader {
+        header_bytes,
+        header_size: header.len() as u32,
+    }
+}
+
+fn chain(start_block: u32, authority_set_sizes: Vec<usize>) -> SyntheticChain {
+    SyntheticChain::new(SyntheticChainConfig {
+        start_block,
+        num_blocks: 48,
+        epoch_length: 16,
+        authority_set_sizes,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_differential_decode_compact_int() {
+    env::set_var("RUST_LOG", "info");
+    env_logger::try_init().unwrap_or_default();
+
+    let mut builder = DefaultBuilder::new();
+    let compact_bytes = builder.read::<ArrayVariable<ByteVariable, MAX_COMPACT_UINT_BYTES>>();
+    let (value, compress_mode) = builder.decode_compact_int(compact_bytes);
+    builder.write(value);
+    builder.write(compress_mode);
+    let circuit = builder.build();
+
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut cases = Vec::new();
+    // Canonical encodings of the boundaries of each mode, and of random values.
+    for value in [
+        0u32,
+        63,
+        64,
+        (1 << 14) - 1,
+        1 << 14,
+        (1 << 30) - 1,
+        1 << 30,
+        u32::MAX,
+    ] {
+        cases.push(Compact(value).encode());
+    }
+    for _ in 0..16 {
+        cases.push(Compact(rng.gen::<u32>() >> rng.gen_range(0..32)).encode());
+    }
+    // Random bytes, including non-canonical encodings and mode 3 encodings of more than 4 bytes.
+    for _ in 0..16 {
+        cases.push((0..MAX_COMPACT_UINT_BYTES).map(|_| rng.gen()).collect());
+    }
+
+    let mut harness = Harness::default();
+    for mut bytes in cases {
+        bytes.resize(MAX_COMPACT_UINT_BYTES, 0);
+        let native = native::decode_compact_u32(&bytes).map(|(value, byte_length)| {
+            let mode = COMPACT_INT_BYTE_LENGTHS
+                .iter()
+                .position(|length| *length == byte_length)
+                .unwrap();
+            (value, F::from_canonical_usize(mode))
+        });
+
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<ByteVariable, MAX_COMPACT_UINT_BYTES>>(bytes.clone());
+        let output = try_prove(|| circuit.prove(&input)).map(|(_, mut output)| {
+            let value = output.read::<U32Variable>();
+            let compress_mode = output.read::<Variable>();
+            (value, compress_mode)
+        });
+
+        harness.check("decode_compact_int", hex::encode(&bytes), native, output);
+    }
+    harness.assert_no_divergences();
+}
+
+#[test]
+fn test_differential_decode_precommit() {
+    env::set_var("RUST_LOG", "info");
+    env_logger::try_init().unwrap_or_default();
+
+    let mut builder = DefaultBuilder::new();
+    let precommit = builder.read::<BytesVariable<ENCODED_PRECOMMIT_LENGTH>>();
+    let decoded = builder.decode_precommit(precommit);
+    builder.write(decoded.block_hash);
+    builder.write(decoded.block_number);
+    builder.write(decoded.justification_round);
+    builder.write(decoded.authority_set_id);
+    let circuit = builder.build();
+
+    let synthetic = chain(1 << 14, vec![4]);
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut cases = (synthetic.start_block() + 1..=synthetic.end_block())
+        .step_by(8)
+        .map(|block_number| {
+            synthetic
+                .justification(block_number)
+                .signed_message
+                .to_vec()
+        })
+        .collect::<Vec<_>>();
+    // Random precommits, with a first byte of 0, 1 (Precommit) or 2.
+    for _ in 0..16 {
+        let mut precommit = (0..ENCODED_PRECOMMIT_LENGTH)
+            .map(|_| rng.gen())
+            .collect::<Vec<u8>>();
+        precommit[0] = rng.gen_range(0..3);
+        cases.push(precommit);
+    }
+
+    let mut harness = Harness::default();
+    for precommit in cases {
+        let native = native::decode_precommit(&precommit).map(|decoded| {
+            (
+                decoded.block_hash,
+                decoded.block_number,
+                decoded.justification_round,
+                decoded.authority_set_id,
+            )
+        });
+
+        let mut input = circuit.input();
+        input.write::<BytesVariable<ENCODED_PRECOMMIT_LENGTH>>(
+            precommit.clone().try_into().unwrap(),
+        );
+        let output = try_prove(|| circuit.prove(&input)).map(|(_, mut output)| {
+            (
+                output.read::<Bytes32Variable>(),
+                output.read::<U32Variable>(),
+                output.read::<U64Variable>(),
+                output.read::<U64Variable>(),
+            )
+        });
+
+        harness.check("decode_precommit", hex::encode(&precommit), native, output);
+    }
+    harness.assert_no_divergences();
+}
+
+#[test]
+fn test_differential_decode_header() {
+    env::set_var("RUST_LOG", "info");
+    env_logger::try_init().unwrap_or_default();
+
+    const MAX_HEADER_SIZE: usize = SMALL_MAX_HEADER_SIZE;
+
+    let mut builder = DefaultBuilder::new();
+    let header = builder.read::<EncodedHeaderVariable<MAX_HEADER_SIZE>>();
+    let header_hash = builder.read::<Bytes32Variable>();
+    let decoded = builder.decode_header(&header, &header_hash);
+    builder.write(decoded.block_number);
+    builder.write(decoded.parent_hash);
+    builder.write(decoded.state_root);
+    builder.write(decoded.data_root);
+    let circuit = builder.build();
+
+    // Block numbers with 1, 2, 4 and 5 byte compact encodings, and epoch end headers.
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut cases = Vec::new();
+    for start_block in [0, (1 << 14) - 8, (1 << 30) - 8] {
+        let synthetic = chain(start_block, vec![4, 7]);
+        for block_number in [
+            start_block,
+            start_block + 9,
+            start_block + 16,
+            start_block + 32,
+        ] {
+            let header = synthetic.header(block_number).to_vec();
+
+            // A random byte flip, which changes a decoded field or nothing.
+            let mut flipped = header.clone();
+            let position = rng.gen_range(0..flipped.len());
+            flipped[position] ^= 1 << rng.gen_range(0..8);
+
+            // The compress mode of the block number set to mode 3, with a 4 byte value only if the
+            // upper 6 bits of the first byte are 0.
+            let mut mode_three = header.clone();
+            mode_three[32] |= 0b11;
+
+            cases.extend([header, flipped, mode_three]);
+        }
+    }
+
+    let mut harness = Harness::default();
+    for header in cases {
+        let header_hash = H256::from(blake2_256(&header));
+        let native = native::decode_header(&header).map(|decoded| {
+            (
+                decoded.block_number,
+                decoded.parent_hash,
+                decoded.state_root,
+                decoded.data_root,
+            )
+        });
+
+        let mut input = circuit.input();
+        input.write::<EncodedHeaderVariable<MAX_HEADER_SIZE>>(encoded_header(&header));
+        input.write::<Bytes32Variable>(header_hash);
+        let output = try_prove(|| circuit.prove(&input)).map(|(_, mut output)| {
+            (
+                output.read::<U32Variable>(),
+                output.read::<Bytes32Variable>(),
+                output.read::<Bytes32Variable>(),
+                output.read::<Bytes32Variable>(),
+            )
+        });
+
+        harness.check("decode_header", hex::encode(&header), native, output);
+    }
+    harness.assert_no_divergences();
+}
+
+#[test]
+#[cfg_attr(feature = "ci", ignore)]
+fn test_differential_verify_epoch_end_header() {
+    env::set_var("RUST_LOG", "info");
+    env_logger::try_init().unwrap_or_default();
+
+    const MAX_HEADER_SIZE: usize = SMALL_MAX_HEADER_SIZE;
+    const MAX_AUTHORITY_SET_SIZE: usize = 8;
+    const MAX_SUBARRAY_SIZE: usize = MAX_AUTHORITY_SET_SIZE * VALIDATOR_LENGTH + DELAY_LENGTH;
+
+    let mut builder = DefaultBuilder::new();
+    let header = builder.read::<EncodedHeaderVariable<MAX_HEADER_SIZE>>();
+    let header_hash = builder.read::<Bytes32Variable>();
+    let num_authorities = builder.read::<Variable>();
+    let start_position = builder.read::<Variable>();
+    let new_pubkeys =
+        builder.read::<ArrayVariable<CompressedEdwardsYVariable, MAX_AUTHORITY_SET_SIZE>>();
+    builder.verify_epoch_end_header::<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE, MAX_SUBARRAY_SIZE>(
+        &header,
+        header_hash,
+        &num_authorities,
+        &start_position,
+        &new_pubkeys,
+    );
+    let circuit = builder.build();
+
+    // A case is an epoch end header, the start position of its ScheduledChange log, and the new
+    // authority set witnessed to the circuit. The native model accepts if it parses the witnessed
+    // authority set from the header.
+    let synthetic = chain(100, vec![4, 7, 8]);
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut cases = Vec::new();
+    for authority_set_id in 0..2 {
+        let rotate = synthetic.rotate(authority_set_id);
+        let header = synthetic.header(rotate.epoch_end_block_number).to_vec();
+        let start_position = rotate.start_position;
+        let pubkeys = synthetic.authorities(authority_set_id + 1);
+        let num_authorities = pubkeys.len();
+
+        let message_length_length = native::decode_compact_u32(
+            &header[start_position + CONSENSUS_ENGINE_ID_PREFIX_LENGTH..],
+        )
+        .unwrap()
+        .1;
+        let flag_position =
+            start_position + CONSENSUS_ENGINE_ID_PREFIX_LENGTH + message_length_length;
+        let num_authorities_position = flag_position + 1;
+        let authorities_start = num_authorities_position + 1;
+        let delay_start = authorities_start + num_authorities * VALIDATOR_LENGTH;
+
+        cases.push((header.clone(), start_position, pubkeys.clone()));
+
+        // Wrong start positions.
+        for position in [
+            start_position - 1,
+            start_position + 1,
+            rng.gen_range(0..header.len() / 2),
+        ] {
+            cases.push((header.clone(), position, pubkeys.clone()));
+        }
+
+        // Byte flips in the consensus log tag, the engine id, the scheduled change message length
+        // (which is not checked), the ScheduledChange flag, the number of authorities, a pubkey, a
+        // weight and the delay.
+        let i = rng.gen_range(0..num_authorities);
+        for position in [
+            start_position + 1,
+            start_position + 2 + rng.gen_range(0..4),
+            start_position + CONSENSUS_ENGINE_ID_PREFIX_LENGTH,
+            flag_position,
+            num_authorities_position,
+            authorities_start + i * VALIDATOR_LENGTH + rng.gen_range(0..PUBKEY_LENGTH),
+            authorities_start + i * VALIDATOR_LENGTH + PUBKEY_LENGTH + rng.gen_range(0..8),
+            delay_start + rng.gen_range(0..DELAY_LENGTH),
+        ] {
+            let mut flipped = header.clone();
+            flipped[position] ^= 1 << rng.gen_range(0..8);
+            cases.push((flipped, start_position, pubkeys.clone()));
+        }
+
+        // Witnessed authority sets that don't match the header: a wrong pubkey, a missing authority
+        // and an extra authority.
+        let mut wrong_pubkey = pubkeys.clone();
+        wrong_pubkey[i] = synthetic.authorities(authority_set_id)[0];
+        cases.push((header.clone(), start_position, wrong_pubkey));
+        cases.push((
+            header.clone(),
+            start_position,
+            pubkeys[..num_authorities - 1].to_vec(),
+        ));
+        if num_authorities < MAX_AUTHORITY_SET_SIZE {
+            let mut extra_authority = pubkeys.clone();
+            extra_authority.push(CompressedEdwardsY::from_slice(&DUMMY_PUBLIC_KEY).unwrap());
+            cases.push((header.clone(), start_position, extra_authority));
+        }
+    }
+
+    let mut harness = Harness::default();
+    for (header, start_position, pubkeys) in cases {
+        let native = native::verify_epoch_end_header(&header, start_position).and_then(|parsed| {
+            anyhow::ensure!(
+                parsed == pubkeys,
+                "Header schedules a different authority set"
+            );
+            Ok(())
+        });
+
+        let num_authorities = pubkeys.len();
+        let mut padded_pubkeys = pubkeys;
+        padded_pubkeys.resize(
+            MAX_AUTHORITY_SET_SIZE,
+            CompressedEdwardsY::from_slice(&DUMMY_PUBLIC_KEY).unwrap(),
+        );
+        let mut input = circuit.input();
+        input.write::<EncodedHeaderVariable<MAX_HEADER_SIZE>>(encoded_header(&header));
+        input.write::<Bytes32Variable>(H256::from(blake2_256(&header)));
+        input.write::<Variable>(F::from_canonical_usize(num_authorities));
+        input.write::<Variable>(F::from_canonical_usize(start_position));
+        input.write::<ArrayVariable<CompressedEdwardsYVariable, MAX_AUTHORITY_SET_SIZE>>(
+            padded_pubkeys,
+        );
+        let output = try_prove(|| circuit.prove(&input)).map(|_| ());
+
+        harness.check(
+            "verify_epoch_end_header",
+            format!(
+                "start_position: {}, num_authorities: {}, header: {}",
+                start_position,
+                num_authorities,
+                hex::encode(&header)
+            ),
+            native,
+            output,
+        );
+    }
+    harness.assert_no_divergences();
+}
@@ -1,7 +1,11 @@
-use avail_subxt::config::Header;
+use codec::Encode;
 use rustx::program::Program;
 
+use crate::consts::MAX_AUTHORITY_SET_SIZE_TIER;
+use crate::evm::{HeaderRangeRequest, HeaderRangeResponse};
 use crate::input::RpcDataFetcher;
+use crate::native;
+use crate::witness::WitnessJustification;
 
 #[derive(Debug, Clone)]
 pub struct DummyHeaderRange<const HEADER_RANGE_COMMITMENT_TREE_SIZE: usize>;
@@ -9,46 +13,47 @@ impl<const HEADER_RANGE_COMMITMENT_TREE_SIZE: usize> Program
     for DummyHeaderRange<HEADER_RANGE_COMMITMENT_TREE_SIZE>
 {
     fn run(input_bytes: Vec<u8>) -> Vec<u8> {
-        // First 4 bytes are the trusted block number.
-        // Next 32 bytes are the trusted header hash.
-        // Next 8 bytes are the authority set id.
-        // Next 32 bytes are the authority set hash.
-        // Next 4 bytes are the target block number.
-        let trusted_block = u32::from_be_bytes(input_bytes[0..4].try_into().unwrap());
-        let _trusted_header_hash = input_bytes[4..36].to_vec();
-        let _authority_set_id = u64::from_be_bytes(input_bytes[36..44].try_into().unwrap());
-        let _authority_set_hash = input_bytes[44..76].to_vec();
-        let target_block = u32::from_be_bytes(input_bytes[76..80].try_into().unwrap());
+        let request =
+            HeaderRangeRequest::decode(&input_bytes).expect("Failed to decode the request");
+        let trusted_block = request.trusted_block;
+        let target_block = request.target_block;
 
         // Initialize tokio runtime.
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let result: (Vec<u8>, Vec<u8>, Vec<u8>) = rt.block_on(async {
+        let (headers, justification) = rt.block_on(async {
             let mut data_fetcher = RpcDataFetcher::new().await;
-            let target_header_hash = data_fetcher
-                .get_header(target_block)
+            let headers = data_fetcher
+                .get_block_headers_range(trusted_block + 1, target_block)
                 .await
-                .hash()
-                .0
-                .to_vec();
-
-            let (state_merkle_root, data_merkle_root) = data_fetcher
-                .get_merkle_root_commitments(
-                    HEADER_RANGE_COMMITMENT_TREE_SIZE as u32,
-                    trusted_block,
-                    target_block,
-                )
-                .await;
-
-            (target_header_hash, state_merkle_root, data_merkle_root)
+                .iter()
+                .map(|header| header.encode())
+                .collect::<Vec<_>>();
+            let justification = data_fetcher
+                .get_justification_from_block::<MAX_AUTHORITY_SET_SIZE_TIER>(target_block)
+                .await
+                .expect("Failed to get justification");
+            (headers, WitnessJustification::from(justification))
         });
 
-        // Encode the result tuple into bytes by concatenating the fields.
-        result
-            .0
-            .into_iter()
-            .chain(result.1)
-            .chain(result.2)
-            .collect()
+        // The RPC is untrusted: verify the headers link from the trusted header to the target block,
+        // and the justification on the target block by the authority set, like the header range
+        // circuit.
+        let output = native::header_range::<HEADER_RANGE_COMMITMENT_TREE_SIZE>(
+            trusted_block,
+            request.trusted_header_hash,
+            request.authority_set_id,
+            request.authority_set_hash,
+            &headers,
+            &justification,
+        )
+        .expect("Failed to verify the header range");
+
+        HeaderRangeResponse {
+            target_header_hash: output.target_header_hash,
+            state_root_commitment: output.state_root_commitment,
+            data_root_commitment: output.data_root_commitment,
+        }
+        .encode()
     }
 }
 
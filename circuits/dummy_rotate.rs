@@ -1,31 +1,55 @@
 use rustx::program::Program;
 
+use crate::consts::{MAX_AUTHORITY_SET_SIZE_TIER, MAX_HEADER_SIZE};
+use crate::evm::{RotateRequest, RotateResponse};
 use crate::input::RpcDataFetcher;
+use crate::native;
+use crate::witness::WitnessJustification;
 
 #[derive(Debug, Clone)]
 pub struct DummyRotate;
 impl Program for DummyRotate {
     fn run(input_bytes: Vec<u8>) -> Vec<u8> {
-        // Decode the input bytes into the request tuple.
-
-        // First 8 bytes are the authority set id.
-        // Next 32 bytes are the authority set hash.
-        let authority_set_id = u64::from_be_bytes(input_bytes[0..8].try_into().unwrap());
-        let _authority_set_hash = input_bytes[8..40].to_vec();
+        let request = RotateRequest::decode(&input_bytes).expect("Failed to decode the request");
+        let authority_set_id = request.authority_set_id;
 
         // Initialize tokio runtime.
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let new_authority_set_hash: Vec<u8> = rt.block_on(async {
+        let (epoch_end_header, start_position, justification) = rt.block_on(async {
             let mut data_fetcher = RpcDataFetcher::new().await;
             let epoch_end_block_number = data_fetcher.last_justified_block(authority_set_id).await;
-            data_fetcher
-                .compute_authority_set_hash(epoch_end_block_number)
+            let rotate_data = data_fetcher
+                .get_header_rotate::<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE_TIER>(
+                    epoch_end_block_number,
+                )
+                .await;
+            let justification = data_fetcher
+                .get_justification_from_block::<MAX_AUTHORITY_SET_SIZE_TIER>(epoch_end_block_number)
                 .await
-                .0
-                .to_vec()
+                .expect("Failed to get justification");
+            (
+                rotate_data.header_bytes[..rotate_data.header_size].to_vec(),
+                rotate_data.start_position,
+                WitnessJustification::from(justification),
+            )
         });
 
-        new_authority_set_hash
+        // The RPC is untrusted: verify the justification on the epoch end header by the current
+        // authority set, and parse the new authority set from its ScheduledChange log, like the
+        // rotate circuit.
+        let output = native::rotate(
+            authority_set_id,
+            request.authority_set_hash,
+            &epoch_end_header,
+            start_position,
+            &justification,
+        )
+        .expect("Failed to verify the rotate");
+
+        RotateResponse {
+            new_authority_set_hash: output.new_authority_set_hash,
+        }
+        .encode()
     }
 }
 
@@ -0,0 +1,597 @@
+use anyhow::{ensure, Error};
+use ethers::types::H256;
+
+// Typed EVM inputs and outputs of the header range and rotate circuits. The encodings are the
+// abi.encodePacked encodings the VectorX contract sends with a request and decodes from the
+// result, and the order of each field matches the evm_read and evm_write calls of the circuits:
+//
+//     HeaderRangeCircuit, HeaderRangeSubsetCircuit, HeaderRangeKzgCircuit,
+//     HeaderRangeAggregationCircuit:
+//         evm_read: uint32 trusted_block, bytes32 trusted_header_hash, uint64 authority_set_id,
+//                   bytes32 authority_set_hash, uint32 target_block
+//         evm_write: bytes32 target_header_hash, bytes32 state_root_commitment,
+//                    bytes32 data_root_commitment
+//     RotateCircuit:
+//         evm_read: uint64 authority_set_id, bytes32 authority_set_hash
+//         evm_write: bytes32 new_authority_set_hash
+//     MultiRotateCircuit:
+//         evm_read: uint64 authority_set_id, bytes32 authority_set_hash
+//         evm_write: uint64 final_authority_set_id, then bytes32 new_authority_set_hash for each
+//                    epoch
+//     RotateHeaderRangeCircuit:
+//         evm_read: uint64 authority_set_id, bytes32 authority_set_hash, uint32 trusted_block,
+//                   bytes32 trusted_header_hash, uint32 target_block
+//         evm_write: bytes32 new_authority_set_hash, bytes32 target_header_hash,
+//                    bytes32 state_root_commitment, bytes32 data_root_commitment
+//     HeaderAncestryCircuit:
+//         evm_read: uint32 trusted_block, bytes32 trusted_header_hash, uint32 target_block
+//         evm_write: bytes32 target_header_hash, bytes32 state_root, bytes32 data_root
+//
+// HeaderRangeKzgCircuit writes its KZG outputs after the header range outputs:
+//
+//     HeaderRangeKzgCircuit:
+//         evm_write: ..., bytes32 kzg_commitments_root, uint32 rows, uint32 cols
+
+/// The input of a header range request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderRangeRequest {
+    pub trusted_block: u32,
+    pub trusted_header_hash: H256,
+    pub authority_set_id: u64,
+    pub authority_set_hash: H256,
+    pub target_block: u32,
+}
+
+/// The output of a header range request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderRangeResponse {
+    pub target_header_hash: H256,
+    pub state_root_commitment: H256,
+    pub data_root_commitment: H256,
+}
+
+/// The output of a header range KZG request, whose input is a HeaderRangeRequest. The KZG
+/// commitments root, rows and cols are of the target header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderRangeKzgResponse {
+    pub target_header_hash: H256,
+    pub state_root_commitment: H256,
+    pub data_root_commitment: H256,
+    pub kzg_commitments_root: H256,
+    pub rows: u32,
+    pub cols: u32,
+}
+
+/// The input of a rotate request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RotateRequest {
+    pub authority_set_id: u64,
+    pub authority_set_hash: H256,
+}
+
+/// The output of a rotate request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RotateResponse {
+    pub new_authority_set_hash: H256,
+}
+
+/// The output of a multi rotate request, whose input is a RotateRequest. new_authority_set_hashes
+/// are the hashes of authority sets authority_set_id + 1 to final_authority_set_id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiRotateResponse {
+    pub final_authority_set_id: u64,
+    pub new_authority_set_hashes: Vec<H256>,
+}
+
+/// The input of a rotate header range request. authority_set_id and authority_set_hash are the
+/// current authority set, and the header range is justified by the next authority set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RotateHeaderRangeRequest {
+    pub authority_set_id: u64,
+    pub authority_set_hash: H256,
+    pub trusted_block: u32,
+    pub trusted_header_hash: H256,
+    pub target_block: u32,
+}
+
+/// The output of a rotate header range request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RotateHeaderRangeResponse {
+    pub new_authority_set_hash: H256,
+    pub target_header_hash: H256,
+    pub state_root_commitment: H256,
+    pub data_root_commitment: H256,
+}
+
+/// The input of a header ancestry request. target_block is at most trusted_block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderAncestryRequest {
+    pub trusted_block: u32,
+    pub trusted_header_hash: H256,
+    pub target_block: u32,
+}
+
+/// The output of a header ancestry request, the hash and roots of the header at target_block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderAncestryResponse {
+    pub target_header_hash: H256,
+    pub state_root: H256,
+    pub data_root: H256,
+}
+
+// Reads the big endian fields of an abi.encodePacked encoding in order.
+struct PackedReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> PackedReader<'a> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let (field, rest) = self.bytes.split_at(N);
+        self.bytes = rest;
+        field.try_into().unwrap()
+    }
+
+    fn read_u32(&mut self) -> u32 {
+        u32::from_be_bytes(self.take())
+    }
+
+    fn read_u64(&mut self) -> u64 {
+        u64::from_be_bytes(self.take())
+    }
+
+    fn read_bytes32(&mut self) -> H256 {
+        H256::from(self.take::<32>())
+    }
+}
+
+fn reader<'a>(
+    name: &str,
+    bytes: &'a [u8],
+    encoded_length: usize,
+) -> Result<PackedReader<'a>, Error> {
+    ensure!(
+        bytes.len() == encoded_length,
+        "{} must be {} bytes, got {}",
+        name,
+        encoded_length,
+        bytes.len()
+    );
+    Ok(PackedReader { bytes })
+}
+
+impl HeaderRangeRequest {
+    pub const ENCODED_LENGTH: usize = 4 + 32 + 8 + 32 + 4;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LENGTH);
+        bytes.extend(self.trusted_block.to_be_bytes());
+        bytes.extend(self.trusted_header_hash.as_bytes());
+        bytes.extend(self.authority_set_id.to_be_bytes());
+        bytes.extend(self.authority_set_hash.as_bytes());
+        bytes.extend(self.target_block.to_be_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = reader("Header range request", bytes, Self::ENCODED_LENGTH)?;
+        Ok(Self {
+            trusted_block: reader.read_u32(),
+            trusted_header_hash: reader.read_bytes32(),
+            authority_set_id: reader.read_u64(),
+            authority_set_hash: reader.read_bytes32(),
+            target_block: reader.read_u32(),
+        })
+    }
+}
+
+impl HeaderRangeResponse {
+    pub const ENCODED_LENGTH: usize = 3 * 32;
+
+    pub fn encode(&self) -> Vec<u8> {
+        [
+            self.target_header_hash,
+            self.state_root_commitment,
+            self.data_root_commitment,
+        ]
+        .iter()
+        .flat_map(|hash| hash.0)
+        .collect()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = reader("Header range response", bytes, Self::ENCODED_LENGTH)?;
+        Ok(Self {
+            target_header_hash: reader.read_bytes32(),
+            state_root_commitment: reader.read_bytes32(),
+            data_root_commitment: reader.read_bytes32(),
+        })
+    }
+}
+
+impl HeaderRangeKzgResponse {
+    pub const ENCODED_LENGTH: usize = 4 * 32 + 4 + 4;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LENGTH);
+        for hash in [
+            self.target_header_hash,
+            self.state_root_commitment,
+            self.data_root_commitment,
+            self.kzg_commitments_root,
+        ] {
+            bytes.extend(hash.as_bytes());
+        }
+        bytes.extend(self.rows.to_be_bytes());
+        bytes.extend(self.cols.to_be_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = reader("Header range KZG response", bytes, Self::ENCODED_LENGTH)?;
+        Ok(Self {
+            target_header_hash: reader.read_bytes32(),
+            state_root_commitment: reader.read_bytes32(),
+            data_root_commitment: reader.read_bytes32(),
+            kzg_commitments_root: reader.read_bytes32(),
+            rows: reader.read_u32(),
+            cols: reader.read_u32(),
+        })
+    }
+}
+
+impl RotateRequest {
+    pub const ENCODED_LENGTH: usize = 8 + 32;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LENGTH);
+        bytes.extend(self.authority_set_id.to_be_bytes());
+        bytes.extend(self.authority_set_hash.as_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = reader("Rotate request", bytes, Self::ENCODED_LENGTH)?;
+        Ok(Self {
+            authority_set_id: reader.read_u64(),
+            authority_set_hash: reader.read_bytes32(),
+        })
+    }
+}
+
+impl RotateResponse {
+    pub const ENCODED_LENGTH: usize = 32;
+
+    pub fn encode(&self) -> Vec<u8> {
+        self.new_authority_set_hash.as_bytes().to_vec()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = reader("Rotate response", bytes, Self::ENCODED_LENGTH)?;
+        Ok(Self {
+            new_authority_set_hash: reader.read_bytes32(),
+        })
+    }
+}
+
+impl MultiRotateResponse {
+    pub fn encoded_length(num_epochs: usize) -> usize {
+        8 + num_epochs * 32
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(Self::encoded_length(self.new_authority_set_hashes.len()));
+        bytes.extend(self.final_authority_set_id.to_be_bytes());
+        for hash in self.new_authority_set_hashes.iter() {
+            bytes.extend(hash.as_bytes());
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8], num_epochs: usize) -> Result<Self, Error> {
+        let mut reader = reader(
+            "Multi rotate response",
+            bytes,
+            Self::encoded_length(num_epochs),
+        )?;
+        Ok(Self {
+            final_authority_set_id: reader.read_u64(),
+            new_authority_set_hashes: (0..num_epochs).map(|_| reader.read_bytes32()).collect(),
+        })
+    }
+}
+
+impl RotateHeaderRangeRequest {
+    pub const ENCODED_LENGTH: usize = 8 + 32 + 4 + 32 + 4;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LENGTH);
+        bytes.extend(self.authority_set_id.to_be_bytes());
+        bytes.extend(self.authority_set_hash.as_bytes());
+        bytes.extend(self.trusted_block.to_be_bytes());
+        bytes.extend(self.trusted_header_hash.as_bytes());
+        bytes.extend(self.target_block.to_be_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = reader("Rotate header range request", bytes, Self::ENCODED_LENGTH)?;
+        Ok(Self {
+            authority_set_id: reader.read_u64(),
+            authority_set_hash: reader.read_bytes32(),
+            trusted_block: reader.read_u32(),
+            trusted_header_hash: reader.read_bytes32(),
+            target_block: reader.read_u32(),
+        })
+    }
+}
+
+impl RotateHeaderRangeResponse {
+    pub const ENCODED_LENGTH: usize = 4 * 32;
+
+    pub fn encode(&self) -> Vec<u8> {
+        [
+            self.new_authority_set_hash,
+            self.target_header_hash,
+            self.state_root_commitment,
+            self.data_root_commitment,
+        ]
+        .iter()
+        .flat_map(|hash| hash.0)
+        .collect()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = reader("Rotate header range response", bytes, Self::ENCODED_LENGTH)?;
+        Ok(Self {
+            new_authority_set_hash: reader.read_bytes32(),
+            target_header_hash: reader.read_bytes32(),
+            state_root_commitment: reader.read_bytes32(),
+            data_root_commitment: reader.read_bytes32(),
+        })
+    }
+}
+
+impl HeaderAncestryRequest {
+    pub const ENCODED_LENGTH: usize = 4 + 32 + 4;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LENGTH);
+        bytes.extend(self.trusted_block.to_be_bytes());
+        bytes.extend(self.trusted_header_hash.as_bytes());
+        bytes.extend(self.target_block.to_be_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = reader("Header ancestry request", bytes, Self::ENCODED_LENGTH)?;
+        Ok(Self {
+            trusted_block: reader.read_u32(),
+            trusted_header_hash: reader.read_bytes32(),
+            target_block: reader.read_u32(),
+        })
+    }
+}
+
+impl HeaderAncestryResponse {
+    pub const ENCODED_LENGTH: usize = 3 * 32;
+
+    pub fn encode(&self) -> Vec<u8> {
+        [self.target_header_hash, self.state_root, self.data_root]
+            .iter()
+            .flat_map(|hash| hash.0)
+            .collect()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = reader("Header ancestry response", bytes, Self::ENCODED_LENGTH)?;
+        Ok(Self {
+            target_header_hash: reader.read_bytes32(),
+            state_root: reader.read_bytes32(),
+            data_root: reader.read_bytes32(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::FixedBytes;
+    use alloy_sol_types::{sol, SolType};
+
+    use super::*;
+
+    type HeaderRangeInputTuple = sol! { tuple(uint32, bytes32, uint64, bytes32, uint32) };
+    type RotateInputTuple = sol! { tuple(uint64, bytes32) };
+    type RotateHeaderRangeInputTuple = sol! { tuple(uint64, bytes32, uint32, bytes32, uint32) };
+    type HeaderAncestryInputTuple = sol! { tuple(uint32, bytes32, uint32) };
+
+    fn hash(byte: u8) -> H256 {
+        H256::from_slice(&(0..32).map(|i| byte.wrapping_add(i)).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_header_range_encoding() {
+        let request = HeaderRangeRequest {
+            trusted_block: 0x0102_0304,
+            trusted_header_hash: hash(0x10),
+            authority_set_id: 0x0506_0708_090a_0b0c,
+            authority_set_hash: hash(0x40),
+            target_block: u32::MAX,
+        };
+        let encoded = request.encode();
+        assert_eq!(encoded.len(), HeaderRangeRequest::ENCODED_LENGTH);
+        assert_eq!(HeaderRangeRequest::decode(&encoded).unwrap(), request);
+
+        // Matches the contract's abi.encodePacked encoding.
+        let packed = HeaderRangeInputTuple::abi_encode_packed(&(
+            request.trusted_block,
+            FixedBytes(request.trusted_header_hash.0),
+            request.authority_set_id,
+            FixedBytes(request.authority_set_hash.0),
+            request.target_block,
+        ));
+        assert_eq!(encoded, packed);
+
+        // The input of test_dummy_header_range.
+        let input = hex::decode("0003c18695f303b01e4834da35e5fdc3971fe297d1b48feb0c3f330491639136a6ada5980000000000000075f2da06eb7ec36f683d2908648c431a1b3f968fa5212b72cc7e8eddce8b80958d0003c23a").unwrap();
+        let request = HeaderRangeRequest::decode(&input).unwrap();
+        assert_eq!(request.trusted_block, 246150);
+        assert_eq!(request.authority_set_id, 117);
+        assert_eq!(request.target_block, 246330);
+        assert_eq!(request.encode(), input);
+
+        let response = HeaderRangeResponse {
+            target_header_hash: hash(1),
+            state_root_commitment: hash(2),
+            data_root_commitment: hash(3),
+        };
+        let encoded = response.encode();
+        assert_eq!(encoded.len(), HeaderRangeResponse::ENCODED_LENGTH);
+        assert_eq!(encoded[32..64], hash(2).0);
+        assert_eq!(HeaderRangeResponse::decode(&encoded).unwrap(), response);
+
+        assert!(HeaderRangeRequest::decode(&input[..79]).is_err());
+        assert!(HeaderRangeResponse::decode(&[encoded, vec![0]].concat()).is_err());
+    }
+
+    #[test]
+    fn test_header_range_kzg_encoding() {
+        let response = HeaderRangeKzgResponse {
+            target_header_hash: hash(1),
+            state_root_commitment: hash(2),
+            data_root_commitment: hash(3),
+            kzg_commitments_root: hash(4),
+            rows: 0x0102_0304,
+            cols: 256,
+        };
+        let encoded = response.encode();
+        assert_eq!(encoded.len(), HeaderRangeKzgResponse::ENCODED_LENGTH);
+        assert_eq!(encoded[96..128], hash(4).0);
+        assert_eq!(encoded[128..132], [1, 2, 3, 4]);
+        assert_eq!(encoded[132..], 256u32.to_be_bytes());
+        assert_eq!(HeaderRangeKzgResponse::decode(&encoded).unwrap(), response);
+
+        // The header range outputs are a prefix of the header range KZG outputs.
+        assert_eq!(
+            HeaderRangeResponse::decode(&encoded[..HeaderRangeResponse::ENCODED_LENGTH]).unwrap(),
+            HeaderRangeResponse {
+                target_header_hash: hash(1),
+                state_root_commitment: hash(2),
+                data_root_commitment: hash(3),
+            }
+        );
+        assert!(HeaderRangeKzgResponse::decode(&encoded[..132]).is_err());
+    }
+
+    #[test]
+    fn test_rotate_encoding() {
+        let request = RotateRequest {
+            authority_set_id: 117,
+            authority_set_hash: hash(0x80),
+        };
+        let encoded = request.encode();
+        assert_eq!(encoded.len(), RotateRequest::ENCODED_LENGTH);
+        assert_eq!(RotateRequest::decode(&encoded).unwrap(), request);
+        assert_eq!(
+            encoded,
+            RotateInputTuple::abi_encode_packed(&(
+                request.authority_set_id,
+                FixedBytes(request.authority_set_hash.0),
+            ))
+        );
+
+        let response = RotateResponse {
+            new_authority_set_hash: hash(0xf0),
+        };
+        assert_eq!(
+            RotateResponse::decode(&response.encode()).unwrap(),
+            response
+        );
+        assert!(RotateRequest::decode(&encoded[1..]).is_err());
+        assert!(RotateResponse::decode(&[]).is_err());
+    }
+
+    #[test]
+    fn test_rotate_header_range_encoding() {
+        let request = RotateHeaderRangeRequest {
+            authority_set_id: 0x0506_0708_090a_0b0c,
+            authority_set_hash: hash(0x40),
+            trusted_block: 0x0102_0304,
+            trusted_header_hash: hash(0x10),
+            target_block: u32::MAX,
+        };
+        let encoded = request.encode();
+        assert_eq!(encoded.len(), RotateHeaderRangeRequest::ENCODED_LENGTH);
+        assert_eq!(RotateHeaderRangeRequest::decode(&encoded).unwrap(), request);
+        assert_eq!(
+            encoded,
+            RotateHeaderRangeInputTuple::abi_encode_packed(&(
+                request.authority_set_id,
+                FixedBytes(request.authority_set_hash.0),
+                request.trusted_block,
+                FixedBytes(request.trusted_header_hash.0),
+                request.target_block,
+            ))
+        );
+
+        let response = RotateHeaderRangeResponse {
+            new_authority_set_hash: hash(0xf0),
+            target_header_hash: hash(1),
+            state_root_commitment: hash(2),
+            data_root_commitment: hash(3),
+        };
+        let encoded = response.encode();
+        assert_eq!(encoded.len(), RotateHeaderRangeResponse::ENCODED_LENGTH);
+        assert_eq!(encoded[..32], hash(0xf0).0);
+        assert_eq!(
+            RotateHeaderRangeResponse::decode(&encoded).unwrap(),
+            response
+        );
+        assert!(RotateHeaderRangeRequest::decode(&encoded[..79]).is_err());
+        assert!(RotateHeaderRangeResponse::decode(&encoded[1..]).is_err());
+    }
+
+    #[test]
+    fn test_multi_rotate_encoding() {
+        let response = MultiRotateResponse {
+            final_authority_set_id: 0x0506_0708_090a_0b0c,
+            new_authority_set_hashes: vec![hash(0x10), hash(0x40)],
+        };
+        let encoded = response.encode();
+        assert_eq!(encoded.len(), MultiRotateResponse::encoded_length(2));
+        assert_eq!(encoded[..8], 0x0506_0708_090a_0b0cu64.to_be_bytes());
+        assert_eq!(encoded[40..], hash(0x40).0);
+        assert_eq!(MultiRotateResponse::decode(&encoded, 2).unwrap(), response);
+        assert!(MultiRotateResponse::decode(&encoded, 1).is_err());
+    }
+
+    #[test]
+    fn test_header_ancestry_encoding() {
+        let request = HeaderAncestryRequest {
+            trusted_block: 0x0102_0304,
+            trusted_header_hash: hash(0x10),
+            target_block: 0x0102_0300,
+        };
+        let encoded = request.encode();
+        assert_eq!(encoded.len(), HeaderAncestryRequest::ENCODED_LENGTH);
+        assert_eq!(HeaderAncestryRequest::decode(&encoded).unwrap(), request);
+        assert_eq!(
+            encoded,
+            HeaderAncestryInputTuple::abi_encode_packed(&(
+                request.trusted_block,
+                FixedBytes(request.trusted_header_hash.0),
+                request.target_block,
+            ))
+        );
+
+        let response = HeaderAncestryResponse {
+            target_header_hash: hash(1),
+            state_root: hash(2),
+            data_root: hash(3),
+        };
+        let encoded = response.encode();
+        assert_eq!(encoded[64..], hash(3).0);
+        assert_eq!(HeaderAncestryResponse::decode(&encoded).unwrap(), response);
+        assert!(HeaderAncestryRequest::decode(&encoded).is_err());
+    }
+}
@@ -0,0 +1,758 @@
+use anyhow::{anyhow, ensure, Error};
+use async_trait::async_trait;
+use avail_subxt::config::substrate::DigestItem;
+use codec::{Decode, Encode};
+use ethers::types::H256;
+use log::info;
+use plonky2x::frontend::curta::ec::point::CompressedEdwardsY;
+use serde::Serialize;
+use sp_core::{blake2_256, twox_128};
+
+use crate::authority_set::AuthoritySetCommitmentMode;
+use crate::consts::MAX_AUTHORITY_SET_SIZE_TIER;
+use crate::input::{compute_authority_set_hash, RpcDataFetcher};
+use crate::layout::get_digest_start;
+use crate::mock_chain::MockChain;
+use crate::native;
+use crate::witness::WitnessJustification;
+
+// Trust-minimized derivation of the VectorX genesis parameters. Starting from the GRANDPA
+// authorities in a chain spec, every epoch end header is verified with a justification by the
+// authority set derived so far, and its ScheduledChange log gives the next authority set. The RPC
+// only provides the headers and justifications, so a dishonest RPC can make the derivation fail,
+// but cannot change its result.
+
+/// The untrusted chain data that `derive_genesis` reads: the authority set ids only choose which
+/// blocks to verify, and the headers and justifications are verified.
+#[async_trait]
+pub trait GenesisDataSource {
+    /// The authority set id in block_number's state. Epoch end blocks have the new authority set id.
+    async fn get_authority_set_id(&mut self, block_number: u32) -> Result<u64, Error>;
+
+    /// The epoch end block of authority_set_id, whose ScheduledChange log schedules the next set.
+    async fn get_epoch_end_block(&mut self, authority_set_id: u64) -> Result<u32, Error>;
+
+    /// The SCALE encoded header of block_number.
+    async fn get_header(&mut self, block_number: u32) -> Result<Vec<u8>, Error>;
+
+    /// The simple justification of block_number.
+    async fn get_justification(&mut self, block_number: u32)
+        -> Result<WitnessJustification, Error>;
+}
+
+#[async_trait]
+impl GenesisDataSource for RpcDataFetcher {
+    async fn get_authority_set_id(&mut self, block_number: u32) -> Result<u64, Error> {
+        Ok(RpcDataFetcher::get_authority_set_id(self, block_number).await)
+    }
+
+    async fn get_epoch_end_block(&mut self, authority_set_id: u64) -> Result<u32, Error> {
+        Ok(self.last_justified_block(authority_set_id).await)
+    }
+
+    async fn get_header(&mut self, block_number: u32) -> Result<Vec<u8>, Error> {
+        Ok(RpcDataFetcher::get_header(self, block_number)
+            .await
+            .encode())
+    }
+
+    async fn get_justification(
+        &mut self,
+        block_number: u32,
+    ) -> Result<WitnessJustification, Error> {
+        Ok(self
+            .get_justification_from_block::<MAX_AUTHORITY_SET_SIZE_TIER>(block_number)
+            .await?
+            .into())
+    }
+}
+
+#[async_trait]
+impl GenesisDataSource for MockChain {
+    async fn get_authority_set_id(&mut self, block_number: u32) -> Result<u64, Error> {
+        Ok(self.authority_set_id(block_number))
+    }
+
+    async fn get_epoch_end_block(&mut self, authority_set_id: u64) -> Result<u32, Error> {
+        self.epoch_end_block(authority_set_id).ok_or_else(|| {
+            anyhow!(
+                "Unknown epoch end block of authority set {}",
+                authority_set_id
+            )
+        })
+    }
+
+    async fn get_header(&mut self, block_number: u32) -> Result<Vec<u8>, Error> {
+        self.header(block_number)
+            .map(|header| header.to_vec())
+            .ok_or_else(|| anyhow!("Unknown block {}", block_number))
+    }
+
+    async fn get_justification(
+        &mut self,
+        block_number: u32,
+    ) -> Result<WitnessJustification, Error> {
+        self.justification(block_number)
+            .cloned()
+            .ok_or_else(|| anyhow!("No justification for block {}", block_number))
+    }
+}
+
+/// The well known storage key of the versioned GRANDPA authority list.
+pub const GRANDPA_AUTHORITIES_WELL_KNOWN_KEY: &[u8] = b":grandpa_authorities";
+
+/// The storage key of `Grandpa::Authorities`: twox128("Grandpa") || twox128("Authorities").
+pub fn grandpa_authorities_storage_key() -> Vec<u8> {
+    let mut key = twox_128(b"Grandpa").to_vec();
+    key.extend(twox_128(b"Authorities"));
+    key
+}
+
+/// The genesis parameters of the VectorX contract, named as in `VectorX.InitParameters`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisParameters {
+    pub height: u32,
+    pub header: H256,
+    pub authority_set_id: u64,
+    pub authority_set_hash: H256,
+}
+
+impl GenesisParameters {
+    /// The environment variables read by `contracts/script/Deploy.s.sol`.
+    pub fn to_env(&self) -> String {
+        format!(
+            "GENESIS_HEIGHT={}\nGENESIS_HEADER={:#x}\nGENESIS_AUTHORITY_SET_ID={}\nGENESIS_AUTHORITY_SET_HASH={:#x}\n",
+            self.height, self.header, self.authority_set_id, self.authority_set_hash
+        )
+    }
+}
+
+/// A verified justification of a block in the transcript.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct JustificationTranscript {
+    pub block_number: u32,
+    pub block_hash: H256,
+    pub authority_set_id: u64,
+    pub authority_set_hash: H256,
+    pub num_authorities: usize,
+    pub num_signers: usize,
+}
+
+/// A verified rotate in the transcript: the epoch end block's justification, and the authority set
+/// scheduled by its ScheduledChange log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RotateTranscript {
+    pub justification: JustificationTranscript,
+    pub new_authority_set_hash: H256,
+    pub new_num_authorities: usize,
+}
+
+/// The verification transcript of the genesis parameters, from the chain spec's authority set to
+/// the genesis block. The target justification is omitted if the genesis block is the epoch end
+/// block of the last rotate.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GenesisTranscript {
+    pub chain_spec_authority_set_hash: H256,
+    pub chain_spec_num_authorities: usize,
+    pub rotates: Vec<RotateTranscript>,
+    pub target_justification: Option<JustificationTranscript>,
+    pub genesis: GenesisParameters,
+}
+
+/// An authority set derived from the chain spec by verified rotates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrustedAuthoritySet {
+    pub authority_set_id: u64,
+    pub pubkeys: Vec<CompressedEdwardsY>,
+}
+
+impl TrustedAuthoritySet {
+    /// The chain spec's authority set, which has authority set id 0.
+    pub fn from_chain_spec(chain_spec: &serde_json::Value) -> Result<Self, Error> {
+        Ok(Self {
+            authority_set_id: 0,
+            pubkeys: chain_spec_authorities(chain_spec)?,
+        })
+    }
+
+    /// The chained authority set hash, as stored in `authoritySetIdToHash`.
+    pub fn authority_set_hash(&self) -> H256 {
+        H256::from_slice(&compute_authority_set_hash(&self.pubkeys))
+    }
+
+    /// Verify the justification of header by this authority set, and return its transcript with the
+    /// header's block number and hash.
+    pub fn verify_justification(
+        &self,
+        header: &[u8],
+        justification: &WitnessJustification,
+    ) -> Result<JustificationTranscript, Error> {
+        let block_number = native::decode_header(header)?.block_number;
+        let block_hash = H256::from(blake2_256(header));
+        let authority_set_hash = self.authority_set_hash();
+        // The justification's pubkeys must be this authority set's, as they match its commitment.
+        native::verify_justification(
+            block_number,
+            block_hash,
+            self.authority_set_id,
+            authority_set_hash,
+            justification,
+            AuthoritySetCommitmentMode::ChainedSha256,
+        )?;
+        Ok(JustificationTranscript {
+            block_number,
+            block_hash,
+            authority_set_id: self.authority_set_id,
+            authority_set_hash,
+            num_authorities: self.pubkeys.len(),
+            num_signers: justification
+                .validator_signed
+                .iter()
+                .filter(|signed| **signed)
+                .count(),
+        })
+    }
+
+    /// Verify the rotate at the epoch end header of this authority set, and return the next
+    /// authority set scheduled by the ScheduledChange log at start_position.
+    pub fn rotate(
+        &self,
+        epoch_end_header: &[u8],
+        start_position: usize,
+        justification: &WitnessJustification,
+    ) -> Result<(Self, RotateTranscript), Error> {
+        let justification = self.verify_justification(epoch_end_header, justification)?;
+        let new_pubkeys = native::verify_epoch_end_header(epoch_end_header, start_position)?;
+        let next = Self {
+            authority_set_id: self.authority_set_id + 1,
+            pubkeys: new_pubkeys,
+        };
+        let transcript = RotateTranscript {
+            justification,
+            new_authority_set_hash: next.authority_set_hash(),
+            new_num_authorities: next.pubkeys.len(),
+        };
+        Ok((next, transcript))
+    }
+}
+
+// Decode a SCALE encoded list of (pubkey, weight) authorities. All authorities have a voting power
+// of 1 in Avail.
+fn decode_authority_list(mut encoded: &[u8]) -> Result<Vec<CompressedEdwardsY>, Error> {
+    let authorities = Vec::<([u8; 32], u64)>::decode(&mut encoded)
+        .map_err(|e| anyhow!("Failed to decode the GRANDPA authorities: {}", e))?;
+    ensure!(
+        encoded.is_empty(),
+        "Unexpected bytes after the GRANDPA authorities"
+    );
+    ensure!(!authorities.is_empty(), "The chain spec has no authorities");
+    authorities
+        .into_iter()
+        .enumerate()
+        .map(|(i, (pubkey, weight))| {
+            ensure!(weight == 1, "Authority {} does not have a weight of 1", i);
+            Ok(CompressedEdwardsY(pubkey))
+        })
+        .collect()
+}
+
+/// The GRANDPA authorities in the genesis storage of a raw chain spec, e.g. the output of
+/// `build-spec --raw`. Reads `Grandpa::Authorities`, or the well known `:grandpa_authorities` key
+/// of older runtimes.
+pub fn chain_spec_authorities(
+    chain_spec: &serde_json::Value,
+) -> Result<Vec<CompressedEdwardsY>, Error> {
+    let storage = chain_spec
+        .pointer("/genesis/raw/top")
+        .and_then(|top| top.as_object())
+        .ok_or_else(|| {
+            anyhow!("The chain spec has no raw genesis storage, use a raw chain spec")
+        })?;
+    let read = |key: &[u8]| -> Result<Option<Vec<u8>>, Error> {
+        storage
+            .get(&format!("0x{}", hex::encode(key)))
+            .map(|value| {
+                let value = value
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Storage value is not a hex string"))?;
+                Ok(hex::decode(value.trim_start_matches("0x"))?)
+            })
+            .transpose()
+    };
+
+    if let Some(value) = read(&grandpa_authorities_storage_key())? {
+        return decode_authority_list(&value);
+    }
+    if let Some(value) = read(GRANDPA_AUTHORITIES_WELL_KNOWN_KEY)? {
+        // VersionedAuthorityList: version 1 || authority list.
+        ensure!(
+            value.first() == Some(&1),
+            "Unsupported GRANDPA authority list version"
+        );
+        return decode_authority_list(&value[1..]);
+    }
+    Err(anyhow!("The chain spec has no GRANDPA authorities"))
+}
+
+/// The start position of the GRANDPA ScheduledChange log in an encoded epoch end header, as in
+/// `get_header_rotate`: the byte before the log's Consensus digest item tag.
+pub fn get_scheduled_change_position(header: &[u8]) -> Result<usize, Error> {
+    let mut position = get_digest_start(header)?;
+    let logs = Vec::<DigestItem>::decode(&mut &header[position..])
+        .map_err(|e| anyhow!("Failed to decode the digest: {}", e))?;
+    for log in logs {
+        if let DigestItem::Consensus(consensus_id, value) = &log {
+            if consensus_id == b"FRNK" && value.first() == Some(&1) {
+                return Ok(position);
+            }
+        }
+        position += log.encode().len();
+    }
+    Err(anyhow!("The header has no ScheduledChange log"))
+}
+
+/// Derive the genesis parameters for target_block from the chain spec's authority set, by verifying
+/// the rotate of every authority set up to target_block's. The headers and justifications are read
+/// from the untrusted source, and target_block must be justified or an epoch end block.
+pub async fn derive_genesis<S: GenesisDataSource + Send>(
+    source: &mut S,
+    chain_spec_authority_set: TrustedAuthoritySet,
+    target_block: u32,
+) -> Result<GenesisTranscript, Error> {
+    ensure!(
+        chain_spec_authority_set.authority_set_id == 0,
+        "The chain spec's authority set must have authority set id 0"
+    );
+    let mut transcript = GenesisTranscript {
+        chain_spec_authority_set_hash: chain_spec_authority_set.authority_set_hash(),
+        chain_spec_num_authorities: chain_spec_authority_set.pubkeys.len(),
+        rotates: Vec::new(),
+        target_justification: None,
+        genesis: GenesisParameters {
+            height: target_block,
+            header: H256::zero(),
+            authority_set_id: 0,
+            authority_set_hash: H256::zero(),
+        },
+    };
+
+    // The source's authority set ids only choose the epoch end blocks to verify.
+    let target_authority_set_id = source.get_authority_set_id(target_block).await?;
+    let mut authority_set = chain_spec_authority_set;
+    let mut last_epoch_end_block = 0;
+    while authority_set.authority_set_id < target_authority_set_id {
+        let epoch_end_block = source
+            .get_epoch_end_block(authority_set.authority_set_id)
+            .await?;
+        ensure!(
+            epoch_end_block > last_epoch_end_block && epoch_end_block <= target_block,
+            "Authority set {}'s epoch end block {} is not in ({}, {}]",
+            authority_set.authority_set_id,
+            epoch_end_block,
+            last_epoch_end_block,
+            target_block
+        );
+
+        let header = source.get_header(epoch_end_block).await?;
+        let justification = source.get_justification(epoch_end_block).await?;
+        let (next, rotate) = get_scheduled_change_position(&header)
+            .and_then(|start_position| {
+                authority_set.rotate(&header, start_position, &justification)
+            })
+            .map_err(|e| {
+                anyhow!(
+                    "Failed to verify the rotate of authority set {} at block {}: {}",
+                    authority_set.authority_set_id,
+                    epoch_end_block,
+                    e
+                )
+            })?;
+        ensure!(
+            rotate.justification.block_number == epoch_end_block,
+            "The source returned block {} for block {}",
+            rotate.justification.block_number,
+            epoch_end_block
+        );
+        info!(
+            "Verified the rotate from authority set {} to {} at block {}",
+            authority_set.authority_set_id, next.authority_set_id, epoch_end_block
+        );
+        transcript.rotates.push(rotate);
+        authority_set = next;
+        last_epoch_end_block = epoch_end_block;
+    }
+
+    // The epoch end block of the last rotate is already verified, any other block must be justified
+    // by the derived authority set.
+    let header = match transcript.rotates.last() {
+        Some(rotate) if rotate.justification.block_number == target_block => {
+            rotate.justification.block_hash
+        }
+        _ => {
+            let header = source.get_header(target_block).await?;
+            let justification = source.get_justification(target_block).await?;
+            let target_justification = authority_set
+                .verify_justification(&header, &justification)
+                .map_err(|e| anyhow!("Failed to verify block {}: {}", target_block, e))?;
+            ensure!(
+                target_justification.block_number == target_block,
+                "The source returned block {} for block {}",
+                target_justification.block_number,
+                target_block
+            );
+            let block_hash = target_justification.block_hash;
+            transcript.target_justification = Some(target_justification);
+            block_hash
+        }
+    };
+
+    transcript.genesis = GenesisParameters {
+        height: target_block,
+        header,
+        authority_set_id: authority_set.authority_set_id,
+        authority_set_hash: authority_set.authority_set_hash(),
+    };
+    Ok(transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::Bytes;
+    use serde_json::json;
+
+    use super::*;
+    use crate::synthetic::{SyntheticChain, SyntheticChainConfig};
+
+    fn chain() -> SyntheticChain {
+        SyntheticChain::new(SyntheticChainConfig {
+            start_block: 0,
+            num_blocks: 60,
+            epoch_length: 16,
+            authority_set_sizes: vec![4, 7, 5],
+            ..Default::default()
+        })
+    }
+
+    fn chain_spec(key: &[u8], value: &[u8]) -> serde_json::Value {
+        json!({
+            "name": "Synthetic",
+            "genesis": {
+                "raw": {
+                    "top": {
+                        format!("0x{}", hex::encode(key)): format!("0x{}", hex::encode(value)),
+                    }
+                }
+            }
+        })
+    }
+
+    fn encode_authorities(pubkeys: &[CompressedEdwardsY]) -> Vec<u8> {
+        pubkeys
+            .iter()
+            .map(|pubkey| (pubkey.0, 1u64))
+            .collect::<Vec<_>>()
+            .encode()
+    }
+
+    #[test]
+    fn test_chain_spec_authorities() {
+        let chain = chain();
+        let authorities = chain.authorities(0);
+
+        let spec = chain_spec(
+            &grandpa_authorities_storage_key(),
+            &encode_authorities(&authorities),
+        );
+        assert_eq!(chain_spec_authorities(&spec).unwrap(), authorities);
+
+        let versioned = [vec![1u8], encode_authorities(&authorities)].concat();
+        let spec = chain_spec(GRANDPA_AUTHORITIES_WELL_KNOWN_KEY, &versioned);
+        let authority_set = TrustedAuthoritySet::from_chain_spec(&spec).unwrap();
+        assert_eq!(authority_set.authority_set_id, 0);
+        assert_eq!(
+            authority_set.authority_set_hash(),
+            chain.authority_set_hash(0)
+        );
+
+        // A weight other than 1, an unknown version, no GRANDPA authorities and a non-raw chain spec.
+        let weighted = vec![(authorities[0].0, 2u64)].encode();
+        assert!(
+            chain_spec_authorities(&chain_spec(&grandpa_authorities_storage_key(), &weighted))
+                .is_err()
+        );
+        let unknown_version = [vec![2u8], encode_authorities(&authorities)].concat();
+        assert!(chain_spec_authorities(&chain_spec(
+            GRANDPA_AUTHORITIES_WELL_KNOWN_KEY,
+            &unknown_version
+        ))
+        .is_err());
+        assert!(chain_spec_authorities(&chain_spec(b"other", &versioned)).is_err());
+        assert!(chain_spec_authorities(&json!({ "genesis": { "runtime": {} } })).is_err());
+    }
+
+    fn chain_spec_authority_set(chain: &SyntheticChain) -> TrustedAuthoritySet {
+        TrustedAuthoritySet {
+            authority_set_id: 0,
+            pubkeys: chain.authorities(0),
+        }
+    }
+
+    // A mock chain source that lies about one authority set id or epoch end block.
+    enum Lie {
+        AuthoritySetId {
+            block_number: u32,
+            authority_set_id: u64,
+        },
+        EpochEndBlock {
+            authority_set_id: u64,
+            block_number: u32,
+        },
+    }
+
+    struct LyingSource {
+        chain: MockChain,
+        lie: Lie,
+    }
+
+    #[async_trait]
+    impl GenesisDataSource for LyingSource {
+        async fn get_authority_set_id(&mut self, block_number: u32) -> Result<u64, Error> {
+            match self.lie {
+                Lie::AuthoritySetId {
+                    block_number: lie_block_number,
+                    authority_set_id,
+                } if lie_block_number == block_number => Ok(authority_set_id),
+                _ => self.chain.get_authority_set_id(block_number).await,
+            }
+        }
+
+        async fn get_epoch_end_block(&mut self, authority_set_id: u64) -> Result<u32, Error> {
+            match self.lie {
+                Lie::EpochEndBlock {
+                    authority_set_id: lie_authority_set_id,
+                    block_number,
+                } if lie_authority_set_id == authority_set_id => Ok(block_number),
+                _ => self.chain.get_epoch_end_block(authority_set_id).await,
+            }
+        }
+
+        async fn get_header(&mut self, block_number: u32) -> Result<Vec<u8>, Error> {
+            self.chain.get_header(block_number).await
+        }
+
+        async fn get_justification(
+            &mut self,
+            block_number: u32,
+        ) -> Result<WitnessJustification, Error> {
+            self.chain.get_justification(block_number).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_derive_genesis() {
+        let chain = chain();
+        let mut source = MockChain::from_synthetic(&chain);
+
+        // Blocks inside an epoch are justified by the derived authority set, and epoch end blocks
+        // are verified by their rotate. The epoch end blocks are 16, 32 and 48.
+        for (target_block, authority_set_id, is_epoch_end_block) in [
+            (5, 0, false),
+            (16, 1, true),
+            (17, 1, false),
+            (48, 3, true),
+            (55, 3, false),
+        ] {
+            let transcript =
+                derive_genesis(&mut source, chain_spec_authority_set(&chain), target_block)
+                    .await
+                    .unwrap();
+            assert_eq!(
+                transcript.genesis,
+                GenesisParameters {
+                    height: target_block,
+                    header: chain.header_hash(target_block),
+                    authority_set_id,
+                    authority_set_hash: chain.authority_set_hash(authority_set_id),
+                }
+            );
+            assert_eq!(
+                transcript.chain_spec_authority_set_hash,
+                chain.authority_set_hash(0)
+            );
+
+            assert_eq!(transcript.rotates.len(), authority_set_id as usize);
+            for (id, rotate) in transcript.rotates.iter().enumerate() {
+                let id = id as u64;
+                assert_eq!(rotate.justification.block_number, chain.epoch_end_block(id));
+                assert_eq!(rotate.justification.authority_set_id, id);
+                assert_eq!(
+                    rotate.new_authority_set_hash,
+                    chain.authority_set_hash(id + 1)
+                );
+            }
+
+            // The epoch end block of the last rotate needs no other justification.
+            match &transcript.target_justification {
+                Some(justification) => {
+                    assert!(!is_epoch_end_block);
+                    assert_eq!(justification.block_number, target_block);
+                    assert_eq!(justification.authority_set_id, authority_set_id);
+                }
+                None => assert!(is_epoch_end_block),
+            }
+        }
+
+        // A chain spec with a different authority set.
+        let wrong_chain_spec = TrustedAuthoritySet {
+            authority_set_id: 0,
+            pubkeys: chain.authorities(1),
+        };
+        assert!(derive_genesis(&mut source, wrong_chain_spec, 55)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_derive_genesis_lying_source() {
+        let chain = chain();
+        let target_block = 55;
+        let lies = [
+            // A lower authority set id at the target block, so the derived authority set does not
+            // justify it.
+            Lie::AuthoritySetId {
+                block_number: target_block,
+                authority_set_id: 1,
+            },
+            // A higher authority set id at the target block, so there is a rotate too many.
+            Lie::AuthoritySetId {
+                block_number: target_block,
+                authority_set_id: 4,
+            },
+            // A block that is not an epoch end block.
+            Lie::EpochEndBlock {
+                authority_set_id: 1,
+                block_number: 31,
+            },
+            // The epoch end block of the previous authority set.
+            Lie::EpochEndBlock {
+                authority_set_id: 1,
+                block_number: 16,
+            },
+            // The epoch end block of a later authority set, justified by that set.
+            Lie::EpochEndBlock {
+                authority_set_id: 1,
+                block_number: 48,
+            },
+            // An epoch end block after the target block.
+            Lie::EpochEndBlock {
+                authority_set_id: 2,
+                block_number: 57,
+            },
+        ];
+        for lie in lies {
+            let mut source = LyingSource {
+                chain: MockChain::from_synthetic(&chain),
+                lie,
+            };
+            assert!(
+                derive_genesis(&mut source, chain_spec_authority_set(&chain), target_block)
+                    .await
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_scheduled_change_position() {
+        let chain = chain();
+        for authority_set_id in 0..3 {
+            let rotate = chain.rotate(authority_set_id);
+            assert_eq!(
+                get_scheduled_change_position(chain.header(rotate.epoch_end_block_number)).unwrap(),
+                rotate.start_position
+            );
+        }
+        assert!(get_scheduled_change_position(chain.header(17)).is_err());
+    }
+
+    #[test]
+    fn test_justify() {
+        let chain = chain();
+        let authority_set = TrustedAuthoritySet {
+            authority_set_id: 3,
+            pubkeys: chain.authorities(3),
+        };
+
+        // A block inside authority set 3's epoch is justified by authority set 3.
+        let target_block = 55;
+        let transcript = authority_set
+            .verify_justification(
+                chain.header(target_block),
+                &chain.justification(target_block),
+            )
+            .unwrap();
+        assert_eq!(transcript.block_hash, chain.header_hash(target_block));
+        assert_eq!(transcript.authority_set_hash, chain.authority_set_hash(3));
+        assert_eq!(transcript.num_signers, 5);
+    }
+
+    #[test]
+    fn test_rotate_invalid() {
+        let chain = chain();
+        let authority_set = TrustedAuthoritySet {
+            authority_set_id: 1,
+            pubkeys: chain.authorities(1),
+        };
+        let rotate = chain.rotate(1);
+        let epoch_end_block = rotate.epoch_end_block_number;
+        let header = chain.header(epoch_end_block);
+
+        // Justified by the previous authority set.
+        let stale = chain_spec_authority_set(&chain);
+        assert!(stale
+            .rotate(
+                header,
+                rotate.start_position,
+                &chain.justification(epoch_end_block)
+            )
+            .is_err());
+
+        // A header that is not the justified one.
+        let mut tampered = header.to_vec();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(authority_set
+            .rotate(
+                &tampered,
+                rotate.start_position,
+                &chain.justification(epoch_end_block)
+            )
+            .is_err());
+
+        // A justification of a different block.
+        assert!(authority_set
+            .rotate(
+                header,
+                rotate.start_position,
+                &chain.justification(epoch_end_block - 1)
+            )
+            .is_err());
+
+        // A forged signature.
+        let mut forged = chain.justification(epoch_end_block);
+        forged.signatures[0] = Bytes::from(vec![0u8; 64]);
+        assert!(authority_set
+            .rotate(header, rotate.start_position, &forged)
+            .is_err());
+
+        // A position that is not the ScheduledChange log.
+        assert!(authority_set
+            .rotate(
+                header,
+                rotate.start_position + 1,
+                &chain.justification(epoch_end_block)
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use plonky2x::backend::circuit::Circuit;
+use plonky2x::frontend::mapreduce::generator::MapReduceGenerator;
+use plonky2x::frontend::vars::U32Variable;
+use plonky2x::prelude::{Bytes32Variable, CircuitBuilder, PlonkParameters};
+
+use crate::builder::ancestry::HeaderAncestryVerifier;
+use crate::builder::subchain_verification::{
+    HeaderRangeFetcherHint, MapReduceSubchainVariable, SubchainVerificationCtx,
+};
+use crate::consts::HEADERS_PER_MAP;
+
+/// Proves the header at target_block is an ancestor of a header committed in VectorX's
+/// `blockHeightToHeaderHash`, and outputs the target header's hash, state root and data root. Used
+/// to attest blocks which are not covered by a ZK-attested data commitment, such as blocks before
+/// genesis or in ranges filled by `updateBlockRangeData`.
+#[derive(Clone, Debug)]
+pub struct HeaderAncestryCircuit<const MAX_HEADER_SIZE: usize, const MAX_NUM_HEADERS: usize> {}
+
+impl<const MAX_HEADER_SIZE: usize, const MAX_NUM_HEADERS: usize> Circuit
+    for HeaderAncestryCircuit<MAX_HEADER_SIZE, MAX_NUM_HEADERS>
+{
+    fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+    where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        // Read the on-chain inputs. trusted_header_hash is read from blockHeightToHeaderHash[trusted_block].
+        let trusted_block = builder.evm_read::<U32Variable>();
+        let trusted_header_hash = builder.evm_read::<Bytes32Variable>();
+        let target_block = builder.evm_read::<U32Variable>();
+
+        let ancestry_output = builder
+            .verify_header_ancestry::<Self, MAX_HEADER_SIZE, MAX_NUM_HEADERS>(
+                trusted_block,
+                trusted_header_hash,
+                target_block,
+            );
+
+        builder.evm_write::<Bytes32Variable>(ancestry_output.target_header_hash);
+        builder.evm_write::<Bytes32Variable>(ancestry_output.state_root);
+        builder.evm_write::<Bytes32Variable>(ancestry_output.data_root);
+    }
+
+    fn register_generators<L: PlonkParameters<D>, const D: usize>(
+        generator_registry: &mut plonky2x::prelude::HintRegistry<L, D>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        generator_registry
+            .register_async_hint::<HeaderRangeFetcherHint<MAX_HEADER_SIZE, HEADERS_PER_MAP>>();
+        generator_registry.register_async_hint::<HeaderRangeFetcherHint<MAX_HEADER_SIZE, 1>>();
+
+        let mr_id = MapReduceGenerator::<
+            L,
+            SubchainVerificationCtx,
+            U32Variable,
+            MapReduceSubchainVariable,
+            Self,
+            HEADERS_PER_MAP,
+            D,
+        >::id();
+        generator_registry.register_simple::<MapReduceGenerator<
+            L,
+            SubchainVerificationCtx,
+            U32Variable,
+            MapReduceSubchainVariable,
+            Self,
+            HEADERS_PER_MAP,
+            D,
+        >>(mr_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use avail_subxt::config::Header;
+    use ethers::types::H256;
+    use plonky2x::prelude::{DefaultBuilder, GateRegistry, HintRegistry};
+
+    use super::*;
+    use crate::consts::MAX_HEADER_SIZE;
+    use crate::input::RpcDataFetcher;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_circuit_function_header_ancestry() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        const MAX_NUM_HEADERS: usize = 4;
+
+        let mut builder = DefaultBuilder::new();
+
+        log::debug!("Defining circuit");
+        HeaderAncestryCircuit::<MAX_HEADER_SIZE, MAX_NUM_HEADERS>::define(&mut builder);
+        let circuit = builder.build();
+        log::debug!("Done building circuit");
+
+        let mut hint_registry = HintRegistry::new();
+        let mut gate_registry = GateRegistry::new();
+        HeaderAncestryCircuit::<MAX_HEADER_SIZE, MAX_NUM_HEADERS>::register_generators(
+            &mut hint_registry,
+        );
+        HeaderAncestryCircuit::<MAX_HEADER_SIZE, MAX_NUM_HEADERS>::register_gates(
+            &mut gate_registry,
+        );
+
+        circuit.test_serializers(&gate_registry, &hint_registry);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_header_ancestry() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        const MAX_NUM_HEADERS: usize = 16;
+        let mut builder = DefaultBuilder::new();
+
+        log::debug!("Defining circuit");
+        HeaderAncestryCircuit::<MAX_HEADER_SIZE, MAX_NUM_HEADERS>::define(&mut builder);
+
+        log::debug!("Building circuit");
+        let circuit = builder.build();
+        log::debug!("Done building circuit");
+
+        let mut input = circuit.input();
+
+        let trusted_header: H256 =
+            "087ee7c739e35c46b2ac422cf683ecf6d4cb4571610efe6a5dff6f5b3d5818c9"
+                .parse()
+                .unwrap();
+        let trusted_block = 4310u32;
+        let target_block = 4300u32;
+
+        input.evm_write::<U32Variable>(trusted_block);
+        input.evm_write::<Bytes32Variable>(trusted_header);
+        input.evm_write::<U32Variable>(target_block);
+
+        log::debug!("Generating proof");
+        let (proof, mut output) = circuit.prove(&input);
+        log::debug!("Done generating proof");
+
+        circuit.verify(&proof, &input, &output);
+        let target_header_hash = output.evm_read::<Bytes32Variable>();
+        let state_root = output.evm_read::<Bytes32Variable>();
+        let data_root = output.evm_read::<Bytes32Variable>();
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let target_header = rt.block_on(async {
+            let data_fetcher = RpcDataFetcher::new().await;
+            data_fetcher.get_header(target_block).await
+        });
+        assert_eq!(
+            target_header_hash,
+            H256::from_slice(&target_header.hash().0)
+        );
+        assert_eq!(state_root, H256::from_slice(&target_header.state_root.0));
+        assert_eq!(data_root, H256::from_slice(&target_header.data_root().0));
+    }
+}
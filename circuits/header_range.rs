@@ -4,6 +4,7 @@ use plonky2x::frontend::uint::uint64::U64Variable;
 use plonky2x::frontend::vars::U32Variable;
 use plonky2x::prelude::{Bytes32Variable, CircuitBuilder, PlonkParameters};
 
+use crate::authority_set::AuthoritySetCommitmentMode;
 use crate::builder::justification::{GrandpaJustificationVerifier, HintSimpleJustification};
 use crate::builder::subchain_verification::{
     HeaderRangeFetcherHint, MapReduceSubchainVariable, SubChainVerifier, SubchainVerificationCtx,
@@ -15,20 +16,28 @@ pub struct HeaderRangeCircuit<
     const MAX_AUTHORITY_SET_SIZE: usize,
     const MAX_HEADER_SIZE: usize,
     const MAX_NUM_HEADERS: usize,
+    const AUTHORITY_SET_COMMITMENT_MODE: u8 = { AuthoritySetCommitmentMode::CHAINED_SHA256 },
 > {}
 
 impl<
         const MAX_AUTHORITY_SET_SIZE: usize,
         const MAX_HEADER_SIZE: usize,
         const MAX_NUM_HEADERS: usize,
-    > Circuit for HeaderRangeCircuit<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, MAX_NUM_HEADERS>
+        const AUTHORITY_SET_COMMITMENT_MODE: u8,
+    > Circuit
+    for HeaderRangeCircuit<
+        MAX_AUTHORITY_SET_SIZE,
+        MAX_HEADER_SIZE,
+        MAX_NUM_HEADERS,
+        AUTHORITY_SET_COMMITMENT_MODE,
+    >
 {
     fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
     where
         <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
         plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
     {
-        // Read the on-chain inputs.
+        // Read the on-chain inputs, encoded as evm::HeaderRangeRequest.
         let trusted_block = builder.evm_read::<U32Variable>();
         let trusted_header_hash = builder.evm_read::<Bytes32Variable>();
         let authority_set_id = builder.evm_read::<U64Variable>();
@@ -36,21 +45,21 @@ impl<
         let target_block = builder.evm_read::<U32Variable>();
 
         // Get the target_header_hash, state_root, and data_root over the range [trusted_block + 1, target_block].
-        let subchain_output = builder.verify_subchain::<HeaderRangeCircuit<
-            MAX_AUTHORITY_SET_SIZE,
-            MAX_HEADER_SIZE,
-            MAX_NUM_HEADERS,
-        >, MAX_NUM_HEADERS>(
-            trusted_block, trusted_header_hash, target_block
+        let subchain_output = builder.verify_subchain::<Self, MAX_HEADER_SIZE, MAX_NUM_HEADERS>(
+            trusted_block,
+            trusted_header_hash,
+            target_block,
         );
 
         // Note: target_header_hash and target_block are trusted at this point.
         // Verify that there is a valid justification on target_header_hash by the authority set at authority_set_id.
-        builder.verify_simple_justification::<MAX_AUTHORITY_SET_SIZE>(
+        builder.verify_simple_justification_with_mode::<MAX_AUTHORITY_SET_SIZE>(
             target_block,
             subchain_output.target_header_hash,
             authority_set_id,
             authority_set_hash,
+            AuthoritySetCommitmentMode::from_id(AUTHORITY_SET_COMMITMENT_MODE)
+                .expect("Unknown authority set commitment mode"),
         );
 
         builder.evm_write::<Bytes32Variable>(subchain_output.target_header_hash);
@@ -0,0 +1,452 @@
+use core::marker::PhantomData;
+
+use plonky2x::backend::circuit::{Circuit, CircuitBuild, PublicInput};
+use plonky2x::frontend::mapreduce::generator::MapReduceGenerator;
+use plonky2x::frontend::uint::uint64::U64Variable;
+use plonky2x::frontend::vars::{EvmVariable, U32Variable};
+use plonky2x::prelude::plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2x::prelude::plonky2::iop::target::Target;
+use plonky2x::prelude::plonky2::iop::witness::{PartitionWitness, WitnessWrite};
+use plonky2x::prelude::plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2x::prelude::plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2x::prelude::plonky2::plonk::proof::ProofWithPublicInputsTarget;
+use plonky2x::prelude::plonky2::util::serialization::{Buffer, IoResult, Read, Write};
+use plonky2x::prelude::{
+    ByteVariable, Bytes32Variable, CircuitBuilder, CircuitVariable, PlonkParameters,
+};
+
+use crate::builder::justification::{GrandpaJustificationVerifier, HintSimpleJustification};
+use crate::builder::subchain_verification::{
+    HeaderRangeFetcherHint, MapReduceSubchainVariable, SubChainVerifier, SubchainVerificationCtx,
+};
+use crate::consts::HEADERS_PER_MAP;
+
+/// The length of the EVM input of SubchainCircuit: uint32 trusted_block, bytes32
+/// trusted_header_hash, uint32 target_block.
+const SUBCHAIN_INPUT_LENGTH: usize = 4 + 32 + 4;
+
+/// The length of the EVM output of SubchainCircuit: bytes32 target_header_hash, bytes32
+/// state_root_merkle_root, bytes32 data_root_merkle_root.
+const SUBCHAIN_OUTPUT_LENGTH: usize = 3 * 32;
+
+/// Proves the headers link from the trusted header to target_block, and outputs the target header
+/// hash and the state and data Merkle roots of the range, like HeaderRangeCircuit without the
+/// justification of the target block. HeaderRangeAggregationCircuit verifies a proof of it for
+/// each range, and the justification of the last range's target block.
+#[derive(Clone, Debug)]
+pub struct SubchainCircuit<const MAX_HEADER_SIZE: usize, const MAX_NUM_HEADERS: usize> {}
+
+impl<const MAX_HEADER_SIZE: usize, const MAX_NUM_HEADERS: usize> Circuit
+    for SubchainCircuit<MAX_HEADER_SIZE, MAX_NUM_HEADERS>
+{
+    fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+    where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        let trusted_block = builder.evm_read::<U32Variable>();
+        let trusted_header_hash = builder.evm_read::<Bytes32Variable>();
+        let target_block = builder.evm_read::<U32Variable>();
+
+        let subchain_output = builder.verify_subchain::<Self, MAX_HEADER_SIZE, MAX_NUM_HEADERS>(
+            trusted_block,
+            trusted_header_hash,
+            target_block,
+        );
+
+        builder.evm_write::<Bytes32Variable>(subchain_output.target_header_hash);
+        builder.evm_write::<Bytes32Variable>(subchain_output.state_root_merkle_root);
+        builder.evm_write::<Bytes32Variable>(subchain_output.data_root_merkle_root);
+    }
+
+    fn register_generators<L: PlonkParameters<D>, const D: usize>(
+        generator_registry: &mut plonky2x::prelude::HintRegistry<L, D>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        generator_registry
+            .register_async_hint::<HeaderRangeFetcherHint<MAX_HEADER_SIZE, HEADERS_PER_MAP>>();
+
+        let mr_id = MapReduceGenerator::<
+            L,
+            SubchainVerificationCtx,
+            U32Variable,
+            MapReduceSubchainVariable,
+            Self,
+            HEADERS_PER_MAP,
+            D,
+        >::id();
+        generator_registry.register_simple::<MapReduceGenerator<
+            L,
+            SubchainVerificationCtx,
+            U32Variable,
+            MapReduceSubchainVariable,
+            Self,
+            HEADERS_PER_MAP,
+            D,
+        >>(mr_id);
+    }
+}
+
+/// Build the subchain circuit of a range. The build is deterministic, so the proofs of a build in
+/// SubchainProofGenerator verify against the verifier data of the build in define.
+fn build_child_circuit<
+    L: PlonkParameters<D>,
+    const D: usize,
+    const MAX_HEADER_SIZE: usize,
+    const RANGE_SIZE: usize,
+>() -> CircuitBuild<L, D>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+{
+    let mut builder = CircuitBuilder::<L, D>::new();
+    SubchainCircuit::<MAX_HEADER_SIZE, RANGE_SIZE>::define(&mut builder);
+    builder.build()
+}
+
+/// Aggregates the header range (trusted_block, target_block] of up to RANGE_SIZE * NUM_RANGES
+/// headers into a single proof. The range is split into NUM_RANGES consecutive ranges of RANGE_SIZE
+/// headers, except the last range which ends at target_block, and a proof of SubchainCircuit is
+/// verified for each range. The state and data Merkle roots of the ranges are combined into the
+/// roots of one tree with RANGE_SIZE * NUM_RANGES leaves.
+///
+/// The input and output are those of HeaderRangeCircuit. Only target_block must have a
+/// justification, by the authority set read with the request, as the headers of the ranges link to
+/// it. The last range must contain at least 1 header, so target_block is more than
+/// RANGE_SIZE * (NUM_RANGES - 1) blocks after trusted_block.
+#[derive(Clone, Debug)]
+pub struct HeaderRangeAggregationCircuit<
+    const MAX_AUTHORITY_SET_SIZE: usize,
+    const MAX_HEADER_SIZE: usize,
+    const RANGE_SIZE: usize,
+    const NUM_RANGES: usize,
+> {}
+
+impl<
+        const MAX_AUTHORITY_SET_SIZE: usize,
+        const MAX_HEADER_SIZE: usize,
+        const RANGE_SIZE: usize,
+        const NUM_RANGES: usize,
+    > Circuit
+    for HeaderRangeAggregationCircuit<
+        MAX_AUTHORITY_SET_SIZE,
+        MAX_HEADER_SIZE,
+        RANGE_SIZE,
+        NUM_RANGES,
+    >
+{
+    fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+    where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        // The roots of each range are only subtree roots of the combined tree if the ranges and
+        // the number of ranges are powers of 2.
+        assert!(
+            RANGE_SIZE.is_power_of_two() && RANGE_SIZE >= HEADERS_PER_MAP,
+            "RANGE_SIZE must be a power of 2 that is at least HEADERS_PER_MAP."
+        );
+        assert!(
+            NUM_RANGES.is_power_of_two(),
+            "NUM_RANGES must be a power of 2."
+        );
+
+        let child = build_child_circuit::<L, D, MAX_HEADER_SIZE, RANGE_SIZE>();
+        // The public inputs of a subchain proof are the bits of its EVM input, followed by the bits
+        // of its EVM output.
+        let num_input_targets = 8 * SUBCHAIN_INPUT_LENGTH;
+        assert_eq!(
+            child.data.common.num_public_inputs,
+            num_input_targets + 8 * SUBCHAIN_OUTPUT_LENGTH,
+            "Unexpected number of public inputs of the subchain circuit."
+        );
+        let child_verifier_data = builder
+            .api
+            .constant_verifier_data(&child.data.verifier_only);
+
+        // Read the on-chain inputs, encoded as evm::HeaderRangeRequest.
+        let trusted_block = builder.evm_read::<U32Variable>();
+        let trusted_header_hash = builder.evm_read::<Bytes32Variable>();
+        let authority_set_id = builder.evm_read::<U64Variable>();
+        let authority_set_hash = builder.evm_read::<Bytes32Variable>();
+        let target_block = builder.evm_read::<U32Variable>();
+
+        // The last range must contain at least 1 and at most RANGE_SIZE headers.
+        let true_const = builder._true();
+        let range_size = builder.constant::<U32Variable>(RANGE_SIZE as u32);
+        let full_ranges_size =
+            builder.constant::<U32Variable>((RANGE_SIZE * (NUM_RANGES - 1)) as u32);
+        let last_range_trusted_block = builder.add(trusted_block, full_ranges_size);
+        let last_range_max_target_block = builder.add(last_range_trusted_block, range_size);
+        let is_last_range_nonempty = builder.lt(last_range_trusted_block, target_block);
+        builder.assert_is_equal(is_last_range_nonempty, true_const);
+        let is_last_range_in_bounds = builder.lte(target_block, last_range_max_target_block);
+        builder.assert_is_equal(is_last_range_in_bounds, true_const);
+
+        let mut range_trusted_block = trusted_block;
+        let mut range_trusted_header_hash = trusted_header_hash;
+        let mut state_merkle_roots = Vec::new();
+        let mut data_merkle_roots = Vec::new();
+        let mut child_inputs = Vec::new();
+        let mut child_proofs = Vec::new();
+        for i in 0..NUM_RANGES {
+            // All ranges except the last are full.
+            let range_target_block = if i < NUM_RANGES - 1 {
+                builder.add(range_trusted_block, range_size)
+            } else {
+                target_block
+            };
+
+            // The EVM input of the range's subchain proof. Note: The range is linked to the
+            // previous range's target header.
+            let mut input_bytes = Vec::new();
+            input_bytes.extend(range_trusted_block.encode(builder));
+            input_bytes.extend(range_trusted_header_hash.encode(builder));
+            input_bytes.extend(range_target_block.encode(builder));
+
+            // Verify the subchain proof of the range, and that its input is the range.
+            let proof = builder.api.add_virtual_proof_with_pis(&child.data.common);
+            builder
+                .api
+                .verify_proof::<L::Config>(&proof, &child_verifier_data, &child.data.common);
+            let input_targets = input_bytes
+                .iter()
+                .flat_map(|byte| byte.targets())
+                .collect::<Vec<_>>();
+            for (input_target, public_input) in input_targets.iter().zip(&proof.public_inputs) {
+                builder.api.connect(*input_target, *public_input);
+            }
+
+            // Note: The outputs of a verified subchain proof are trusted.
+            let output_bytes = proof.public_inputs[num_input_targets..]
+                .chunks(8)
+                .map(ByteVariable::from_targets)
+                .collect::<Vec<_>>();
+            let target_header_hash = Bytes32Variable::decode(builder, &output_bytes[0..32]);
+            state_merkle_roots.push(Bytes32Variable::decode(builder, &output_bytes[32..64]));
+            data_merkle_roots.push(Bytes32Variable::decode(builder, &output_bytes[64..96]));
+
+            child_inputs.push(input_bytes);
+            child_proofs.push(proof);
+
+            range_trusted_block = range_target_block;
+            range_trusted_header_hash = target_header_hash;
+        }
+
+        // Prove the subchain of each range when generating the witness.
+        builder.api.add_simple_generator(SubchainProofGenerator::<
+            L,
+            MAX_HEADER_SIZE,
+            RANGE_SIZE,
+            D,
+        > {
+            inputs: child_inputs,
+            proofs: child_proofs,
+            _phantom: PhantomData,
+        });
+
+        // Note: The target header hash is trusted at this point. Verify that there is a valid
+        // justification on it by the authority set at authority_set_id.
+        builder.verify_simple_justification::<MAX_AUTHORITY_SET_SIZE>(
+            target_block,
+            range_trusted_header_hash,
+            authority_set_id,
+            authority_set_hash,
+        );
+
+        // Combine the roots of the ranges into the roots of the tree over all of the ranges.
+        while state_merkle_roots.len() > 1 {
+            state_merkle_roots = state_merkle_roots
+                .chunks(2)
+                .map(|pair| {
+                    let mut root_bytes = pair[0].as_bytes().to_vec();
+                    root_bytes.extend(&pair[1].as_bytes());
+                    builder.sha256(&root_bytes)
+                })
+                .collect();
+            data_merkle_roots = data_merkle_roots
+                .chunks(2)
+                .map(|pair| {
+                    let mut root_bytes = pair[0].as_bytes().to_vec();
+                    root_bytes.extend(&pair[1].as_bytes());
+                    builder.sha256(&root_bytes)
+                })
+                .collect();
+        }
+
+        builder.evm_write::<Bytes32Variable>(range_trusted_header_hash);
+        builder.evm_write::<Bytes32Variable>(state_merkle_roots[0]);
+        builder.evm_write::<Bytes32Variable>(data_merkle_roots[0]);
+    }
+
+    fn register_generators<L: PlonkParameters<D>, const D: usize>(
+        generator_registry: &mut plonky2x::prelude::HintRegistry<L, D>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        generator_registry.register_async_hint::<HintSimpleJustification<MAX_AUTHORITY_SET_SIZE>>();
+
+        // Note: The generators of the subchain circuit are not registered, as
+        // SubchainProofGenerator builds the circuit instead of loading it.
+        let id = SubchainProofGenerator::<L, MAX_HEADER_SIZE, RANGE_SIZE, D>::id();
+        generator_registry
+            .register_simple::<SubchainProofGenerator<L, MAX_HEADER_SIZE, RANGE_SIZE, D>>(id);
+    }
+}
+
+/// Proves the subchain of each range of HeaderRangeAggregationCircuit with the subchain circuit,
+/// and sets the range's proof target.
+#[derive(Debug, Clone)]
+pub struct SubchainProofGenerator<
+    L: PlonkParameters<D>,
+    const MAX_HEADER_SIZE: usize,
+    const RANGE_SIZE: usize,
+    const D: usize,
+> {
+    /// The EVM input of each range's subchain proof.
+    inputs: Vec<Vec<ByteVariable>>,
+    proofs: Vec<ProofWithPublicInputsTarget<D>>,
+    _phantom: PhantomData<L>,
+}
+
+impl<
+        L: PlonkParameters<D>,
+        const MAX_HEADER_SIZE: usize,
+        const RANGE_SIZE: usize,
+        const D: usize,
+    > SubchainProofGenerator<L, MAX_HEADER_SIZE, RANGE_SIZE, D>
+{
+    pub fn id() -> String {
+        format!(
+            "SubchainProofGenerator<{}, {}>",
+            MAX_HEADER_SIZE, RANGE_SIZE
+        )
+    }
+}
+
+impl<
+        L: PlonkParameters<D>,
+        const MAX_HEADER_SIZE: usize,
+        const RANGE_SIZE: usize,
+        const D: usize,
+    > SimpleGenerator<L::Field, D> for SubchainProofGenerator<L, MAX_HEADER_SIZE, RANGE_SIZE, D>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.inputs
+            .iter()
+            .flatten()
+            .flat_map(|byte| byte.targets())
+            .collect()
+    }
+
+    fn run_once(
+        &self,
+        witness: &PartitionWitness<L::Field>,
+        out_buffer: &mut GeneratedValues<L::Field>,
+    ) {
+        let child = build_child_circuit::<L, D, MAX_HEADER_SIZE, RANGE_SIZE>();
+        for (input, proof_target) in self.inputs.iter().zip(&self.proofs) {
+            let input_bytes = input
+                .iter()
+                .map(|byte| byte.get(witness))
+                .collect::<Vec<_>>();
+            log::debug!(
+                "Proving the subchain from block {} to block {}",
+                u32::from_be_bytes(input_bytes[0..4].try_into().unwrap()),
+                u32::from_be_bytes(input_bytes[36..40].try_into().unwrap())
+            );
+            let (proof, _) = child.prove(&PublicInput::Bytes(input_bytes));
+            out_buffer.set_proof_with_pis_target(proof_target, &proof);
+        }
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _common_data: &CommonCircuitData<L::Field, D>,
+    ) -> IoResult<()> {
+        dst.write_usize(self.inputs.len())?;
+        for (input, proof) in self.inputs.iter().zip(&self.proofs) {
+            let input_targets = input
+                .iter()
+                .flat_map(|byte| byte.targets())
+                .collect::<Vec<_>>();
+            dst.write_target_vec(&input_targets)?;
+            dst.write_target_proof_with_public_inputs(proof)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _common_data: &CommonCircuitData<L::Field, D>,
+    ) -> IoResult<Self> {
+        let num_ranges = src.read_usize()?;
+        let mut inputs = Vec::with_capacity(num_ranges);
+        let mut proofs = Vec::with_capacity(num_ranges);
+        for _ in 0..num_ranges {
+            let input_targets = src.read_target_vec()?;
+            inputs.push(
+                input_targets
+                    .chunks(8)
+                    .map(ByteVariable::from_targets)
+                    .collect(),
+            );
+            proofs.push(src.read_target_proof_with_public_inputs()?);
+        }
+        Ok(Self {
+            inputs,
+            proofs,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use plonky2x::prelude::{DefaultBuilder, GateRegistry, HintRegistry};
+
+    use super::*;
+    use crate::consts::MAX_HEADER_SIZE;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_circuit_function_header_range_aggregation() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        const NUM_AUTHORITIES: usize = 4;
+        const RANGE_SIZE: usize = 16;
+        const NUM_RANGES: usize = 2;
+
+        let mut builder = DefaultBuilder::new();
+
+        log::debug!("Defining circuit");
+        HeaderRangeAggregationCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, RANGE_SIZE, NUM_RANGES>::define(
+            &mut builder,
+        );
+        let circuit = builder.build();
+        log::debug!("Done building circuit");
+
+        let mut hint_registry = HintRegistry::new();
+        let mut gate_registry = GateRegistry::new();
+        HeaderRangeAggregationCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, RANGE_SIZE, NUM_RANGES>::register_generators(
+            &mut hint_registry,
+        );
+        HeaderRangeAggregationCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, RANGE_SIZE, NUM_RANGES>::register_gates(
+            &mut gate_registry,
+        );
+
+        circuit.test_serializers(&gate_registry, &hint_registry);
+    }
+}
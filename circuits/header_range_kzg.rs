@@ -0,0 +1,224 @@
+use plonky2x::backend::circuit::Circuit;
+use plonky2x::frontend::mapreduce::generator::MapReduceGenerator;
+use plonky2x::frontend::uint::uint64::U64Variable;
+use plonky2x::frontend::vars::U32Variable;
+use plonky2x::prelude::{Bytes32Variable, CircuitBuilder, PlonkParameters};
+
+use crate::builder::extension::{HeaderExtensionFetcherHint, HeaderExtensionMethods};
+use crate::builder::header::HeaderMethods;
+use crate::builder::justification::{GrandpaJustificationVerifier, HintSimpleJustification};
+use crate::builder::subchain_verification::{
+    HeaderRangeFetcherHint, MapReduceSubchainVariable, SubChainVerifier, SubchainVerificationCtx,
+};
+use crate::consts::HEADERS_PER_MAP;
+
+/// Header range circuit which also commits to the KZG commitments of the target header. Outputs the
+/// header range outputs, followed by the Merkle root over the target header's KZG commitments and the
+/// dimensions of its data matrix, which EVM-side data availability sampling verifiers can be
+/// anchored to.
+///
+/// Note: Only the target header's KZG commitments are committed, not those of the other headers in
+/// the range. To sample the data of a block, prove a header range that ends at the block.
+#[derive(Clone, Debug)]
+pub struct HeaderRangeKzgCircuit<
+    const MAX_AUTHORITY_SET_SIZE: usize,
+    const MAX_HEADER_SIZE: usize,
+    const MAX_NUM_HEADERS: usize,
+> {}
+
+impl<
+        const MAX_AUTHORITY_SET_SIZE: usize,
+        const MAX_HEADER_SIZE: usize,
+        const MAX_NUM_HEADERS: usize,
+    > Circuit for HeaderRangeKzgCircuit<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, MAX_NUM_HEADERS>
+{
+    fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+    where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        // Read the on-chain inputs, encoded as evm::HeaderRangeRequest.
+        let trusted_block = builder.evm_read::<U32Variable>();
+        let trusted_header_hash = builder.evm_read::<Bytes32Variable>();
+        let authority_set_id = builder.evm_read::<U64Variable>();
+        let authority_set_hash = builder.evm_read::<Bytes32Variable>();
+        let target_block = builder.evm_read::<U32Variable>();
+
+        // Get the target_header_hash, state_root, and data_root over the range [trusted_block + 1, target_block].
+        let subchain_output = builder.verify_subchain::<Self, MAX_HEADER_SIZE, MAX_NUM_HEADERS>(
+            trusted_block,
+            trusted_header_hash,
+            target_block,
+        );
+
+        // Note: target_header_hash and target_block are trusted at this point.
+        // Verify that there is a valid justification on target_header_hash by the authority set at authority_set_id.
+        builder.verify_simple_justification::<MAX_AUTHORITY_SET_SIZE>(
+            target_block,
+            subchain_output.target_header_hash,
+            authority_set_id,
+            authority_set_hash,
+        );
+
+        // Fetch the target header and constrain it to the verified target_header_hash.
+        let target_header = builder.fetch_header_extension::<MAX_HEADER_SIZE>(target_block);
+        let target_header_hash = builder.hash_encoded_header::<MAX_HEADER_SIZE>(&target_header);
+        builder.assert_is_equal(target_header_hash, subchain_output.target_header_hash);
+
+        let extension =
+            builder.decode_header_extension::<MAX_HEADER_SIZE>(&target_header, &target_header_hash);
+        let kzg_commitments_root = builder.compute_kzg_commitments_root(&extension);
+
+        builder.evm_write::<Bytes32Variable>(subchain_output.target_header_hash);
+        builder.evm_write::<Bytes32Variable>(subchain_output.state_root_merkle_root);
+        builder.evm_write::<Bytes32Variable>(subchain_output.data_root_merkle_root);
+        builder.evm_write::<Bytes32Variable>(kzg_commitments_root);
+        builder.evm_write::<U32Variable>(extension.rows);
+        builder.evm_write::<U32Variable>(extension.cols);
+    }
+
+    fn register_generators<L: PlonkParameters<D>, const D: usize>(
+        generator_registry: &mut plonky2x::prelude::HintRegistry<L, D>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        generator_registry
+            .register_async_hint::<HeaderRangeFetcherHint<MAX_HEADER_SIZE, HEADERS_PER_MAP>>();
+        generator_registry.register_async_hint::<HintSimpleJustification<MAX_AUTHORITY_SET_SIZE>>();
+        generator_registry.register_async_hint::<HeaderExtensionFetcherHint<MAX_HEADER_SIZE>>();
+
+        let mr_id = MapReduceGenerator::<
+            L,
+            SubchainVerificationCtx,
+            U32Variable,
+            MapReduceSubchainVariable,
+            Self,
+            HEADERS_PER_MAP,
+            D,
+        >::id();
+        generator_registry.register_simple::<MapReduceGenerator<
+            L,
+            SubchainVerificationCtx,
+            U32Variable,
+            MapReduceSubchainVariable,
+            Self,
+            HEADERS_PER_MAP,
+            D,
+        >>(mr_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use codec::Encode;
+    use plonky2x::prelude::{DefaultBuilder, GateRegistry, HintRegistry};
+
+    use super::*;
+    use crate::consts::MAX_HEADER_SIZE;
+    use crate::input::RpcDataFetcher;
+    use crate::layout::CURRENT_HEADER_LAYOUT;
+    use crate::witness::WitnessBundle;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_circuit_function_header_range_kzg() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        const NUM_AUTHORITIES: usize = 4;
+        const NUM_HEADERS: usize = 32;
+
+        let mut builder = DefaultBuilder::new();
+
+        log::debug!("Defining circuit");
+        HeaderRangeKzgCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, NUM_HEADERS>::define(
+            &mut builder,
+        );
+        let circuit = builder.build();
+        log::debug!("Done building circuit");
+
+        let mut hint_registry = HintRegistry::new();
+        let mut gate_registry = GateRegistry::new();
+        HeaderRangeKzgCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, NUM_HEADERS>::register_generators(
+            &mut hint_registry,
+        );
+        HeaderRangeKzgCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, NUM_HEADERS>::register_gates(
+            &mut gate_registry,
+        );
+
+        circuit.test_serializers(&gate_registry, &hint_registry);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_header_range_kzg() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        const NUM_AUTHORITIES: usize = 5;
+        const NUM_HEADERS: usize = 32;
+        let mut builder = DefaultBuilder::new();
+
+        log::debug!("Defining circuit");
+        HeaderRangeKzgCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, NUM_HEADERS>::define(
+            &mut builder,
+        );
+
+        log::debug!("Building circuit");
+        let circuit = builder.build();
+        log::debug!("Done building circuit");
+
+        let mut input = circuit.input();
+
+        let trusted_header = "087ee7c739e35c46b2ac422cf683ecf6d4cb4571610efe6a5dff6f5b3d5818c9"
+            .parse()
+            .unwrap();
+        let trusted_block = 4310u32;
+        // Step to an epoch end block, so it's not reliant on a stored justification.
+        let target_block = 4321u32;
+        let authority_set_id = 0u64;
+        let authority_set_hash = "54eb3049b763a6a84c391d53ffb5e93515a171b2dbaaa6a900ec09e3b6bb8dfb"
+            .parse()
+            .unwrap();
+
+        input.evm_write::<U32Variable>(trusted_block);
+        input.evm_write::<Bytes32Variable>(trusted_header);
+        input.evm_write::<U64Variable>(authority_set_id);
+        input.evm_write::<Bytes32Variable>(authority_set_hash);
+        input.evm_write::<U32Variable>(target_block);
+
+        log::debug!("Generating proof");
+        let (proof, mut output) = circuit.prove(&input);
+        log::debug!("Done generating proof");
+
+        circuit.verify(&proof, &input, &output);
+        let _target_header = output.evm_read::<Bytes32Variable>();
+        let _state_root_merkle_root = output.evm_read::<Bytes32Variable>();
+        let _data_root_merkle_root = output.evm_read::<Bytes32Variable>();
+        let kzg_commitments_root = output.evm_read::<Bytes32Variable>();
+        let rows = output.evm_read::<U32Variable>();
+        let cols = output.evm_read::<U32Variable>();
+
+        // Check the outputs against the natively decoded header extension.
+        let header = match WitnessBundle::from_env() {
+            Some(bundle) => bundle.get_header(target_block).unwrap(),
+            None => {
+                let rt = tokio::runtime::Runtime::new().unwrap();
+                rt.block_on(async {
+                    let fetcher = RpcDataFetcher::new().await;
+                    fetcher.get_header(target_block).await.encode()
+                })
+            }
+        };
+        let extension = CURRENT_HEADER_LAYOUT.decode_extension(&header).unwrap();
+        assert_eq!(
+            kzg_commitments_root,
+            extension.kzg_commitments_root().unwrap()
+        );
+        assert_eq!(rows, extension.rows as u32);
+        assert_eq!(cols, extension.cols as u32);
+    }
+}
@@ -0,0 +1,201 @@
+use plonky2x::backend::circuit::Circuit;
+use plonky2x::frontend::mapreduce::generator::MapReduceGenerator;
+use plonky2x::frontend::uint::uint64::U64Variable;
+use plonky2x::frontend::vars::U32Variable;
+use plonky2x::prelude::{Bytes32Variable, CircuitBuilder, PlonkParameters};
+
+use crate::authority_set::AuthoritySetCommitmentMode;
+use crate::builder::justification::{GrandpaJustificationVerifier, HintSubsetJustification};
+use crate::builder::subchain_verification::{
+    HeaderRangeFetcherHint, MapReduceSubchainVariable, SubChainVerifier, SubchainVerificationCtx,
+};
+use crate::consts::HEADERS_PER_MAP;
+
+/// Header range circuit which verifies the justification on the target block from the signatures of
+/// NUM_SIGNERS authorities (see `verify_simple_justification_subset`) instead of every authority.
+/// Takes the same inputs and has the same outputs as `HeaderRangeCircuit`, so the contract can use
+/// it as the header range function.
+#[derive(Clone, Debug)]
+pub struct HeaderRangeSubsetCircuit<
+    const MAX_AUTHORITY_SET_SIZE: usize,
+    const MAX_HEADER_SIZE: usize,
+    const MAX_NUM_HEADERS: usize,
+    const NUM_SIGNERS: usize,
+    const AUTHORITY_SET_COMMITMENT_MODE: u8 = { AuthoritySetCommitmentMode::CHAINED_SHA256 },
+> {}
+
+impl<
+        const MAX_AUTHORITY_SET_SIZE: usize,
+        const MAX_HEADER_SIZE: usize,
+        const MAX_NUM_HEADERS: usize,
+        const NUM_SIGNERS: usize,
+        const AUTHORITY_SET_COMMITMENT_MODE: u8,
+    > Circuit
+    for HeaderRangeSubsetCircuit<
+        MAX_AUTHORITY_SET_SIZE,
+        MAX_HEADER_SIZE,
+        MAX_NUM_HEADERS,
+        NUM_SIGNERS,
+        AUTHORITY_SET_COMMITMENT_MODE,
+    >
+{
+    fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+    where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        // Read the on-chain inputs, encoded as evm::HeaderRangeRequest.
+        let trusted_block = builder.evm_read::<U32Variable>();
+        let trusted_header_hash = builder.evm_read::<Bytes32Variable>();
+        let authority_set_id = builder.evm_read::<U64Variable>();
+        let authority_set_hash = builder.evm_read::<Bytes32Variable>();
+        let target_block = builder.evm_read::<U32Variable>();
+
+        // Get the target_header_hash, state_root, and data_root over the range [trusted_block + 1, target_block].
+        let subchain_output = builder.verify_subchain::<Self, MAX_HEADER_SIZE, MAX_NUM_HEADERS>(
+            trusted_block,
+            trusted_header_hash,
+            target_block,
+        );
+
+        // Note: target_header_hash and target_block are trusted at this point.
+        // Verify that there is a valid justification on target_header_hash by the authority set at authority_set_id.
+        builder.verify_simple_justification_subset::<MAX_AUTHORITY_SET_SIZE, NUM_SIGNERS>(
+            target_block,
+            subchain_output.target_header_hash,
+            authority_set_id,
+            authority_set_hash,
+            AuthoritySetCommitmentMode::from_id(AUTHORITY_SET_COMMITMENT_MODE)
+                .expect("Unknown authority set commitment mode"),
+        );
+
+        builder.evm_write::<Bytes32Variable>(subchain_output.target_header_hash);
+        builder.evm_write::<Bytes32Variable>(subchain_output.state_root_merkle_root);
+        builder.evm_write::<Bytes32Variable>(subchain_output.data_root_merkle_root);
+    }
+
+    fn register_generators<L: PlonkParameters<D>, const D: usize>(
+        generator_registry: &mut plonky2x::prelude::HintRegistry<L, D>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        generator_registry
+            .register_async_hint::<HeaderRangeFetcherHint<MAX_HEADER_SIZE, HEADERS_PER_MAP>>();
+        generator_registry
+            .register_async_hint::<HintSubsetJustification<MAX_AUTHORITY_SET_SIZE, NUM_SIGNERS>>();
+
+        let mr_id = MapReduceGenerator::<
+            L,
+            SubchainVerificationCtx,
+            U32Variable,
+            MapReduceSubchainVariable,
+            Self,
+            HEADERS_PER_MAP,
+            D,
+        >::id();
+        generator_registry.register_simple::<MapReduceGenerator<
+            L,
+            SubchainVerificationCtx,
+            U32Variable,
+            MapReduceSubchainVariable,
+            Self,
+            HEADERS_PER_MAP,
+            D,
+        >>(mr_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use ethers::types::H256;
+    use plonky2x::prelude::DefaultBuilder;
+
+    use super::*;
+    use crate::builder::justification::get_num_justification_signers;
+    use crate::consts::MAX_HEADER_SIZE;
+    use crate::header_range::HeaderRangeCircuit;
+
+    // Prove a header range circuit from the trusted block to the target block, and return its
+    // target header, state root merkle root and data root merkle root.
+    fn prove_header_range<C: Circuit>(
+        trusted_block: u32,
+        trusted_header: H256,
+        authority_set_id: u64,
+        authority_set_hash: H256,
+        target_block: u32,
+    ) -> (H256, H256, H256) {
+        let mut builder = DefaultBuilder::new();
+
+        log::debug!("Defining circuit");
+        C::define(&mut builder);
+
+        log::debug!("Building circuit");
+        let circuit = builder.build();
+        log::debug!("Done building circuit");
+
+        let mut input = circuit.input();
+        input.evm_write::<U32Variable>(trusted_block);
+        input.evm_write::<Bytes32Variable>(trusted_header);
+        input.evm_write::<U64Variable>(authority_set_id);
+        input.evm_write::<Bytes32Variable>(authority_set_hash);
+        input.evm_write::<U32Variable>(target_block);
+
+        log::debug!("Generating proof");
+        let (proof, mut output) = circuit.prove(&input);
+        log::debug!("Done generating proof");
+
+        circuit.verify(&proof, &input, &output);
+        (
+            output.evm_read::<Bytes32Variable>(),
+            output.evm_read::<Bytes32Variable>(),
+            output.evm_read::<Bytes32Variable>(),
+        )
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_header_range_subset_small() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        const NUM_AUTHORITIES: usize = 5;
+        const NUM_SIGNERS: usize = get_num_justification_signers(NUM_AUTHORITIES);
+        const NUM_HEADERS: usize = 32;
+
+        let trusted_header = "087ee7c739e35c46b2ac422cf683ecf6d4cb4571610efe6a5dff6f5b3d5818c9"
+            .parse()
+            .unwrap();
+        let trusted_block = 4310u32;
+        // Step to an epoch end block, so it's not reliant on a stored justification.
+        let target_block = 4321u32;
+        let authority_set_id = 0u64;
+        let authority_set_hash = "54eb3049b763a6a84c391d53ffb5e93515a171b2dbaaa6a900ec09e3b6bb8dfb"
+            .parse()
+            .unwrap();
+
+        let subset_outputs = prove_header_range::<
+            HeaderRangeSubsetCircuit<NUM_AUTHORITIES, MAX_HEADER_SIZE, NUM_HEADERS, NUM_SIGNERS>,
+        >(
+            trusted_block,
+            trusted_header,
+            authority_set_id,
+            authority_set_hash,
+            target_block,
+        );
+        let outputs = prove_header_range::<
+            HeaderRangeCircuit<NUM_AUTHORITIES, MAX_HEADER_SIZE, NUM_HEADERS>,
+        >(
+            trusted_block,
+            trusted_header,
+            authority_set_id,
+            authority_set_hash,
+            target_block,
+        );
+
+        // The subset of signers justifies the same target header, with the same commitments.
+        assert_eq!(subset_outputs, outputs);
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{ensure, Error};
+use serde::{Deserialize, Serialize};
+use sp_core::keccak_256;
+
+use super::{
+    build_data_root_proof, data_root_from_sub_roots, verify_data_proof, DataRootInclusionProof,
+};
+use crate::input::types::{DataProof, ProofResponse};
+use crate::input::{DataCommitmentRange, RpcDataFetcher};
+
+/// Proof that a data submission to Avail is included in a data root commitment stored in VectorX.
+/// The submission's leaf is proven against the block's blob root, the blob root and bridge root are
+/// hashed into the block's data root, and the data root is proven against the VectorX data root
+/// commitment.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSubmissionProof {
+    pub tx_index: u32,
+    /// Proof of the submission leaf against the blob root.
+    pub data_proof: DataProof,
+    /// Proof of the block's data root against the VectorX data root commitment.
+    pub data_root_proof: DataRootInclusionProof,
+}
+
+impl DataSubmissionProof {
+    /// Verify the submission is included in the data root commitment of data_root_proof.
+    pub fn verify(&self) -> Result<(), Error> {
+        let roots = &self.data_proof.roots;
+
+        ensure!(
+            verify_data_proof(roots.blob_root, &self.data_proof),
+            "Data submission is not included in the blob root"
+        );
+        ensure!(
+            data_root_from_sub_roots(roots.blob_root, roots.bridge_root) == roots.data_root,
+            "Blob root and bridge root do not hash to the data root"
+        );
+        ensure!(
+            roots.data_root == self.data_root_proof.data_root,
+            "Data root does not match the data root of block {}",
+            self.data_root_proof.block_number
+        );
+        ensure!(
+            self.data_root_proof.verify(),
+            "Data root is not included in the data root commitment"
+        );
+        Ok(())
+    }
+
+    /// Check that data is the submitted data, whose keccak256 hash is the proven leaf.
+    pub fn verify_data(&self, data: &[u8]) -> bool {
+        keccak_256(data) == self.data_proof.leaf.0
+    }
+}
+
+/// Build the proof of the data submission at tx_index in block_number against the data root
+/// commitment of range.
+pub async fn build_data_submission_proof(
+    fetcher: &mut RpcDataFetcher,
+    range: &DataCommitmentRange,
+    block_number: u32,
+    tx_index: u32,
+) -> Result<DataSubmissionProof, Error> {
+    let response = fetcher.get_data_proof(block_number, tx_index).await?;
+    ensure!(
+        response.message.is_none(),
+        "Transaction {} in block {} is a bridge message, not a data submission",
+        tx_index,
+        block_number
+    );
+
+    // Verify the data proof against the data root in the block's header before fetching the range.
+    let header = fetcher.get_header(block_number).await;
+    let roots = &response.data_proof.roots;
+    ensure!(
+        header.data_root().0 == roots.data_root.0,
+        "Data root of the data proof does not match the header of block {}",
+        block_number
+    );
+    ensure!(
+        verify_data_proof(roots.blob_root, &response.data_proof),
+        "Data submission is not included in the blob root of block {}",
+        block_number
+    );
+
+    let data_root_proof = build_data_root_proof(fetcher, range, block_number).await?;
+
+    data_submission_proof(response, tx_index, data_root_proof)
+}
+
+/// The proof of the data submission in response, the data proof of the transaction at tx_index in
+/// the block of data_root_proof.
+pub fn data_submission_proof(
+    response: ProofResponse,
+    tx_index: u32,
+    data_root_proof: DataRootInclusionProof,
+) -> Result<DataSubmissionProof, Error> {
+    ensure!(
+        response.message.is_none(),
+        "Transaction {} in block {} is a bridge message, not a data submission",
+        tx_index,
+        data_root_proof.block_number
+    );
+
+    let proof = DataSubmissionProof {
+        tx_index,
+        data_proof: response.data_proof,
+        data_root_proof,
+    };
+    proof.verify()?;
+
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::*;
+    use crate::inclusion::tests::keccak_root_and_proof;
+    use crate::input::types::TxDataRoots;
+
+    // A proof of the second of three submissions in block 103, against the data root commitment
+    // of the range (100, 104].
+    fn proof(data: &[&[u8]]) -> DataSubmissionProof {
+        let leaves = data
+            .iter()
+            .map(|data| H256::from(keccak_256(data)))
+            .collect::<Vec<_>>();
+        let (blob_root, blob_proof) = keccak_root_and_proof(&leaves, 1);
+        let bridge_root = H256::from([7u8; 32]);
+        let data_root = data_root_from_sub_roots(blob_root, bridge_root);
+
+        let data_roots = (101..=104u8)
+            .map(|block_number| {
+                if block_number == 103 {
+                    data_root.0.to_vec()
+                } else {
+                    vec![block_number; 32]
+                }
+            })
+            .collect::<Vec<_>>();
+        let data_commitment = RpcDataFetcher::get_merkle_root(data_roots.clone());
+        let branch = RpcDataFetcher::get_merkle_branch(data_roots, 2);
+
+        DataSubmissionProof {
+            tx_index: 1,
+            data_proof: DataProof {
+                roots: TxDataRoots {
+                    data_root,
+                    blob_root,
+                    bridge_root,
+                },
+                proof: blob_proof,
+                number_of_leaves: leaves.len() as u32,
+                leaf_index: 1,
+                leaf: leaves[1],
+            },
+            data_root_proof: DataRootInclusionProof {
+                block_number: 103,
+                data_root,
+                range_start_block: 100,
+                range_end_block: 104,
+                data_commitment: H256::from_slice(&data_commitment),
+                index: 2,
+                branch: branch.iter().map(|node| H256::from_slice(node)).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let data: [&[u8]; 3] = [b"first", b"second", b"third"];
+        let proof = proof(&data);
+        proof.verify().unwrap();
+        assert!(proof.verify_data(b"second"));
+        assert!(!proof.verify_data(b"first"));
+
+        // A leaf that is not in the blob root.
+        let mut tampered = proof.clone();
+        tampered.data_proof.leaf = H256::from(keccak_256(b"forged"));
+        assert!(tampered.verify().is_err());
+
+        // A tampered branch of the blob root proof.
+        let mut tampered = proof.clone();
+        tampered.data_proof.proof[0].0[0] ^= 1;
+        assert!(tampered.verify().is_err());
+
+        // A bridge root that does not hash to the data root.
+        let mut tampered = proof.clone();
+        tampered.data_proof.roots.bridge_root = H256::zero();
+        assert!(tampered.verify().is_err());
+
+        // A data root proof of a different data root.
+        let mut tampered = proof.clone();
+        tampered.data_root_proof.data_root = H256::from([103u8; 32]);
+        assert!(tampered.verify().is_err());
+
+        // A tampered branch of the data root proof.
+        let mut tampered = proof.clone();
+        tampered.data_root_proof.branch[1].0[31] ^= 1;
+        assert!(tampered.verify().is_err());
+    }
+}
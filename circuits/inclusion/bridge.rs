@@ -0,0 +1,160 @@
+use alloy_primitives::{FixedBytes, U256};
+use alloy_sol_types::{sol, SolType};
+use anyhow::{anyhow, ensure, Error};
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use sp_core::keccak_256;
+
+use super::{
+    build_data_root_proof, data_root_from_sub_roots, verify_data_proof, DataRootInclusionProof,
+};
+use crate::input::types::{AddressedMessage, DataProof, Message, ProofResponse};
+use crate::input::{DataCommitmentRange, RpcDataFetcher};
+
+type BridgeMessageTuple = sol! { tuple(bytes1, bytes32, bytes32, uint32, uint32, bytes, uint64) };
+
+type FungibleTokenTuple = sol! { tuple(bytes32, uint256) };
+
+// Message type flags of the Avail bridge contract.
+const ARBITRARY_MESSAGE_TYPE: u8 = 0x01;
+const FUNGIBLE_TOKEN_MESSAGE_TYPE: u8 = 0x02;
+
+/// The leaf of a bridge message in the bridge root, which is the keccak256 hash of the ABI encoded
+/// message. Matches keccak256(abi.encode(message)) in the Avail bridge contract.
+pub fn message_leaf(message: &AddressedMessage) -> H256 {
+    let (message_type, data) = match &message.message {
+        Message::ArbitraryMessage(data) => (ARBITRARY_MESSAGE_TYPE, data.0.clone()),
+        Message::FungibleToken { asset_id, amount } => (
+            FUNGIBLE_TOKEN_MESSAGE_TYPE,
+            FungibleTokenTuple::abi_encode(&(FixedBytes(asset_id.0), U256::from(*amount))),
+        ),
+    };
+
+    let encoded = BridgeMessageTuple::abi_encode(&(
+        FixedBytes([message_type]),
+        FixedBytes(message.from.0),
+        FixedBytes(message.to.0),
+        message.origin_domain,
+        message.destination_domain,
+        data.into(),
+        message.id,
+    ));
+    H256::from(keccak_256(&encoded))
+}
+
+/// Proof that a bridge message sent from Avail is included in a data root commitment stored in
+/// VectorX. The message leaf is proven against the block's bridge root, the bridge root and blob
+/// root are hashed into the block's data root, and the data root is proven against the VectorX
+/// data root commitment.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeMessageProof {
+    pub message: AddressedMessage,
+    pub tx_index: u32,
+    /// Proof of the message leaf against the bridge root.
+    pub data_proof: DataProof,
+    /// Proof of the block's data root against the VectorX data root commitment.
+    pub data_root_proof: DataRootInclusionProof,
+}
+
+impl BridgeMessageProof {
+    /// Verify the message is included in the data root commitment of data_root_proof.
+    pub fn verify(&self) -> Result<(), Error> {
+        let roots = &self.data_proof.roots;
+
+        ensure!(
+            message_leaf(&self.message) == self.data_proof.leaf,
+            "Message does not match the proven leaf"
+        );
+        ensure!(
+            verify_data_proof(roots.bridge_root, &self.data_proof),
+            "Message is not included in the bridge root"
+        );
+        ensure!(
+            data_root_from_sub_roots(roots.blob_root, roots.bridge_root) == roots.data_root,
+            "Blob root and bridge root do not hash to the data root"
+        );
+        ensure!(
+            roots.data_root == self.data_root_proof.data_root,
+            "Data root does not match the data root of block {}",
+            self.data_root_proof.block_number
+        );
+        ensure!(
+            self.data_root_proof.verify(),
+            "Data root is not included in the data root commitment"
+        );
+        Ok(())
+    }
+}
+
+/// Build the proof of the bridge message at tx_index in block_number against the data root
+/// commitment of range.
+pub async fn build_bridge_message_proof(
+    fetcher: &mut RpcDataFetcher,
+    range: &DataCommitmentRange,
+    block_number: u32,
+    tx_index: u32,
+) -> Result<BridgeMessageProof, Error> {
+    let response = fetcher.get_data_proof(block_number, tx_index).await?;
+    let data_root_proof = build_data_root_proof(fetcher, range, block_number).await?;
+
+    bridge_message_proof(response, tx_index, data_root_proof)
+}
+
+/// The proof of the bridge message in response, the data proof of the transaction at tx_index in
+/// the block of data_root_proof.
+pub fn bridge_message_proof(
+    response: ProofResponse,
+    tx_index: u32,
+    data_root_proof: DataRootInclusionProof,
+) -> Result<BridgeMessageProof, Error> {
+    let message = response.message.ok_or_else(|| {
+        anyhow!(
+            "Transaction {} in block {} is not a bridge message",
+            tx_index,
+            data_root_proof.block_number
+        )
+    })?;
+
+    let proof = BridgeMessageProof {
+        message,
+        tx_index,
+        data_proof: response.data_proof,
+        data_root_proof,
+    };
+    proof.verify()?;
+
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[tokio::test]
+    #[cfg_attr(feature = "ci", ignore)]
+    async fn test_get_bridge_messages() {
+        env::set_var("RUST_LOG", "debug");
+        dotenv::dotenv().ok();
+        env_logger::try_init().unwrap_or_default();
+
+        let mut fetcher = RpcDataFetcher::new().await;
+
+        let block_number = 272515;
+        let messages = fetcher.get_bridge_messages(block_number).await;
+        for (tx_index, message) in messages {
+            let response = fetcher
+                .get_data_proof(block_number, tx_index)
+                .await
+                .unwrap();
+            assert_eq!(message_leaf(&message), response.data_proof.leaf);
+            assert!(verify_data_proof(
+                response.data_proof.roots.bridge_root,
+                &response.data_proof
+            ));
+            println!("tx {} message {:?}", tx_index, message);
+        }
+    }
+}
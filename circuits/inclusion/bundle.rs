@@ -0,0 +1,214 @@
+use anyhow::{ensure, Error};
+use codec::Encode;
+use ethers::types::{Address, Bytes, H256};
+use serde::{Deserialize, Serialize};
+use sp_core::{blake2_256, keccak_256};
+
+use super::blob::{data_submission_proof, DataSubmissionProof};
+use super::bridge::{bridge_message_proof, BridgeMessageProof};
+use super::{build_data_root_proof, DataRootInclusionProof};
+use crate::input::{DataCommitmentRange, RpcDataFetcher};
+use crate::native;
+
+/// Version of the proof bundle format. Bump when the layout of ProofBundle changes.
+pub const PROOF_BUNDLE_VERSION: u32 = 2;
+
+/// Selector of the VectorX contract's `dataRootCommitments(bytes32)` getter.
+pub fn data_root_commitments_selector() -> [u8; 4] {
+    keccak_256(b"dataRootCommitments(bytes32)")[..4]
+        .try_into()
+        .unwrap()
+}
+
+/// Calldata of the `dataRootCommitments(range_hash)` call on the VectorX contract.
+pub fn data_root_commitments_calldata(range_hash: H256) -> Bytes {
+    let mut calldata = data_root_commitments_selector().to_vec();
+    calldata.extend_from_slice(range_hash.as_bytes());
+    Bytes::from(calldata)
+}
+
+/// An `eth_call` which returns the data root commitment the proof bundle is verified against.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthCall {
+    pub to: Address,
+    pub data: Bytes,
+}
+
+/// Proof of a transaction in the block, if the bundle was requested with a transaction index.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TransactionProof {
+    BridgeMessage(BridgeMessageProof),
+    DataSubmission(DataSubmissionProof),
+}
+
+impl TransactionProof {
+    pub fn data_root_proof(&self) -> &DataRootInclusionProof {
+        match self {
+            TransactionProof::BridgeMessage(proof) => &proof.data_root_proof,
+            TransactionProof::DataSubmission(proof) => &proof.data_root_proof,
+        }
+    }
+
+    pub fn verify(&self) -> Result<(), Error> {
+        match self {
+            TransactionProof::BridgeMessage(proof) => proof.verify(),
+            TransactionProof::DataSubmission(proof) => proof.verify(),
+        }
+    }
+}
+
+/// Self-contained proof that an Avail block (and optionally a transaction in it) is attested by a
+/// VectorX deployment.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofBundle {
+    pub version: u32,
+    pub contract_chain_id: u64,
+    pub contract_address: Address,
+    pub block_number: u32,
+    pub block_hash: H256,
+    /// The encoded header of the block, which links the block hash to the data root.
+    pub header: Bytes,
+    pub range_hash: H256,
+    pub data_root_proof: DataRootInclusionProof,
+    /// Value of `dataRootCommitments(rangeHash)` on the VectorX contract when the bundle was built.
+    pub on_chain_data_commitment: H256,
+    pub eth_call: EthCall,
+    pub transaction_proof: Option<TransactionProof>,
+}
+
+impl ProofBundle {
+    /// Verify the bundle is internally consistent and matches the on-chain data commitment.
+    pub fn verify(&self) -> Result<(), Error> {
+        ensure!(
+            self.version == PROOF_BUNDLE_VERSION,
+            "Unsupported proof bundle version {}",
+            self.version
+        );
+        ensure!(
+            self.data_root_proof.block_number == self.block_number,
+            "Data root proof is for block {}, not block {}",
+            self.data_root_proof.block_number,
+            self.block_number
+        );
+        ensure!(
+            H256::from(blake2_256(&self.header)) == self.block_hash,
+            "Header does not hash to the block hash {:?}",
+            self.block_hash
+        );
+        let header = native::decode_header(&self.header)?;
+        ensure!(
+            header.block_number == self.block_number,
+            "Header is of block {}, not block {}",
+            header.block_number,
+            self.block_number
+        );
+        ensure!(
+            header.data_root == self.data_root_proof.data_root,
+            "Data root of the data root proof is not the data root of the header"
+        );
+        ensure!(
+            self.data_root_proof.range_hash() == self.range_hash,
+            "Range hash does not match the range of the data root proof"
+        );
+        ensure!(
+            self.eth_call.to == self.contract_address
+                && self.eth_call.data == data_root_commitments_calldata(self.range_hash),
+            "eth_call does not query dataRootCommitments(rangeHash) on the contract"
+        );
+        ensure!(
+            self.data_root_proof.data_commitment == self.on_chain_data_commitment,
+            "Data commitment does not match the data commitment stored on-chain"
+        );
+        ensure!(
+            self.data_root_proof.verify(),
+            "Data root is not included in the data commitment"
+        );
+        if let Some(transaction_proof) = &self.transaction_proof {
+            ensure!(
+                transaction_proof.data_root_proof() == &self.data_root_proof,
+                "Transaction proof is against a different data root proof"
+            );
+            transaction_proof.verify()?;
+        }
+        Ok(())
+    }
+}
+
+/// Build the proof bundle for block_number against the data commitment of range, which is stored
+/// on-chain as on_chain_data_commitment. If tx_index is set, include the proof of the bridge
+/// message or data submission at tx_index.
+#[allow(clippy::too_many_arguments)]
+pub async fn build_proof_bundle(
+    fetcher: &mut RpcDataFetcher,
+    contract_chain_id: u64,
+    contract_address: Address,
+    range: &DataCommitmentRange,
+    on_chain_data_commitment: H256,
+    block_number: u32,
+    tx_index: Option<u32>,
+) -> Result<ProofBundle, Error> {
+    let data_root_proof = build_data_root_proof(fetcher, range, block_number).await?;
+
+    // The transaction proofs share the data root proof of the bundle.
+    let transaction_proof = match tx_index {
+        Some(tx_index) => {
+            let response = fetcher.get_data_proof(block_number, tx_index).await?;
+            if response.message.is_some() {
+                Some(TransactionProof::BridgeMessage(bridge_message_proof(
+                    response,
+                    tx_index,
+                    data_root_proof.clone(),
+                )?))
+            } else {
+                Some(TransactionProof::DataSubmission(data_submission_proof(
+                    response,
+                    tx_index,
+                    data_root_proof.clone(),
+                )?))
+            }
+        }
+        None => None,
+    };
+
+    let block_hash = fetcher.get_block_hash(block_number).await;
+    let header = fetcher.get_header(block_number).await.encode();
+    let range_hash = data_root_proof.range_hash();
+    let bundle = ProofBundle {
+        version: PROOF_BUNDLE_VERSION,
+        contract_chain_id,
+        contract_address,
+        block_number,
+        block_hash,
+        header: Bytes::from(header),
+        range_hash,
+        data_root_proof,
+        on_chain_data_commitment,
+        eth_call: EthCall {
+            to: contract_address,
+            data: data_root_commitments_calldata(range_hash),
+        },
+        transaction_proof,
+    };
+    bundle.verify()?;
+
+    Ok(bundle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_root_commitments_calldata() {
+        // cast sig "dataRootCommitments(bytes32)"
+        assert_eq!(data_root_commitments_selector(), [0x52, 0x77, 0xd5, 0x50]);
+
+        let range_hash = H256::repeat_byte(0xab);
+        let calldata = data_root_commitments_calldata(range_hash);
+        assert_eq!(calldata.len(), 36);
+        assert_eq!(&calldata[4..], range_hash.as_bytes());
+    }
+}
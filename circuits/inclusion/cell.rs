@@ -0,0 +1,322 @@
+use anyhow::{anyhow, ensure, Error};
+use avail_subxt::primitives::Header;
+use codec::{Decode, Encode};
+use ethers::types::{Bytes, H256};
+use kate_recovery::data::Cell;
+use kate_recovery::matrix::{Dimensions, Position};
+use kate_recovery::{couscous, proof};
+use serde::{Deserialize, Serialize};
+use sp_core::blake2_256;
+
+use super::{build_data_root_proof, DataRootInclusionProof};
+use crate::consts::KZG_COMMITMENT_SIZE;
+use crate::input::types::KateCell;
+use crate::input::{DataCommitmentRange, RpcDataFetcher};
+use crate::layout::CURRENT_HEADER_LAYOUT;
+
+/// A cell of the extended data matrix and its KZG opening proof against the commitment of the
+/// cell's row.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CellProof {
+    pub row: u32,
+    pub col: u16,
+    /// The cell's data, a big-endian BLS12-381 scalar.
+    pub data: H256,
+    pub proof: Bytes,
+}
+
+impl CellProof {
+    fn to_cell(&self) -> Result<Cell, Error> {
+        ensure!(
+            self.proof.len() == KZG_COMMITMENT_SIZE,
+            "Proof of cell ({}, {}) is {} bytes, expected {} bytes",
+            self.row,
+            self.col,
+            self.proof.len(),
+            KZG_COMMITMENT_SIZE
+        );
+        let mut content = [0u8; 80];
+        content[..KZG_COMMITMENT_SIZE].copy_from_slice(&self.proof);
+        content[KZG_COMMITMENT_SIZE..].copy_from_slice(self.data.as_bytes());
+        Ok(Cell {
+            position: Position {
+                row: self.row,
+                col: self.col,
+            },
+            content,
+        })
+    }
+}
+
+/// Proof that cells of a block's extended data matrix are available, tied to a data root commitment
+/// stored in VectorX. The cells are verified against the KZG commitments in the block's header, and
+/// the header's data root is proven against the VectorX data root commitment.
+///
+/// The data root does not commit to the KZG commitments, so the header is tied to VectorX by its
+/// ancestry instead: the headers after it up to the end of the data commitment range, whose header
+/// hash is stored in VectorX's `blockHeightToHeaderHash`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CellSamplingProof {
+    pub block_number: u32,
+    /// The SCALE encoded header of the block.
+    pub header: Bytes,
+    /// The SCALE encoded headers of the blocks after the block, up to and including the end block
+    /// of the data commitment range.
+    pub ancestry: Vec<Bytes>,
+    pub cells: Vec<CellProof>,
+    /// Proof of the block's data root against the VectorX data root commitment.
+    pub data_root_proof: DataRootInclusionProof,
+}
+
+impl CellSamplingProof {
+    /// Verify the cells against the KZG commitments in the header, and the header's data root
+    /// against the data root commitment of data_root_proof. trusted_header_hash is the header hash
+    /// of the end block of the data commitment range stored in VectorX's `blockHeightToHeaderHash`,
+    /// which the header is verified to be an ancestor of.
+    pub fn verify(&self, trusted_header_hash: H256) -> Result<(), Error> {
+        let header = Header::decode(&mut self.header.as_ref())?;
+        ensure!(
+            header.number == self.block_number,
+            "Header is for block {}, expected block {}",
+            header.number,
+            self.block_number
+        );
+
+        // Link the header to the trusted header by the parent hashes of its ancestry.
+        let mut block_number = header.number;
+        let mut header_hash = H256::from(blake2_256(&self.header));
+        for encoded_header in &self.ancestry {
+            let header = Header::decode(&mut encoded_header.as_ref())?;
+            ensure!(
+                header.number == block_number + 1
+                    && H256::from(header.parent_hash.0) == header_hash,
+                "Header of block {} is not the child of block {}",
+                header.number,
+                block_number
+            );
+            block_number = header.number;
+            header_hash = H256::from(blake2_256(encoded_header));
+        }
+        ensure!(
+            block_number == self.data_root_proof.range_end_block,
+            "Ancestry ends at block {}, expected the end block {} of the data commitment range",
+            block_number,
+            self.data_root_proof.range_end_block
+        );
+        ensure!(
+            header_hash == trusted_header_hash,
+            "Header of block {} does not match the trusted header hash",
+            block_number
+        );
+
+        let extension = CURRENT_HEADER_LAYOUT.decode_extension(&self.header)?;
+        ensure!(
+            extension.data_root == self.data_root_proof.data_root,
+            "Data root does not match the data root of block {}",
+            self.data_root_proof.block_number
+        );
+        ensure!(
+            self.data_root_proof.block_number == self.block_number,
+            "Data root proof is for block {}, expected block {}",
+            self.data_root_proof.block_number,
+            self.block_number
+        );
+        ensure!(
+            self.data_root_proof.verify(),
+            "Data root is not included in the data root commitment"
+        );
+
+        let commitments = extension.kzg_commitments()?;
+        let dimensions = Dimensions::new(extension.rows, extension.cols).ok_or_else(|| {
+            anyhow!(
+                "Invalid data matrix dimensions {}x{}",
+                extension.rows,
+                extension.cols
+            )
+        })?;
+        let public_params = couscous::public_params();
+        for cell in &self.cells {
+            let commitment = commitments.get(cell.row as usize).ok_or_else(|| {
+                anyhow!(
+                    "Cell ({}, {}) is outside of the {} committed rows",
+                    cell.row,
+                    cell.col,
+                    commitments.len()
+                )
+            })?;
+            let is_valid = proof::verify(&public_params, dimensions, commitment, &cell.to_cell()?)
+                .map_err(|e| {
+                    anyhow!(
+                        "Failed to verify cell ({}, {}): {:?}",
+                        cell.row,
+                        cell.col,
+                        e
+                    )
+                })?;
+            ensure!(
+                is_valid,
+                "Cell ({}, {}) does not open the commitment of its row",
+                cell.row,
+                cell.col
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Fetch the cells of block_number with their KZG proofs, and build the proof that they are
+/// available against the data root commitment of range. trusted_header_hash is the header hash of
+/// range's end block stored in VectorX.
+pub async fn build_cell_sampling_proof(
+    fetcher: &mut RpcDataFetcher,
+    range: &DataCommitmentRange,
+    trusted_header_hash: H256,
+    block_number: u32,
+    cells: &[(u32, u16)],
+) -> Result<CellSamplingProof, Error> {
+    let kate_cells = cells
+        .iter()
+        .map(|&(row, col)| KateCell {
+            row,
+            col: col as u32,
+        })
+        .collect::<Vec<_>>();
+    let responses = fetcher.get_cell_proofs(block_number, &kate_cells).await?;
+    ensure!(
+        responses.len() == cells.len(),
+        "Queried {} cells of block {}, but got {} proofs",
+        cells.len(),
+        block_number,
+        responses.len()
+    );
+
+    let cell_proofs = cells
+        .iter()
+        .zip(responses)
+        .map(|(&(row, col), (scalar, proof))| {
+            let mut data = [0u8; 32];
+            scalar.to_big_endian(&mut data);
+            CellProof {
+                row,
+                col,
+                data: H256::from(data),
+                proof: Bytes::from(proof),
+            }
+        })
+        .collect();
+
+    let header = fetcher.get_header(block_number).await;
+    let ancestry = fetcher
+        .get_block_headers_range(block_number + 1, range.end)
+        .await
+        .iter()
+        .map(|header| Bytes::from(header.encode()))
+        .collect();
+
+    let data_root_proof = build_data_root_proof(fetcher, range, block_number).await?;
+
+    let proof = CellSamplingProof {
+        block_number,
+        header: Bytes::from(header.encode()),
+        ancestry,
+        cells: cell_proofs,
+        data_root_proof,
+    };
+    proof.verify(trusted_header_hash)?;
+
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthetic::{SyntheticChain, SyntheticChainConfig};
+
+    // A proof of block 102 without cells, against the data root commitment of the range (100, 104]
+    // of a synthetic chain.
+    fn proof(chain: &SyntheticChain) -> CellSamplingProof {
+        let data_roots = (101..=104)
+            .map(|block_number| chain.data_root(block_number).0.to_vec())
+            .collect::<Vec<_>>();
+        let data_commitment = RpcDataFetcher::get_merkle_root(data_roots.clone());
+        let branch = RpcDataFetcher::get_merkle_branch(data_roots, 1);
+
+        CellSamplingProof {
+            block_number: 102,
+            header: Bytes::from(chain.header(102).to_vec()),
+            ancestry: (103..=104)
+                .map(|block_number| Bytes::from(chain.header(block_number).to_vec()))
+                .collect(),
+            cells: vec![],
+            data_root_proof: DataRootInclusionProof {
+                block_number: 102,
+                data_root: chain.data_root(102),
+                range_start_block: 100,
+                range_end_block: 104,
+                data_commitment: H256::from_slice(&data_commitment),
+                index: 1,
+                branch: branch.iter().map(|node| H256::from_slice(node)).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_verify_header() {
+        let chain = SyntheticChain::new(SyntheticChainConfig {
+            start_block: 100,
+            num_blocks: 5,
+            ..Default::default()
+        });
+        let trusted_header_hash = chain.header_hash(104);
+        let proof = proof(&chain);
+        proof.verify(trusted_header_hash).unwrap();
+
+        // The header must be an ancestor of the trusted header.
+        assert!(proof.verify(chain.header_hash(103)).is_err());
+
+        // The ancestry must reach the end block of the range.
+        let mut truncated = proof.clone();
+        truncated.ancestry.pop();
+        assert!(truncated.verify(chain.header_hash(103)).is_err());
+
+        // A forged header with other KZG commitments, but the same data root, is rejected.
+        let mut header = chain.header(102).to_vec();
+        let commitment_end = header.len() - 32;
+        header[commitment_end - 1] ^= 1;
+        let forged = CellSamplingProof {
+            header: Bytes::from(header.clone()),
+            ..proof.clone()
+        };
+        assert_eq!(
+            CURRENT_HEADER_LAYOUT.decode_data_root(&header).unwrap(),
+            chain.data_root(102)
+        );
+        assert!(forged.verify(trusted_header_hash).is_err());
+    }
+
+    #[test]
+    fn test_cell_content() {
+        let cell_proof = CellProof {
+            row: 1,
+            col: 2,
+            data: H256::repeat_byte(9),
+            proof: Bytes::from(vec![7u8; KZG_COMMITMENT_SIZE]),
+        };
+        let cell = cell_proof.to_cell().unwrap();
+        assert_eq!(cell.position.row, 1);
+        assert_eq!(cell.position.col, 2);
+        assert_eq!(
+            &cell.content[..KZG_COMMITMENT_SIZE],
+            &[7u8; KZG_COMMITMENT_SIZE]
+        );
+        assert_eq!(&cell.content[KZG_COMMITMENT_SIZE..], &[9u8; 32]);
+
+        let invalid_cell_proof = CellProof {
+            proof: Bytes::from(vec![7u8; 32]),
+            ..cell_proof
+        };
+        assert!(invalid_cell_proof.to_cell().is_err());
+    }
+}
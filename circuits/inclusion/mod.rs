@@ -0,0 +1,283 @@
+pub mod blob;
+pub mod bridge;
+pub mod bundle;
+pub mod cell;
+
+use alloy_sol_types::{sol, SolType};
+use anyhow::{anyhow, Error};
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp_core::keccak_256;
+
+use crate::input::types::DataProof;
+use crate::input::{DataCommitmentRange, RpcDataFetcher};
+
+type RangeHashTuple = sol! { tuple(uint32, uint32) };
+
+/// Proof that a block's data root is a leaf of the VectorX data root commitment over the range
+/// (range_start_block, range_end_block].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataRootInclusionProof {
+    pub block_number: u32,
+    pub data_root: H256,
+    pub range_start_block: u32,
+    pub range_end_block: u32,
+    pub data_commitment: H256,
+    /// Index of the data root in the data root commitment.
+    pub index: u32,
+    /// Sibling nodes from the data root up to the data root commitment.
+    pub branch: Vec<H256>,
+}
+
+impl DataRootInclusionProof {
+    /// The key of the range in the VectorX contract's `dataRootCommitments` mapping, which is
+    /// keccak256(abi.encode(startBlock, endBlock)).
+    pub fn range_hash(&self) -> H256 {
+        get_range_hash(self.range_start_block, self.range_end_block)
+    }
+
+    /// Verify the data root is the leaf for block_number in the data root commitment.
+    pub fn verify(&self) -> bool {
+        self.block_number > self.range_start_block
+            && self.block_number <= self.range_end_block
+            && self.index == self.block_number - self.range_start_block - 1
+            && verify_merkle_branch(
+                self.data_commitment,
+                self.data_root,
+                self.index,
+                &self.branch,
+            )
+    }
+}
+
+/// The key of the range (start_block, end_block] in the VectorX contract's `dataRootCommitments`
+/// and `stateRootCommitments` mappings.
+pub fn get_range_hash(start_block: u32, end_block: u32) -> H256 {
+    let encoded = RangeHashTuple::abi_encode(&(start_block, end_block));
+    H256::from(keccak_256(&encoded))
+}
+
+/// Verify a branch of the simple SHA256 Merkle tree used for VectorX data root commitments. The
+/// leaves of the tree are not hashed.
+pub fn verify_merkle_branch(root: H256, leaf: H256, index: u32, branch: &[H256]) -> bool {
+    let mut node = leaf.0.to_vec();
+    let mut index = index;
+    for sibling in branch {
+        let mut hasher = Sha256::new();
+        if index % 2 == 0 {
+            hasher.update(&node);
+            hasher.update(sibling.0);
+        } else {
+            hasher.update(sibling.0);
+            hasher.update(&node);
+        }
+        node = hasher.finalize().to_vec();
+        index /= 2;
+    }
+    index == 0 && node == root.0
+}
+
+/// Verify a proof of the keccak256 binary Merkle tree Avail uses for the blob and bridge roots.
+/// The last node of a row with an odd number of nodes is promoted to the next row without hashing,
+/// so it has no sibling in the proof.
+/// Spec: https://github.com/availproject/polkadot-sdk/blob/polkadot-1.7.1-patch-2/substrate/utils/binary-merkle-tree/src/lib.rs
+pub fn verify_keccak_merkle_proof(
+    root: H256,
+    leaf: H256,
+    leaf_index: u32,
+    number_of_leaves: u32,
+    proof: &[H256],
+) -> bool {
+    if leaf_index >= number_of_leaves {
+        return false;
+    }
+
+    let mut proof = proof.iter();
+    let mut node = leaf.0;
+    let mut position = leaf_index;
+    let mut width = number_of_leaves;
+    while width > 1 {
+        if position % 2 == 1 {
+            let Some(sibling) = proof.next() else {
+                return false;
+            };
+            node = keccak_256(&[sibling.0, node].concat());
+        } else if position + 1 < width {
+            let Some(sibling) = proof.next() else {
+                return false;
+            };
+            node = keccak_256(&[node, sibling.0].concat());
+        }
+        position /= 2;
+        width = (width + 1) / 2;
+    }
+
+    // All of the proof must be consumed.
+    proof.next().is_none() && node == root.0
+}
+
+/// Verify a data proof returned by `kate_queryDataProof` against the sub-root it proves into.
+pub fn verify_data_proof(sub_root: H256, data_proof: &DataProof) -> bool {
+    verify_keccak_merkle_proof(
+        sub_root,
+        data_proof.leaf,
+        data_proof.leaf_index,
+        data_proof.number_of_leaves,
+        &data_proof.proof,
+    )
+}
+
+/// The data root of an Avail header, which commits to the blob root and the bridge root.
+pub fn data_root_from_sub_roots(blob_root: H256, bridge_root: H256) -> H256 {
+    H256::from(keccak_256(&[blob_root.0, bridge_root.0].concat()))
+}
+
+/// Build the proof of block_number's data root against the data root commitment of range, over a
+/// tree of the range's commitment tree size.
+pub async fn build_data_root_proof(
+    fetcher: &mut RpcDataFetcher,
+    range: &DataCommitmentRange,
+    block_number: u32,
+) -> Result<DataRootInclusionProof, Error> {
+    if block_number <= range.start || block_number > range.end {
+        return Err(anyhow!(
+            "Block {} is not in the range ({}, {}]",
+            block_number,
+            range.start,
+            range.end
+        ));
+    }
+
+    let (data_root, data_commitment, branch) = fetcher
+        .get_data_root_branch(
+            range.commitment_tree_size,
+            range.start,
+            range.end,
+            block_number,
+        )
+        .await;
+
+    if data_commitment != range.data_commitment {
+        return Err(anyhow!(
+            "Computed data commitment {} does not match the data commitment {} of range ({}, {}]",
+            hex::encode(&data_commitment),
+            hex::encode(&range.data_commitment),
+            range.start,
+            range.end
+        ));
+    }
+
+    Ok(DataRootInclusionProof {
+        block_number,
+        data_root: H256::from_slice(&data_root),
+        range_start_block: range.start,
+        range_end_block: range.end,
+        data_commitment: H256::from_slice(&data_commitment),
+        index: block_number - range.start - 1,
+        branch: branch.iter().map(|node| H256::from_slice(node)).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(i: u8) -> H256 {
+        H256::from([i; 32])
+    }
+
+    // Computes the root of Avail's keccak256 binary Merkle tree and the proof of the leaf at index.
+    pub(super) fn keccak_root_and_proof(leaves: &[H256], index: usize) -> (H256, Vec<H256>) {
+        let mut nodes = leaves.iter().map(|x| x.0).collect::<Vec<_>>();
+        let mut index = index;
+        let mut proof = Vec::new();
+        while nodes.len() > 1 {
+            if index ^ 1 < nodes.len() {
+                proof.push(H256::from(nodes[index ^ 1]));
+            }
+            nodes = nodes
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => keccak_256(&[*left, *right].concat()),
+                    [last] => *last,
+                    _ => unreachable!(),
+                })
+                .collect();
+            index /= 2;
+        }
+        (H256::from(nodes[0]), proof)
+    }
+
+    #[test]
+    fn test_verify_merkle_branch() {
+        let leaves = (1..6).map(|i| leaf(i).0.to_vec()).collect::<Vec<_>>();
+        let root = H256::from_slice(&RpcDataFetcher::get_merkle_root(leaves.clone()));
+
+        for i in 0..leaves.len() {
+            let branch = RpcDataFetcher::get_merkle_branch(leaves.clone(), i)
+                .iter()
+                .map(|node| H256::from_slice(node))
+                .collect::<Vec<_>>();
+            assert_eq!(branch.len(), 3);
+            assert!(verify_merkle_branch(
+                root,
+                leaf(i as u8 + 1),
+                i as u32,
+                &branch
+            ));
+            assert!(!verify_merkle_branch(
+                root,
+                leaf(i as u8 + 1),
+                (i as u32) ^ 1,
+                &branch
+            ));
+        }
+    }
+
+    #[test]
+    fn test_verify_keccak_merkle_proof() {
+        for num_leaves in 1..10 {
+            let leaves = (0..num_leaves).map(leaf).collect::<Vec<_>>();
+            for i in 0..leaves.len() {
+                let (root, proof) = keccak_root_and_proof(&leaves, i);
+                assert!(verify_keccak_merkle_proof(
+                    root,
+                    leaves[i],
+                    i as u32,
+                    num_leaves as u32,
+                    &proof
+                ));
+                // A proof for one leaf must not verify another leaf.
+                assert!(!verify_keccak_merkle_proof(
+                    root,
+                    leaf(100),
+                    i as u32,
+                    num_leaves as u32,
+                    &proof
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_range_hash() {
+        let proof = DataRootInclusionProof {
+            block_number: 2,
+            data_root: H256::zero(),
+            range_start_block: 1,
+            range_end_block: 2,
+            data_commitment: H256::zero(),
+            index: 0,
+            branch: vec![],
+        };
+        let mut expected_encoding = [0u8; 64];
+        expected_encoding[31] = 1;
+        expected_encoding[63] = 2;
+        assert_eq!(
+            proof.range_hash(),
+            H256::from(keccak_256(&expected_encoding))
+        );
+    }
+}
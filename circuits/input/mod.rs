@@ -25,12 +25,15 @@ use sp_core::{ed25519, H160};
 use tokio::time::sleep;
 
 use self::types::{
-    CircuitJustification, EncodedFinalityProof, FinalityProof, GrandpaJustification,
-    HeaderRotateData, SignerMessage, SimpleJustificationData, StoredJustificationData,
+    AddressedMessage, CircuitJustification, EncodedFinalityProof, FinalityProof,
+    GrandpaJustification, HeaderRotateData, KateCell, KateCellProof, ProofResponse, SignerMessage,
+    SimpleJustificationData, StoredJustificationData,
 };
+use crate::authority_set::{get_authority_set_merkle_root, AuthoritySetCommitmentMode};
 use crate::consts::{
     CONSENSUS_ENGINE_ID_PREFIX_LENGTH, DELAY_LENGTH, HASH_SIZE, PUBKEY_LENGTH, VALIDATOR_LENGTH,
 };
+use crate::layout::{get_digest_start, CURRENT_HEADER_LAYOUT};
 
 #[derive(Clone)]
 pub struct RedisClient {
@@ -41,9 +44,13 @@ pub struct DataCommitmentRange {
     pub start: u32,
     pub end: u32,
     pub data_commitment: Vec<u8>,
+    /// The number of leaves of the data commitment's Merkle tree, from the
+    /// `HeaderRangeCommitmentStored` event. Aggregated ranges have a larger tree than
+    /// `headerRangeCommitmentTreeSize`.
+    pub commitment_tree_size: u32,
 }
 
-type DataCommitmentRangeTuple = sol! { tuple(uint32, uint32, bytes32) };
+type DataCommitmentRangeTuple = sol! { tuple(uint32, uint32, bytes32, uint32) };
 
 impl RedisClient {
     const MAX_RECONNECT_ATTEMPTS: usize = 3;
@@ -218,8 +225,18 @@ impl RedisClient {
 
         let data_commitment: [u8; 32] = range.data_commitment.try_into().unwrap();
 
-        let range_data: Vec<u8> =
-            DataCommitmentRangeTuple::abi_encode_packed(&(range.start, range.end, data_commitment));
+        let range_data: Vec<u8> = DataCommitmentRangeTuple::abi_encode_packed(&(
+            range.start,
+            range.end,
+            data_commitment,
+            range.commitment_tree_size,
+        ));
+        // Replace the range previously stored with the same end block, so re-indexing the events
+        // replaces ranges stored without their commitment tree size.
+        let _: () = con
+            .zrembyscore(key.clone(), range.end, range.end)
+            .await
+            .expect("Failed to remove key");
         // Branch is stored as an ABI encode packed tuple.
         let _: () = con
             .zadd(key.clone(), hex::encode(range_data), range.end)
@@ -227,14 +244,63 @@ impl RedisClient {
             .expect("Failed to set key");
 
         info!(
-            "Added range: {:?}-{:?} with data commitment: {:?} to address: {} on chain: {}",
+            "Added range: {:?}-{:?} with data commitment: {:?} and tree size {} to address: {} on chain: {}",
             range.start,
             range.end,
             hex::encode(data_commitment),
+            range.commitment_tree_size,
             address,
             chain_id
         );
     }
+
+    /// Gets the stored data commitment range containing block_number, which is the range
+    /// (start, end] with start < block_number <= end. Returns None if no stored range contains
+    /// the block.
+    pub async fn get_data_commitment_range(
+        &mut self,
+        chain_id: u64,
+        address: Vec<u8>,
+        block_number: u32,
+    ) -> Option<DataCommitmentRange> {
+        let mut con = match self.get_connection().await {
+            Ok(con) => con,
+            Err(e) => panic!("{}", e),
+        };
+
+        // Add 0x prefix to address.
+        let address = format!("0x{}", hex::encode(address));
+
+        let key = format!("{}:{}:ranges", chain_id, address);
+
+        // Ranges are scored by their end block, so the first range ending at or after block_number
+        // is the only range that can contain it.
+        let ranges: Vec<String> = con
+            .zrangebyscore_limit(key, block_number, "+inf", 0, 1)
+            .await
+            .expect("Failed to get keys");
+
+        // Range is stored as an ABI encode packed tuple of (uint32, uint32, bytes32, uint32).
+        let range_data = hex::decode(ranges.first()?).expect("Invalid range data");
+        let start = u32::from_be_bytes(range_data[0..4].try_into().unwrap());
+        let end = u32::from_be_bytes(range_data[4..8].try_into().unwrap());
+        if range_data.len() != 44 {
+            panic!(
+                "Range ({}, {}] was stored without its commitment tree size. Re-index the contract's events.",
+                start, end
+            );
+        }
+        if start >= block_number {
+            return None;
+        }
+
+        Some(DataCommitmentRange {
+            start,
+            end,
+            data_commitment: range_data[8..40].to_vec(),
+            commitment_tree_size: u32::from_be_bytes(range_data[40..44].try_into().unwrap()),
+        })
+    }
 }
 
 /// This function is useful for verifying that a Ed25519 signature is valid, it will panic if the signature is not valid
@@ -259,6 +325,21 @@ pub fn compute_authority_set_hash(authorities: &[CompressedEdwardsY]) -> Vec<u8>
     hash_so_far
 }
 
+// Compute the commitment to the authority set with the given commitment mode.
+pub fn compute_authority_set_hash_with_mode(
+    authorities: &[CompressedEdwardsY],
+    mode: AuthoritySetCommitmentMode,
+) -> H256 {
+    match mode {
+        AuthoritySetCommitmentMode::ChainedSha256 => {
+            H256::from_slice(&compute_authority_set_hash(authorities))
+        }
+        AuthoritySetCommitmentMode::MerkleSha256 | AuthoritySetCommitmentMode::MerkleKeccak => {
+            get_authority_set_merkle_root(authorities, mode)
+        }
+    }
+}
+
 pub fn decode_precommit(precommit: Vec<u8>) -> (H256, u32, u64, u64) {
     // The first byte should be a 1.
     assert_eq!(precommit[0], 1);
@@ -488,6 +569,35 @@ impl RpcDataFetcher {
         nodes[0].clone()
     }
 
+    // Computes the simple Merkle branch of the leaf at index over the same tree as get_merkle_root.
+    // The branch is the list of sibling nodes from the leaf up to the root.
+    pub fn get_merkle_branch(leaves: Vec<Vec<u8>>, index: usize) -> Vec<Vec<u8>> {
+        assert!(index < leaves.len(), "Leaf index out of range!");
+
+        // Extend leaves to a power of 2.
+        let mut nodes = leaves;
+        while nodes.len().count_ones() != 1 {
+            nodes.push([0u8; 32].to_vec());
+        }
+
+        let mut index = index;
+        let mut branch = Vec::new();
+        while nodes.len() > 1 {
+            branch.push(nodes[index ^ 1].clone());
+            nodes = (0..nodes.len() / 2)
+                .map(|i| {
+                    let mut hasher = Sha256::new();
+                    hasher.update(&nodes[2 * i]);
+                    hasher.update(&nodes[2 * i + 1]);
+                    hasher.finalize().to_vec()
+                })
+                .collect();
+            index /= 2;
+        }
+
+        branch
+    }
+
     /// Get the state root commitment and data root commitment for the range [start_block + 1, end_block].
     /// Returns a tuple of the state root commitment and data root commitment.
     pub async fn get_merkle_root_commitments(
@@ -527,6 +637,50 @@ impl RpcDataFetcher {
         )
     }
 
+    /// Get the Merkle branch of block_number's data root against the data root commitment for the
+    /// range [start_block + 1, end_block]. Returns a tuple of the data root, the data root
+    /// commitment and the branch.
+    pub async fn get_data_root_branch(
+        &mut self,
+        header_range_commitment_tree_size: u32,
+        start_block: u32,
+        end_block: u32,
+        block_number: u32,
+    ) -> (Vec<u8>, Vec<u8>, Vec<Vec<u8>>) {
+        assert!(header_range_commitment_tree_size.is_power_of_two());
+
+        if end_block - start_block > header_range_commitment_tree_size {
+            panic!("Range too large!");
+        }
+        if block_number <= start_block || block_number > end_block {
+            panic!(
+                "Block {} is not in the range [{}, {}]!",
+                block_number,
+                start_block + 1,
+                end_block
+            );
+        }
+
+        let headers = self
+            .get_block_headers_range(start_block + 1, end_block)
+            .await;
+
+        let mut data_root_leaves = headers
+            .iter()
+            .map(|header| header.data_root().0.to_vec())
+            .collect::<Vec<_>>();
+        data_root_leaves.resize(
+            header_range_commitment_tree_size as usize,
+            [0u8; 32].to_vec(),
+        );
+
+        let index = (block_number - start_block - 1) as usize;
+        let data_root = data_root_leaves[index].clone();
+        let branch = Self::get_merkle_branch(data_root_leaves.clone(), index);
+
+        (data_root, Self::get_merkle_root(data_root_leaves), branch)
+    }
+
     // This function returns a vector of headers for a given range of block numbers, inclusive of the start and end block numbers.
     pub async fn get_block_headers_range(
         &mut self,
@@ -573,6 +727,111 @@ impl RpcDataFetcher {
         header_result.unwrap().unwrap()
     }
 
+    /// Get the header of block_hash, or None if the block is not known to the node.
+    pub async fn get_header_by_hash(&mut self, block_hash: H256) -> Result<Option<Header>, Error> {
+        self.refresh_ws_connection()
+            .await
+            .expect("Failed to establish connection to Avail WS.");
+
+        self.client
+            .legacy_rpc()
+            .chain_get_header(Some(block_hash))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get the header of {:?}: {}", block_hash, e))
+    }
+
+    /// Get the proof of the transaction at tx_index in block_number against the block's data root
+    /// with the `kate_queryDataProof` RPC. Errors if the transaction is not a data submission or a
+    /// bridge message.
+    pub async fn get_data_proof(
+        &mut self,
+        block_number: u32,
+        tx_index: u32,
+    ) -> Result<ProofResponse, Error> {
+        self.refresh_ws_connection()
+            .await
+            .expect("Failed to establish connection to Avail WS.");
+
+        let block_hash = self.get_block_hash(block_number).await;
+
+        let mut params = RpcParams::new();
+        let _ = params.push(tx_index);
+        let _ = params.push(block_hash);
+
+        self.client
+            .rpc()
+            .request::<ProofResponse>("kate_queryDataProof", params)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to query data proof for tx {} in block {}: {}",
+                    tx_index,
+                    block_number,
+                    e
+                )
+            })
+    }
+
+    /// Get the cells of the extended data matrix of block_number and their KZG opening proofs with
+    /// the `kate_queryProof` RPC.
+    pub async fn get_cell_proofs(
+        &mut self,
+        block_number: u32,
+        cells: &[KateCell],
+    ) -> Result<Vec<KateCellProof>, Error> {
+        self.refresh_ws_connection()
+            .await
+            .expect("Failed to establish connection to Avail WS.");
+
+        let block_hash = self.get_block_hash(block_number).await;
+
+        let mut params = RpcParams::new();
+        let _ = params.push(cells);
+        let _ = params.push(block_hash);
+
+        self.client
+            .rpc()
+            .request::<Vec<KateCellProof>>("kate_queryProof", params)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to query cell proofs in block {}: {}",
+                    block_number,
+                    e
+                )
+            })
+    }
+
+    /// Get all bridge messages sent in block_number, along with their transaction indices.
+    pub async fn get_bridge_messages(&mut self, block_number: u32) -> Vec<(u32, AddressedMessage)> {
+        self.refresh_ws_connection()
+            .await
+            .expect("Failed to establish connection to Avail WS.");
+
+        let block_hash = self.get_block_hash(block_number).await;
+        let block = self
+            .client
+            .legacy_rpc()
+            .chain_get_block(Some(block_hash))
+            .await
+            .unwrap()
+            .unwrap();
+        let num_extrinsics = block.block.extrinsics.len() as u32;
+
+        let mut messages = Vec::new();
+        for tx_index in 0..num_extrinsics {
+            // Transactions that are not data submissions or bridge messages have no data proof.
+            if let Ok(ProofResponse {
+                message: Some(message),
+                ..
+            }) = self.get_data_proof(block_number, tx_index).await
+            {
+                messages.push((tx_index, message));
+            }
+        }
+        messages
+    }
+
     pub async fn get_head(&mut self) -> Header {
         self.refresh_ws_connection()
             .await
@@ -641,17 +900,22 @@ impl RpcDataFetcher {
     // Computes the authority_set_hash for a given block number. Note: This is the authority set hash
     // that validates the next block after the given block number.
     pub async fn compute_authority_set_hash(&mut self, block_number: u32) -> H256 {
+        self.compute_authority_set_hash_with_mode(
+            block_number,
+            AuthoritySetCommitmentMode::ChainedSha256,
+        )
+        .await
+    }
+
+    // Computes the authority_set_hash for a given block number with the given commitment mode.
+    pub async fn compute_authority_set_hash_with_mode(
+        &mut self,
+        block_number: u32,
+        mode: AuthoritySetCommitmentMode,
+    ) -> H256 {
         let authorities = self.get_authorities(block_number).await;
 
-        let mut hash_so_far = Vec::new();
-        for i in 0..authorities.len() {
-            let authority = authorities[i];
-            let mut hasher = sha2::Sha256::new();
-            hasher.update(hash_so_far);
-            hasher.update(authority.as_bytes());
-            hash_so_far = hasher.finalize().to_vec();
-        }
-        H256::from_slice(&hash_so_far)
+        compute_authority_set_hash_with_mode(&authorities, mode)
     }
 
     async fn get_justification_data<const VALIDATOR_SET_SIZE_MAX: usize>(
@@ -854,6 +1118,11 @@ impl RpcDataFetcher {
                 header_size, HEADER_LENGTH
             );
         }
+        if let Err(e) = CURRENT_HEADER_LAYOUT.check(&header_bytes) {
+            panic!("Block {}'s header is invalid: {}", epoch_end_block, e);
+        }
+        // Skip past parent_hash, number, state_root, extrinsics_root.
+        let mut position = get_digest_start(&header_bytes).unwrap();
         header_bytes.resize(HEADER_LENGTH, 0);
 
         // Fetch the new authority set specified in the epoch end block.
@@ -873,11 +1142,6 @@ impl RpcDataFetcher {
                 .encode()
                 .len();
 
-        let mut position = 0;
-        let number_encoded = Compact(epoch_end_block).encode();
-        // Skip past parent_hash, number, state_root, extrinsics_root.
-        position += HASH_SIZE + number_encoded.len() + HASH_SIZE + HASH_SIZE;
-
         let mut found_correct_log = false;
         for log in header.digest.logs {
             let encoded_log = log.clone().encode();
@@ -1,6 +1,6 @@
 use avail_subxt::primitives::Header;
 use codec::{Decode, Encode};
-use ethers::types::H256;
+use ethers::types::{H256, U256};
 use plonky2x::frontend::curta::ec::point::CompressedEdwardsY;
 use serde::de::Error;
 use serde::{Deserialize, Serialize};
@@ -112,3 +112,68 @@ pub struct FinalityProof {
     /// The set of headers in the range (B; F] that are unknown to the caller, ordered by block number.
     pub unknown_headers: Vec<Header>,
 }
+
+/// The roots committed to by an Avail header's data root. The data root is the keccak256 hash of
+/// the blob root and the bridge root.
+/// Spec: https://github.com/availproject/avail-core/blob/main/core/src/data_proof.rs
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxDataRoots {
+    pub data_root: H256,
+    pub blob_root: H256,
+    pub bridge_root: H256,
+}
+
+/// Merkle proof of a data submission or bridge message leaf against its sub-root (blob root or
+/// bridge root) in a block's data root.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataProof {
+    pub roots: TxDataRoots,
+    /// Sibling hashes from the leaf up to the sub-root.
+    pub proof: Vec<H256>,
+    pub number_of_leaves: u32,
+    pub leaf_index: u32,
+    /// The keccak256 hash of the leaf data.
+    pub leaf: H256,
+}
+
+/// Payload of a bridge message sent from Avail.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Message {
+    ArbitraryMessage(Bytes),
+    FungibleToken { asset_id: H256, amount: u128 },
+}
+
+/// A bridge message sent from Avail, addressed to a contract on the destination domain.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressedMessage {
+    pub message: Message,
+    pub from: H256,
+    pub to: H256,
+    pub origin_domain: u32,
+    pub destination_domain: u32,
+    pub id: u64,
+}
+
+/// Response of the `kate_queryDataProof` RPC. `message` is only set if the transaction at the
+/// queried index is a bridge message.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofResponse {
+    pub data_proof: DataProof,
+    pub message: Option<AddressedMessage>,
+}
+
+/// A cell of the extended data matrix, as queried with the `kate_queryProof` RPC.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KateCell {
+    pub row: u32,
+    pub col: u32,
+}
+
+/// Response of the `kate_queryProof` RPC for a cell: the cell's data as a BLS12-381 scalar, and its
+/// 48-byte KZG opening proof against the commitment of the cell's row.
+pub type KateCellProof = (U256, Vec<u8>);
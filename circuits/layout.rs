@@ -0,0 +1,380 @@
+use anyhow::{anyhow, ensure, Error};
+use avail_subxt::config::substrate::DigestItem;
+use codec::{Compact, Decode};
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::consts::{
+    DATA_ROOT_OFFSET_FROM_END, HASH_SIZE, KZG_COMMITMENT_SIZE, MAX_NUM_KZG_COMMITMENTS,
+};
+use crate::input::RpcDataFetcher;
+
+/// The layout of the Avail headers the circuits are built for. An upgrade of the Avail header
+/// format is a new HeaderLayout, and headers that do not match this layout are rejected by the
+/// header fetcher hints instead of producing wrong commitments.
+pub const CURRENT_HEADER_LAYOUT: HeaderLayout = HeaderLayout::V3;
+
+/// The layout of an encoded Avail header, determined by the version of its header extension.
+/// Spec: https://github.com/availproject/avail-core/blob/main/core/src/header/extension/mod.rs
+///
+/// An encoded header is {parent_hash, number (compact), state_root, extrinsics_root, digest,
+/// extension}, and the extension is a SCALE enum whose first byte is the version tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HeaderLayout {
+    /// {commitment: {rows, cols, data_root, commitment}, app_lookup}.
+    V1,
+    /// {app_lookup, commitment: {rows, cols, commitment, data_root}}.
+    V2,
+    /// {app_lookup (compact), commitment: {rows, cols, commitment, data_root}}.
+    V3,
+}
+
+impl HeaderLayout {
+    /// The SCALE enum index of the header extension version.
+    pub const fn version_tag(self) -> u8 {
+        match self {
+            HeaderLayout::V1 => 0,
+            HeaderLayout::V2 => 1,
+            HeaderLayout::V3 => 2,
+        }
+    }
+
+    pub fn from_version_tag(version_tag: u8) -> Option<Self> {
+        match version_tag {
+            0 => Some(HeaderLayout::V1),
+            1 => Some(HeaderLayout::V2),
+            2 => Some(HeaderLayout::V3),
+            _ => None,
+        }
+    }
+
+    /// The offset of the data root from the end of the encoded header. None if the data root is
+    /// not at a fixed offset from the end of the header (V1, where the variable length commitment
+    /// and app lookup follow the data root).
+    pub const fn data_root_offset_from_end(self) -> Option<usize> {
+        match self {
+            HeaderLayout::V1 => None,
+            HeaderLayout::V2 | HeaderLayout::V3 => Some(DATA_ROOT_OFFSET_FROM_END as usize),
+        }
+    }
+
+    /// Get the layout of an encoded header from the version tag of its header extension.
+    pub fn from_encoded_header(encoded_header: &[u8]) -> Result<Self, Error> {
+        let extension_start = get_extension_start(encoded_header)?;
+        let version_tag = *encoded_header
+            .get(extension_start)
+            .ok_or_else(|| anyhow!("header has no header extension"))?;
+        Self::from_version_tag(version_tag)
+            .ok_or_else(|| anyhow!("unknown header extension version tag {}", version_tag))
+    }
+
+    /// Check that an encoded header has this layout.
+    pub fn check(self, encoded_header: &[u8]) -> Result<(), Error> {
+        let layout = Self::from_encoded_header(encoded_header)?;
+        ensure!(
+            layout == self,
+            "header has layout {:?}, expected layout {:?}",
+            layout,
+            self
+        );
+        Ok(())
+    }
+
+    /// Decode the header extension of an encoded header with this layout.
+    pub fn decode_extension(self, encoded_header: &[u8]) -> Result<HeaderExtension, Error> {
+        self.check(encoded_header)?;
+        let extension_start = get_extension_start(encoded_header)?;
+        // Skip the version tag.
+        let mut input = &encoded_header[extension_start + 1..];
+
+        let extension = match self {
+            HeaderLayout::V1 => {
+                let rows = Compact::<u16>::decode(&mut input)?.0;
+                let cols = Compact::<u16>::decode(&mut input)?.0;
+                let data_root = H256::from(<[u8; 32]>::decode(&mut input)?);
+                let commitment = Vec::<u8>::decode(&mut input)?;
+                let (app_lookup_size, app_lookup) = decode_app_lookup(&mut input)?;
+                HeaderExtension {
+                    layout: self,
+                    app_lookup_size,
+                    app_lookup,
+                    rows,
+                    cols,
+                    commitment,
+                    data_root,
+                }
+            }
+            HeaderLayout::V2 | HeaderLayout::V3 => {
+                let (app_lookup_size, app_lookup) = if self == HeaderLayout::V2 {
+                    decode_app_lookup(&mut input)?
+                } else {
+                    decode_compact_app_lookup(&mut input)?
+                };
+                let rows = Compact::<u16>::decode(&mut input)?.0;
+                let cols = Compact::<u16>::decode(&mut input)?.0;
+                let commitment = Vec::<u8>::decode(&mut input)?;
+                let data_root = H256::from(<[u8; 32]>::decode(&mut input)?);
+                HeaderExtension {
+                    layout: self,
+                    app_lookup_size,
+                    app_lookup,
+                    rows,
+                    cols,
+                    commitment,
+                    data_root,
+                }
+            }
+        };
+        ensure!(
+            input.is_empty(),
+            "{} trailing bytes after the header extension",
+            input.len()
+        );
+        Ok(extension)
+    }
+
+    /// Decode the data root of an encoded header with this layout.
+    pub fn decode_data_root(self, encoded_header: &[u8]) -> Result<H256, Error> {
+        Ok(self.decode_extension(encoded_header)?.data_root)
+    }
+}
+
+/// An entry of the app lookup index. The data of app_id starts at chunk start of the data matrix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppLookupItem {
+    pub app_id: u32,
+    pub start: u32,
+}
+
+/// The decoded header extension of an Avail header.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderExtension {
+    pub layout: HeaderLayout,
+    /// The number of chunks of app data in the data matrix.
+    pub app_lookup_size: u32,
+    pub app_lookup: Vec<AppLookupItem>,
+    /// The dimensions of the data matrix.
+    pub rows: u16,
+    pub cols: u16,
+    /// The KZG commitments of the rows of the extended data matrix, concatenated.
+    pub commitment: Vec<u8>,
+    pub data_root: H256,
+}
+
+impl HeaderExtension {
+    /// Split the commitment into the KZG commitments of the rows of the extended data matrix.
+    pub fn kzg_commitments(&self) -> Result<Vec<[u8; KZG_COMMITMENT_SIZE]>, Error> {
+        ensure!(
+            self.commitment.len() % KZG_COMMITMENT_SIZE == 0,
+            "commitment length {} is not a multiple of {}",
+            self.commitment.len(),
+            KZG_COMMITMENT_SIZE
+        );
+        Ok(self
+            .commitment
+            .chunks_exact(KZG_COMMITMENT_SIZE)
+            .map(|c| c.try_into().unwrap())
+            .collect())
+    }
+
+    /// Compute the simple Merkle root over the SHA256 hashes of the KZG commitments, padded with
+    /// empty leaves to MAX_NUM_KZG_COMMITMENTS. Matches the KZG commitments root computed by the
+    /// circuit.
+    pub fn kzg_commitments_root(&self) -> Result<H256, Error> {
+        let kzg_commitments = self.kzg_commitments()?;
+        ensure!(
+            kzg_commitments.len() <= MAX_NUM_KZG_COMMITMENTS,
+            "{} KZG commitments is more than the maximum of {}",
+            kzg_commitments.len(),
+            MAX_NUM_KZG_COMMITMENTS
+        );
+        let mut leaves = kzg_commitments
+            .iter()
+            .map(|c| Sha256::digest(c).to_vec())
+            .collect::<Vec<_>>();
+        leaves.resize(MAX_NUM_KZG_COMMITMENTS, [0u8; 32].to_vec());
+        Ok(H256::from_slice(&RpcDataFetcher::get_merkle_root(leaves)))
+    }
+}
+
+// V1 and V2 app lookup: {size: u32, index: Vec<(app_id: u32, start: u32)>}.
+fn decode_app_lookup(input: &mut &[u8]) -> Result<(u32, Vec<AppLookupItem>), Error> {
+    let size = u32::decode(input)?;
+    let index = Vec::<(u32, u32)>::decode(input)?
+        .into_iter()
+        .map(|(app_id, start)| AppLookupItem { app_id, start })
+        .collect();
+    Ok((size, index))
+}
+
+// V3 compact app lookup: {size: Compact<u32>, index: Vec<(app_id: Compact<u32>, start: Compact<u32>)>}.
+fn decode_compact_app_lookup(input: &mut &[u8]) -> Result<(u32, Vec<AppLookupItem>), Error> {
+    let size = Compact::<u32>::decode(input)?.0;
+    let index = Vec::<(Compact<u32>, Compact<u32>)>::decode(input)?
+        .into_iter()
+        .map(|(app_id, start)| AppLookupItem {
+            app_id: app_id.0,
+            start: start.0,
+        })
+        .collect();
+    Ok((size, index))
+}
+
+/// Get the start of the digest (its compact encoded number of logs) in an encoded header, after the
+/// parent hash, number, state root and extrinsics root.
+pub fn get_digest_start(encoded_header: &[u8]) -> Result<usize, Error> {
+    ensure!(
+        encoded_header.len() > HASH_SIZE,
+        "header is too short to contain a parent hash"
+    );
+    let mut input = &encoded_header[HASH_SIZE..];
+    Compact::<u32>::decode(&mut input)?;
+    let digest_start = encoded_header.len() - input.len() + 2 * HASH_SIZE;
+    ensure!(
+        digest_start <= encoded_header.len(),
+        "header is too short to contain a state root and extrinsics root"
+    );
+    Ok(digest_start)
+}
+
+/// Get the start of the header extension (its version tag) in an encoded header.
+pub fn get_extension_start(encoded_header: &[u8]) -> Result<usize, Error> {
+    let mut input = &encoded_header[get_digest_start(encoded_header)?..];
+    Vec::<DigestItem>::decode(&mut input)?;
+    Ok(encoded_header.len() - input.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::Encode;
+
+    use super::*;
+
+    const DATA_ROOT: [u8; 32] = [9u8; 32];
+
+    fn encode_header_prefix(block_number: u32) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend([1u8; 32]);
+        header.extend(Compact(block_number).encode());
+        header.extend([2u8; 32]);
+        header.extend([3u8; 32]);
+        header.extend(Vec::<DigestItem>::new().encode());
+        header
+    }
+
+    fn encode_commitment_dimensions(header: &mut Vec<u8>) {
+        header.extend(Compact(1u16).encode());
+        header.extend(Compact(256u16).encode());
+    }
+
+    #[test]
+    fn test_version_tag() {
+        for layout in [HeaderLayout::V1, HeaderLayout::V2, HeaderLayout::V3] {
+            assert_eq!(
+                HeaderLayout::from_version_tag(layout.version_tag()),
+                Some(layout)
+            );
+        }
+        assert_eq!(HeaderLayout::from_version_tag(3), None);
+    }
+
+    #[test]
+    fn test_decode_v3_header() {
+        let mut header = encode_header_prefix(100_000);
+        let extension_start = header.len();
+        header.push(HeaderLayout::V3.version_tag());
+        // Compact app lookup of size 4 with app 1 starting at chunk 1.
+        header.extend(Compact(4u32).encode());
+        header.extend(vec![(Compact(1u32), Compact(1u32))].encode());
+        encode_commitment_dimensions(&mut header);
+        header.extend(vec![7u8; 2 * KZG_COMMITMENT_SIZE].encode());
+        header.extend(DATA_ROOT);
+
+        assert_eq!(get_digest_start(&header).unwrap(), 32 + 4 + 32 + 32);
+        assert_eq!(get_extension_start(&header).unwrap(), extension_start);
+        assert_eq!(
+            HeaderLayout::from_encoded_header(&header).unwrap(),
+            HeaderLayout::V3
+        );
+        assert_eq!(
+            HeaderLayout::V3.decode_data_root(&header).unwrap(),
+            H256::from(DATA_ROOT)
+        );
+        assert!(HeaderLayout::V2.check(&header).is_err());
+        assert!(HeaderLayout::V1.decode_data_root(&header).is_err());
+
+        let extension = HeaderLayout::V3.decode_extension(&header).unwrap();
+        assert_eq!(extension.app_lookup_size, 4);
+        assert_eq!(
+            extension.app_lookup,
+            vec![AppLookupItem {
+                app_id: 1,
+                start: 1
+            }]
+        );
+        assert_eq!((extension.rows, extension.cols), (1, 256));
+        assert_eq!(
+            extension.kzg_commitments().unwrap(),
+            vec![[7u8; KZG_COMMITMENT_SIZE]; 2]
+        );
+        // Trailing bytes after the data root are rejected.
+        header.push(0);
+        assert!(HeaderLayout::V3.decode_extension(&header).is_err());
+    }
+
+    #[test]
+    fn test_decode_v1_header() {
+        let mut header = encode_header_prefix(1);
+        header.push(HeaderLayout::V1.version_tag());
+        encode_commitment_dimensions(&mut header);
+        header.extend(DATA_ROOT);
+        header.extend(vec![7u8; KZG_COMMITMENT_SIZE].encode());
+        // App lookup of size 1 with no index.
+        header.extend(1u32.encode());
+        header.extend(Vec::<(u32, u32)>::new().encode());
+
+        assert_eq!(get_digest_start(&header).unwrap(), 32 + 1 + 32 + 32);
+        assert_eq!(
+            HeaderLayout::from_encoded_header(&header).unwrap(),
+            HeaderLayout::V1
+        );
+        assert_eq!(HeaderLayout::V1.data_root_offset_from_end(), None);
+        assert_eq!(
+            HeaderLayout::V1.decode_data_root(&header).unwrap(),
+            H256::from(DATA_ROOT)
+        );
+        assert!(CURRENT_HEADER_LAYOUT.check(&header).is_err());
+
+        let extension = HeaderLayout::V1.decode_extension(&header).unwrap();
+        assert_eq!(extension.app_lookup_size, 1);
+        assert!(extension.app_lookup.is_empty());
+        assert_eq!(extension.kzg_commitments().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(feature = "ci", ignore)]
+    async fn test_decode_header_layout() {
+        let fetcher = RpcDataFetcher::new().await;
+        let header = fetcher.get_header(100_000).await;
+        let encoded_header = header.encode();
+
+        assert_eq!(
+            HeaderLayout::from_encoded_header(&encoded_header).unwrap(),
+            CURRENT_HEADER_LAYOUT
+        );
+        assert_eq!(
+            CURRENT_HEADER_LAYOUT
+                .decode_data_root(&encoded_header)
+                .unwrap()
+                .0,
+            header.data_root().0
+        );
+
+        let extension = CURRENT_HEADER_LAYOUT
+            .decode_extension(&encoded_header)
+            .unwrap();
+        assert!(!extension.kzg_commitments().unwrap().is_empty());
+        extension.kzg_commitments_root().unwrap();
+    }
+}
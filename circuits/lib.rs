@@ -1,11 +1,30 @@
 #![allow(clippy::needless_range_loop)]
 #![allow(clippy::too_many_arguments)]
 
+pub mod authority_set;
 pub mod builder;
 pub mod consts;
+pub mod cost;
+#[cfg(test)]
+mod differential;
 pub mod dummy_header_range;
 pub mod dummy_rotate;
+pub mod evm;
+pub mod genesis;
+pub mod header_ancestry;
 pub mod header_range;
+pub mod header_range_aggregation;
+pub mod header_range_kzg;
+pub mod header_range_subset;
+pub mod inclusion;
 pub mod input;
+pub mod layout;
+pub mod mock_chain;
+pub mod multi_rotate;
+pub mod native;
 pub mod rotate;
+pub mod rotate_header_range;
+pub mod synthetic;
+pub mod tiers;
 pub mod vars;
+pub mod witness;
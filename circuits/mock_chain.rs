@@ -0,0 +1,359 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, ensure, Error};
+use codec::Encode;
+use ethers::types::H256;
+use sp_core::{blake2_256, twox_128};
+
+use crate::synthetic::SyntheticChain;
+use crate::witness::{WitnessBundle, WitnessJustification};
+
+/// The storage key of `Grandpa::CurrentSetId`: twox128("Grandpa") || twox128("CurrentSetId").
+pub fn current_set_id_storage_key() -> Vec<u8> {
+    let mut key = twox_128(b"Grandpa").to_vec();
+    key.extend(twox_128(b"CurrentSetId"));
+    key
+}
+
+/// The chain a mock Avail node serves: headers, authority sets and justifications. Built from a
+/// synthetic chain, or from a recorded chain in a witness bundle.
+#[derive(Clone, Debug, Default)]
+pub struct MockChain {
+    /// SCALE encoded headers by block number.
+    headers: BTreeMap<u32, Vec<u8>>,
+    block_numbers: HashMap<H256, u32>,
+    /// The authority set id in the state of the first block.
+    first_authority_set_id: u64,
+    /// Epoch end blocks by the authority set id of the epoch ending at the block.
+    epoch_end_blocks: BTreeMap<u64, u32>,
+    /// Authority pubkeys by authority set id.
+    authorities: BTreeMap<u64, Vec<H256>>,
+    /// Simple justifications by block number.
+    justifications: BTreeMap<u32, WitnessJustification>,
+}
+
+impl MockChain {
+    /// Serve the synthetic chain, with a justification for every block after the first.
+    pub fn from_synthetic(chain: &SyntheticChain) -> Self {
+        let mut mock_chain = Self::default();
+        for block_number in chain.start_block()..=chain.end_block() {
+            mock_chain.insert_header(block_number, chain.header(block_number).to_vec());
+        }
+        for authority_set_id in 0..=chain.authority_set_id(chain.end_block()) {
+            mock_chain.authorities.insert(
+                authority_set_id,
+                chain
+                    .authorities(authority_set_id)
+                    .iter()
+                    .map(|pubkey| H256::from(pubkey.0))
+                    .collect(),
+            );
+            if chain.epoch_end_block(authority_set_id) <= chain.end_block() {
+                mock_chain
+                    .epoch_end_blocks
+                    .insert(authority_set_id, chain.epoch_end_block(authority_set_id));
+            }
+        }
+        for block_number in chain.start_block() + 1..=chain.end_block() {
+            mock_chain
+                .justifications
+                .insert(block_number, chain.justification(block_number));
+        }
+        mock_chain
+    }
+
+    /// Serve the recorded chain in a witness bundle. The authority sets are those of the bundle's
+    /// justifications and rotates, and the bundle's headers must be contiguous.
+    pub fn from_witness_bundle(bundle: &WitnessBundle) -> Result<Self, Error> {
+        let mut mock_chain = Self::default();
+        for (block_number, header) in bundle.headers.iter() {
+            mock_chain.insert_header(*block_number, header.to_vec());
+        }
+        let (Some(start_block), Some(end_block)) = (
+            mock_chain.headers.keys().next().copied(),
+            mock_chain.headers.keys().last().copied(),
+        ) else {
+            return Err(anyhow!("The witness bundle has no headers"));
+        };
+        ensure!(
+            mock_chain.headers.len() as u32 == end_block - start_block + 1,
+            "The witness bundle's headers are not contiguous"
+        );
+
+        for (authority_set_id, rotate) in bundle.rotates.iter() {
+            mock_chain
+                .epoch_end_blocks
+                .insert(*authority_set_id, rotate.epoch_end_block_number);
+            mock_chain
+                .authorities
+                .insert(authority_set_id + 1, rotate.pubkeys.clone());
+        }
+        for (block_number, justification) in bundle.justifications.iter() {
+            mock_chain
+                .authorities
+                .entry(justification.authority_set_id)
+                .or_insert_with(|| justification.pubkeys.clone());
+            mock_chain
+                .justifications
+                .insert(*block_number, justification.clone());
+        }
+
+        // The authority set id of the first block is the id of the earliest justification or
+        // rotate, less the epoch end blocks between the first block and it.
+        let earliest = bundle
+            .justifications
+            .iter()
+            .map(|(block_number, justification)| (block_number - 1, justification.authority_set_id))
+            .chain(
+                bundle
+                    .rotates
+                    .iter()
+                    .map(|(id, rotate)| (rotate.epoch_end_block_number - 1, *id)),
+            )
+            .min()
+            .ok_or_else(|| anyhow!("The witness bundle has no justifications or rotates"))?;
+        let num_epoch_ends = mock_chain
+            .epoch_end_blocks
+            .values()
+            .filter(|&&block_number| block_number > start_block && block_number <= earliest.0)
+            .count() as u64;
+        mock_chain.first_authority_set_id = earliest.1 - num_epoch_ends;
+
+        Ok(mock_chain)
+    }
+
+    fn insert_header(&mut self, block_number: u32, header: Vec<u8>) {
+        self.block_numbers
+            .insert(H256::from(blake2_256(&header)), block_number);
+        self.headers.insert(block_number, header);
+    }
+
+    pub fn start_block(&self) -> u32 {
+        *self.headers.keys().next().unwrap()
+    }
+
+    pub fn end_block(&self) -> u32 {
+        *self.headers.keys().last().unwrap()
+    }
+
+    pub fn header(&self, block_number: u32) -> Option<&[u8]> {
+        self.headers.get(&block_number).map(|header| &header[..])
+    }
+
+    pub fn block_hash(&self, block_number: u32) -> Option<H256> {
+        self.header(block_number)
+            .map(|header| H256::from(blake2_256(header)))
+    }
+
+    pub fn block_number(&self, block_hash: H256) -> Option<u32> {
+        self.block_numbers.get(&block_hash).copied()
+    }
+
+    /// The authority set id in block_number's state. Epoch end blocks have the new authority set id.
+    pub fn authority_set_id(&self, block_number: u32) -> u64 {
+        let num_epoch_ends = self
+            .epoch_end_blocks
+            .values()
+            .filter(|&&epoch_end_block| {
+                epoch_end_block > self.start_block() && epoch_end_block <= block_number
+            })
+            .count() as u64;
+        self.first_authority_set_id + num_epoch_ends
+    }
+
+    /// The epoch end block of authority_set_id, if it is in the chain.
+    pub fn epoch_end_block(&self, authority_set_id: u64) -> Option<u32> {
+        self.epoch_end_blocks.get(&authority_set_id).copied()
+    }
+
+    /// The GRANDPA authorities in block_number's state, if known.
+    pub fn authorities(&self, block_number: u32) -> Option<&[H256]> {
+        self.authorities
+            .get(&self.authority_set_id(block_number))
+            .map(|pubkeys| &pubkeys[..])
+    }
+
+    /// The simple justification of block_number, if any.
+    pub fn justification(&self, block_number: u32) -> Option<&WitnessJustification> {
+        self.justifications.get(&block_number)
+    }
+
+    /// The blocks with a justification, in order.
+    pub fn justified_blocks(&self) -> Vec<u32> {
+        self.justifications.keys().copied().collect()
+    }
+
+    /// The value of `Grandpa::CurrentSetId` in block_number's state, SCALE encoded.
+    pub fn encode_current_set_id(&self, block_number: u32) -> Vec<u8> {
+        self.authority_set_id(block_number).encode()
+    }
+
+    /// The result of the `GrandpaApi_grandpa_authorities` runtime call at block_number, a SCALE
+    /// encoded Vec<(pubkey, weight)>.
+    pub fn encode_grandpa_authorities(&self, block_number: u32) -> Option<Vec<u8>> {
+        let authorities = self.authorities(block_number)?;
+        Some(
+            authorities
+                .iter()
+                .map(|pubkey| (pubkey.0, 1u64))
+                .collect::<Vec<_>>()
+                .encode(),
+        )
+    }
+
+    /// The SCALE encoded GrandpaJustification of block_number, with a precommit from each signer.
+    pub fn encode_justification(&self, block_number: u32) -> Option<Vec<u8>> {
+        let justification = self.justifications.get(&block_number)?;
+
+        // Encoded precommit: 1u8 || block hash || block number || round || authority set id.
+        let signed_message = &justification.signed_message;
+        let target_hash: [u8; 32] = signed_message[1..33].try_into().unwrap();
+        let target_number = u32::from_le_bytes(signed_message[33..37].try_into().unwrap());
+        let round = u64::from_le_bytes(signed_message[37..45].try_into().unwrap());
+
+        let precommits = (0..justification.pubkeys.len())
+            .filter(|&i| justification.validator_signed[i])
+            .map(|i| {
+                let signature: [u8; 64] = justification.signatures[i].as_ref().try_into().unwrap();
+                (
+                    (target_hash, target_number),
+                    signature,
+                    justification.pubkeys[i].0,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // The justification has no votes ancestries.
+        let votes_ancestries: Vec<Vec<u8>> = Vec::new();
+        Some(
+            (
+                round,
+                (target_hash, target_number, precommits),
+                votes_ancestries,
+            )
+                .encode(),
+        )
+    }
+
+    /// The SCALE encoded FinalityProof served by `grandpa_proveFinality` for block_number.
+    pub fn encode_finality_proof(&self, block_number: u32) -> Option<Vec<u8>> {
+        let justification = self.encode_justification(block_number)?;
+        let block_hash = self.block_hash(block_number)?;
+        let unknown_headers: Vec<Vec<u8>> = Vec::new();
+        Some((block_hash.0, justification, unknown_headers).encode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use avail_subxt::primitives::Header;
+    use codec::Decode;
+
+    use super::*;
+    use crate::input::types::{FinalityProof, GrandpaJustification};
+    use crate::synthetic::SyntheticChainConfig;
+
+    fn get_synthetic_chain() -> SyntheticChain {
+        SyntheticChain::new(SyntheticChainConfig {
+            start_block: 100,
+            num_blocks: 40,
+            epoch_length: 10,
+            authority_set_sizes: vec![3, 5],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_current_set_id_storage_key() {
+        assert_eq!(
+            hex::encode(current_set_id_storage_key()),
+            "5f9cc45b7a00c5899361e1c6099678dc8a2d09463effcc78a22d75b9cb87dffc"
+        );
+    }
+
+    #[test]
+    fn test_mock_chain_from_synthetic() {
+        let chain = get_synthetic_chain();
+        let mock_chain = MockChain::from_synthetic(&chain);
+
+        for block_number in chain.start_block()..=chain.end_block() {
+            let block_hash = mock_chain.block_hash(block_number).unwrap();
+            assert_eq!(block_hash, chain.header_hash(block_number));
+            assert_eq!(mock_chain.block_number(block_hash), Some(block_number));
+            assert_eq!(
+                mock_chain.authority_set_id(block_number),
+                chain.authority_set_id(block_number)
+            );
+            let header = Header::decode(&mut mock_chain.header(block_number).unwrap()).unwrap();
+            assert_eq!(header.number, block_number);
+        }
+
+        let authorities = Vec::<([u8; 32], u64)>::decode(
+            &mut &mock_chain.encode_grandpa_authorities(115).unwrap()[..],
+        )
+        .unwrap();
+        assert_eq!(
+            authorities
+                .iter()
+                .map(|(pubkey, _)| *pubkey)
+                .collect::<Vec<_>>(),
+            chain
+                .authorities(1)
+                .iter()
+                .map(|pubkey| pubkey.0)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            u64::decode(&mut &mock_chain.encode_current_set_id(110)[..]).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_mock_chain_finality_proof() {
+        let chain = get_synthetic_chain();
+        let mock_chain = MockChain::from_synthetic(&chain);
+
+        let finality_proof =
+            FinalityProof::decode(&mut &mock_chain.encode_finality_proof(120).unwrap()[..])
+                .unwrap();
+        assert_eq!(finality_proof.block.0, chain.header_hash(120).0);
+        let justification =
+            GrandpaJustification::decode(&mut &finality_proof.justification[..]).unwrap();
+        assert_eq!(justification.round, 1);
+        assert_eq!(justification.commit.target_number, 120);
+        assert_eq!(justification.commit.target_hash.0, chain.header_hash(120).0);
+
+        // The first get_num_justification_signers(5) authorities of authority set 1 sign.
+        let witness_justification = chain.justification(120);
+        assert_eq!(justification.commit.precommits.len(), 5);
+        for (i, precommit) in justification.commit.precommits.iter().enumerate() {
+            assert_eq!(precommit.id.0, witness_justification.pubkeys[i].0);
+            assert_eq!(
+                precommit.signature.0.to_vec(),
+                witness_justification.signatures[i].to_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_mock_chain_from_witness_bundle() {
+        let chain = get_synthetic_chain();
+        let bundle = chain.witness_bundle(&[105, 125]);
+        let mock_chain = MockChain::from_witness_bundle(&bundle).unwrap();
+
+        assert_eq!(mock_chain.start_block(), chain.start_block());
+        assert_eq!(mock_chain.end_block(), chain.end_block());
+        for block_number in chain.start_block()..=chain.end_block() {
+            assert_eq!(
+                mock_chain.authority_set_id(block_number),
+                chain.authority_set_id(block_number)
+            );
+        }
+        assert_eq!(mock_chain.justified_blocks(), vec![105, 110, 120, 125, 130]);
+        assert_eq!(
+            mock_chain.authorities(125).unwrap(),
+            &bundle.justifications[&125].pubkeys[..]
+        );
+    }
+}
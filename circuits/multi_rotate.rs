@@ -0,0 +1,127 @@
+use plonky2x::backend::circuit::Circuit;
+use plonky2x::frontend::uint::uint64::U64Variable;
+use plonky2x::prelude::{Bytes32Variable, CircuitBuilder, PlonkParameters, VariableStream};
+
+use crate::builder::justification::HintSimpleJustification;
+use crate::builder::rotate::RotateMethods;
+use crate::rotate::RotateHint;
+use crate::vars::RotateVariable;
+
+/// Applies NUM_EPOCHS consecutive rotates starting from authority_set_id, so the light client can be
+/// bootstrapped or recovered across many epochs with a single proof. Outputs the final authority
+/// set id, followed by the hashes of authority sets authority_set_id + 1 to
+/// authority_set_id + NUM_EPOCHS in order. The last hash is the hash of the final authority set.
+#[derive(Clone, Debug)]
+pub struct MultiRotateCircuit<
+    const MAX_AUTHORITY_SET_SIZE: usize,
+    const MAX_HEADER_SIZE: usize,
+    const MAX_SUBARRAY_SIZE: usize,
+    const NUM_EPOCHS: usize,
+> {}
+
+impl<
+        const MAX_AUTHORITY_SET_SIZE: usize,
+        const MAX_HEADER_SIZE: usize,
+        const MAX_SUBARRAY_SIZE: usize,
+        const NUM_EPOCHS: usize,
+    > Circuit
+    for MultiRotateCircuit<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE, NUM_EPOCHS>
+{
+    fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+    where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        // Read the on-chain inputs. The validators that signed the first epoch end block are
+        // defined by authority_set_id and authority_set_hash.
+        let authority_set_id = builder.evm_read::<U64Variable>();
+        let authority_set_hash = builder.evm_read::<Bytes32Variable>();
+
+        let one = builder.constant::<U64Variable>(1u64);
+
+        let mut current_authority_set_id = authority_set_id;
+        let mut current_authority_set_hash = authority_set_hash;
+        let mut authority_set_hashes = Vec::new();
+        for _ in 0..NUM_EPOCHS {
+            // Fetch the data for the rotate of current_authority_set_id.
+            let rotate_fetcher = RotateHint::<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE> {};
+            let mut input_stream = VariableStream::new();
+            input_stream.write(&current_authority_set_id);
+            let output_stream = builder.async_hint(input_stream, rotate_fetcher);
+
+            // rotate_var is untrusted and is linked to the current authority set in rotate.
+            let rotate_var = output_stream
+                .read::<RotateVariable<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE>>(builder);
+
+            // Note: current_authority_set_id and current_authority_set_hash are trusted at this
+            // point, as they are either the public inputs or the output of the previous rotate.
+            let next_authority_set_hash = builder
+                .rotate::<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE, MAX_SUBARRAY_SIZE>(
+                    current_authority_set_id,
+                    current_authority_set_hash,
+                    rotate_var,
+                );
+            authority_set_hashes.push(next_authority_set_hash);
+
+            current_authority_set_id = builder.add(current_authority_set_id, one);
+            current_authority_set_hash = next_authority_set_hash;
+        }
+
+        builder.evm_write::<U64Variable>(current_authority_set_id);
+        for authority_set_hash in authority_set_hashes {
+            builder.evm_write::<Bytes32Variable>(authority_set_hash);
+        }
+    }
+
+    fn register_generators<L: PlonkParameters<D>, const D: usize>(
+        generator_registry: &mut plonky2x::prelude::HintRegistry<L, D>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        generator_registry
+            .register_async_hint::<RotateHint<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE>>();
+        generator_registry.register_async_hint::<HintSimpleJustification<MAX_AUTHORITY_SET_SIZE>>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use plonky2x::prelude::{DefaultBuilder, GateRegistry, HintRegistry};
+
+    use super::*;
+    use crate::consts::{DELAY_LENGTH, MAX_HEADER_SIZE, VALIDATOR_LENGTH};
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_multi_rotate_serialization() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        const NUM_AUTHORITIES: usize = 4;
+        const MAX_SUBARRAY_SIZE: usize = NUM_AUTHORITIES * VALIDATOR_LENGTH + DELAY_LENGTH;
+        const NUM_EPOCHS: usize = 2;
+
+        let mut builder = DefaultBuilder::new();
+
+        log::debug!("Defining circuit");
+        MultiRotateCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE, NUM_EPOCHS>::define(
+            &mut builder,
+        );
+        let circuit = builder.build();
+        log::debug!("Done building circuit");
+
+        let mut hint_registry = HintRegistry::new();
+        let mut gate_registry = GateRegistry::new();
+        MultiRotateCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE, NUM_EPOCHS>::register_generators(
+            &mut hint_registry,
+        );
+        MultiRotateCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE, NUM_EPOCHS>::register_gates(
+            &mut gate_registry,
+        );
+
+        circuit.test_serializers(&gate_registry, &hint_registry);
+    }
+}
@@ -0,0 +1,750 @@
+use anyhow::{anyhow, ensure, Error};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use ethers::types::H256;
+use plonky2x::frontend::curta::ec::point::CompressedEdwardsY;
+use sp_core::blake2_256;
+
+use crate::authority_set::AuthoritySetCommitmentMode;
+use crate::consts::{
+    CONSENSUS_ENGINE_ID_PREFIX_LENGTH, DELAY_LENGTH, ENCODED_PRECOMMIT_LENGTH, HASH_SIZE,
+    HEADERS_PER_MAP, MAX_COMPACT_UINT_BYTES, MAX_NUM_DIGEST_LOGS, PUBKEY_LENGTH, VALIDATOR_LENGTH,
+};
+use crate::input::{compute_authority_set_hash_with_mode, RpcDataFetcher};
+use crate::layout::CURRENT_HEADER_LAYOUT;
+use crate::witness::WitnessJustification;
+
+// Native reference implementation of the statements proven by the header range and rotate circuits.
+// Every function enforces the same checks as the corresponding circuit gadget, and returns an error
+// where the circuit would fail to prove. Use them as the specification of the circuits, and to
+// validate inputs before requesting a proof.
+
+/// The header fields decoded by `decode_header`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedHeader {
+    pub block_number: u32,
+    pub parent_hash: H256,
+    pub state_root: H256,
+    pub data_root: H256,
+}
+
+/// The fields of an encoded precommit decoded by `decode_precommit`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedPrecommit {
+    pub block_hash: H256,
+    pub block_number: u32,
+    pub justification_round: u64,
+    pub authority_set_id: u64,
+}
+
+/// The outputs of the header range circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderRangeOutput {
+    pub target_block: u32,
+    pub target_header_hash: H256,
+    pub state_root_commitment: H256,
+    pub data_root_commitment: H256,
+}
+
+/// The outputs of the rotate circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RotateOutput {
+    pub epoch_end_block: u32,
+    pub epoch_end_header_hash: H256,
+    pub new_pubkeys: Vec<CompressedEdwardsY>,
+    pub new_authority_set_hash: H256,
+}
+
+/// Decode a SCALE compact u32 from the first MAX_COMPACT_UINT_BYTES bytes of compact_bytes (zero
+/// padded if shorter) like `decode_compact_int`, and return the value and the byte length of the
+/// encoding. As in the circuit, a mode 3 encoding must have a 4 byte value, and non-canonical
+/// encodings are accepted.
+pub fn decode_compact_u32(compact_bytes: &[u8]) -> Result<(u32, usize), Error> {
+    let mut bytes = [0u8; MAX_COMPACT_UINT_BYTES];
+    let len = compact_bytes.len().min(MAX_COMPACT_UINT_BYTES);
+    bytes[..len].copy_from_slice(&compact_bytes[..len]);
+
+    let value = u64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], 0, 0, 0]);
+    match bytes[0] & 0b11 {
+        0 => Ok(((value as u32 & 0xff) >> 2, 1)),
+        1 => Ok(((value as u32 & 0xffff) >> 2, 2)),
+        2 => Ok(((value & 0xffff_ffff) as u32 >> 2, 4)),
+        _ => {
+            ensure!(
+                bytes[0] >> 2 == 0,
+                "Compact int with mode 3 must have a 4 byte value"
+            );
+            Ok(((value >> 8) as u32, 5))
+        }
+    }
+}
+
+/// Get the start of the header extension of an encoded header like `get_extension_start`, by
+/// decoding its block number and at most MAX_NUM_DIGEST_LOGS digest logs. As in the circuit, bytes
+/// past the end of the header are read as zeros.
+pub fn get_extension_start(header: &[u8]) -> Result<usize, Error> {
+    let bytes_at = |start: usize| header.get(start..).unwrap_or_default();
+
+    let (_, block_number_length) = decode_compact_u32(bytes_at(HASH_SIZE))?;
+    let digest_start = HASH_SIZE + block_number_length + 2 * HASH_SIZE;
+    let (num_logs, num_logs_length) = decode_compact_u32(bytes_at(digest_start))?;
+    ensure!(
+        num_logs as usize <= MAX_NUM_DIGEST_LOGS,
+        "Header has {} digest logs, more than the maximum of {}",
+        num_logs,
+        MAX_NUM_DIGEST_LOGS
+    );
+
+    let mut cursor = digest_start + num_logs_length;
+    for _ in 0..num_logs {
+        let variant = bytes_at(cursor).first().copied().unwrap_or_default();
+        cursor += match variant {
+            // Other.
+            0 => {
+                let (data_length, compact_length) = decode_compact_u32(bytes_at(cursor + 1))?;
+                1 + compact_length + data_length as usize
+            }
+            // Consensus, Seal and PreRuntime.
+            4..=6 => {
+                let (data_length, compact_length) = decode_compact_u32(bytes_at(cursor + 5))?;
+                5 + compact_length + data_length as usize
+            }
+            // RuntimeEnvironmentUpdated.
+            8 => 1,
+            _ => return Err(anyhow!("Unknown digest log variant {}", variant)),
+        };
+    }
+    Ok(cursor)
+}
+
+/// Check the header extension of an encoded header starts with CURRENT_HEADER_LAYOUT's version tag
+/// like `assert_header_layout`.
+pub fn check_header_layout(header: &[u8]) -> Result<(), Error> {
+    let extension_start = get_extension_start(header)?;
+    ensure!(
+        header.get(extension_start) == Some(&CURRENT_HEADER_LAYOUT.version_tag()),
+        "Header extension does not have the version tag of {:?}",
+        CURRENT_HEADER_LAYOUT
+    );
+    Ok(())
+}
+
+/// Decode the parent hash, block number, state root and data root of an encoded header like
+/// `decode_header`. The data root is at CURRENT_HEADER_LAYOUT's offset from the end of the header.
+/// As in the circuit, the version tag of the header extension is not checked.
+pub fn decode_header(header: &[u8]) -> Result<DecodedHeader, Error> {
+    let data_root_offset_from_end = CURRENT_HEADER_LAYOUT
+        .data_root_offset_from_end()
+        .ok_or_else(|| anyhow!("Header layout does not have the data root at a fixed offset"))?;
+
+    ensure!(
+        header.len() >= HASH_SIZE + MAX_COMPACT_UINT_BYTES + HASH_SIZE,
+        "Header is too short: {} bytes",
+        header.len()
+    );
+    ensure!(
+        header.len() >= data_root_offset_from_end,
+        "Header is too short for the data root: {} bytes",
+        header.len()
+    );
+
+    let parent_hash = H256::from_slice(&header[..HASH_SIZE]);
+    let (block_number, compact_length) = decode_compact_u32(&header[HASH_SIZE..])?;
+    let state_root_start = HASH_SIZE + compact_length;
+    let state_root = H256::from_slice(&header[state_root_start..state_root_start + HASH_SIZE]);
+    let data_root_start = header.len() - data_root_offset_from_end;
+    let data_root = H256::from_slice(&header[data_root_start..data_root_start + HASH_SIZE]);
+
+    Ok(DecodedHeader {
+        block_number,
+        parent_hash,
+        state_root,
+        data_root,
+    })
+}
+
+/// Decode an encoded precommit like `decode_precommit`. The first byte must be 1 (Precommit).
+pub fn decode_precommit(precommit: &[u8]) -> Result<DecodedPrecommit, Error> {
+    ensure!(
+        precommit.len() == ENCODED_PRECOMMIT_LENGTH,
+        "Encoded precommit must be {} bytes, got {}",
+        ENCODED_PRECOMMIT_LENGTH,
+        precommit.len()
+    );
+    ensure!(precommit[0] == 1, "Encoded precommit is not a precommit");
+
+    Ok(DecodedPrecommit {
+        block_hash: H256::from_slice(&precommit[1..33]),
+        block_number: u32::from_le_bytes(precommit[33..37].try_into().unwrap()),
+        justification_round: u64::from_le_bytes(precommit[37..45].try_into().unwrap()),
+        authority_set_id: u64::from_le_bytes(precommit[45..53].try_into().unwrap()),
+    })
+}
+
+/// Verify a simple justification on block_number with block_hash by the authority set with
+/// authority_set_id and authority_set_hash, like `verify_simple_justification_with_mode`:
+///     1) The authority set commitment of the justification's pubkeys is authority_set_hash.
+///     2) The precommit matches the block number, authority set id and block hash.
+///     3) The signature of each authority marked as signed on the precommit is valid.
+///     4) More than 2/3 of the authorities signed.
+pub fn verify_justification(
+    block_number: u32,
+    block_hash: H256,
+    authority_set_id: u64,
+    authority_set_hash: H256,
+    justification: &WitnessJustification,
+    mode: AuthoritySetCommitmentMode,
+) -> Result<(), Error> {
+    let num_authorities = justification.pubkeys.len();
+    ensure!(num_authorities > 0, "Justification has no authorities");
+    ensure!(
+        justification.signatures.len() == num_authorities
+            && justification.validator_signed.len() == num_authorities,
+        "Justification has {} pubkeys, {} signatures and {} signed flags",
+        num_authorities,
+        justification.signatures.len(),
+        justification.validator_signed.len()
+    );
+
+    let pubkeys = justification
+        .pubkeys
+        .iter()
+        .map(|pubkey| CompressedEdwardsY(pubkey.0))
+        .collect::<Vec<_>>();
+    let commitment = compute_authority_set_hash_with_mode(&pubkeys, mode);
+    ensure!(
+        commitment == authority_set_hash,
+        "Authority set commitment {:?} does not match the authority set hash {:?}",
+        commitment,
+        authority_set_hash
+    );
+
+    let precommit = decode_precommit(&justification.signed_message)?;
+    ensure!(
+        precommit.block_number == block_number,
+        "Precommit is for block {}, expected {}",
+        precommit.block_number,
+        block_number
+    );
+    ensure!(
+        precommit.authority_set_id == authority_set_id,
+        "Precommit is for authority set {}, expected {}",
+        precommit.authority_set_id,
+        authority_set_id
+    );
+    ensure!(
+        precommit.block_hash == block_hash,
+        "Precommit is for block hash {:?}, expected {:?}",
+        precommit.block_hash,
+        block_hash
+    );
+
+    let mut num_signed = 0;
+    for (i, signed) in justification.validator_signed.iter().enumerate() {
+        if !signed {
+            continue;
+        }
+        let pubkey = VerifyingKey::from_bytes(&justification.pubkeys[i].0)
+            .map_err(|e| anyhow!("Authority {} has an invalid pubkey: {}", i, e))?;
+        let signature: [u8; 64] = justification.signatures[i]
+            .as_ref()
+            .try_into()
+            .map_err(|_| anyhow!("Authority {}'s signature is not 64 bytes", i))?;
+        pubkey
+            .verify(
+                &justification.signed_message,
+                &Signature::from_bytes(&signature),
+            )
+            .map_err(|_| anyhow!("Authority {}'s signature is not valid", i))?;
+        num_signed += 1;
+    }
+
+    // All authorities have a voting power of 1 in Avail.
+    ensure!(
+        num_signed * 3 > num_authorities * 2,
+        "Only {} of {} authorities signed, more than 2/3 must sign",
+        num_signed,
+        num_authorities
+    );
+    Ok(())
+}
+
+/// Verify the headers link from trusted_header_hash and compute the header range outputs for the
+/// range [trusted_block + 1, trusted_block + headers.len()], like `verify_subchain`. headers[i] is
+/// the encoded header of trusted_block + 1 + i. The commitments are the Merkle roots of the state
+/// and data roots, padded with zero leaves to the header range circuit's number of leaves for
+/// MAX_NUM_HEADERS.
+pub fn verify_subchain<const MAX_NUM_HEADERS: usize>(
+    trusted_block: u32,
+    trusted_header_hash: H256,
+    headers: &[Vec<u8>],
+) -> Result<HeaderRangeOutput, Error> {
+    ensure!(!headers.is_empty(), "Header range must have a header");
+    ensure!(
+        headers.len() <= MAX_NUM_HEADERS,
+        "Header range has {} headers, more than {}",
+        headers.len(),
+        MAX_NUM_HEADERS
+    );
+
+    let mut parent_hash = trusted_header_hash;
+    let mut state_root_leaves = Vec::new();
+    let mut data_root_leaves = Vec::new();
+    for (i, header) in headers.iter().enumerate() {
+        let expected_block_number = trusted_block
+            .checked_add(i as u32 + 1)
+            .ok_or_else(|| anyhow!("Block number overflows"))?;
+        let decoded = decode_header(header)?;
+        ensure!(
+            decoded.block_number == expected_block_number,
+            "Header {} has block number {}, expected {}",
+            i,
+            decoded.block_number,
+            expected_block_number
+        );
+        ensure!(
+            decoded.parent_hash == parent_hash,
+            "Block {}'s parent hash {:?} does not match the previous header hash {:?}",
+            expected_block_number,
+            decoded.parent_hash,
+            parent_hash
+        );
+        parent_hash = H256::from(blake2_256(header));
+        state_root_leaves.push(decoded.state_root.0.to_vec());
+        data_root_leaves.push(decoded.data_root.0.to_vec());
+    }
+
+    // The map jobs of the header range circuit cover a power of 2 number of batches.
+    let num_leaves = (MAX_NUM_HEADERS / HEADERS_PER_MAP).next_power_of_two() * HEADERS_PER_MAP;
+    state_root_leaves.resize(num_leaves, vec![0u8; HASH_SIZE]);
+    data_root_leaves.resize(num_leaves, vec![0u8; HASH_SIZE]);
+
+    Ok(HeaderRangeOutput {
+        target_block: trusted_block + headers.len() as u32,
+        target_header_hash: parent_hash,
+        state_root_commitment: H256::from_slice(&RpcDataFetcher::get_merkle_root(
+            state_root_leaves,
+        )),
+        data_root_commitment: H256::from_slice(&RpcDataFetcher::get_merkle_root(data_root_leaves)),
+    })
+}
+
+/// The header range statement: the headers link from the trusted header to the target block, and
+/// the target header has a justification by the authority set with authority_set_id and
+/// authority_set_hash.
+pub fn header_range<const MAX_NUM_HEADERS: usize>(
+    trusted_block: u32,
+    trusted_header_hash: H256,
+    authority_set_id: u64,
+    authority_set_hash: H256,
+    headers: &[Vec<u8>],
+    justification: &WitnessJustification,
+) -> Result<HeaderRangeOutput, Error> {
+    header_range_with_mode::<MAX_NUM_HEADERS>(
+        trusted_block,
+        trusted_header_hash,
+        authority_set_id,
+        authority_set_hash,
+        headers,
+        justification,
+        AuthoritySetCommitmentMode::ChainedSha256,
+    )
+}
+
+/// The header range statement where authority_set_hash uses the given commitment mode.
+pub fn header_range_with_mode<const MAX_NUM_HEADERS: usize>(
+    trusted_block: u32,
+    trusted_header_hash: H256,
+    authority_set_id: u64,
+    authority_set_hash: H256,
+    headers: &[Vec<u8>],
+    justification: &WitnessJustification,
+    mode: AuthoritySetCommitmentMode,
+) -> Result<HeaderRangeOutput, Error> {
+    let output = verify_subchain::<MAX_NUM_HEADERS>(trusted_block, trusted_header_hash, headers)?;
+    verify_justification(
+        output.target_block,
+        output.target_header_hash,
+        authority_set_id,
+        authority_set_hash,
+        justification,
+        mode,
+    )?;
+    Ok(output)
+}
+
+/// Parse the new authority set from the GRANDPA ScheduledChange log at start_position in the epoch
+/// end header, like `verify_epoch_end_header`. start_position is the byte before the log's
+/// Consensus digest item tag, as in `get_header_rotate`.
+pub fn verify_epoch_end_header(
+    header: &[u8],
+    start_position: usize,
+) -> Result<Vec<CompressedEdwardsY>, Error> {
+    let byte_at = |position: usize| -> Result<u8, Error> {
+        header
+            .get(position)
+            .copied()
+            .ok_or_else(|| anyhow!("ScheduledChange log extends past the end of the header"))
+    };
+
+    // The log is a Consensus digest item with the GRANDPA engine id.
+    ensure!(
+        byte_at(start_position + 1)? == 4,
+        "Log at {} is not a consensus log",
+        start_position
+    );
+    ensure!(
+        header.get(start_position + 2..start_position + 6) == Some(b"FRNK".as_slice()),
+        "Log at {} is not a GRANDPA consensus log",
+        start_position
+    );
+
+    // Skip the scheduled change message length, which is not checked, and verify the
+    // ScheduledChange flag.
+    let mut cursor = start_position + CONSENSUS_ENGINE_ID_PREFIX_LENGTH;
+    let (_, message_length_length) = decode_compact_u32(&header[cursor..])?;
+    cursor += message_length_length;
+    ensure!(
+        byte_at(cursor)? == 1,
+        "Log at {} is not a ScheduledChange log",
+        start_position
+    );
+    cursor += 1;
+
+    let (num_authorities, num_authorities_length) = decode_compact_u32(&header[cursor..])?;
+    ensure!(num_authorities > 0, "ScheduledChange has no authorities");
+    cursor += num_authorities_length;
+
+    // All authorities have a voting power of 1 in Avail, and the delay is 0.
+    let end = cursor + num_authorities as usize * VALIDATOR_LENGTH + DELAY_LENGTH;
+    ensure!(
+        end <= header.len(),
+        "ScheduledChange log extends past the end of the header"
+    );
+    let mut new_pubkeys = Vec::new();
+    for (i, validator) in header[cursor..end - DELAY_LENGTH]
+        .chunks_exact(VALIDATOR_LENGTH)
+        .enumerate()
+    {
+        ensure!(
+            validator[PUBKEY_LENGTH..] == [1u8, 0, 0, 0, 0, 0, 0, 0],
+            "Authority {} does not have a weight of 1",
+            i
+        );
+        new_pubkeys.push(CompressedEdwardsY(
+            validator[..PUBKEY_LENGTH].try_into().unwrap(),
+        ));
+    }
+    ensure!(
+        header[end - DELAY_LENGTH..end] == [0u8; DELAY_LENGTH],
+        "ScheduledChange has a non-zero delay"
+    );
+    Ok(new_pubkeys)
+}
+
+/// The rotate statement: the epoch end header has a justification by the current authority set with
+/// authority_set_id and authority_set_hash, and schedules the new authority set.
+pub fn rotate(
+    authority_set_id: u64,
+    authority_set_hash: H256,
+    epoch_end_header: &[u8],
+    start_position: usize,
+    justification: &WitnessJustification,
+) -> Result<RotateOutput, Error> {
+    rotate_with_mode(
+        authority_set_id,
+        authority_set_hash,
+        epoch_end_header,
+        start_position,
+        justification,
+        AuthoritySetCommitmentMode::ChainedSha256,
+    )
+}
+
+/// The rotate statement where the current and new authority set commitments use the given
+/// commitment mode.
+pub fn rotate_with_mode(
+    authority_set_id: u64,
+    authority_set_hash: H256,
+    epoch_end_header: &[u8],
+    start_position: usize,
+    justification: &WitnessJustification,
+    mode: AuthoritySetCommitmentMode,
+) -> Result<RotateOutput, Error> {
+    let epoch_end_block = decode_header(epoch_end_header)?.block_number;
+    check_header_layout(epoch_end_header)?;
+    let epoch_end_header_hash = H256::from(blake2_256(epoch_end_header));
+    verify_justification(
+        epoch_end_block,
+        epoch_end_header_hash,
+        authority_set_id,
+        authority_set_hash,
+        justification,
+        mode,
+    )?;
+
+    let new_pubkeys = verify_epoch_end_header(epoch_end_header, start_position)?;
+    let new_authority_set_hash = compute_authority_set_hash_with_mode(&new_pubkeys, mode);
+    Ok(RotateOutput {
+        epoch_end_block,
+        epoch_end_header_hash,
+        new_pubkeys,
+        new_authority_set_hash,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{Compact, Encode};
+    use ethers::types::Bytes;
+
+    use super::*;
+    use crate::synthetic::{SyntheticChain, SyntheticChainConfig};
+
+    fn chain() -> SyntheticChain {
+        SyntheticChain::new(SyntheticChainConfig {
+            start_block: 100,
+            num_blocks: 40,
+            epoch_length: 16,
+            authority_set_sizes: vec![4, 7],
+            ..Default::default()
+        })
+    }
+
+    fn headers(chain: &SyntheticChain, trusted_block: u32, target_block: u32) -> Vec<Vec<u8>> {
+        (trusted_block + 1..=target_block)
+            .map(|block_number| chain.header(block_number).to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_compact_u32() {
+        for value in [
+            0u32,
+            1,
+            63,
+            64,
+            (1 << 14) - 1,
+            1 << 14,
+            (1 << 30) - 1,
+            1 << 30,
+            u32::MAX,
+        ] {
+            let encoded = Compact(value).encode();
+            assert_eq!(
+                decode_compact_u32(&encoded).unwrap(),
+                (value, encoded.len())
+            );
+        }
+        // A mode 3 encoding of a value larger than 4 bytes.
+        assert!(decode_compact_u32(&[0b111, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_header_range() {
+        let chain = chain();
+        let (trusted_block, target_block) = (101, 112);
+        let authority_set_id = chain.authority_set_id(target_block - 1);
+
+        let output = header_range::<16>(
+            trusted_block,
+            chain.header_hash(trusted_block),
+            authority_set_id,
+            chain.authority_set_hash(authority_set_id),
+            &headers(&chain, trusted_block, target_block),
+            &chain.justification(target_block),
+        )
+        .unwrap();
+
+        let leaves = |root: fn(&SyntheticChain, u32) -> H256| {
+            let mut leaves = (trusted_block + 1..=target_block)
+                .map(|block_number| root(&chain, block_number).0.to_vec())
+                .collect::<Vec<_>>();
+            leaves.resize(16, vec![0u8; 32]);
+            H256::from_slice(&RpcDataFetcher::get_merkle_root(leaves))
+        };
+        assert_eq!(output.target_block, target_block);
+        assert_eq!(output.target_header_hash, chain.header_hash(target_block));
+        assert_eq!(
+            output.state_root_commitment,
+            leaves(SyntheticChain::state_root)
+        );
+        assert_eq!(
+            output.data_root_commitment,
+            leaves(SyntheticChain::data_root)
+        );
+    }
+
+    #[test]
+    fn test_header_range_invalid() {
+        let chain = chain();
+        let (trusted_block, target_block) = (101, 112);
+        let authority_set_id = chain.authority_set_id(target_block - 1);
+        let authority_set_hash = chain.authority_set_hash(authority_set_id);
+        let trusted_header_hash = chain.header_hash(trusted_block);
+        let headers = headers(&chain, trusted_block, target_block);
+        let justification = chain.justification(target_block);
+
+        // Wrong trusted header hash.
+        assert!(header_range::<16>(
+            trusted_block,
+            H256::zero(),
+            authority_set_id,
+            authority_set_hash,
+            &headers,
+            &justification,
+        )
+        .is_err());
+
+        // Missing header, so the chain is not linked and the block numbers are not sequential.
+        let mut missing_header = headers.clone();
+        missing_header.remove(3);
+        assert!(
+            verify_subchain::<16>(trusted_block, trusted_header_hash, &missing_header).is_err()
+        );
+
+        // More headers than the circuit supports.
+        assert!(verify_subchain::<8>(trusted_block, trusted_header_hash, &headers).is_err());
+
+        // Wrong authority set id and hash.
+        assert!(header_range::<16>(
+            trusted_block,
+            trusted_header_hash,
+            authority_set_id + 1,
+            authority_set_hash,
+            &headers,
+            &justification,
+        )
+        .is_err());
+        assert!(header_range::<16>(
+            trusted_block,
+            trusted_header_hash,
+            authority_set_id,
+            chain.authority_set_hash(authority_set_id + 1),
+            &headers,
+            &justification,
+        )
+        .is_err());
+
+        // Justification of a different block.
+        assert!(header_range::<16>(
+            trusted_block,
+            trusted_header_hash,
+            authority_set_id,
+            authority_set_hash,
+            &headers,
+            &chain.justification(target_block - 1),
+        )
+        .is_err());
+
+        // Invalid signature.
+        let mut invalid_signature = justification.clone();
+        let mut signature = invalid_signature.signatures[0].to_vec();
+        signature[0] ^= 1;
+        invalid_signature.signatures[0] = Bytes::from(signature);
+        assert!(verify_justification(
+            target_block,
+            chain.header_hash(target_block),
+            authority_set_id,
+            authority_set_hash,
+            &invalid_signature,
+            AuthoritySetCommitmentMode::ChainedSha256,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_justification_threshold() {
+        // With 6 authorities, 4 signers is exactly 2/3 and 5 signers is more than 2/3.
+        let cases: [(fn(usize) -> usize, bool); 2] = [(|_| 4, false), (|_| 5, true)];
+        for (num_signers, valid) in cases {
+            let chain = SyntheticChain::new(SyntheticChainConfig {
+                authority_set_sizes: vec![6],
+                num_signers,
+                ..Default::default()
+            });
+            let result = verify_justification(
+                10,
+                chain.header_hash(10),
+                0,
+                chain.authority_set_hash(0),
+                &chain.justification(10),
+                AuthoritySetCommitmentMode::ChainedSha256,
+            );
+            assert_eq!(result.is_ok(), valid);
+        }
+    }
+
+    #[test]
+    fn test_rotate() {
+        let chain = chain();
+        for authority_set_id in 0..2 {
+            let rotate_data = chain.rotate(authority_set_id);
+            let epoch_end_block = rotate_data.epoch_end_block_number;
+            let output = super::rotate(
+                authority_set_id,
+                chain.authority_set_hash(authority_set_id),
+                chain.header(epoch_end_block),
+                rotate_data.start_position,
+                &chain.justification(epoch_end_block),
+            )
+            .unwrap();
+            assert_eq!(output.epoch_end_block, epoch_end_block);
+            assert_eq!(output.new_pubkeys, chain.authorities(authority_set_id + 1));
+            assert_eq!(
+                output.new_authority_set_hash,
+                chain.authority_set_hash(authority_set_id + 1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_rotate_invalid() {
+        let chain = chain();
+        let rotate_data = chain.rotate(0);
+        let epoch_end_block = rotate_data.epoch_end_block_number;
+        let header = chain.header(epoch_end_block);
+        let start_position = rotate_data.start_position;
+        let justification = chain.justification(epoch_end_block);
+
+        // Justification by the wrong authority set.
+        assert!(super::rotate(
+            1,
+            chain.authority_set_hash(1),
+            header,
+            start_position,
+            &justification,
+        )
+        .is_err());
+
+        // Wrong start position.
+        assert!(verify_epoch_end_header(header, start_position + 1).is_err());
+        assert!(verify_epoch_end_header(header, 0).is_err());
+
+        // Not a ScheduledChange, wrong weight and non-zero delay.
+        let flag_position = start_position + CONSENSUS_ENGINE_ID_PREFIX_LENGTH + 2;
+        let num_authorities_length = 1;
+        let authorities_start = flag_position + 1 + num_authorities_length;
+        let delay_start = authorities_start + 7 * VALIDATOR_LENGTH;
+        for position in [
+            flag_position,
+            authorities_start + PUBKEY_LENGTH,
+            delay_start,
+        ] {
+            let mut modified = header.to_vec();
+            modified[position] ^= 1;
+            assert!(verify_epoch_end_header(&modified, start_position).is_err());
+        }
+        // The modified header's hash is not justified.
+        let mut modified = header.to_vec();
+        modified[authorities_start] ^= 1;
+        assert!(verify_epoch_end_header(&modified, start_position).is_ok());
+        assert!(super::rotate(
+            0,
+            chain.authority_set_hash(0),
+            &modified,
+            start_position,
+            &justification,
+        )
+        .is_err());
+    }
+}
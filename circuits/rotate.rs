@@ -7,10 +7,12 @@ use plonky2x::prelude::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::authority_set::AuthoritySetCommitmentMode;
 use crate::builder::justification::HintSimpleJustification;
 use crate::builder::rotate::RotateMethods;
 use crate::input::RpcDataFetcher;
 use crate::vars::{EncodedHeader, RotateStruct, RotateVariable};
+use crate::witness::WitnessBundle;
 
 // Get the data for the rotate circuit.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,13 +33,21 @@ impl<
     ) {
         let authority_set_id = input_stream.read_value::<U64Variable>();
 
-        let mut data_fetcher = RpcDataFetcher::new().await;
+        let (epoch_end_block_nb, rotate_data) = match WitnessBundle::from_env() {
+            Some(bundle) => bundle
+                .get_header_rotate::<HEADER_LENGTH, MAX_AUTHORITY_SET_SIZE>(authority_set_id)
+                .expect("Failed to get rotate from the witness bundle"),
+            None => {
+                let mut data_fetcher = RpcDataFetcher::new().await;
 
-        let epoch_end_block_nb = data_fetcher.last_justified_block(authority_set_id).await;
+                let epoch_end_block_nb = data_fetcher.last_justified_block(authority_set_id).await;
 
-        let rotate_data = data_fetcher
-            .get_header_rotate::<HEADER_LENGTH, MAX_AUTHORITY_SET_SIZE>(epoch_end_block_nb)
-            .await;
+                let rotate_data = data_fetcher
+                    .get_header_rotate::<HEADER_LENGTH, MAX_AUTHORITY_SET_SIZE>(epoch_end_block_nb)
+                    .await;
+                (epoch_end_block_nb, rotate_data)
+            }
+        };
 
         // Note: This is a developer sanity check & not necessary for the security of the circuit.
         if rotate_data.num_authorities > MAX_AUTHORITY_SET_SIZE {
@@ -69,21 +79,29 @@ pub struct RotateCircuit<
     const MAX_AUTHORITY_SET_SIZE: usize,
     const MAX_HEADER_SIZE: usize,
     const MAX_SUBARRAY_SIZE: usize,
+    const AUTHORITY_SET_COMMITMENT_MODE: u8 = { AuthoritySetCommitmentMode::CHAINED_SHA256 },
 > {}
 
 impl<
         const MAX_AUTHORITY_SET_SIZE: usize,
         const MAX_HEADER_SIZE: usize,
         const MAX_SUBARRAY_SIZE: usize,
-    > Circuit for RotateCircuit<MAX_AUTHORITY_SET_SIZE, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE>
+        const AUTHORITY_SET_COMMITMENT_MODE: u8,
+    > Circuit
+    for RotateCircuit<
+        MAX_AUTHORITY_SET_SIZE,
+        MAX_HEADER_SIZE,
+        MAX_SUBARRAY_SIZE,
+        AUTHORITY_SET_COMMITMENT_MODE,
+    >
 {
     fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
     where
         <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
         plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
     {
-        // Read the on-chain inputs. The validators that signed epoch_end_block_number are defined
-        // by authority_set_id and authority_set_hash.
+        // Read the on-chain inputs, encoded as evm::RotateRequest. The validators that signed
+        // epoch_end_block_number are defined by authority_set_id and authority_set_hash.
         let authority_set_id = builder.evm_read::<U64Variable>();
         let authority_set_hash = builder.evm_read::<Bytes32Variable>();
 
@@ -98,10 +116,12 @@ impl<
             output_stream.read::<RotateVariable<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE>>(builder);
 
         let new_authority_set_hash = builder
-            .rotate::<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE, MAX_SUBARRAY_SIZE>(
+            .rotate_with_mode::<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE, MAX_SUBARRAY_SIZE>(
                 authority_set_id,
                 authority_set_hash,
                 rotate_var,
+                AuthoritySetCommitmentMode::from_id(AUTHORITY_SET_COMMITMENT_MODE)
+                    .expect("Unknown authority set commitment mode"),
             );
 
         // Write the hash of the new authority set to the output.
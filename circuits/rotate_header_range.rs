@@ -0,0 +1,176 @@
+use plonky2x::backend::circuit::Circuit;
+use plonky2x::frontend::mapreduce::generator::MapReduceGenerator;
+use plonky2x::frontend::uint::uint64::U64Variable;
+use plonky2x::frontend::vars::U32Variable;
+use plonky2x::prelude::{Bytes32Variable, CircuitBuilder, PlonkParameters, VariableStream};
+
+use crate::builder::justification::{GrandpaJustificationVerifier, HintSimpleJustification};
+use crate::builder::rotate::RotateMethods;
+use crate::builder::subchain_verification::{
+    HeaderRangeFetcherHint, MapReduceSubchainVariable, SubChainVerifier, SubchainVerificationCtx,
+};
+use crate::consts::HEADERS_PER_MAP;
+use crate::rotate::RotateHint;
+use crate::vars::RotateVariable;
+
+/// Rotates from authority set N to authority set N + 1 and commits a header range whose target
+/// block is justified by authority set N + 1 in a single proof. This lets the operator step into
+/// the next authority set without waiting for a separate rotate proof to be fulfilled.
+#[derive(Clone, Debug)]
+pub struct RotateHeaderRangeCircuit<
+    const MAX_AUTHORITY_SET_SIZE: usize,
+    const MAX_HEADER_SIZE: usize,
+    const MAX_SUBARRAY_SIZE: usize,
+    const MAX_NUM_HEADERS: usize,
+> {}
+
+impl<
+        const MAX_AUTHORITY_SET_SIZE: usize,
+        const MAX_HEADER_SIZE: usize,
+        const MAX_SUBARRAY_SIZE: usize,
+        const MAX_NUM_HEADERS: usize,
+    > Circuit
+    for RotateHeaderRangeCircuit<
+        MAX_AUTHORITY_SET_SIZE,
+        MAX_HEADER_SIZE,
+        MAX_SUBARRAY_SIZE,
+        MAX_NUM_HEADERS,
+    >
+{
+    fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+    where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        // Read the on-chain inputs. authority_set_id and authority_set_hash define the current
+        // authority set, which signed the epoch end header of authority_set_id.
+        let authority_set_id = builder.evm_read::<U64Variable>();
+        let authority_set_hash = builder.evm_read::<Bytes32Variable>();
+        let trusted_block = builder.evm_read::<U32Variable>();
+        let trusted_header_hash = builder.evm_read::<Bytes32Variable>();
+        let target_block = builder.evm_read::<U32Variable>();
+
+        // Fetch the data for the rotate of authority_set_id.
+        let rotate_fetcher = RotateHint::<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE> {};
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&authority_set_id);
+        let output_stream = builder.async_hint(input_stream, rotate_fetcher);
+
+        // rotate_var is untrusted and needs to be linked to the public inputs.
+        let rotate_var =
+            output_stream.read::<RotateVariable<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE>>(builder);
+
+        let next_authority_set_hash = builder
+            .rotate::<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE, MAX_SUBARRAY_SIZE>(
+                authority_set_id,
+                authority_set_hash,
+                rotate_var,
+            );
+        let one = builder.constant::<U64Variable>(1u64);
+        let next_authority_set_id = builder.add(authority_set_id, one);
+
+        // Get the target_header_hash, state_root, and data_root over the range [trusted_block + 1, target_block].
+        let subchain_output = builder.verify_subchain::<Self, MAX_HEADER_SIZE, MAX_NUM_HEADERS>(
+            trusted_block,
+            trusted_header_hash,
+            target_block,
+        );
+
+        // Note: target_header_hash, next_authority_set_id and next_authority_set_hash are trusted
+        // at this point. Verify that there is a valid justification on target_header_hash by the
+        // next authority set.
+        builder.verify_simple_justification::<MAX_AUTHORITY_SET_SIZE>(
+            target_block,
+            subchain_output.target_header_hash,
+            next_authority_set_id,
+            next_authority_set_hash,
+        );
+
+        builder.evm_write::<Bytes32Variable>(next_authority_set_hash);
+        builder.evm_write::<Bytes32Variable>(subchain_output.target_header_hash);
+        builder.evm_write::<Bytes32Variable>(subchain_output.state_root_merkle_root);
+        builder.evm_write::<Bytes32Variable>(subchain_output.data_root_merkle_root);
+    }
+
+    fn register_generators<L: PlonkParameters<D>, const D: usize>(
+        generator_registry: &mut plonky2x::prelude::HintRegistry<L, D>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<L::Field>,
+    {
+        generator_registry
+            .register_async_hint::<RotateHint<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE>>();
+        generator_registry
+            .register_async_hint::<HeaderRangeFetcherHint<MAX_HEADER_SIZE, HEADERS_PER_MAP>>();
+        generator_registry.register_async_hint::<HintSimpleJustification<MAX_AUTHORITY_SET_SIZE>>();
+
+        let mr_id = MapReduceGenerator::<
+            L,
+            SubchainVerificationCtx,
+            U32Variable,
+            MapReduceSubchainVariable,
+            Self,
+            HEADERS_PER_MAP,
+            D,
+        >::id();
+        generator_registry.register_simple::<MapReduceGenerator<
+            L,
+            SubchainVerificationCtx,
+            U32Variable,
+            MapReduceSubchainVariable,
+            Self,
+            HEADERS_PER_MAP,
+            D,
+        >>(mr_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use plonky2x::prelude::{DefaultBuilder, GateRegistry, HintRegistry};
+
+    use super::*;
+    use crate::consts::{DELAY_LENGTH, MAX_HEADER_SIZE, VALIDATOR_LENGTH};
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_rotate_header_range_serialization() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        const NUM_AUTHORITIES: usize = 4;
+        const MAX_SUBARRAY_SIZE: usize = NUM_AUTHORITIES * VALIDATOR_LENGTH + DELAY_LENGTH;
+        const NUM_HEADERS: usize = 32;
+
+        let mut builder = DefaultBuilder::new();
+
+        log::debug!("Defining circuit");
+        RotateHeaderRangeCircuit::<
+            NUM_AUTHORITIES,
+            MAX_HEADER_SIZE,
+            MAX_SUBARRAY_SIZE,
+            NUM_HEADERS,
+        >::define(&mut builder);
+        let circuit = builder.build();
+        log::debug!("Done building circuit");
+
+        let mut hint_registry = HintRegistry::new();
+        let mut gate_registry = GateRegistry::new();
+        RotateHeaderRangeCircuit::<
+            NUM_AUTHORITIES,
+            MAX_HEADER_SIZE,
+            MAX_SUBARRAY_SIZE,
+            NUM_HEADERS,
+        >::register_generators(&mut hint_registry);
+        RotateHeaderRangeCircuit::<
+            NUM_AUTHORITIES,
+            MAX_HEADER_SIZE,
+            MAX_SUBARRAY_SIZE,
+            NUM_HEADERS,
+        >::register_gates(&mut gate_registry);
+
+        circuit.test_serializers(&gate_registry, &hint_registry);
+    }
+}
@@ -0,0 +1,490 @@
+use avail_subxt::config::substrate::DigestItem;
+use codec::{Compact, Encode};
+use ed25519_dalek::{Signer, SigningKey};
+use ethers::types::{Bytes, H256};
+use plonky2x::frontend::curta::ec::point::CompressedEdwardsY;
+use plonky2x::frontend::ecc::curve25519::ed25519::eddsa::DUMMY_SIGNATURE;
+use sha2::{Digest, Sha256};
+use sp_core::blake2_256;
+
+use crate::builder::justification::get_num_justification_signers;
+use crate::consts::{DELAY_LENGTH, KZG_COMMITMENT_SIZE};
+use crate::input::compute_authority_set_hash;
+use crate::layout::{get_digest_start, HeaderLayout};
+use crate::witness::{WitnessBundle, WitnessJustification, WitnessRotate};
+
+// Engine id of GRANDPA consensus logs.
+const GRANDPA_ENGINE_ID: [u8; 4] = *b"FRNK";
+
+// Engine id of BABE pre-runtime logs.
+const BABE_ENGINE_ID: [u8; 4] = *b"BABE";
+
+/// Configuration of a synthetic chain.
+#[derive(Clone, Debug)]
+pub struct SyntheticChainConfig {
+    /// Number of the first block, which is authority set 0's genesis. Use a large start block to
+    /// cover the 2, 4 and 5 byte compact encodings of block numbers.
+    pub start_block: u32,
+    /// Number of blocks, including the first block.
+    pub num_blocks: u32,
+    /// Number of blocks in each epoch. The epoch end block of authority set id N is
+    /// start_block + epoch_length * (N + 1).
+    pub epoch_length: u32,
+    /// Size of authority set N. Authority sets past the end of the list use the last size.
+    pub authority_set_sizes: Vec<usize>,
+    /// If set, every header is padded to exactly this many encoded bytes.
+    pub header_size: Option<usize>,
+    /// Number of authorities of a set of the given size that sign each justification. The first
+    /// authorities of the set sign. Defaults to the number the justification circuits verify.
+    pub num_signers: fn(usize) -> usize,
+}
+
+impl Default for SyntheticChainConfig {
+    fn default() -> Self {
+        Self {
+            start_block: 0,
+            num_blocks: 64,
+            epoch_length: 16,
+            authority_set_sizes: vec![4],
+            header_size: None,
+            num_signers: get_num_justification_signers,
+        }
+    }
+}
+
+/// A deterministic Avail-style chain for offline tests. Headers are SCALE encoded with the current
+/// header layout, linked by their blake2b hashes, and have a GRANDPA ScheduledChange log at each
+/// epoch end block. Justifications are signed by ed25519 keys derived from the authority set id
+/// and the authority's index.
+///
+/// Use `witness_bundle` to prove circuits against the chain without an Avail RPC.
+#[derive(Clone, Debug)]
+pub struct SyntheticChain {
+    pub config: SyntheticChainConfig,
+    // Encoded headers, indexed by block number - start_block.
+    headers: Vec<Vec<u8>>,
+    // Signing keys of the authorities, indexed by authority set id.
+    authority_sets: Vec<Vec<SigningKey>>,
+}
+
+impl SyntheticChain {
+    pub fn new(config: SyntheticChainConfig) -> Self {
+        assert!(config.num_blocks > 0, "Chain must have at least 1 block");
+        assert!(config.epoch_length > 0, "Epoch length must be positive");
+        assert!(
+            !config.authority_set_sizes.is_empty(),
+            "Authority set sizes must not be empty"
+        );
+
+        let mut chain = Self {
+            config,
+            headers: Vec::new(),
+            authority_sets: Vec::new(),
+        };
+
+        let num_authority_sets = (chain.config.num_blocks - 1) / chain.config.epoch_length + 1;
+        for authority_set_id in 0..num_authority_sets as u64 {
+            let size = chain.config.authority_set_sizes
+                [(authority_set_id as usize).min(chain.config.authority_set_sizes.len() - 1)];
+            let keys = (0..size)
+                .map(|i| {
+                    let mut hasher = Sha256::new();
+                    hasher.update(b"vectorx-synthetic-authority");
+                    hasher.update(authority_set_id.to_le_bytes());
+                    hasher.update((i as u64).to_le_bytes());
+                    SigningKey::from_bytes(&hasher.finalize().into())
+                })
+                .collect();
+            chain.authority_sets.push(keys);
+        }
+
+        let mut parent_hash = H256::zero();
+        for block_number in chain.start_block()..=chain.end_block() {
+            let header = chain.encode_header(block_number, parent_hash);
+            parent_hash = H256::from(blake2_256(&header));
+            chain.headers.push(header);
+        }
+        chain
+    }
+
+    pub fn start_block(&self) -> u32 {
+        self.config.start_block
+    }
+
+    pub fn end_block(&self) -> u32 {
+        self.config.start_block + self.config.num_blocks - 1
+    }
+
+    /// The SCALE encoded header of block_number.
+    pub fn header(&self, block_number: u32) -> &[u8] {
+        assert!(
+            block_number >= self.start_block() && block_number <= self.end_block(),
+            "Block {} is not in the synthetic chain",
+            block_number
+        );
+        &self.headers[(block_number - self.start_block()) as usize]
+    }
+
+    pub fn header_hash(&self, block_number: u32) -> H256 {
+        H256::from(blake2_256(self.header(block_number)))
+    }
+
+    /// The data root in the header of block_number.
+    pub fn data_root(&self, block_number: u32) -> H256 {
+        let mut hasher = Sha256::new();
+        hasher.update(b"vectorx-synthetic-data-root");
+        hasher.update(block_number.to_le_bytes());
+        H256::from_slice(&hasher.finalize())
+    }
+
+    /// The state root in the header of block_number.
+    pub fn state_root(&self, block_number: u32) -> H256 {
+        let mut hasher = Sha256::new();
+        hasher.update(b"vectorx-synthetic-state-root");
+        hasher.update(block_number.to_le_bytes());
+        H256::from_slice(&hasher.finalize())
+    }
+
+    /// The id of the authority set in block_number's state, which justifies block_number + 1.
+    pub fn authority_set_id(&self, block_number: u32) -> u64 {
+        ((block_number - self.start_block()) / self.config.epoch_length) as u64
+    }
+
+    /// The epoch end block of authority_set_id, the last block justified by the authority set.
+    pub fn epoch_end_block(&self, authority_set_id: u64) -> u32 {
+        self.start_block() + self.config.epoch_length * (authority_set_id as u32 + 1)
+    }
+
+    pub fn is_epoch_end_block(&self, block_number: u32) -> bool {
+        block_number > self.start_block()
+            && (block_number - self.start_block()) % self.config.epoch_length == 0
+    }
+
+    pub fn authorities(&self, authority_set_id: u64) -> Vec<CompressedEdwardsY> {
+        self.authority_sets[authority_set_id as usize]
+            .iter()
+            .map(|key| CompressedEdwardsY(key.verifying_key().to_bytes()))
+            .collect()
+    }
+
+    /// The chained authority set hash of authority_set_id, as stored in `authoritySetIdToHash`.
+    pub fn authority_set_hash(&self, authority_set_id: u64) -> H256 {
+        H256::from_slice(&compute_authority_set_hash(
+            &self.authorities(authority_set_id),
+        ))
+    }
+
+    /// The simple justification of block_number by the authority set in the state of block_number - 1.
+    /// The first config.num_signers(num_authorities) authorities sign.
+    pub fn justification(&self, block_number: u32) -> WitnessJustification {
+        assert!(
+            block_number > self.start_block(),
+            "The first block is not justified"
+        );
+        let authority_set_id = self.authority_set_id(block_number - 1);
+        let keys = &self.authority_sets[authority_set_id as usize];
+
+        // Encoded precommit: 1u8 || block hash || block number || round || authority set id.
+        let mut signed_message = vec![1u8];
+        signed_message.extend(self.header_hash(block_number).as_bytes());
+        signed_message.extend(block_number.to_le_bytes());
+        signed_message.extend(1u64.to_le_bytes());
+        signed_message.extend(authority_set_id.to_le_bytes());
+
+        let num_signers = (self.config.num_signers)(keys.len()).min(keys.len());
+        let validator_signed = (0..keys.len()).map(|i| i < num_signers).collect::<Vec<_>>();
+        let signatures = keys
+            .iter()
+            .zip(&validator_signed)
+            .map(|(key, signed)| {
+                if *signed {
+                    Bytes::from(key.sign(&signed_message).to_bytes().to_vec())
+                } else {
+                    Bytes::from(DUMMY_SIGNATURE.to_vec())
+                }
+            })
+            .collect();
+
+        WitnessJustification {
+            authority_set_id,
+            signed_message: Bytes::from(signed_message),
+            pubkeys: self
+                .authorities(authority_set_id)
+                .iter()
+                .map(|pubkey| H256::from(pubkey.0))
+                .collect(),
+            signatures,
+            validator_signed,
+        }
+    }
+
+    /// The rotate from authority_set_id to authority_set_id + 1 at authority_set_id's epoch end block.
+    pub fn rotate(&self, authority_set_id: u64) -> WitnessRotate {
+        let epoch_end_block_number = self.epoch_end_block(authority_set_id);
+        let header = self.header(epoch_end_block_number);
+        let pubkeys = self.authorities(authority_set_id + 1);
+
+        // Matches the positions of RpcDataFetcher::get_header_rotate, which start from the digest's
+        // compact encoded number of logs and skip the logs before the ScheduledChange log. The
+        // prefix includes the 1 byte compact encoded number of logs.
+        let babe_log = self.babe_log(epoch_end_block_number).encode();
+        let scheduled_change_log = self.scheduled_change_log(&pubkeys).encode();
+        let start_position = get_digest_start(header).unwrap() + babe_log.len();
+        let end_position = start_position + 1 + scheduled_change_log.len();
+
+        WitnessRotate {
+            epoch_end_block_number,
+            start_position,
+            end_position,
+            pubkeys: pubkeys.iter().map(|pubkey| H256::from(pubkey.0)).collect(),
+        }
+    }
+
+    /// A witness bundle with every header and rotate of the chain, and the justifications of every
+    /// epoch end block and of justified_blocks.
+    pub fn witness_bundle(&self, justified_blocks: &[u32]) -> WitnessBundle {
+        let mut bundle = WitnessBundle::new();
+        for block_number in self.start_block()..=self.end_block() {
+            bundle.headers.insert(
+                block_number,
+                Bytes::from(self.header(block_number).to_vec()),
+            );
+        }
+        let mut authority_set_id = 0;
+        while self.epoch_end_block(authority_set_id) <= self.end_block() {
+            let rotate = self.rotate(authority_set_id);
+            bundle.justifications.insert(
+                rotate.epoch_end_block_number,
+                self.justification(rotate.epoch_end_block_number),
+            );
+            bundle.rotates.insert(authority_set_id, rotate);
+            authority_set_id += 1;
+        }
+        for &block_number in justified_blocks {
+            bundle
+                .justifications
+                .insert(block_number, self.justification(block_number));
+        }
+        bundle
+    }
+
+    fn babe_log(&self, block_number: u32) -> DigestItem {
+        DigestItem::PreRuntime(BABE_ENGINE_ID, block_number.encode())
+    }
+
+    // ConsensusLog::ScheduledChange: 1u8 || compact number of authorities || (pubkey, weight)... || delay.
+    fn scheduled_change_log(&self, pubkeys: &[CompressedEdwardsY]) -> DigestItem {
+        let mut value = vec![1u8];
+        value.extend(Compact(pubkeys.len() as u32).encode());
+        for pubkey in pubkeys {
+            value.extend(pubkey.0);
+            value.extend(1u64.to_le_bytes());
+        }
+        value.extend([0u8; DELAY_LENGTH]);
+        DigestItem::Consensus(GRANDPA_ENGINE_ID, value)
+    }
+
+    // Encode a V3 header extension with a single row of commitments.
+    fn encode_extension(&self, block_number: u32) -> Vec<u8> {
+        let mut extension = vec![HeaderLayout::V3.version_tag()];
+        extension.extend(Compact(1u32).encode());
+        extension.extend(Vec::<(Compact<u32>, Compact<u32>)>::new().encode());
+        extension.extend(Compact(1u16).encode());
+        extension.extend(Compact(4u16).encode());
+        extension.extend(vec![block_number as u8; KZG_COMMITMENT_SIZE].encode());
+        extension.extend(self.data_root(block_number).as_bytes());
+        extension
+    }
+
+    fn encode_header_with_logs(
+        &self,
+        block_number: u32,
+        parent_hash: H256,
+        logs: &[DigestItem],
+    ) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend(parent_hash.as_bytes());
+        header.extend(Compact(block_number).encode());
+        header.extend(self.state_root(block_number).as_bytes());
+        header.extend(H256::repeat_byte(block_number as u8).as_bytes());
+        header.extend(logs.encode());
+        header.extend(self.encode_extension(block_number));
+        header
+    }
+
+    fn encode_header(&self, block_number: u32, parent_hash: H256) -> Vec<u8> {
+        let mut logs = vec![self.babe_log(block_number)];
+        if self.is_epoch_end_block(block_number) {
+            let next_authority_set_id = self.authority_set_id(block_number);
+            logs.push(self.scheduled_change_log(&self.authorities(next_authority_set_id)));
+        }
+        let header = self.encode_header_with_logs(block_number, parent_hash, &logs);
+
+        let Some(header_size) = self.config.header_size else {
+            return header;
+        };
+
+        // Pad the header with an extra log. The encoded length of the log's data depends on its
+        // length, so converge on the padding length.
+        let mut padding_len = header_size.saturating_sub(header.len() + 2);
+        for _ in 0..4 {
+            let mut padded_logs = logs.clone();
+            padded_logs.push(DigestItem::Other(vec![0u8; padding_len]));
+            let padded_header =
+                self.encode_header_with_logs(block_number, parent_hash, &padded_logs);
+            if padded_header.len() == header_size {
+                return padded_header;
+            }
+            padding_len = (padding_len + header_size).saturating_sub(padded_header.len());
+        }
+        panic!(
+            "Can't pad block {}'s header of {} bytes to {} bytes",
+            block_number,
+            header.len(),
+            header_size
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use avail_subxt::primitives::Header;
+    use codec::Decode;
+
+    use super::*;
+    use crate::consts::MAX_HEADER_SIZE;
+    use crate::input::{decode_precommit, verify_signature};
+    use crate::layout::CURRENT_HEADER_LAYOUT;
+
+    fn verify_chain(chain: &SyntheticChain) {
+        let mut parent_hash = H256::zero();
+        for block_number in chain.start_block()..=chain.end_block() {
+            let encoded_header = chain.header(block_number);
+            let header = Header::decode(&mut &encoded_header[..]).unwrap();
+            assert_eq!(header.number, block_number);
+            assert_eq!(header.parent_hash.0, parent_hash.0);
+            CURRENT_HEADER_LAYOUT.check(encoded_header).unwrap();
+            assert_eq!(
+                CURRENT_HEADER_LAYOUT
+                    .decode_data_root(encoded_header)
+                    .unwrap(),
+                chain.data_root(block_number)
+            );
+            if let Some(header_size) = chain.config.header_size {
+                assert_eq!(encoded_header.len(), header_size);
+            }
+            parent_hash = chain.header_hash(block_number);
+        }
+    }
+
+    fn verify_justification(chain: &SyntheticChain, block_number: u32) -> usize {
+        let justification = chain.justification(block_number);
+        let (block_hash, precommit_block_number, _, authority_set_id) =
+            decode_precommit(justification.signed_message.to_vec());
+        assert_eq!(block_hash, chain.header_hash(block_number));
+        assert_eq!(precommit_block_number, block_number);
+        assert_eq!(authority_set_id, chain.authority_set_id(block_number - 1));
+
+        let mut num_signed = 0;
+        for i in 0..justification.pubkeys.len() {
+            if justification.validator_signed[i] {
+                verify_signature(
+                    &justification.pubkeys[i].0,
+                    &justification.signed_message,
+                    justification.signatures[i].as_ref().try_into().unwrap(),
+                );
+                num_signed += 1;
+            }
+        }
+        num_signed
+    }
+
+    #[test]
+    fn test_synthetic_chain() {
+        let chain = SyntheticChain::new(SyntheticChainConfig {
+            authority_set_sizes: vec![1, 4, 10],
+            ..Default::default()
+        });
+        verify_chain(&chain);
+
+        assert_eq!(verify_justification(&chain, 5), 1);
+        assert_eq!(verify_justification(&chain, 20), 4);
+        assert_eq!(verify_justification(&chain, 40), 8);
+
+        for authority_set_id in 0..3 {
+            let rotate = chain.rotate(authority_set_id);
+            let header = chain.header(rotate.epoch_end_block_number);
+            assert_eq!(
+                chain.authority_set_id(rotate.epoch_end_block_number - 1),
+                authority_set_id
+            );
+            assert_eq!(
+                chain.authority_set_id(rotate.epoch_end_block_number),
+                authority_set_id + 1
+            );
+            // The ScheduledChange log starts 1 byte after start_position.
+            assert_eq!(header[rotate.start_position + 1], 4);
+            assert_eq!(
+                header[rotate.start_position + 2..rotate.start_position + 6],
+                GRANDPA_ENGINE_ID
+            );
+            // The ScheduledChange log is followed by the header extension.
+            assert_eq!(header[rotate.end_position], HeaderLayout::V3.version_tag());
+            assert_eq!(
+                chain.authority_set_hash(authority_set_id + 1).as_bytes(),
+                compute_authority_set_hash(
+                    &rotate
+                        .pubkeys
+                        .iter()
+                        .map(|pubkey| CompressedEdwardsY(pubkey.0))
+                        .collect::<Vec<_>>()
+                )
+            );
+        }
+
+        let bundle = chain.witness_bundle(&[10]);
+        assert_eq!(bundle.headers.len(), 64);
+        assert_eq!(bundle.rotates.len(), 3);
+        assert_eq!(
+            bundle.get_justification::<16>(10).unwrap().num_authorities,
+            1
+        );
+    }
+
+    #[test]
+    fn test_synthetic_chain_compact_block_numbers() {
+        for start_block in [60, (1 << 14) - 4, (1 << 30) - 4] {
+            let chain = SyntheticChain::new(SyntheticChainConfig {
+                start_block,
+                num_blocks: 8,
+                epoch_length: 4,
+                ..Default::default()
+            });
+            verify_chain(&chain);
+            assert_eq!(verify_justification(&chain, start_block + 4), 4);
+        }
+    }
+
+    #[test]
+    fn test_synthetic_chain_header_size() {
+        for header_size in [1024, 2048, 10_000, MAX_HEADER_SIZE] {
+            let chain = SyntheticChain::new(SyntheticChainConfig {
+                num_blocks: 20,
+                header_size: Some(header_size),
+                authority_set_sizes: vec![4, 16],
+                ..Default::default()
+            });
+            verify_chain(&chain);
+        }
+    }
+
+    #[test]
+    fn test_synthetic_chain_exactly_two_thirds() {
+        let chain = SyntheticChain::new(SyntheticChainConfig {
+            authority_set_sizes: vec![6],
+            num_signers: |num_authorities| num_authorities * 2 / 3,
+            ..Default::default()
+        });
+        assert_eq!(verify_justification(&chain, 10), 4);
+    }
+}
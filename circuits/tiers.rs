@@ -0,0 +1,50 @@
+use crate::consts::AUTHORITY_SET_SIZE_TIERS;
+
+/// Get the max authority set size of the smallest authority set size tier that fits an authority set
+/// with num_authorities authorities. Returns None if the authority set is larger than every tier.
+pub fn get_authority_set_size_tier(num_authorities: usize) -> Option<usize> {
+    AUTHORITY_SET_SIZE_TIERS
+        .iter()
+        .copied()
+        .find(|&max_authority_set_size| num_authorities <= max_authority_set_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::{
+        DELAY_LENGTH, MAX_AUTHORITY_SET_SIZE, MAX_AUTHORITY_SET_SIZE_TIER, MAX_HEADER_SIZE,
+        VALIDATOR_LENGTH,
+    };
+
+    #[test]
+    fn test_get_authority_set_size_tier() {
+        assert_eq!(get_authority_set_size_tier(1), Some(64));
+        assert_eq!(get_authority_set_size_tier(64), Some(64));
+        assert_eq!(get_authority_set_size_tier(65), Some(128));
+        assert_eq!(
+            get_authority_set_size_tier(200),
+            Some(MAX_AUTHORITY_SET_SIZE)
+        );
+        assert_eq!(
+            get_authority_set_size_tier(MAX_AUTHORITY_SET_SIZE + 1),
+            Some(512)
+        );
+        assert_eq!(
+            get_authority_set_size_tier(MAX_AUTHORITY_SET_SIZE_TIER),
+            Some(MAX_AUTHORITY_SET_SIZE_TIER)
+        );
+        assert_eq!(
+            get_authority_set_size_tier(MAX_AUTHORITY_SET_SIZE_TIER + 1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tiers_fit_max_header_size() {
+        // The rotate circuit of each tier reads the encoded authority set from the epoch end header.
+        for max_authority_set_size in AUTHORITY_SET_SIZE_TIERS {
+            assert!(max_authority_set_size * VALIDATOR_LENGTH + DELAY_LENGTH <= MAX_HEADER_SIZE);
+        }
+    }
+}
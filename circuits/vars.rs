@@ -9,7 +9,9 @@ use plonky2x::prelude::{
     PlonkParameters, RichField, Variable,
 };
 
-use crate::consts::ENCODED_PRECOMMIT_LENGTH;
+use crate::consts::{
+    ENCODED_PRECOMMIT_LENGTH, KZG_COMMITMENT_SIZE, MAX_APP_LOOKUP_LEN, MAX_NUM_KZG_COMMITMENTS,
+};
 
 #[derive(Clone, Debug, CircuitVariable)]
 #[value_name(EncodedHeader)]
@@ -45,6 +47,22 @@ pub struct JustificationVariable<const MAX_AUTHORITY_SET_SIZE: usize> {
     pub num_authorities: U32Variable,
 }
 
+/// A justification with the signatures of a subset of NUM_SIGNERS authorities, identified by their
+/// indices in the authority set.
+#[derive(Clone, Debug, CircuitVariable)]
+#[value_name(JustificationSubsetStruct)]
+pub struct JustificationSubsetVariable<
+    const MAX_AUTHORITY_SET_SIZE: usize,
+    const NUM_SIGNERS: usize,
+> {
+    pub encoded_precommit: BytesVariable<ENCODED_PRECOMMIT_LENGTH>,
+    pub pubkeys: ArrayVariable<CompressedEdwardsYVariable, MAX_AUTHORITY_SET_SIZE>,
+    pub num_authorities: U32Variable,
+    pub signer_enabled: ArrayVariable<BoolVariable, NUM_SIGNERS>,
+    pub signer_indices: ArrayVariable<U32Variable, NUM_SIGNERS>,
+    pub signatures: ArrayVariable<EDDSASignatureVariable, NUM_SIGNERS>,
+}
+
 #[derive(Clone, Debug, CircuitVariable)]
 #[value_name(RotateStruct)]
 pub struct RotateVariable<const MAX_HEADER_SIZE: usize, const MAX_AUTHORITY_SET_SIZE: usize> {
@@ -62,3 +80,30 @@ pub struct SubchainVerificationVariable {
     pub state_root_merkle_root: Bytes32Variable,
     pub data_root_merkle_root: Bytes32Variable,
 }
+
+#[derive(Clone, Debug, CircuitVariable)]
+#[value_name(HeaderAncestryStruct)]
+pub struct HeaderAncestryVariable {
+    pub target_header_hash: Bytes32Variable,
+    pub state_root: Bytes32Variable,
+    pub data_root: Bytes32Variable,
+}
+
+#[derive(Clone, Debug, CircuitVariable)]
+#[value_name(AppLookupItemStruct)]
+pub struct AppLookupItemVariable {
+    pub app_id: U32Variable,
+    pub start: U32Variable,
+}
+
+#[derive(Clone, Debug, CircuitVariable)]
+#[value_name(HeaderExtensionStruct)]
+pub struct HeaderExtensionVariable {
+    pub app_lookup_size: U32Variable,
+    pub app_lookup_len: U32Variable,
+    pub app_lookup: ArrayVariable<AppLookupItemVariable, MAX_APP_LOOKUP_LEN>,
+    pub rows: U32Variable,
+    pub cols: U32Variable,
+    pub num_commitments: U32Variable,
+    pub commitments: ArrayVariable<BytesVariable<KZG_COMMITMENT_SIZE>, MAX_NUM_KZG_COMMITMENTS>,
+}
@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::{env, fs};
+
+use anyhow::{anyhow, ensure, Error};
+use codec::Encode;
+use ethers::types::{Bytes, H256};
+use log::info;
+use plonky2x::frontend::curta::ec::point::CompressedEdwardsY;
+use plonky2x::frontend::ecc::curve25519::ed25519::eddsa::{DUMMY_PUBLIC_KEY, DUMMY_SIGNATURE};
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{MAX_AUTHORITY_SET_SIZE_TIER, MAX_HEADER_SIZE};
+use crate::input::types::{CircuitJustification, HeaderRotateData};
+use crate::input::{compute_authority_set_hash, RpcDataFetcher};
+
+/// Path of the witness bundle the hints read from. When unset, the hints fetch their data from the
+/// Avail RPC and the indexer's Redis instead.
+pub const WITNESS_BUNDLE_ENV: &str = "WITNESS_BUNDLE";
+
+/// Version of the witness bundle format.
+pub const WITNESS_BUNDLE_VERSION: u32 = 1;
+
+/// A simple justification of a block, without padding.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WitnessJustification {
+    pub authority_set_id: u64,
+    pub signed_message: Bytes,
+    pub pubkeys: Vec<H256>,
+    pub signatures: Vec<Bytes>,
+    pub validator_signed: Vec<bool>,
+}
+
+impl From<CircuitJustification> for WitnessJustification {
+    /// Strip the padding of a justification fetched for the circuits.
+    fn from(justification: CircuitJustification) -> Self {
+        let num_authorities = justification.num_authorities;
+        Self {
+            authority_set_id: justification.authority_set_id,
+            signed_message: Bytes::from(justification.signed_message),
+            pubkeys: justification.pubkeys[..num_authorities]
+                .iter()
+                .map(|pubkey| H256::from(pubkey.0))
+                .collect(),
+            signatures: justification.signatures[..num_authorities]
+                .iter()
+                .map(|signature| Bytes::from(signature.to_vec()))
+                .collect(),
+            validator_signed: justification.validator_signed[..num_authorities].to_vec(),
+        }
+    }
+}
+
+/// The new authority set of an epoch end block. The block's header is in the bundle's headers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WitnessRotate {
+    pub epoch_end_block_number: u32,
+    /// Position of the ScheduledChange log in the epoch end block's header.
+    pub start_position: usize,
+    pub end_position: usize,
+    pub pubkeys: Vec<H256>,
+}
+
+/// Pre-generated data for the circuit hints, so proofs can be generated without access to the Avail
+/// RPC or Redis, and reproduced exactly. Generate a bundle with the `witness` binary, and set
+/// WITNESS_BUNDLE to its path when proving.
+///
+/// Note: The bundle is untrusted like any hint data, the circuits verify everything read from it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WitnessBundle {
+    pub version: u32,
+    /// SCALE encoded headers by block number.
+    pub headers: BTreeMap<u32, Bytes>,
+    /// Justifications by block number.
+    pub justifications: BTreeMap<u32, WitnessJustification>,
+    /// Rotates by the authority set id of the epoch ending at the epoch end block.
+    pub rotates: BTreeMap<u64, WitnessRotate>,
+}
+
+static WITNESS_BUNDLE: OnceLock<Option<WitnessBundle>> = OnceLock::new();
+
+impl WitnessBundle {
+    pub fn new() -> Self {
+        Self {
+            version: WITNESS_BUNDLE_VERSION,
+            ..Default::default()
+        }
+    }
+
+    /// The witness bundle at WITNESS_BUNDLE, if set. The bundle is read once per process. Panics if
+    /// the bundle can't be read.
+    pub fn from_env() -> Option<&'static WitnessBundle> {
+        WITNESS_BUNDLE
+            .get_or_init(|| {
+                let path = env::var(WITNESS_BUNDLE_ENV).ok()?;
+                info!("Reading hints from the witness bundle at {}", path);
+                Some(Self::read(&path).expect("Failed to read the witness bundle"))
+            })
+            .as_ref()
+    }
+
+    pub fn read(path: &str) -> Result<Self, Error> {
+        let bundle: WitnessBundle = serde_json::from_str(&fs::read_to_string(path)?)?;
+        ensure!(
+            bundle.version == WITNESS_BUNDLE_VERSION,
+            "Unsupported witness bundle version {}, expected {}",
+            bundle.version,
+            WITNESS_BUNDLE_VERSION
+        );
+        Ok(bundle)
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add the headers of the blocks in [start_block, end_block].
+    pub async fn add_headers(
+        &mut self,
+        fetcher: &mut RpcDataFetcher,
+        start_block: u32,
+        end_block: u32,
+    ) {
+        if end_block < start_block {
+            return;
+        }
+        let headers = fetcher
+            .get_block_headers_range(start_block, end_block)
+            .await;
+        for (block_number, header) in (start_block..=end_block).zip(headers) {
+            self.headers
+                .insert(block_number, Bytes::from(header.encode()));
+        }
+    }
+
+    /// Add the simple justification of block_number.
+    pub async fn add_justification(
+        &mut self,
+        fetcher: &mut RpcDataFetcher,
+        block_number: u32,
+    ) -> Result<(), Error> {
+        let justification = fetcher
+            .get_justification_from_block::<MAX_AUTHORITY_SET_SIZE_TIER>(block_number)
+            .await?;
+        self.justifications
+            .insert(block_number, WitnessJustification::from(justification));
+        Ok(())
+    }
+
+    /// Add the rotate of authority_set_id, and the header and justification of its epoch end block.
+    pub async fn add_rotate(
+        &mut self,
+        fetcher: &mut RpcDataFetcher,
+        authority_set_id: u64,
+    ) -> Result<(), Error> {
+        let epoch_end_block_number = fetcher.last_justified_block(authority_set_id).await;
+        let rotate_data = fetcher
+            .get_header_rotate::<MAX_HEADER_SIZE, MAX_AUTHORITY_SET_SIZE_TIER>(
+                epoch_end_block_number,
+            )
+            .await;
+
+        self.headers.insert(
+            epoch_end_block_number,
+            Bytes::from(rotate_data.header_bytes[..rotate_data.header_size].to_vec()),
+        );
+        self.rotates.insert(
+            authority_set_id,
+            WitnessRotate {
+                epoch_end_block_number,
+                start_position: rotate_data.start_position,
+                end_position: rotate_data.end_position,
+                pubkeys: rotate_data.padded_pubkeys[..rotate_data.num_authorities]
+                    .iter()
+                    .map(|pubkey| H256::from(pubkey.0))
+                    .collect(),
+            },
+        );
+        self.add_justification(fetcher, epoch_end_block_number)
+            .await
+    }
+
+    /// Get the SCALE encoded header of block_number.
+    pub fn get_header(&self, block_number: u32) -> Result<Vec<u8>, Error> {
+        self.headers
+            .get(&block_number)
+            .map(|header| header.to_vec())
+            .ok_or_else(|| anyhow!("Witness bundle has no header for block {}", block_number))
+    }
+
+    /// Get the SCALE encoded headers of the blocks in [start_block, end_block].
+    pub fn get_headers_range(
+        &self,
+        start_block: u32,
+        end_block: u32,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        (start_block..=end_block)
+            .map(|block_number| self.get_header(block_number))
+            .collect()
+    }
+
+    /// Get the justification of block_number, padded to VALIDATOR_SET_SIZE_MAX authorities like
+    /// `RpcDataFetcher::get_justification_from_block`.
+    pub fn get_justification<const VALIDATOR_SET_SIZE_MAX: usize>(
+        &self,
+        block_number: u32,
+    ) -> Result<CircuitJustification, Error> {
+        let justification = self.justifications.get(&block_number).ok_or_else(|| {
+            anyhow!(
+                "Witness bundle has no justification for block {}",
+                block_number
+            )
+        })?;
+        let num_authorities = justification.pubkeys.len();
+        ensure!(
+            num_authorities <= VALIDATOR_SET_SIZE_MAX,
+            "Justification of block {} has {} authorities, more than the max of {}",
+            block_number,
+            num_authorities,
+            VALIDATOR_SET_SIZE_MAX
+        );
+        ensure!(
+            justification.signatures.len() == num_authorities
+                && justification.validator_signed.len() == num_authorities,
+            "Justification of block {} has mismatched lengths",
+            block_number
+        );
+
+        let mut pubkeys = justification
+            .pubkeys
+            .iter()
+            .map(|pubkey| CompressedEdwardsY(pubkey.0))
+            .collect::<Vec<_>>();
+        let current_authority_set_hash = compute_authority_set_hash(&pubkeys);
+        let mut signatures = justification
+            .signatures
+            .iter()
+            .map(|signature| {
+                signature
+                    .as_ref()
+                    .try_into()
+                    .map_err(|_| anyhow!("Signature is not 64 bytes"))
+            })
+            .collect::<Result<Vec<[u8; 64]>, Error>>()?;
+        let mut validator_signed = justification.validator_signed.clone();
+
+        // Pad the arrays with dummy pubkeys and signatures to VALIDATOR_SET_SIZE_MAX.
+        for _ in num_authorities..VALIDATOR_SET_SIZE_MAX {
+            validator_signed.push(false);
+            pubkeys.push(CompressedEdwardsY::from_slice(&DUMMY_PUBLIC_KEY).unwrap());
+            signatures.push(DUMMY_SIGNATURE);
+        }
+
+        Ok(CircuitJustification {
+            authority_set_id: justification.authority_set_id,
+            signed_message: justification.signed_message.to_vec(),
+            validator_signed,
+            pubkeys,
+            signatures,
+            num_authorities,
+            current_authority_set_hash,
+        })
+    }
+
+    /// Get the epoch end block of authority_set_id and its rotate data, padded like
+    /// `RpcDataFetcher::get_header_rotate`.
+    pub fn get_header_rotate<const HEADER_LENGTH: usize, const VALIDATOR_SET_SIZE_MAX: usize>(
+        &self,
+        authority_set_id: u64,
+    ) -> Result<(u32, HeaderRotateData), Error> {
+        let rotate = self.rotates.get(&authority_set_id).ok_or_else(|| {
+            anyhow!(
+                "Witness bundle has no rotate for authority set {}",
+                authority_set_id
+            )
+        })?;
+        let mut header_bytes = self.get_header(rotate.epoch_end_block_number)?;
+        let header_size = header_bytes.len();
+        ensure!(
+            header_size <= HEADER_LENGTH,
+            "header size {} is greater than HEADER_LENGTH {}",
+            header_size,
+            HEADER_LENGTH
+        );
+        header_bytes.resize(HEADER_LENGTH, 0);
+
+        let num_authorities = rotate.pubkeys.len();
+        ensure!(
+            num_authorities <= VALIDATOR_SET_SIZE_MAX,
+            "Rotate of authority set {} has {} authorities, more than the max of {}",
+            authority_set_id,
+            num_authorities,
+            VALIDATOR_SET_SIZE_MAX
+        );
+        let new_authorities = rotate
+            .pubkeys
+            .iter()
+            .map(|pubkey| CompressedEdwardsY(pubkey.0))
+            .collect::<Vec<_>>();
+        let new_authority_set_hash = compute_authority_set_hash(&new_authorities);
+        let mut padded_pubkeys = new_authorities;
+        for _ in num_authorities..VALIDATOR_SET_SIZE_MAX {
+            // Pad the array with dummy pubkeys to VALIDATOR_SET_SIZE_MAX.
+            padded_pubkeys.push(CompressedEdwardsY::from_slice(&DUMMY_PUBLIC_KEY).unwrap());
+        }
+
+        Ok((
+            rotate.epoch_end_block_number,
+            HeaderRotateData {
+                header_bytes,
+                header_size,
+                num_authorities,
+                start_position: rotate.start_position,
+                end_position: rotate.end_position,
+                new_authority_set_hash,
+                padded_pubkeys,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_bundle() -> WitnessBundle {
+        let mut bundle = WitnessBundle::new();
+        bundle.headers.insert(10, Bytes::from(vec![1u8; 100]));
+        bundle.headers.insert(11, Bytes::from(vec![2u8; 120]));
+        bundle.justifications.insert(
+            11,
+            WitnessJustification {
+                authority_set_id: 3,
+                signed_message: Bytes::from(vec![4u8; 53]),
+                pubkeys: vec![H256::repeat_byte(5), H256::repeat_byte(6)],
+                signatures: vec![Bytes::from(vec![7u8; 64]), Bytes::from(vec![8u8; 64])],
+                validator_signed: vec![true, false],
+            },
+        );
+        bundle.rotates.insert(
+            2,
+            WitnessRotate {
+                epoch_end_block_number: 11,
+                start_position: 40,
+                end_position: 90,
+                pubkeys: vec![H256::repeat_byte(9)],
+            },
+        );
+        bundle
+    }
+
+    #[test]
+    fn test_witness_bundle_serde() {
+        let bundle = get_bundle();
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert_eq!(
+            serde_json::from_str::<WitnessBundle>(&json).unwrap(),
+            bundle
+        );
+    }
+
+    #[test]
+    fn test_witness_bundle_hints() {
+        let bundle = get_bundle();
+
+        let headers = bundle.get_headers_range(10, 11).unwrap();
+        assert_eq!(headers, vec![vec![1u8; 100], vec![2u8; 120]]);
+        assert!(bundle.get_headers_range(10, 12).is_err());
+
+        let justification = bundle.get_justification::<4>(11).unwrap();
+        assert_eq!(justification.authority_set_id, 3);
+        assert_eq!(justification.num_authorities, 2);
+        assert_eq!(justification.pubkeys.len(), 4);
+        assert_eq!(justification.signatures[1], [8u8; 64]);
+        assert_eq!(justification.signatures[2], DUMMY_SIGNATURE);
+        assert_eq!(
+            justification.validator_signed,
+            vec![true, false, false, false]
+        );
+        assert!(bundle.get_justification::<1>(11).is_err());
+        assert!(bundle.get_justification::<4>(10).is_err());
+
+        let (epoch_end_block, rotate_data) = bundle.get_header_rotate::<128, 4>(2).unwrap();
+        assert_eq!(epoch_end_block, 11);
+        assert_eq!(rotate_data.header_size, 120);
+        assert_eq!(rotate_data.header_bytes.len(), 128);
+        assert_eq!(rotate_data.num_authorities, 1);
+        assert_eq!(rotate_data.padded_pubkeys.len(), 4);
+        assert!(bundle.get_header_rotate::<100, 4>(2).is_err());
+    }
+}
@@ -50,6 +50,66 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
     /// @notice The commitment tree size for the header range.
     uint32 public headerRangeCommitmentTreeSize;
 
+    /// @notice Maps the max authority set size of a tier to the header range function id for the tier.
+    mapping(uint32 => bytes32) public headerRangeTierFunctionIds;
+
+    /// @notice Maps the max authority set size of a tier to the rotate function id for the tier.
+    mapping(uint32 => bytes32) public rotateTierFunctionIds;
+
+    /// @notice Maps the max authority set size of a tier to the function id of the tier's header
+    ///     range circuit built for a small max header size.
+    mapping(uint32 => bytes32) public smallHeaderRangeTierFunctionIds;
+
+    /// @notice The function for requesting a rotate and a header range justified by the new
+    ///     authority set in a single proof.
+    bytes32 public rotateHeaderRangeFunctionId;
+
+    /// @notice The function for requesting an aggregation of consecutive header ranges.
+    bytes32 public headerRangeAggregationFunctionId;
+
+    /// @notice The number of headers in each range of the header range aggregation function.
+    uint32 public headerRangeAggregationRangeSize;
+
+    /// @notice The number of ranges of the header range aggregation function.
+    uint32 public headerRangeAggregationNumRanges;
+
+    /// @notice The function for requesting consecutive rotates in a single proof.
+    bytes32 public multiRotateFunctionId;
+
+    /// @notice The number of rotates of the multi rotate function.
+    uint32 public multiRotateNumEpochs;
+
+    /// @notice The function for attesting a header which is an ancestor of a stored header.
+    bytes32 public headerAncestryFunctionId;
+
+    /// @notice The maximum number of headers from the target header to the trusted header of the
+    ///     header ancestry function, including both.
+    uint32 public headerAncestryMaxNumHeaders;
+
+    /// @notice Maps block height to the state root of a header attested by the header ancestry
+    ///     function.
+    mapping(uint32 => bytes32) public blockHeightToStateRoot;
+
+    /// @notice Maps block height to the data root of a header attested by the header ancestry
+    ///     function.
+    mapping(uint32 => bytes32) public blockHeightToDataRoot;
+
+    /// @notice The function for requesting a header range which also commits to the KZG
+    ///     commitments of the target header.
+    bytes32 public headerRangeKzgFunctionId;
+
+    /// @notice Maps block height to the Merkle root over the KZG commitments of the block's header,
+    ///     for blocks which are the target block of a header range KZG proof.
+    mapping(uint32 => bytes32) public blockHeightToKzgCommitmentsRoot;
+
+    /// @notice Maps block height to the rows of the block's data matrix, for blocks with a KZG
+    ///     commitments root.
+    mapping(uint32 => uint32) public blockHeightToDataMatrixRows;
+
+    /// @notice Maps block height to the cols of the block's data matrix, for blocks with a KZG
+    ///     commitments root.
+    mapping(uint32 => uint32) public blockHeightToDataMatrixCols;
+
     struct InitParameters {
         address guardian;
         address gateway;
@@ -63,7 +123,7 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
     }
 
     function VERSION() external pure override returns (string memory) {
-        return "1.0.0";
+        return "1.1.0";
     }
 
     /// @dev Initializes the contract.
@@ -99,6 +159,84 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
         headerRangeCommitmentTreeSize = _headerRangeCommitmentTreeSize;
     }
 
+    /// @notice Update the function IDs of the circuits built for an authority set size tier.
+    /// @param _maxAuthoritySetSize The max authority set size of the tier.
+    /// @dev The header range function must have the same commitment tree size as
+    ///     headerRangeCommitmentTreeSize. Set the function IDs to 0 to remove the tier.
+    function updateTierFunctionIds(
+        uint32 _maxAuthoritySetSize,
+        bytes32 _headerRangeFunctionId,
+        bytes32 _rotateFunctionId
+    ) external onlyGuardian {
+        headerRangeTierFunctionIds[_maxAuthoritySetSize] = _headerRangeFunctionId;
+        rotateTierFunctionIds[_maxAuthoritySetSize] = _rotateFunctionId;
+    }
+
+    /// @notice Update the function ID of the header range circuit built for a small max header size
+    ///     for an authority set size tier. Header ranges containing a larger header are proven with
+    ///     the tier's header range function.
+    /// @param _maxAuthoritySetSize The max authority set size of the tier.
+    /// @dev The header range function must have the same commitment tree size as
+    ///     headerRangeCommitmentTreeSize. Set the function ID to 0 to remove it.
+    function updateSmallHeaderRangeTierFunctionId(
+        uint32 _maxAuthoritySetSize,
+        bytes32 _smallHeaderRangeFunctionId
+    ) external onlyGuardian {
+        smallHeaderRangeTierFunctionIds[
+            _maxAuthoritySetSize
+        ] = _smallHeaderRangeFunctionId;
+    }
+
+    /// @notice Update the function ID of the rotate header range circuit.
+    /// @dev The rotate header range function must have the same commitment tree size as
+    ///     headerRangeCommitmentTreeSize. Set the function ID to 0 to remove it.
+    function updateRotateHeaderRangeFunctionId(
+        bytes32 _rotateHeaderRangeFunctionId
+    ) external onlyGuardian {
+        rotateHeaderRangeFunctionId = _rotateHeaderRangeFunctionId;
+    }
+
+    /// @notice Update the function ID of the header range aggregation circuit, which aggregates
+    ///     _numRanges header ranges of _rangeSize headers. Set the function ID to 0 to remove it.
+    function updateHeaderRangeAggregationFunctionId(
+        bytes32 _headerRangeAggregationFunctionId,
+        uint32 _rangeSize,
+        uint32 _numRanges
+    ) external onlyGuardian {
+        headerRangeAggregationFunctionId = _headerRangeAggregationFunctionId;
+        headerRangeAggregationRangeSize = _rangeSize;
+        headerRangeAggregationNumRanges = _numRanges;
+    }
+
+    /// @notice Update the function ID of the multi rotate circuit, which applies _numEpochs
+    ///     consecutive rotates. Set the function ID to 0 to remove it.
+    function updateMultiRotateFunctionId(
+        bytes32 _multiRotateFunctionId,
+        uint32 _numEpochs
+    ) external onlyGuardian {
+        multiRotateFunctionId = _multiRotateFunctionId;
+        multiRotateNumEpochs = _numEpochs;
+    }
+
+    /// @notice Update the function ID of the header ancestry circuit, which walks back at most
+    ///     _maxNumHeaders - 1 blocks from the trusted block. Set the function ID to 0 to remove it.
+    function updateHeaderAncestryFunctionId(
+        bytes32 _headerAncestryFunctionId,
+        uint32 _maxNumHeaders
+    ) external onlyGuardian {
+        headerAncestryFunctionId = _headerAncestryFunctionId;
+        headerAncestryMaxNumHeaders = _maxNumHeaders;
+    }
+
+    /// @notice Update the function ID of the header range KZG circuit.
+    /// @dev The header range KZG function must have the same commitment tree size as
+    ///     headerRangeCommitmentTreeSize. Set the function ID to 0 to remove it.
+    function updateHeaderRangeKzgFunctionId(
+        bytes32 _headerRangeKzgFunctionId
+    ) external onlyGuardian {
+        headerRangeKzgFunctionId = _headerRangeKzgFunctionId;
+    }
+
     /// @notice Update the gateway address.
     function updateGateway(address _gateway) external onlyGuardian {
         gateway = _gateway;
@@ -172,6 +310,84 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
         uint64 _authoritySetId,
         uint32 _requestedBlock
     ) external payable {
+        _requestHeaderRange(
+            headerRangeFunctionId,
+            abi.encodeWithSelector(
+                this.commitHeaderRange.selector,
+                _authoritySetId,
+                _requestedBlock
+            ),
+            _authoritySetId,
+            _requestedBlock
+        );
+    }
+
+    /// @notice Request a header update and data commitment from range (latestBlock, requestedBlock]
+    ///     with the header range circuit of an authority set size tier.
+    /// @param _maxAuthoritySetSize The max authority set size of the tier.
+    /// @param _authoritySetId The authority set id of the header range (latestBlock, requestedBlock].
+    /// @param _requestedBlock The block height of the requested block.
+    function requestHeaderRangeTier(
+        uint32 _maxAuthoritySetSize,
+        uint64 _authoritySetId,
+        uint32 _requestedBlock
+    ) external payable {
+        bytes32 functionId = headerRangeTierFunctionIds[_maxAuthoritySetSize];
+        if (functionId == bytes32(0)) {
+            revert TierNotFound();
+        }
+        _requestHeaderRange(
+            functionId,
+            abi.encodeWithSelector(
+                this.commitHeaderRangeTier.selector,
+                _maxAuthoritySetSize,
+                _authoritySetId,
+                _requestedBlock
+            ),
+            _authoritySetId,
+            _requestedBlock
+        );
+    }
+
+    /// @notice Request a header update and data commitment from range (latestBlock, requestedBlock]
+    ///     with the small max header size header range circuit of an authority set size tier.
+    /// @param _maxAuthoritySetSize The max authority set size of the tier.
+    /// @param _authoritySetId The authority set id of the header range (latestBlock, requestedBlock].
+    /// @param _requestedBlock The block height of the requested block.
+    /// @dev If a header in the range is larger than the circuit's max header size, the proof will
+    ///     fail to generate.
+    function requestHeaderRangeSmallTier(
+        uint32 _maxAuthoritySetSize,
+        uint64 _authoritySetId,
+        uint32 _requestedBlock
+    ) external payable {
+        bytes32 functionId = smallHeaderRangeTierFunctionIds[
+            _maxAuthoritySetSize
+        ];
+        if (functionId == bytes32(0)) {
+            revert TierNotFound();
+        }
+        _requestHeaderRange(
+            functionId,
+            abi.encodeWithSelector(
+                this.commitHeaderRangeSmallTier.selector,
+                _maxAuthoritySetSize,
+                _authoritySetId,
+                _requestedBlock
+            ),
+            _authoritySetId,
+            _requestedBlock
+        );
+    }
+
+    /// @dev Request a header range proof from the header range function _functionId, with the
+    ///     callback _data.
+    function _requestHeaderRange(
+        bytes32 _functionId,
+        bytes memory _data,
+        uint64 _authoritySetId,
+        uint32 _requestedBlock
+    ) internal {
         bytes32 trustedHeader = blockHeightToHeaderHash[latestBlock];
         if (trustedHeader == bytes32(0)) {
             revert AuthoritySetNotFound();
@@ -193,17 +409,11 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
             _requestedBlock
         );
 
-        bytes memory data = abi.encodeWithSelector(
-            this.commitHeaderRange.selector,
-            _authoritySetId,
-            _requestedBlock
-        );
-
         ISuccinctGateway(gateway).requestCall{value: msg.value}(
-            headerRangeFunctionId,
+            _functionId,
             input,
             address(this),
-            data,
+            _data,
             500000
         );
 
@@ -225,6 +435,97 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
         uint64 _authoritySetId,
         uint32 _targetBlock
     ) external {
+        _commitHeaderRange(
+            headerRangeFunctionId,
+            _authoritySetId,
+            _targetBlock,
+            headerRangeCommitmentTreeSize
+        );
+    }
+
+    /// @notice Add target header hash, and data + state commitments for (latestBlock, targetBlock]
+    ///     proven by the header range circuit of an authority set size tier.
+    /// @param _maxAuthoritySetSize The max authority set size of the tier.
+    /// @param _authoritySetId The authority set id of the header range (latestBlock, targetBlock].
+    /// @param _targetBlock The block height of the target block.
+    function commitHeaderRangeTier(
+        uint32 _maxAuthoritySetSize,
+        uint64 _authoritySetId,
+        uint32 _targetBlock
+    ) external {
+        bytes32 functionId = headerRangeTierFunctionIds[_maxAuthoritySetSize];
+        if (functionId == bytes32(0)) {
+            revert TierNotFound();
+        }
+        _commitHeaderRange(
+            functionId,
+            _authoritySetId,
+            _targetBlock,
+            headerRangeCommitmentTreeSize
+        );
+    }
+
+    /// @notice Add target header hash, and data + state commitments for (latestBlock, targetBlock]
+    ///     proven by the small max header size header range circuit of an authority set size tier.
+    /// @param _maxAuthoritySetSize The max authority set size of the tier.
+    /// @param _authoritySetId The authority set id of the header range (latestBlock, targetBlock].
+    /// @param _targetBlock The block height of the target block.
+    function commitHeaderRangeSmallTier(
+        uint32 _maxAuthoritySetSize,
+        uint64 _authoritySetId,
+        uint32 _targetBlock
+    ) external {
+        bytes32 functionId = smallHeaderRangeTierFunctionIds[
+            _maxAuthoritySetSize
+        ];
+        if (functionId == bytes32(0)) {
+            revert TierNotFound();
+        }
+        _commitHeaderRange(
+            functionId,
+            _authoritySetId,
+            _targetBlock,
+            headerRangeCommitmentTreeSize
+        );
+    }
+
+    /// @dev Verify the header range proof from the header range function _functionId and store
+    ///     the commitments for (latestBlock, targetBlock], which are over a tree of
+    ///     _commitmentTreeSize leaves.
+    function _commitHeaderRange(
+        bytes32 _functionId,
+        uint64 _authoritySetId,
+        uint32 _targetBlock,
+        uint32 _commitmentTreeSize
+    ) internal {
+        bytes memory output = _verifyHeaderRange(
+            _functionId,
+            _authoritySetId,
+            _targetBlock
+        );
+
+        (
+            bytes32 targetHeaderHash,
+            bytes32 stateRootCommitment,
+            bytes32 dataRootCommitment
+        ) = abi.decode(output, (bytes32, bytes32, bytes32));
+
+        _storeHeaderRange(
+            _targetBlock,
+            targetHeaderHash,
+            stateRootCommitment,
+            dataRootCommitment,
+            _commitmentTreeSize
+        );
+    }
+
+    /// @dev Verify the proof of the header range function _functionId for (latestBlock,
+    ///     targetBlock], and return its output.
+    function _verifyHeaderRange(
+        bytes32 _functionId,
+        uint64 _authoritySetId,
+        uint32 _targetBlock
+    ) internal returns (bytes memory) {
         if (frozen) {
             revert ContractFrozen();
         }
@@ -256,33 +557,35 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
             _targetBlock
         );
 
-        bytes memory output = ISuccinctGateway(gateway).verifiedCall(
-            headerRangeFunctionId,
-            input
-        );
-
-        (
-            bytes32 targetHeaderHash,
-            bytes32 stateRootCommitment,
-            bytes32 dataRootCommitment
-        ) = abi.decode(output, (bytes32, bytes32, bytes32));
+        return ISuccinctGateway(gateway).verifiedCall(_functionId, input);
+    }
 
-        blockHeightToHeaderHash[_targetBlock] = targetHeaderHash;
+    /// @dev Store the target header hash and the data + state commitments for
+    ///     (latestBlock, targetBlock] over a tree of _commitmentTreeSize leaves, and update the
+    ///     latest block.
+    function _storeHeaderRange(
+        uint32 _targetBlock,
+        bytes32 _targetHeaderHash,
+        bytes32 _stateRootCommitment,
+        bytes32 _dataRootCommitment,
+        uint32 _commitmentTreeSize
+    ) internal {
+        blockHeightToHeaderHash[_targetBlock] = _targetHeaderHash;
 
         // Store the data and state commitments for the range (latestBlock, targetBlock].
         bytes32 key = keccak256(abi.encode(latestBlock, _targetBlock));
-        dataRootCommitments[key] = dataRootCommitment;
-        stateRootCommitments[key] = stateRootCommitment;
+        dataRootCommitments[key] = _dataRootCommitment;
+        stateRootCommitments[key] = _stateRootCommitment;
         rangeStartBlocks[key] = latestBlock;
 
-        emit HeadUpdate(_targetBlock, targetHeaderHash);
+        emit HeadUpdate(_targetBlock, _targetHeaderHash);
 
         emit HeaderRangeCommitmentStored(
             latestBlock,
             _targetBlock,
-            dataRootCommitment,
-            stateRootCommitment,
-            headerRangeCommitmentTreeSize
+            _dataRootCommitment,
+            _stateRootCommitment,
+            _commitmentTreeSize
         );
 
         // Update latest block.
@@ -292,6 +595,45 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
     /// @notice Requests a rotate to the next authority set.
     /// @param _currentAuthoritySetId The authority set id of the current authority set.
     function requestRotate(uint64 _currentAuthoritySetId) external payable {
+        _requestRotate(
+            rotateFunctionId,
+            abi.encodeWithSelector(
+                this.rotate.selector,
+                _currentAuthoritySetId
+            ),
+            _currentAuthoritySetId
+        );
+    }
+
+    /// @notice Requests a rotate to the next authority set with the rotate circuit of an authority
+    ///     set size tier.
+    /// @param _maxAuthoritySetSize The max authority set size of the tier.
+    /// @param _currentAuthoritySetId The authority set id of the current authority set.
+    function requestRotateTier(
+        uint32 _maxAuthoritySetSize,
+        uint64 _currentAuthoritySetId
+    ) external payable {
+        bytes32 functionId = rotateTierFunctionIds[_maxAuthoritySetSize];
+        if (functionId == bytes32(0)) {
+            revert TierNotFound();
+        }
+        _requestRotate(
+            functionId,
+            abi.encodeWithSelector(
+                this.rotateTier.selector,
+                _maxAuthoritySetSize,
+                _currentAuthoritySetId
+            ),
+            _currentAuthoritySetId
+        );
+    }
+
+    /// @dev Request a rotate proof from the rotate function _functionId, with the callback _data.
+    function _requestRotate(
+        bytes32 _functionId,
+        bytes memory _data,
+        uint64 _currentAuthoritySetId
+    ) internal {
         bytes32 currentAuthoritySetHash = authoritySetIdToHash[
             _currentAuthoritySetId
         ];
@@ -311,16 +653,11 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
             currentAuthoritySetHash
         );
 
-        bytes memory data = abi.encodeWithSelector(
-            this.rotate.selector,
-            _currentAuthoritySetId
-        );
-
         ISuccinctGateway(gateway).requestCall{value: msg.value}(
-            rotateFunctionId,
+            _functionId,
             input,
             address(this),
-            data,
+            _data,
             500000
         );
 
@@ -330,6 +667,49 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
     /// @notice Adds the authority set hash for the next authority set id.
     /// @param _currentAuthoritySetId The authority set id of the current authority set.
     function rotate(uint64 _currentAuthoritySetId) external {
+        _rotate(rotateFunctionId, _currentAuthoritySetId);
+    }
+
+    /// @notice Adds the authority set hash for the next authority set id, proven by the rotate
+    ///     circuit of an authority set size tier.
+    /// @param _maxAuthoritySetSize The max authority set size of the tier.
+    /// @param _currentAuthoritySetId The authority set id of the current authority set.
+    function rotateTier(
+        uint32 _maxAuthoritySetSize,
+        uint64 _currentAuthoritySetId
+    ) external {
+        bytes32 functionId = rotateTierFunctionIds[_maxAuthoritySetSize];
+        if (functionId == bytes32(0)) {
+            revert TierNotFound();
+        }
+        _rotate(functionId, _currentAuthoritySetId);
+    }
+
+    /// @dev Verify the rotate proof from the rotate function _functionId and store the authority
+    ///     set hash for the next authority set id.
+    function _rotate(
+        bytes32 _functionId,
+        uint64 _currentAuthoritySetId
+    ) internal {
+        bytes memory output = _verifyRotate(_functionId, _currentAuthoritySetId);
+
+        bytes32 newAuthoritySetHash = abi.decode(output, (bytes32));
+
+        // Store the authority set hash for the next authority set id.
+        authoritySetIdToHash[_currentAuthoritySetId + 1] = newAuthoritySetHash;
+
+        emit AuthoritySetStored(
+            _currentAuthoritySetId + 1,
+            newAuthoritySetHash
+        );
+    }
+
+    /// @dev Verify the proof of the rotate function _functionId from the current authority set, and
+    ///     return its output.
+    function _verifyRotate(
+        bytes32 _functionId,
+        uint64 _currentAuthoritySetId
+    ) internal returns (bytes memory) {
         if (frozen) {
             revert ContractFrozen();
         }
@@ -354,19 +734,416 @@ contract VectorX is IVectorX, TimelockedUpgradeable {
             currentAuthoritySetHash
         );
 
+        return ISuccinctGateway(gateway).verifiedCall(_functionId, input);
+    }
+
+    /// @notice Requests a rotate to the next authority set and a header update and data
+    ///     commitment from range (latestBlock, requestedBlock] justified by the next authority set,
+    ///     in a single proof.
+    /// @param _currentAuthoritySetId The authority set id of the current authority set.
+    /// @param _requestedBlock The block height of the requested block.
+    /// @dev The requested block must be justified by the next authority set.
+    function requestRotateHeaderRange(
+        uint64 _currentAuthoritySetId,
+        uint32 _requestedBlock
+    ) external payable {
+        if (rotateHeaderRangeFunctionId == bytes32(0)) {
+            revert FunctionIdNotSet();
+        }
+
+        bytes32 trustedHeader = blockHeightToHeaderHash[latestBlock];
+        if (trustedHeader == bytes32(0)) {
+            revert TrustedHeaderNotFound();
+        }
+        bytes32 currentAuthoritySetHash = authoritySetIdToHash[
+            _currentAuthoritySetId
+        ];
+        if (currentAuthoritySetHash == bytes32(0)) {
+            revert AuthoritySetNotFound();
+        }
+        if (authoritySetIdToHash[_currentAuthoritySetId + 1] != bytes32(0)) {
+            revert NextAuthoritySetExists();
+        }
+
+        require(_requestedBlock > latestBlock);
+
+        bytes memory input = abi.encodePacked(
+            _currentAuthoritySetId,
+            currentAuthoritySetHash,
+            latestBlock,
+            trustedHeader,
+            _requestedBlock
+        );
+
+        ISuccinctGateway(gateway).requestCall{value: msg.value}(
+            rotateHeaderRangeFunctionId,
+            input,
+            address(this),
+            abi.encodeWithSelector(
+                this.rotateHeaderRange.selector,
+                _currentAuthoritySetId,
+                _requestedBlock
+            ),
+            500000
+        );
+
+        emit RotateHeaderRangeRequested(
+            _currentAuthoritySetId,
+            currentAuthoritySetHash,
+            latestBlock,
+            trustedHeader,
+            _requestedBlock
+        );
+    }
+
+    /// @notice Adds the authority set hash for the next authority set id, and the target header
+    ///     hash and data + state commitments for (latestBlock, targetBlock] justified by the next
+    ///     authority set.
+    /// @param _currentAuthoritySetId The authority set id of the current authority set.
+    /// @param _targetBlock The block height of the target block.
+    function rotateHeaderRange(
+        uint64 _currentAuthoritySetId,
+        uint32 _targetBlock
+    ) external {
+        if (frozen) {
+            revert ContractFrozen();
+        }
+        if (rotateHeaderRangeFunctionId == bytes32(0)) {
+            revert FunctionIdNotSet();
+        }
+
+        bytes32 trustedHeader = blockHeightToHeaderHash[latestBlock];
+        if (trustedHeader == bytes32(0)) {
+            revert TrustedHeaderNotFound();
+        }
+        bytes32 currentAuthoritySetHash = authoritySetIdToHash[
+            _currentAuthoritySetId
+        ];
+        if (currentAuthoritySetHash == bytes32(0)) {
+            revert AuthoritySetNotFound();
+        }
+        if (authoritySetIdToHash[_currentAuthoritySetId + 1] != bytes32(0)) {
+            revert NextAuthoritySetExists();
+        }
+
+        // The header range is justified by the next authority set.
+        uint64 nextAuthoritySetId = _currentAuthoritySetId + 1;
+        if (nextAuthoritySetId < latestAuthoritySetId) {
+            revert OldAuthoritySetId();
+        }
+
+        require(_targetBlock > latestBlock);
+
+        bytes memory input = abi.encodePacked(
+            _currentAuthoritySetId,
+            currentAuthoritySetHash,
+            latestBlock,
+            trustedHeader,
+            _targetBlock
+        );
+
         bytes memory output = ISuccinctGateway(gateway).verifiedCall(
-            rotateFunctionId,
+            rotateHeaderRangeFunctionId,
             input
         );
 
-        bytes32 newAuthoritySetHash = abi.decode(output, (bytes32));
+        (
+            bytes32 newAuthoritySetHash,
+            bytes32 targetHeaderHash,
+            bytes32 stateRootCommitment,
+            bytes32 dataRootCommitment
+        ) = abi.decode(output, (bytes32, bytes32, bytes32, bytes32));
 
         // Store the authority set hash for the next authority set id.
-        authoritySetIdToHash[_currentAuthoritySetId + 1] = newAuthoritySetHash;
+        authoritySetIdToHash[nextAuthoritySetId] = newAuthoritySetHash;
+        latestAuthoritySetId = nextAuthoritySetId;
 
-        emit AuthoritySetStored(
-            _currentAuthoritySetId + 1,
-            newAuthoritySetHash
+        emit AuthoritySetStored(nextAuthoritySetId, newAuthoritySetHash);
+
+        _storeHeaderRange(
+            _targetBlock,
+            targetHeaderHash,
+            stateRootCommitment,
+            dataRootCommitment,
+            headerRangeCommitmentTreeSize
+        );
+    }
+
+    /// @notice Request a header update and data commitment from range (latestBlock, requestedBlock]
+    ///     proven as an aggregation of headerRangeAggregationNumRanges consecutive header ranges.
+    /// @param _authoritySetId The authority set id of the header range (latestBlock + 1).
+    /// @param _requestedBlock The block height of the requested block.
+    /// @dev Every range except the last has headerRangeAggregationRangeSize headers, so the last
+    ///     range must contain at least 1 header. Only the requested block must be justified.
+    function requestHeaderRangeAggregation(
+        uint64 _authoritySetId,
+        uint32 _requestedBlock
+    ) external payable {
+        _checkHeaderRangeAggregation(_requestedBlock);
+        _requestHeaderRange(
+            headerRangeAggregationFunctionId,
+            abi.encodeWithSelector(
+                this.commitHeaderRangeAggregation.selector,
+                _authoritySetId,
+                _requestedBlock
+            ),
+            _authoritySetId,
+            _requestedBlock
+        );
+    }
+
+    /// @notice Add target header hash, and data + state commitments for (latestBlock, targetBlock]
+    ///     proven by the header range aggregation function.
+    /// @param _authoritySetId The authority set id of the header range (latestBlock + 1).
+    /// @param _targetBlock The block height of the target block.
+    function commitHeaderRangeAggregation(
+        uint64 _authoritySetId,
+        uint32 _targetBlock
+    ) external {
+        _checkHeaderRangeAggregation(_targetBlock);
+        _commitHeaderRange(
+            headerRangeAggregationFunctionId,
+            _authoritySetId,
+            _targetBlock,
+            headerRangeAggregationRangeSize * headerRangeAggregationNumRanges
+        );
+    }
+
+    /// @dev Check the header range aggregation function is set, and the last range of the range
+    ///     (latestBlock, _targetBlock] contains at least 1 and at most rangeSize headers.
+    function _checkHeaderRangeAggregation(uint32 _targetBlock) internal view {
+        if (headerRangeAggregationFunctionId == bytes32(0)) {
+            revert FunctionIdNotSet();
+        }
+
+        uint32 rangeSize = headerRangeAggregationRangeSize;
+        uint32 numRanges = headerRangeAggregationNumRanges;
+        require(_targetBlock > latestBlock + (numRanges - 1) * rangeSize);
+        require(_targetBlock <= latestBlock + numRanges * rangeSize);
+    }
+
+    /// @notice Request a header update and data commitment from range (latestBlock, requestedBlock],
+    ///     and the KZG commitments root of the requested block.
+    /// @param _authoritySetId The authority set id of the header range (latestBlock, requestedBlock].
+    /// @param _requestedBlock The block height of the requested block.
+    function requestHeaderRangeKzg(
+        uint64 _authoritySetId,
+        uint32 _requestedBlock
+    ) external payable {
+        if (headerRangeKzgFunctionId == bytes32(0)) {
+            revert FunctionIdNotSet();
+        }
+        _requestHeaderRange(
+            headerRangeKzgFunctionId,
+            abi.encodeWithSelector(
+                this.commitHeaderRangeKzg.selector,
+                _authoritySetId,
+                _requestedBlock
+            ),
+            _authoritySetId,
+            _requestedBlock
         );
     }
+
+    /// @notice Add target header hash, and data + state commitments for (latestBlock, targetBlock]
+    ///     proven by the header range KZG function, and the KZG commitments root and data matrix
+    ///     dimensions of the target block.
+    /// @param _authoritySetId The authority set id of the header range (latestBlock, targetBlock].
+    /// @param _targetBlock The block height of the target block.
+    function commitHeaderRangeKzg(
+        uint64 _authoritySetId,
+        uint32 _targetBlock
+    ) external {
+        if (headerRangeKzgFunctionId == bytes32(0)) {
+            revert FunctionIdNotSet();
+        }
+
+        bytes memory output = _verifyHeaderRange(
+            headerRangeKzgFunctionId,
+            _authoritySetId,
+            _targetBlock
+        );
+
+        // The output is abi.encodePacked(targetHeaderHash, stateRootCommitment,
+        // dataRootCommitment, kzgCommitmentsRoot, rows, cols), where rows and cols are uint32s.
+        require(output.length == 4 * 32 + 2 * 4);
+        bytes32 kzgCommitmentsRoot = _readBytes32(output, 96);
+        uint32 rows = _readUint32(output, 128);
+        uint32 cols = _readUint32(output, 132);
+
+        _storeHeaderRange(
+            _targetBlock,
+            _readBytes32(output, 0),
+            _readBytes32(output, 32),
+            _readBytes32(output, 64),
+            headerRangeCommitmentTreeSize
+        );
+
+        blockHeightToKzgCommitmentsRoot[_targetBlock] = kzgCommitmentsRoot;
+        blockHeightToDataMatrixRows[_targetBlock] = rows;
+        blockHeightToDataMatrixCols[_targetBlock] = cols;
+
+        emit KzgCommitmentsStored(_targetBlock, kzgCommitmentsRoot, rows, cols);
+    }
+
+    /// @notice Requests multiRotateNumEpochs consecutive rotates from the current authority set in
+    ///     a single proof.
+    /// @param _currentAuthoritySetId The authority set id of the current authority set.
+    function requestMultiRotate(uint64 _currentAuthoritySetId) external payable {
+        if (multiRotateFunctionId == bytes32(0)) {
+            revert FunctionIdNotSet();
+        }
+        _requestRotate(
+            multiRotateFunctionId,
+            abi.encodeWithSelector(
+                this.multiRotate.selector,
+                _currentAuthoritySetId
+            ),
+            _currentAuthoritySetId
+        );
+    }
+
+    /// @notice Adds the authority set hashes for the next multiRotateNumEpochs authority set ids.
+    /// @param _currentAuthoritySetId The authority set id of the current authority set.
+    function multiRotate(uint64 _currentAuthoritySetId) external {
+        if (multiRotateFunctionId == bytes32(0)) {
+            revert FunctionIdNotSet();
+        }
+
+        bytes memory output = _verifyRotate(
+            multiRotateFunctionId,
+            _currentAuthoritySetId
+        );
+
+        // The output is abi.encodePacked(finalAuthoritySetId, newAuthoritySetHashes), where
+        // finalAuthoritySetId is a uint64 and newAuthoritySetHashes has a hash for each epoch.
+        uint32 numEpochs = multiRotateNumEpochs;
+        require(output.length == 8 + 32 * uint256(numEpochs));
+        require(
+            _readUint64(output, 0) == _currentAuthoritySetId + numEpochs
+        );
+
+        for (uint32 i = 0; i < numEpochs; i++) {
+            uint64 authoritySetId = _currentAuthoritySetId + 1 + i;
+            bytes32 authoritySetHash = _readBytes32(output, 8 + 32 * uint256(i));
+            authoritySetIdToHash[authoritySetId] = authoritySetHash;
+
+            emit AuthoritySetStored(authoritySetId, authoritySetHash);
+        }
+    }
+
+    /// @dev Read the big endian uint64 at byte _offset of _data.
+    function _readUint64(
+        bytes memory _data,
+        uint256 _offset
+    ) internal pure returns (uint64 value) {
+        assembly {
+            value := shr(192, mload(add(add(_data, 32), _offset)))
+        }
+    }
+
+    /// @dev Read the big endian uint32 at byte _offset of _data.
+    function _readUint32(
+        bytes memory _data,
+        uint256 _offset
+    ) internal pure returns (uint32 value) {
+        assembly {
+            value := shr(224, mload(add(add(_data, 32), _offset)))
+        }
+    }
+
+    /// @dev Read the bytes32 at byte _offset of _data.
+    function _readBytes32(
+        bytes memory _data,
+        uint256 _offset
+    ) internal pure returns (bytes32 value) {
+        assembly {
+            value := mload(add(add(_data, 32), _offset))
+        }
+    }
+
+    /// @notice Requests the header, state root and data root of _targetBlock, which is an ancestor
+    ///     of the stored header at _trustedBlock.
+    /// @param _trustedBlock The block height of a header in blockHeightToHeaderHash.
+    /// @param _targetBlock The block height of the header to attest.
+    function requestHeaderAncestry(
+        uint32 _trustedBlock,
+        uint32 _targetBlock
+    ) external payable {
+        bytes memory input = _headerAncestryInput(_trustedBlock, _targetBlock);
+
+        ISuccinctGateway(gateway).requestCall{value: msg.value}(
+            headerAncestryFunctionId,
+            input,
+            address(this),
+            abi.encodeWithSelector(
+                this.attestHeaderAncestry.selector,
+                _trustedBlock,
+                _targetBlock
+            ),
+            500000
+        );
+
+        emit HeaderAncestryRequested(
+            _trustedBlock,
+            blockHeightToHeaderHash[_trustedBlock],
+            _targetBlock
+        );
+    }
+
+    /// @notice Stores the header hash, state root and data root of _targetBlock. The header hash is
+    ///     stored in blockHeightToHeaderHash, so _targetBlock can be the trusted block of later
+    ///     header ancestry requests.
+    /// @param _trustedBlock The block height of a header in blockHeightToHeaderHash.
+    /// @param _targetBlock The block height of the header to attest.
+    function attestHeaderAncestry(
+        uint32 _trustedBlock,
+        uint32 _targetBlock
+    ) external {
+        if (frozen) {
+            revert ContractFrozen();
+        }
+
+        bytes memory input = _headerAncestryInput(_trustedBlock, _targetBlock);
+
+        bytes memory output = ISuccinctGateway(gateway).verifiedCall(
+            headerAncestryFunctionId,
+            input
+        );
+
+        (bytes32 targetHeaderHash, bytes32 stateRoot, bytes32 dataRoot) = abi
+            .decode(output, (bytes32, bytes32, bytes32));
+
+        blockHeightToHeaderHash[_targetBlock] = targetHeaderHash;
+        blockHeightToStateRoot[_targetBlock] = stateRoot;
+        blockHeightToDataRoot[_targetBlock] = dataRoot;
+
+        emit HeaderAncestryAttested(
+            _targetBlock,
+            targetHeaderHash,
+            stateRoot,
+            dataRoot
+        );
+    }
+
+    /// @dev The input of the header ancestry function from the stored header at _trustedBlock to
+    ///     _targetBlock.
+    function _headerAncestryInput(
+        uint32 _trustedBlock,
+        uint32 _targetBlock
+    ) internal view returns (bytes memory) {
+        if (headerAncestryFunctionId == bytes32(0)) {
+            revert FunctionIdNotSet();
+        }
+
+        bytes32 trustedHeader = blockHeightToHeaderHash[_trustedBlock];
+        if (trustedHeader == bytes32(0)) {
+            revert TrustedHeaderNotFound();
+        }
+
+        require(_targetBlock <= _trustedBlock);
+        require(_trustedBlock - _targetBlock < headerAncestryMaxNumHeaders);
+
+        return abi.encodePacked(_trustedBlock, trustedHeader, _targetBlock);
+    }
 }
@@ -24,6 +24,30 @@ interface IVectorX {
         bytes32 currentAuthoritySetHash
     );
 
+    /// @notice Emits event with the inputs of a rotate header range request.
+    /// @param currentAuthoritySetId The authority set id of the current authority set.
+    /// @param currentAuthoritySetHash The authority set hash of the current authority set.
+    /// @param trustedBlock The block height of the trusted block.
+    /// @param trustedHeader The header hash of the trusted block.
+    /// @param targetBlock The block height of the target block.
+    event RotateHeaderRangeRequested(
+        uint64 currentAuthoritySetId,
+        bytes32 currentAuthoritySetHash,
+        uint32 trustedBlock,
+        bytes32 trustedHeader,
+        uint32 targetBlock
+    );
+
+    /// @notice Emits event with the inputs of a header ancestry request.
+    /// @param trustedBlock The block height of the trusted block.
+    /// @param trustedHeader The header hash of the trusted block.
+    /// @param targetBlock The block height of the target block.
+    event HeaderAncestryRequested(
+        uint32 trustedBlock,
+        bytes32 trustedHeader,
+        uint32 targetBlock
+    );
+
     /// @notice Emitted when the light client's head is updated.
     event HeadUpdate(uint32 blockNumber, bytes32 headerHash);
 
@@ -37,6 +61,24 @@ interface IVectorX {
         uint32 headerRangeCommitmentTreeSize
     );
 
+    /// @notice Emitted when the header, state root and data root of an ancestor of a stored header
+    ///     are stored.
+    event HeaderAncestryAttested(
+        uint32 blockNumber,
+        bytes32 headerHash,
+        bytes32 stateRoot,
+        bytes32 dataRoot
+    );
+
+    /// @notice Emitted when the KZG commitments root and data matrix dimensions of a block are
+    ///     stored.
+    event KzgCommitmentsStored(
+        uint32 blockNumber,
+        bytes32 kzgCommitmentsRoot,
+        uint32 rows,
+        uint32 cols
+    );
+
     /// @notice Emitted when a new authority set is stored.
     event AuthoritySetStored(uint64 authoritySetId, bytes32 authoritySetHash);
 
@@ -52,6 +94,12 @@ interface IVectorX {
     /// @notice Authority set not found.
     error AuthoritySetNotFound();
 
+    /// @notice No function id is set for the authority set size tier.
+    error TierNotFound();
+
+    /// @notice No function id is set for the requested circuit.
+    error FunctionIdNotSet();
+
     /// @notice The authority set id is older than the authority set id of the latest commitHeaderRange.
     error OldAuthoritySetId();
 }
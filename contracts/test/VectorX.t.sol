@@ -3,12 +3,104 @@ pragma solidity ^0.8.13;
 
 import "forge-std/Test.sol";
 import "../src/VectorX.sol";
+import {ERC1967Proxy} from "@openzeppelin/proxy/ERC1967/ERC1967Proxy.sol";
+
+/// @notice A gateway which returns the output set for a function id and input from verifiedCall,
+///     and records the last request.
+contract MockGateway {
+    mapping(bytes32 => bytes) internal outputs;
+
+    bytes32 public requestedFunctionId;
+    bytes public requestedInput;
+    bytes public requestedCallbackData;
+
+    function setOutput(
+        bytes32 _functionId,
+        bytes memory _input,
+        bytes memory _output
+    ) external {
+        outputs[keccak256(abi.encode(_functionId, _input))] = _output;
+    }
+
+    function requestCall(
+        bytes32 _functionId,
+        bytes memory _input,
+        address,
+        bytes memory _callbackData,
+        uint32
+    ) external payable {
+        requestedFunctionId = _functionId;
+        requestedInput = _input;
+        requestedCallbackData = _callbackData;
+    }
+
+    function verifiedCall(
+        bytes32 _functionId,
+        bytes memory _input
+    ) external view returns (bytes memory) {
+        bytes memory output = outputs[keccak256(abi.encode(_functionId, _input))];
+        require(output.length > 0, "MockGateway: proof not found");
+        return output;
+    }
+}
 
 contract VectorXTest is Test {
+    uint32 constant GENESIS_HEIGHT = 100;
+    bytes32 constant GENESIS_HEADER = keccak256("header 100");
+    uint64 constant AUTHORITY_SET_ID = 1;
+    bytes32 constant AUTHORITY_SET_HASH = keccak256("authority set 1");
+    uint32 constant COMMITMENT_TREE_SIZE = 256;
+
+    bytes32 constant HEADER_RANGE_FUNCTION_ID = keccak256("header_range");
+    bytes32 constant ROTATE_FUNCTION_ID = keccak256("rotate");
+    bytes32 constant ROTATE_HEADER_RANGE_FUNCTION_ID = keccak256("rotate_header_range");
+    bytes32 constant AGGREGATION_FUNCTION_ID = keccak256("header_range_aggregation");
+    bytes32 constant MULTI_ROTATE_FUNCTION_ID = keccak256("multi_rotate");
+    bytes32 constant ANCESTRY_FUNCTION_ID = keccak256("header_ancestry");
+    bytes32 constant KZG_FUNCTION_ID = keccak256("header_range_kzg");
+
     VectorX public lightClient;
+    MockGateway public gateway;
 
     function setUp() public {
-        lightClient = new VectorX();
+        gateway = new MockGateway();
+        lightClient = VectorX(address(new ERC1967Proxy(address(new VectorX()), "")));
+        lightClient.initialize(
+            VectorX.InitParameters({
+                guardian: address(this),
+                gateway: address(gateway),
+                height: GENESIS_HEIGHT,
+                header: GENESIS_HEADER,
+                authoritySetId: AUTHORITY_SET_ID,
+                authoritySetHash: AUTHORITY_SET_HASH,
+                headerRangeFunctionId: HEADER_RANGE_FUNCTION_ID,
+                rotateFunctionId: ROTATE_FUNCTION_ID,
+                headerRangeCommitmentTreeSize: COMMITMENT_TREE_SIZE
+            })
+        );
+    }
+
+    function headerRangeInput(
+        uint64 _authoritySetId,
+        bytes32 _authoritySetHash,
+        uint32 _targetBlock
+    ) internal view returns (bytes memory) {
+        uint32 latestBlock = lightClient.latestBlock();
+        return
+            abi.encodePacked(
+                latestBlock,
+                lightClient.blockHeightToHeaderHash(latestBlock),
+                _authoritySetId,
+                _authoritySetHash,
+                _targetBlock
+            );
+    }
+
+    function rotateInput(
+        uint64 _authoritySetId,
+        bytes32 _authoritySetHash
+    ) internal pure returns (bytes memory) {
+        return abi.encodePacked(_authoritySetId, _authoritySetHash);
     }
 
     function testEncoding() public view {
@@ -25,4 +117,377 @@ contract VectorXTest is Test {
 
         console.logBytes(encodedBytes);
     }
+
+    function testCommitHeaderRange() public {
+        uint32 targetBlock = GENESIS_HEIGHT + 50;
+        bytes32 targetHeader = keccak256("header 150");
+        gateway.setOutput(
+            HEADER_RANGE_FUNCTION_ID,
+            headerRangeInput(AUTHORITY_SET_ID, AUTHORITY_SET_HASH, targetBlock),
+            abi.encode(targetHeader, keccak256("state"), keccak256("data"))
+        );
+
+        lightClient.commitHeaderRange(AUTHORITY_SET_ID, targetBlock);
+
+        assertEq(lightClient.latestBlock(), targetBlock);
+        assertEq(lightClient.blockHeightToHeaderHash(targetBlock), targetHeader);
+        bytes32 key = keccak256(abi.encode(GENESIS_HEIGHT, targetBlock));
+        assertEq(lightClient.dataRootCommitments(key), keccak256("data"));
+        assertEq(lightClient.stateRootCommitments(key), keccak256("state"));
+        assertEq(lightClient.rangeStartBlocks(key), GENESIS_HEIGHT);
+    }
+
+    function testCommitHeaderRangeOldAuthoritySetId() public {
+        lightClient.updateGenesisState(GENESIS_HEIGHT, GENESIS_HEADER, 0, keccak256("set 0"));
+        lightClient.updateGenesisState(
+            GENESIS_HEIGHT,
+            GENESIS_HEADER,
+            AUTHORITY_SET_ID,
+            AUTHORITY_SET_HASH
+        );
+
+        vm.expectRevert(IVectorX.OldAuthoritySetId.selector);
+        lightClient.commitHeaderRange(0, GENESIS_HEIGHT + 1);
+    }
+
+    function testCommitHeaderRangeTrustedHeaderNotFound() public {
+        lightClient.updateGenesisState(
+            GENESIS_HEIGHT + 1,
+            bytes32(0),
+            AUTHORITY_SET_ID,
+            AUTHORITY_SET_HASH
+        );
+
+        vm.expectRevert(IVectorX.TrustedHeaderNotFound.selector);
+        lightClient.commitHeaderRange(AUTHORITY_SET_ID, GENESIS_HEIGHT + 2);
+    }
+
+    function testHeaderRangeTierNotFound() public {
+        vm.expectRevert(IVectorX.TierNotFound.selector);
+        lightClient.requestHeaderRangeTier(64, AUTHORITY_SET_ID, GENESIS_HEIGHT + 1);
+
+        vm.expectRevert(IVectorX.TierNotFound.selector);
+        lightClient.commitHeaderRangeTier(64, AUTHORITY_SET_ID, GENESIS_HEIGHT + 1);
+
+        vm.expectRevert(IVectorX.TierNotFound.selector);
+        lightClient.requestRotateTier(64, AUTHORITY_SET_ID);
+
+        vm.expectRevert(IVectorX.TierNotFound.selector);
+        lightClient.rotateTier(64, AUTHORITY_SET_ID);
+    }
+
+    function testRotate() public {
+        bytes32 nextAuthoritySetHash = keccak256("authority set 2");
+        gateway.setOutput(
+            ROTATE_FUNCTION_ID,
+            rotateInput(AUTHORITY_SET_ID, AUTHORITY_SET_HASH),
+            abi.encode(nextAuthoritySetHash)
+        );
+
+        lightClient.rotate(AUTHORITY_SET_ID);
+        assertEq(lightClient.authoritySetIdToHash(AUTHORITY_SET_ID + 1), nextAuthoritySetHash);
+
+        // The next authority set is already stored.
+        vm.expectRevert(IVectorX.NextAuthoritySetExists.selector);
+        lightClient.requestRotate(AUTHORITY_SET_ID);
+
+        vm.expectRevert(IVectorX.NextAuthoritySetExists.selector);
+        lightClient.rotate(AUTHORITY_SET_ID);
+    }
+
+    function testRotateHeaderRange() public {
+        lightClient.updateRotateHeaderRangeFunctionId(ROTATE_HEADER_RANGE_FUNCTION_ID);
+
+        uint32 targetBlock = GENESIS_HEIGHT + 50;
+        bytes32 nextAuthoritySetHash = keccak256("authority set 2");
+        bytes32 targetHeader = keccak256("header 150");
+        bytes memory input = abi.encodePacked(
+            AUTHORITY_SET_ID,
+            AUTHORITY_SET_HASH,
+            GENESIS_HEIGHT,
+            GENESIS_HEADER,
+            targetBlock
+        );
+        gateway.setOutput(
+            ROTATE_HEADER_RANGE_FUNCTION_ID,
+            input,
+            abi.encode(nextAuthoritySetHash, targetHeader, keccak256("state"), keccak256("data"))
+        );
+
+        lightClient.requestRotateHeaderRange(AUTHORITY_SET_ID, targetBlock);
+        assertEq(gateway.requestedFunctionId(), ROTATE_HEADER_RANGE_FUNCTION_ID);
+        assertEq(gateway.requestedInput(), input);
+
+        lightClient.rotateHeaderRange(AUTHORITY_SET_ID, targetBlock);
+
+        // The header range is justified by the next authority set.
+        assertEq(lightClient.latestAuthoritySetId(), AUTHORITY_SET_ID + 1);
+        assertEq(lightClient.authoritySetIdToHash(AUTHORITY_SET_ID + 1), nextAuthoritySetHash);
+        assertEq(lightClient.latestBlock(), targetBlock);
+        assertEq(lightClient.blockHeightToHeaderHash(targetBlock), targetHeader);
+
+        vm.expectRevert(IVectorX.NextAuthoritySetExists.selector);
+        lightClient.requestRotateHeaderRange(AUTHORITY_SET_ID, targetBlock + 1);
+    }
+
+    function testRotateHeaderRangeFunctionIdNotSet() public {
+        vm.expectRevert(IVectorX.FunctionIdNotSet.selector);
+        lightClient.requestRotateHeaderRange(AUTHORITY_SET_ID, GENESIS_HEIGHT + 1);
+
+        vm.expectRevert(IVectorX.FunctionIdNotSet.selector);
+        lightClient.rotateHeaderRange(AUTHORITY_SET_ID, GENESIS_HEIGHT + 1);
+    }
+
+    function testRotateHeaderRangeTrustedHeaderNotFound() public {
+        lightClient.updateRotateHeaderRangeFunctionId(ROTATE_HEADER_RANGE_FUNCTION_ID);
+        lightClient.updateGenesisState(
+            GENESIS_HEIGHT + 1,
+            bytes32(0),
+            AUTHORITY_SET_ID,
+            AUTHORITY_SET_HASH
+        );
+
+        vm.expectRevert(IVectorX.TrustedHeaderNotFound.selector);
+        lightClient.requestRotateHeaderRange(AUTHORITY_SET_ID, GENESIS_HEIGHT + 2);
+
+        vm.expectRevert(IVectorX.TrustedHeaderNotFound.selector);
+        lightClient.rotateHeaderRange(AUTHORITY_SET_ID, GENESIS_HEIGHT + 2);
+    }
+
+    function testHeaderRangeAggregationBounds() public {
+        uint32 rangeSize = 256;
+        uint32 numRanges = 8;
+        lightClient.updateHeaderRangeAggregationFunctionId(
+            AGGREGATION_FUNCTION_ID,
+            rangeSize,
+            numRanges
+        );
+
+        // The last range must contain at least 1 and at most rangeSize headers.
+        uint32 minTargetBlock = GENESIS_HEIGHT + (numRanges - 1) * rangeSize + 1;
+        uint32 maxTargetBlock = GENESIS_HEIGHT + numRanges * rangeSize;
+
+        vm.expectRevert();
+        lightClient.requestHeaderRangeAggregation(AUTHORITY_SET_ID, minTargetBlock - 1);
+        vm.expectRevert();
+        lightClient.requestHeaderRangeAggregation(AUTHORITY_SET_ID, maxTargetBlock + 1);
+
+        lightClient.requestHeaderRangeAggregation(AUTHORITY_SET_ID, minTargetBlock);
+        lightClient.requestHeaderRangeAggregation(AUTHORITY_SET_ID, maxTargetBlock);
+        assertEq(gateway.requestedFunctionId(), AGGREGATION_FUNCTION_ID);
+
+        bytes32 targetHeader = keccak256("header 2148");
+        gateway.setOutput(
+            AGGREGATION_FUNCTION_ID,
+            headerRangeInput(AUTHORITY_SET_ID, AUTHORITY_SET_HASH, maxTargetBlock),
+            abi.encode(targetHeader, keccak256("state"), keccak256("data"))
+        );
+
+        vm.expectRevert();
+        lightClient.commitHeaderRangeAggregation(AUTHORITY_SET_ID, maxTargetBlock + 1);
+
+        lightClient.commitHeaderRangeAggregation(AUTHORITY_SET_ID, maxTargetBlock);
+        assertEq(lightClient.latestBlock(), maxTargetBlock);
+        assertEq(lightClient.blockHeightToHeaderHash(maxTargetBlock), targetHeader);
+    }
+
+    function testHeaderRangeAggregationFunctionIdNotSet() public {
+        vm.expectRevert(IVectorX.FunctionIdNotSet.selector);
+        lightClient.requestHeaderRangeAggregation(AUTHORITY_SET_ID, GENESIS_HEIGHT + 1);
+
+        vm.expectRevert(IVectorX.FunctionIdNotSet.selector);
+        lightClient.commitHeaderRangeAggregation(AUTHORITY_SET_ID, GENESIS_HEIGHT + 1);
+    }
+
+    function testMultiRotate() public {
+        uint32 numEpochs = 4;
+        lightClient.updateMultiRotateFunctionId(MULTI_ROTATE_FUNCTION_ID, numEpochs);
+
+        bytes32[] memory hashes = new bytes32[](numEpochs);
+        bytes memory output = abi.encodePacked(AUTHORITY_SET_ID + numEpochs);
+        for (uint32 i = 0; i < numEpochs; i++) {
+            hashes[i] = keccak256(abi.encode(AUTHORITY_SET_ID + 1 + i));
+            output = abi.encodePacked(output, hashes[i]);
+        }
+        gateway.setOutput(
+            MULTI_ROTATE_FUNCTION_ID,
+            rotateInput(AUTHORITY_SET_ID, AUTHORITY_SET_HASH),
+            output
+        );
+
+        lightClient.requestMultiRotate(AUTHORITY_SET_ID);
+        assertEq(gateway.requestedFunctionId(), MULTI_ROTATE_FUNCTION_ID);
+
+        lightClient.multiRotate(AUTHORITY_SET_ID);
+
+        // The i-th hash of the output is the authority set hash of AUTHORITY_SET_ID + 1 + i.
+        for (uint32 i = 0; i < numEpochs; i++) {
+            assertEq(lightClient.authoritySetIdToHash(AUTHORITY_SET_ID + 1 + i), hashes[i]);
+        }
+        assertEq(lightClient.authoritySetIdToHash(AUTHORITY_SET_ID + 1 + numEpochs), bytes32(0));
+
+        vm.expectRevert(IVectorX.NextAuthoritySetExists.selector);
+        lightClient.requestMultiRotate(AUTHORITY_SET_ID);
+
+        vm.expectRevert(IVectorX.NextAuthoritySetExists.selector);
+        lightClient.multiRotate(AUTHORITY_SET_ID);
+    }
+
+    function testMultiRotateInvalidOutput() public {
+        uint32 numEpochs = 2;
+        lightClient.updateMultiRotateFunctionId(MULTI_ROTATE_FUNCTION_ID, numEpochs);
+        bytes memory input = rotateInput(AUTHORITY_SET_ID, AUTHORITY_SET_HASH);
+        bytes32 hash = keccak256("authority set");
+
+        // The final authority set id is not AUTHORITY_SET_ID + numEpochs.
+        gateway.setOutput(
+            MULTI_ROTATE_FUNCTION_ID,
+            input,
+            abi.encodePacked(AUTHORITY_SET_ID + numEpochs - 1, hash, hash)
+        );
+        vm.expectRevert();
+        lightClient.multiRotate(AUTHORITY_SET_ID);
+
+        // The output has a hash for fewer than numEpochs epochs.
+        gateway.setOutput(
+            MULTI_ROTATE_FUNCTION_ID,
+            input,
+            abi.encodePacked(AUTHORITY_SET_ID + numEpochs, hash)
+        );
+        vm.expectRevert();
+        lightClient.multiRotate(AUTHORITY_SET_ID);
+
+        // The output has a hash for more than numEpochs epochs.
+        gateway.setOutput(
+            MULTI_ROTATE_FUNCTION_ID,
+            input,
+            abi.encodePacked(AUTHORITY_SET_ID + numEpochs, hash, hash, hash)
+        );
+        vm.expectRevert();
+        lightClient.multiRotate(AUTHORITY_SET_ID);
+    }
+
+    function testMultiRotateFunctionIdNotSet() public {
+        vm.expectRevert(IVectorX.FunctionIdNotSet.selector);
+        lightClient.requestMultiRotate(AUTHORITY_SET_ID);
+
+        vm.expectRevert(IVectorX.FunctionIdNotSet.selector);
+        lightClient.multiRotate(AUTHORITY_SET_ID);
+    }
+
+    function testHeaderAncestry() public {
+        uint32 maxNumHeaders = 16;
+        lightClient.updateHeaderAncestryFunctionId(ANCESTRY_FUNCTION_ID, maxNumHeaders);
+
+        // The target block is maxNumHeaders - 1 blocks before the trusted block.
+        uint32 targetBlock = GENESIS_HEIGHT - (maxNumHeaders - 1);
+        bytes memory input = abi.encodePacked(GENESIS_HEIGHT, GENESIS_HEADER, targetBlock);
+        bytes32 targetHeader = keccak256("header 85");
+        gateway.setOutput(
+            ANCESTRY_FUNCTION_ID,
+            input,
+            abi.encode(targetHeader, keccak256("state"), keccak256("data"))
+        );
+
+        lightClient.requestHeaderAncestry(GENESIS_HEIGHT, targetBlock);
+        assertEq(gateway.requestedInput(), input);
+
+        lightClient.attestHeaderAncestry(GENESIS_HEIGHT, targetBlock);
+        assertEq(lightClient.blockHeightToHeaderHash(targetBlock), targetHeader);
+        assertEq(lightClient.blockHeightToStateRoot(targetBlock), keccak256("state"));
+        assertEq(lightClient.blockHeightToDataRoot(targetBlock), keccak256("data"));
+
+        // The trusted block itself is in range.
+        lightClient.requestHeaderAncestry(GENESIS_HEIGHT, GENESIS_HEIGHT);
+    }
+
+    function testHeaderAncestryRangeBounds() public {
+        uint32 maxNumHeaders = 16;
+        lightClient.updateHeaderAncestryFunctionId(ANCESTRY_FUNCTION_ID, maxNumHeaders);
+
+        // The target block is after the trusted block.
+        vm.expectRevert();
+        lightClient.requestHeaderAncestry(GENESIS_HEIGHT, GENESIS_HEIGHT + 1);
+
+        // The target block is maxNumHeaders blocks before the trusted block.
+        vm.expectRevert();
+        lightClient.requestHeaderAncestry(GENESIS_HEIGHT, GENESIS_HEIGHT - maxNumHeaders);
+
+        vm.expectRevert();
+        lightClient.attestHeaderAncestry(GENESIS_HEIGHT, GENESIS_HEIGHT - maxNumHeaders);
+
+        // The trusted block has no stored header.
+        vm.expectRevert(IVectorX.TrustedHeaderNotFound.selector);
+        lightClient.requestHeaderAncestry(GENESIS_HEIGHT - 1, GENESIS_HEIGHT - 2);
+    }
+
+    function testHeaderAncestryFunctionIdNotSet() public {
+        vm.expectRevert(IVectorX.FunctionIdNotSet.selector);
+        lightClient.requestHeaderAncestry(GENESIS_HEIGHT, GENESIS_HEIGHT - 1);
+    }
+
+    function testCommitHeaderRangeKzg() public {
+        lightClient.updateHeaderRangeKzgFunctionId(KZG_FUNCTION_ID);
+
+        uint32 targetBlock = GENESIS_HEIGHT + 50;
+        bytes memory input = headerRangeInput(AUTHORITY_SET_ID, AUTHORITY_SET_HASH, targetBlock);
+        bytes32 targetHeader = keccak256("header 150");
+        bytes32 kzgCommitmentsRoot = keccak256("kzg commitments");
+        gateway.setOutput(
+            KZG_FUNCTION_ID,
+            input,
+            abi.encodePacked(
+                targetHeader,
+                keccak256("state"),
+                keccak256("data"),
+                kzgCommitmentsRoot,
+                uint32(256),
+                uint32(64)
+            )
+        );
+
+        lightClient.requestHeaderRangeKzg(AUTHORITY_SET_ID, targetBlock);
+        assertEq(gateway.requestedFunctionId(), KZG_FUNCTION_ID);
+        assertEq(gateway.requestedInput(), input);
+
+        lightClient.commitHeaderRangeKzg(AUTHORITY_SET_ID, targetBlock);
+        assertEq(lightClient.latestBlock(), targetBlock);
+        assertEq(lightClient.blockHeightToHeaderHash(targetBlock), targetHeader);
+        bytes32 key = keccak256(abi.encode(GENESIS_HEIGHT, targetBlock));
+        assertEq(lightClient.dataRootCommitments(key), keccak256("data"));
+        assertEq(lightClient.stateRootCommitments(key), keccak256("state"));
+        assertEq(lightClient.blockHeightToKzgCommitmentsRoot(targetBlock), kzgCommitmentsRoot);
+        assertEq(lightClient.blockHeightToDataMatrixRows(targetBlock), 256);
+        assertEq(lightClient.blockHeightToDataMatrixCols(targetBlock), 64);
+    }
+
+    function testCommitHeaderRangeKzgInvalidOutputLength() public {
+        lightClient.updateHeaderRangeKzgFunctionId(KZG_FUNCTION_ID);
+
+        uint32 targetBlock = GENESIS_HEIGHT + 50;
+        bytes memory input = headerRangeInput(AUTHORITY_SET_ID, AUTHORITY_SET_HASH, targetBlock);
+        bytes32 hash = keccak256("hash");
+
+        // The output of a header range function without the KZG commitments.
+        gateway.setOutput(KZG_FUNCTION_ID, input, abi.encode(hash, hash, hash));
+        vm.expectRevert();
+        lightClient.commitHeaderRangeKzg(AUTHORITY_SET_ID, targetBlock);
+
+        // The output has an extra byte.
+        gateway.setOutput(
+            KZG_FUNCTION_ID,
+            input,
+            abi.encodePacked(hash, hash, hash, hash, uint32(256), uint32(64), uint8(0))
+        );
+        vm.expectRevert();
+        lightClient.commitHeaderRangeKzg(AUTHORITY_SET_ID, targetBlock);
+    }
+
+    function testHeaderRangeKzgFunctionIdNotSet() public {
+        vm.expectRevert(IVectorX.FunctionIdNotSet.selector);
+        lightClient.requestHeaderRangeKzg(AUTHORITY_SET_ID, GENESIS_HEIGHT + 1);
+
+        vm.expectRevert(IVectorX.FunctionIdNotSet.selector);
+        lightClient.commitHeaderRangeKzg(AUTHORITY_SET_ID, GENESIS_HEIGHT + 1);
+    }
 }
@@ -0,0 +1,166 @@
+// Differential test of `verify_subchain` against `native::verify_subchain`, on a synthetic chain
+// with a tampered header. The circuit reads its headers from the witness bundle at
+// WITNESS_BUNDLE_ENV, which is read once per process, so this test runs in its own test binary and
+// sets the bundle before any hint reads it.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::{env, fs};
+
+use ethers::types::H256;
+use plonky2x::backend::circuit::Circuit;
+use plonky2x::frontend::vars::U32Variable;
+use plonky2x::prelude::{Bytes32Variable, CircuitBuilder, DefaultBuilder, PlonkParameters};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sp_core::blake2_256;
+use vectorx::builder::subchain_verification::SubChainVerifier;
+use vectorx::consts::SMALL_MAX_HEADER_SIZE;
+use vectorx::native;
+use vectorx::synthetic::{SyntheticChain, SyntheticChainConfig};
+use vectorx::witness::{WitnessBundle, WITNESS_BUNDLE_ENV};
+
+const SEED: u64 = 0x7665_6374_6f72_78;
+
+// MapReduce circuits requires a circuit to be defined in order to invoke the mapreduce method.
+#[derive(Clone, Debug)]
+struct DifferentialSubchainCircuit<const MAX_HEADER_SIZE: usize, const MAX_NUM_HEADERS: usize>;
+
+impl<const MAX_HEADER_SIZE: usize, const MAX_NUM_HEADERS: usize> Circuit
+    for DifferentialSubchainCircuit<MAX_HEADER_SIZE, MAX_NUM_HEADERS>
+{
+    fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+    where
+        <<L as PlonkParameters<D>>::Config as plonky2x::prelude::plonky2::plonk::config::GenericConfig<D>>::Hasher:
+        plonky2x::prelude::plonky2::plonk::config::AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let trusted_block = builder.evm_read::<U32Variable>();
+        let trusted_header_hash = builder.evm_read::<Bytes32Variable>();
+        let target_block = builder.evm_read::<U32Variable>();
+
+        let subchain_output = builder.verify_subchain::<Self, MAX_HEADER_SIZE, MAX_NUM_HEADERS>(
+            trusted_block,
+            trusted_header_hash,
+            target_block,
+        );
+        builder.evm_write(subchain_output.target_header_hash);
+        builder.evm_write(subchain_output.state_root_merkle_root);
+        builder.evm_write(subchain_output.data_root_merkle_root);
+    }
+}
+
+#[test]
+#[cfg_attr(feature = "ci", ignore)]
+fn test_differential_verify_subchain() {
+    env::set_var("RUST_LOG", "info");
+    env_logger::try_init().unwrap_or_default();
+
+    const MAX_HEADER_SIZE: usize = SMALL_MAX_HEADER_SIZE;
+    const MAX_NUM_HEADERS: usize = 16;
+
+    // The chain's header at TAMPERED_BLOCK has a modified state root, so it links to its parent but
+    // the next header does not link to it.
+    const TAMPERED_BLOCK: u32 = 125;
+    let synthetic = SyntheticChain::new(SyntheticChainConfig {
+        start_block: 100,
+        num_blocks: 48,
+        epoch_length: 16,
+        authority_set_sizes: vec![4],
+        ..Default::default()
+    });
+    let mut bundle = synthetic.witness_bundle(&[]);
+    let mut tampered_header = synthetic.header(TAMPERED_BLOCK).to_vec();
+    tampered_header[40] ^= 1;
+    bundle
+        .headers
+        .insert(TAMPERED_BLOCK, tampered_header.into());
+
+    let path = env::temp_dir().join(format!(
+        "vectorx_differential_witness_{}.json",
+        std::process::id()
+    ));
+    bundle.write(path.to_str().unwrap()).unwrap();
+    env::set_var(WITNESS_BUNDLE_ENV, &path);
+    assert_eq!(WitnessBundle::from_env(), Some(&bundle));
+
+    let mut builder = DefaultBuilder::new();
+    DifferentialSubchainCircuit::<MAX_HEADER_SIZE, MAX_NUM_HEADERS>::define(&mut builder);
+    let circuit = builder.build();
+
+    let header_hash = |block_number: u32| H256::from(blake2_256(&bundle.headers[&block_number]));
+    let mut cases = vec![
+        // Full and partial ranges.
+        (100, header_hash(100), 116),
+        (101, header_hash(101), 104),
+        (105, header_hash(105), 106),
+        // A range ending at the tampered header, which is linked.
+        (110, header_hash(110), TAMPERED_BLOCK),
+        // A range through the tampered header, which is not linked.
+        (120, header_hash(120), 130),
+        // More headers than MAX_NUM_HEADERS.
+        (100, header_hash(100), 117),
+        // Wrong trusted header hashes.
+        (101, header_hash(102), 110),
+        (101, H256::zero(), 110),
+    ];
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for _ in 0..4 {
+        let trusted_block = rng.gen_range(100..140);
+        let target_block = rng.gen_range(trusted_block + 1..=147);
+        cases.push((trusted_block, header_hash(trusted_block), target_block));
+    }
+
+    let mut divergences = Vec::new();
+    for (trusted_block, trusted_header_hash, target_block) in cases.iter().copied() {
+        let headers = bundle
+            .get_headers_range(trusted_block + 1, target_block)
+            .unwrap();
+        let native = native::verify_subchain::<MAX_NUM_HEADERS>(
+            trusted_block,
+            trusted_header_hash,
+            &headers,
+        )
+        .map(|output| {
+            (
+                output.target_header_hash,
+                output.state_root_commitment,
+                output.data_root_commitment,
+            )
+        });
+
+        let mut input = circuit.input();
+        input.evm_write::<U32Variable>(trusted_block);
+        input.evm_write::<Bytes32Variable>(trusted_header_hash);
+        input.evm_write::<U32Variable>(target_block);
+        // None if the circuit failed to prove.
+        let output = catch_unwind(AssertUnwindSafe(|| circuit.prove(&input)))
+            .ok()
+            .map(|(_, mut output)| {
+                (
+                    output.evm_read::<Bytes32Variable>(),
+                    output.evm_read::<Bytes32Variable>(),
+                    output.evm_read::<Bytes32Variable>(),
+                )
+            });
+
+        let diverged = match (&native, &output) {
+            (Ok(native), Some(output)) => native != output,
+            (Err(_), None) => false,
+            _ => true,
+        };
+        if diverged {
+            divergences.push(format!(
+                "trusted_block: {}, trusted_header_hash: {:?}, target_block: {}, native: {:?}, \
+                 circuit: {:?}",
+                trusted_block, trusted_header_hash, target_block, native, output
+            ));
+        }
+    }
+    fs::remove_file(path).ok();
+    assert!(
+        divergences.is_empty(),
+        "{} of {} cases diverged:\n{:#?}",
+        divergences.len(),
+        cases.len(),
+        divergences
+    );
+}
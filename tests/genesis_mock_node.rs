@@ -0,0 +1,50 @@
+// Test of `derive_genesis` over JSON-RPC against the mock Avail node. Start the mock node with the
+// synthetic chain below, and point AVAIL_URL at it:
+//
+//     cargo run --release --bin mock_rpc -- --metadata <path> --num-blocks 60 --epoch-length 16 \
+//         --authority-set-sizes 4,7,5
+//     AVAIL_URL=ws://127.0.0.1:9944 cargo test --release --test genesis_mock_node -- --ignored
+
+use vectorx::genesis::{derive_genesis, GenesisParameters, TrustedAuthoritySet};
+use vectorx::input::RpcDataFetcher;
+use vectorx::synthetic::{SyntheticChain, SyntheticChainConfig};
+
+#[tokio::test]
+#[ignore]
+async fn test_derive_genesis_mock_node() {
+    dotenv::dotenv().ok();
+    env_logger::try_init().unwrap_or_default();
+
+    let chain = SyntheticChain::new(SyntheticChainConfig {
+        start_block: 0,
+        num_blocks: 60,
+        epoch_length: 16,
+        authority_set_sizes: vec![4, 7, 5],
+        ..Default::default()
+    });
+    let mut fetcher = RpcDataFetcher::new().await;
+
+    // A block inside an epoch, and an epoch end block.
+    for (target_block, authority_set_id) in [(55, 3), (48, 3)] {
+        let transcript = derive_genesis(
+            &mut fetcher,
+            TrustedAuthoritySet {
+                authority_set_id: 0,
+                pubkeys: chain.authorities(0),
+            },
+            target_block,
+        )
+        .await
+        .unwrap();
+        assert_eq!(transcript.rotates.len(), 3);
+        assert_eq!(
+            transcript.genesis,
+            GenesisParameters {
+                height: target_block,
+                header: chain.header_hash(target_block),
+                authority_set_id,
+                authority_set_hash: chain.authority_set_hash(authority_set_id),
+            }
+        );
+    }
+}
@@ -0,0 +1,95 @@
+// Test of `build_proof_bundle` for a block in an aggregated range over JSON-RPC against the mock
+// Avail node. Start the mock node with the synthetic chain below, and point AVAIL_URL at it:
+//
+//     cargo run --release --bin mock_rpc -- --metadata <path> --num-blocks 700 --epoch-length 400 \
+//         --authority-set-sizes 4,5
+//     AVAIL_URL=ws://127.0.0.1:9944 cargo test --release --test proof_bundle_mock_node -- --ignored
+
+use ethers::types::Address;
+use vectorx::inclusion::bundle::build_proof_bundle;
+use vectorx::input::{DataCommitmentRange, RpcDataFetcher};
+use vectorx::native;
+use vectorx::synthetic::{SyntheticChain, SyntheticChainConfig};
+
+// The commitment tree size of header_range_aggregation_2048, and of the header range circuits it
+// aggregates.
+const AGGREGATION_TREE_SIZE: usize = 2048;
+const HEADER_RANGE_TREE_SIZE: u32 = 256;
+
+#[tokio::test]
+#[ignore]
+async fn test_proof_bundle_aggregated_range_mock_node() {
+    dotenv::dotenv().ok();
+    env_logger::try_init().unwrap_or_default();
+
+    let chain = SyntheticChain::new(SyntheticChainConfig {
+        start_block: 0,
+        num_blocks: 700,
+        epoch_length: 400,
+        authority_set_sizes: vec![4, 5],
+        ..Default::default()
+    });
+    let mut fetcher = RpcDataFetcher::new().await;
+
+    // An aggregated range of more than HEADER_RANGE_TREE_SIZE headers, across the epoch end block
+    // 400.
+    let (start, end) = (0, 600);
+    let headers = (start + 1..=end)
+        .map(|block_number| chain.header(block_number).to_vec())
+        .collect::<Vec<_>>();
+    let data_commitment =
+        native::verify_subchain::<AGGREGATION_TREE_SIZE>(start, chain.header_hash(start), &headers)
+            .unwrap()
+            .data_root_commitment;
+
+    let range = DataCommitmentRange {
+        start,
+        end,
+        data_commitment: data_commitment.0.to_vec(),
+        commitment_tree_size: AGGREGATION_TREE_SIZE as u32,
+    };
+    let contract_address = Address::repeat_byte(0x11);
+    for block_number in [1, 300, 600] {
+        let bundle = build_proof_bundle(
+            &mut fetcher,
+            1,
+            contract_address,
+            &range,
+            data_commitment,
+            block_number,
+            None,
+        )
+        .await
+        .unwrap();
+        bundle.verify().unwrap();
+        assert_eq!(bundle.block_hash, chain.header_hash(block_number));
+        assert_eq!(bundle.header.to_vec(), chain.header(block_number));
+        assert_eq!(bundle.data_root_proof.branch.len(), 11);
+        assert_eq!(
+            bundle.data_root_proof.data_root,
+            native::decode_header(chain.header(block_number))
+                .unwrap()
+                .data_root
+        );
+    }
+
+    // The data commitment of the aggregated range is not over a tree of the contract's
+    // headerRangeCommitmentTreeSize.
+    let range = DataCommitmentRange {
+        start,
+        end: HEADER_RANGE_TREE_SIZE,
+        data_commitment: data_commitment.0.to_vec(),
+        commitment_tree_size: HEADER_RANGE_TREE_SIZE,
+    };
+    assert!(build_proof_bundle(
+        &mut fetcher,
+        1,
+        contract_address,
+        &range,
+        data_commitment,
+        HEADER_RANGE_TREE_SIZE,
+        None,
+    )
+    .await
+    .is_err());
+}
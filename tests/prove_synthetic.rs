@@ -0,0 +1,493 @@
+// Prove and verify tests of the circuits that span authority sets, on a synthetic chain. The
+// circuits read their headers, justifications and rotates from the witness bundle at
+// WITNESS_BUNDLE_ENV, which is read once per process, so these tests run in their own test binary
+// and share one bundle of the chain.
+
+use std::env;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::OnceLock;
+
+use ethers::types::H256;
+use plonky2x::backend::circuit::Circuit;
+use plonky2x::frontend::uint::uint64::U64Variable;
+use plonky2x::frontend::vars::U32Variable;
+use plonky2x::prelude::{Bytes32Variable, DefaultBuilder};
+use vectorx::consts::{self, DELAY_LENGTH, SMALL_MAX_HEADER_SIZE, VALIDATOR_LENGTH};
+use vectorx::evm::{
+    HeaderAncestryRequest, HeaderAncestryResponse, HeaderRangeKzgResponse, HeaderRangeRequest,
+    HeaderRangeResponse, MultiRotateResponse, RotateHeaderRangeRequest, RotateHeaderRangeResponse,
+    RotateRequest,
+};
+use vectorx::header_ancestry::HeaderAncestryCircuit;
+use vectorx::header_range_aggregation::HeaderRangeAggregationCircuit;
+use vectorx::header_range_kzg::HeaderRangeKzgCircuit;
+use vectorx::layout::CURRENT_HEADER_LAYOUT;
+use vectorx::multi_rotate::MultiRotateCircuit;
+use vectorx::native;
+use vectorx::rotate_header_range::RotateHeaderRangeCircuit;
+use vectorx::synthetic::{SyntheticChain, SyntheticChainConfig};
+use vectorx::witness::{WitnessBundle, WITNESS_BUNDLE_ENV};
+
+// Fits every authority set of the chain.
+const NUM_AUTHORITIES: usize = 5;
+const MAX_SUBARRAY_SIZE: usize = NUM_AUTHORITIES * VALIDATOR_LENGTH + DELAY_LENGTH;
+const MAX_HEADER_SIZE: usize = SMALL_MAX_HEADER_SIZE;
+const NUM_HEADERS: usize = 16;
+
+// Blocks that are not epoch end blocks, with a justification in the witness bundle.
+const JUSTIFIED_BLOCKS: [u32; 2] = [24, 40];
+
+// The synthetic chain, with epoch end blocks 16 and 32, whose witness bundle is at
+// WITNESS_BUNDLE_ENV.
+fn chain() -> &'static SyntheticChain {
+    static CHAIN: OnceLock<SyntheticChain> = OnceLock::new();
+    CHAIN.get_or_init(|| {
+        env::set_var("RUST_LOG", "info");
+        env_logger::try_init().unwrap_or_default();
+
+        let chain = SyntheticChain::new(SyntheticChainConfig {
+            start_block: 0,
+            num_blocks: 48,
+            epoch_length: 16,
+            authority_set_sizes: vec![4, 5, 4],
+            ..Default::default()
+        });
+        let bundle = chain.witness_bundle(&JUSTIFIED_BLOCKS);
+        let path = env::temp_dir().join(format!(
+            "vectorx_prove_synthetic_witness_{}.json",
+            std::process::id()
+        ));
+        bundle.write(path.to_str().unwrap()).unwrap();
+        env::set_var(WITNESS_BUNDLE_ENV, &path);
+        assert_eq!(WitnessBundle::from_env(), Some(&bundle));
+        chain
+    })
+}
+
+#[test]
+#[cfg_attr(feature = "ci", ignore)]
+fn test_rotate_header_range() {
+    let chain = chain();
+
+    let mut builder = DefaultBuilder::new();
+    RotateHeaderRangeCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE, NUM_HEADERS>::define(
+        &mut builder,
+    );
+    let circuit = builder.build();
+
+    // Rotate from authority set 0 at its epoch end block 16, and step to block 24, which is
+    // justified by authority set 1.
+    let request = RotateHeaderRangeRequest {
+        authority_set_id: 0,
+        authority_set_hash: chain.authority_set_hash(0),
+        trusted_block: chain.epoch_end_block(0),
+        trusted_header_hash: chain.header_hash(chain.epoch_end_block(0)),
+        target_block: 24,
+    };
+    let prove = |request: &RotateHeaderRangeRequest| {
+        let mut input = circuit.input();
+        input.evm_write::<U64Variable>(request.authority_set_id);
+        input.evm_write::<Bytes32Variable>(request.authority_set_hash);
+        input.evm_write::<U32Variable>(request.trusted_block);
+        input.evm_write::<Bytes32Variable>(request.trusted_header_hash);
+        input.evm_write::<U32Variable>(request.target_block);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        RotateHeaderRangeResponse {
+            new_authority_set_hash: output.evm_read::<Bytes32Variable>(),
+            target_header_hash: output.evm_read::<Bytes32Variable>(),
+            state_root_commitment: output.evm_read::<Bytes32Variable>(),
+            data_root_commitment: output.evm_read::<Bytes32Variable>(),
+        }
+    };
+
+    let rotate = chain.rotate(0);
+    let native_rotate = native::rotate(
+        request.authority_set_id,
+        request.authority_set_hash,
+        chain.header(rotate.epoch_end_block_number),
+        rotate.start_position,
+        &chain.justification(rotate.epoch_end_block_number),
+    )
+    .unwrap();
+    let headers = (request.trusted_block + 1..=request.target_block)
+        .map(|block_number| chain.header(block_number).to_vec())
+        .collect::<Vec<_>>();
+    let native_header_range = native::header_range::<NUM_HEADERS>(
+        request.trusted_block,
+        request.trusted_header_hash,
+        1,
+        native_rotate.new_authority_set_hash,
+        &headers,
+        &chain.justification(request.target_block),
+    )
+    .unwrap();
+    assert_eq!(
+        native_rotate.new_authority_set_hash,
+        chain.authority_set_hash(1)
+    );
+    assert_eq!(
+        prove(&request),
+        RotateHeaderRangeResponse {
+            new_authority_set_hash: native_rotate.new_authority_set_hash,
+            target_header_hash: native_header_range.target_header_hash,
+            state_root_commitment: native_header_range.state_root_commitment,
+            data_root_commitment: native_header_range.data_root_commitment,
+        }
+    );
+
+    let invalid_requests = [
+        // The trusted header hash is not the header hash of the trusted block.
+        RotateHeaderRangeRequest {
+            trusted_header_hash: H256::zero(),
+            ..request.clone()
+        },
+        // The authority set hash is not the hash of authority set 0.
+        RotateHeaderRangeRequest {
+            authority_set_hash: chain.authority_set_hash(1),
+            ..request.clone()
+        },
+        // Block 40 is justified by authority set 2, not the next authority set.
+        RotateHeaderRangeRequest {
+            trusted_block: chain.epoch_end_block(1),
+            trusted_header_hash: chain.header_hash(chain.epoch_end_block(1)),
+            target_block: 40,
+            ..request.clone()
+        },
+    ];
+    for invalid_request in invalid_requests.iter() {
+        assert!(
+            catch_unwind(AssertUnwindSafe(|| prove(invalid_request))).is_err(),
+            "{:?} should fail to prove",
+            invalid_request
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(feature = "ci", ignore)]
+fn test_header_range_aggregation() {
+    let chain = chain();
+
+    const RANGE_SIZE: usize = 8;
+    const NUM_RANGES: usize = 2;
+
+    let mut builder = DefaultBuilder::new();
+    HeaderRangeAggregationCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, RANGE_SIZE, NUM_RANGES>::define(
+        &mut builder,
+    );
+    let circuit = builder.build();
+
+    let request =
+        |trusted_block: u32, authority_set_id: u64, target_block: u32| HeaderRangeRequest {
+            trusted_block,
+            trusted_header_hash: chain.header_hash(trusted_block),
+            authority_set_id,
+            authority_set_hash: chain.authority_set_hash(authority_set_id),
+            target_block,
+        };
+    let prove = |request: &HeaderRangeRequest| {
+        let mut input = circuit.input();
+        input.evm_write::<U32Variable>(request.trusted_block);
+        input.evm_write::<Bytes32Variable>(request.trusted_header_hash);
+        input.evm_write::<U64Variable>(request.authority_set_id);
+        input.evm_write::<Bytes32Variable>(request.authority_set_hash);
+        input.evm_write::<U32Variable>(request.target_block);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        HeaderRangeResponse {
+            target_header_hash: output.evm_read::<Bytes32Variable>(),
+            state_root_commitment: output.evm_read::<Bytes32Variable>(),
+            data_root_commitment: output.evm_read::<Bytes32Variable>(),
+        }
+    };
+
+    // Only the target block has a justification: block 26, the end of the first range, has none.
+    // Block 32 is the epoch end block of authority set 1. Block 40 is justified by authority set 2,
+    // and the range from block 24 to it spans the epoch end block 32.
+    for request in [request(18, 1, 32), request(24, 2, 40)] {
+        // The commitments are over one tree of RANGE_SIZE * NUM_RANGES headers.
+        let headers = (request.trusted_block + 1..=request.target_block)
+            .map(|block_number| chain.header(block_number).to_vec())
+            .collect::<Vec<_>>();
+        let native = native::verify_subchain::<{ RANGE_SIZE * NUM_RANGES }>(
+            request.trusted_block,
+            request.trusted_header_hash,
+            &headers,
+        )
+        .unwrap();
+        assert_eq!(
+            prove(&request),
+            HeaderRangeResponse {
+                target_header_hash: native.target_header_hash,
+                state_root_commitment: native.state_root_commitment,
+                data_root_commitment: native.data_root_commitment,
+            }
+        );
+    }
+
+    let invalid_requests = [
+        // Block 40 is justified by authority set 2, not authority set 1.
+        request(24, 1, 40),
+        // The last range is empty.
+        request(24, 1, 32),
+        // The range has more than RANGE_SIZE * NUM_RANGES headers.
+        request(16, 2, 40),
+        // The trusted header hash is not the header hash of the trusted block.
+        HeaderRangeRequest {
+            trusted_header_hash: chain.header_hash(25),
+            ..request(24, 2, 40)
+        },
+    ];
+    for invalid_request in invalid_requests.iter() {
+        assert!(
+            catch_unwind(AssertUnwindSafe(|| prove(invalid_request))).is_err(),
+            "{:?} should fail to prove",
+            invalid_request
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(feature = "ci", ignore)]
+fn test_multi_rotate() {
+    let chain = chain();
+
+    const NUM_EPOCHS: usize = 2;
+
+    let mut builder = DefaultBuilder::new();
+    MultiRotateCircuit::<NUM_AUTHORITIES, MAX_HEADER_SIZE, MAX_SUBARRAY_SIZE, NUM_EPOCHS>::define(
+        &mut builder,
+    );
+    let circuit = builder.build();
+
+    let prove = |request: &RotateRequest| {
+        let mut input = circuit.input();
+        input.evm_write::<U64Variable>(request.authority_set_id);
+        input.evm_write::<Bytes32Variable>(request.authority_set_hash);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        MultiRotateResponse {
+            final_authority_set_id: output.evm_read::<U64Variable>(),
+            new_authority_set_hashes: (0..NUM_EPOCHS)
+                .map(|_| output.evm_read::<Bytes32Variable>())
+                .collect(),
+        }
+    };
+
+    // Rotate natively through the epoch end blocks 16 and 32.
+    let mut authority_set_hash = chain.authority_set_hash(0);
+    let mut new_authority_set_hashes = Vec::new();
+    for authority_set_id in 0..NUM_EPOCHS as u64 {
+        let rotate = chain.rotate(authority_set_id);
+        authority_set_hash = native::rotate(
+            authority_set_id,
+            authority_set_hash,
+            chain.header(rotate.epoch_end_block_number),
+            rotate.start_position,
+            &chain.justification(rotate.epoch_end_block_number),
+        )
+        .unwrap()
+        .new_authority_set_hash;
+        assert_eq!(
+            authority_set_hash,
+            chain.authority_set_hash(authority_set_id + 1)
+        );
+        new_authority_set_hashes.push(authority_set_hash);
+    }
+
+    let request = RotateRequest {
+        authority_set_id: 0,
+        authority_set_hash: chain.authority_set_hash(0),
+    };
+    assert_eq!(
+        prove(&request),
+        MultiRotateResponse {
+            final_authority_set_id: NUM_EPOCHS as u64,
+            new_authority_set_hashes,
+        }
+    );
+
+    let invalid_requests = [
+        // The authority set hash is not the hash of authority set 0.
+        RotateRequest {
+            authority_set_hash: chain.authority_set_hash(1),
+            ..request.clone()
+        },
+        // The chain has no epoch end block for authority set 2.
+        RotateRequest {
+            authority_set_id: 1,
+            authority_set_hash: chain.authority_set_hash(1),
+        },
+    ];
+    for invalid_request in invalid_requests.iter() {
+        assert!(
+            catch_unwind(AssertUnwindSafe(|| prove(invalid_request))).is_err(),
+            "{:?} should fail to prove",
+            invalid_request
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(feature = "ci", ignore)]
+fn test_header_ancestry() {
+    let chain = chain();
+
+    let mut builder = DefaultBuilder::new();
+    HeaderAncestryCircuit::<MAX_HEADER_SIZE, NUM_HEADERS>::define(&mut builder);
+    let circuit = builder.build();
+
+    let prove = |request: &HeaderAncestryRequest| {
+        let mut input = circuit.input();
+        input.evm_write::<U32Variable>(request.trusted_block);
+        input.evm_write::<Bytes32Variable>(request.trusted_header_hash);
+        input.evm_write::<U32Variable>(request.target_block);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        HeaderAncestryResponse {
+            target_header_hash: output.evm_read::<Bytes32Variable>(),
+            state_root: output.evm_read::<Bytes32Variable>(),
+            data_root: output.evm_read::<Bytes32Variable>(),
+        }
+    };
+    let expected = |target_block: u32| {
+        let header = native::decode_header(chain.header(target_block)).unwrap();
+        HeaderAncestryResponse {
+            target_header_hash: chain.header_hash(target_block),
+            state_root: header.state_root,
+            data_root: header.data_root,
+        }
+    };
+
+    // Walk back from block 24 across the epoch end block 16 to block 9, NUM_HEADERS - 1 blocks
+    // before it.
+    let request = HeaderAncestryRequest {
+        trusted_block: 24,
+        trusted_header_hash: chain.header_hash(24),
+        target_block: 9,
+    };
+    assert_eq!(prove(&request), expected(9));
+
+    // The trusted header is its own ancestor.
+    let request = HeaderAncestryRequest {
+        target_block: 24,
+        ..request
+    };
+    assert_eq!(prove(&request), expected(24));
+
+    let invalid_requests = [
+        // The trusted header hash is not the hash of the trusted block.
+        HeaderAncestryRequest {
+            trusted_header_hash: chain.header_hash(23),
+            target_block: 20,
+            ..request.clone()
+        },
+        // The target block is after the trusted block.
+        HeaderAncestryRequest {
+            target_block: 25,
+            ..request.clone()
+        },
+        // The target block is NUM_HEADERS blocks before the trusted block.
+        HeaderAncestryRequest {
+            target_block: 8,
+            ..request.clone()
+        },
+    ];
+    for invalid_request in invalid_requests.iter() {
+        assert!(
+            catch_unwind(AssertUnwindSafe(|| prove(invalid_request))).is_err(),
+            "{:?} should fail to prove",
+            invalid_request
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(feature = "ci", ignore)]
+fn test_header_range_kzg() {
+    // decode_header_extension requires a max header size that fits MAX_NUM_KZG_COMMITMENTS
+    // commitments.
+    const KZG_MAX_HEADER_SIZE: usize = consts::MAX_HEADER_SIZE;
+
+    let chain = chain();
+
+    let mut builder = DefaultBuilder::new();
+    HeaderRangeKzgCircuit::<NUM_AUTHORITIES, KZG_MAX_HEADER_SIZE, NUM_HEADERS>::define(
+        &mut builder,
+    );
+    let circuit = builder.build();
+
+    let prove = |request: &HeaderRangeRequest| {
+        let mut input = circuit.input();
+        input.evm_write::<U32Variable>(request.trusted_block);
+        input.evm_write::<Bytes32Variable>(request.trusted_header_hash);
+        input.evm_write::<U64Variable>(request.authority_set_id);
+        input.evm_write::<Bytes32Variable>(request.authority_set_hash);
+        input.evm_write::<U32Variable>(request.target_block);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        HeaderRangeKzgResponse {
+            target_header_hash: output.evm_read::<Bytes32Variable>(),
+            state_root_commitment: output.evm_read::<Bytes32Variable>(),
+            data_root_commitment: output.evm_read::<Bytes32Variable>(),
+            kzg_commitments_root: output.evm_read::<Bytes32Variable>(),
+            rows: output.evm_read::<U32Variable>(),
+            cols: output.evm_read::<U32Variable>(),
+        }
+    };
+
+    // Step from the epoch end block 16 to block 24, which is justified by authority set 1.
+    let request = HeaderRangeRequest {
+        trusted_block: chain.epoch_end_block(0),
+        trusted_header_hash: chain.header_hash(chain.epoch_end_block(0)),
+        authority_set_id: 1,
+        authority_set_hash: chain.authority_set_hash(1),
+        target_block: 24,
+    };
+    let headers = (request.trusted_block + 1..=request.target_block)
+        .map(|block_number| chain.header(block_number).to_vec())
+        .collect::<Vec<_>>();
+    let native_header_range = native::header_range::<NUM_HEADERS>(
+        request.trusted_block,
+        request.trusted_header_hash,
+        request.authority_set_id,
+        request.authority_set_hash,
+        &headers,
+        &chain.justification(request.target_block),
+    )
+    .unwrap();
+    let extension = CURRENT_HEADER_LAYOUT
+        .decode_extension(chain.header(request.target_block))
+        .unwrap();
+    let expected = HeaderRangeKzgResponse {
+        target_header_hash: native_header_range.target_header_hash,
+        state_root_commitment: native_header_range.state_root_commitment,
+        data_root_commitment: native_header_range.data_root_commitment,
+        kzg_commitments_root: extension.kzg_commitments_root().unwrap(),
+        rows: extension.rows as u32,
+        cols: extension.cols as u32,
+    };
+    assert_eq!(prove(&request), expected);
+
+    let invalid_requests = [
+        // Block 24 is not justified by authority set 0.
+        HeaderRangeRequest {
+            authority_set_id: 0,
+            authority_set_hash: chain.authority_set_hash(0),
+            ..request.clone()
+        },
+        // The trusted header hash is not the header hash of the trusted block.
+        HeaderRangeRequest {
+            trusted_header_hash: H256::zero(),
+            ..request.clone()
+        },
+    ];
+    for invalid_request in invalid_requests.iter() {
+        assert!(
+            catch_unwind(AssertUnwindSafe(|| prove(invalid_request))).is_err(),
+            "{:?} should fail to prove",
+            invalid_request
+        );
+    }
+}